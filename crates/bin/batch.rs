@@ -0,0 +1,80 @@
+//! Processing several PDBs concurrently for `--jobs`
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+
+/// Runs `job` on items `0..count` using `jobs` threads and passes each result to `write`
+/// in item order. Workers don't start an item more than `2 * jobs` items ahead of the
+/// next one to be written, so a slow item can't cause an unbounded number of finished
+/// results to pile up in memory. Stops at the first error in item order.
+pub fn run_ordered<T: Send>(
+    count: usize,
+    jobs: usize,
+    job: impl Fn(usize) -> anyhow::Result<T> + Sync,
+    mut write: impl FnMut(T) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let window = jobs * 2;
+    let next = AtomicUsize::new(0);
+    let written = Mutex::new(0);
+    let advanced = Condvar::new();
+    let stop = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel(jobs);
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (next, written, advanced, stop, job) = (&next, &written, &advanced, &stop, &job);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= count {
+                    return;
+                }
+
+                let mut written = written.lock().expect("batch lock poisoned");
+                while index >= *written + window && !stop.load(Ordering::Relaxed) {
+                    written = advanced.wait(written).expect("batch lock poisoned");
+                }
+                drop(written);
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                // Fails once the writer has given up
+                if sender.send((index, job(index))).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut write_all = || -> anyhow::Result<()> {
+            let mut pending = BTreeMap::new();
+            let mut expected = 0;
+            for (index, result) in receiver.iter() {
+                pending.insert(index, result);
+                while let Some(result) = pending.remove(&expected) {
+                    write(result?)?;
+                    expected += 1;
+
+                    *written.lock().expect("batch lock poisoned") = expected;
+                    advanced.notify_all();
+                }
+            }
+
+            Ok(())
+        };
+        let result = write_all();
+
+        if result.is_err() {
+            // Set while holding the lock so no worker misses the wakeup
+            let _written = written.lock().expect("batch lock poisoned");
+            stop.store(true, Ordering::Relaxed);
+            advanced.notify_all();
+        }
+        // Unblocks workers waiting to send
+        drop(receiver);
+
+        result
+    })
+}
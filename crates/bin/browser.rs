@@ -0,0 +1,434 @@
+use crate::output;
+use crate::output::cdecl;
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::{Type, Typed};
+use std::fmt::Write as _;
+use std::io;
+use tui::backend::{Backend, CrosstermBackend};
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use tui::{Frame, Terminal};
+
+/// Number of rows PageUp/PageDown move the selection by
+const PAGE_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pane {
+    Modules,
+    Symbols,
+    Types,
+}
+
+impl Pane {
+    fn next(self) -> Self {
+        match self {
+            Pane::Modules => Pane::Symbols,
+            Pane::Symbols => Pane::Types,
+            Pane::Types => Pane::Modules,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Pane::Modules => Pane::Types,
+            Pane::Symbols => Pane::Modules,
+            Pane::Types => Pane::Symbols,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Item {
+    Module(usize),
+    Procedure(usize),
+    Public(usize),
+    Data(usize),
+    Type(TypeIndexNumber),
+}
+
+/// A list of items and the subset of them which matches the current search
+struct PaneList {
+    items: Vec<(String, Item)>,
+    visible: Vec<usize>,
+    state: ListState,
+}
+
+impl PaneList {
+    fn new(mut items: Vec<(String, Item)>) -> Self {
+        items.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let visible = (0..items.len()).collect();
+        let mut state = ListState::default();
+        state.select(Some(0));
+
+        PaneList {
+            items,
+            visible,
+            state,
+        }
+    }
+
+    fn filter(&mut self, query: &str) {
+        let query = query.to_lowercase();
+        self.visible = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_i, (label, _item))| label.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect();
+        self.state.select(Some(0));
+    }
+
+    fn selected(&self) -> Option<Item> {
+        let index = self.state.selected()?;
+        self.visible.get(index).map(|i| self.items[*i].1)
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let last = self.visible.len() as isize - 1;
+        self.state
+            .select(Some((current + delta).max(0).min(last) as usize));
+    }
+}
+
+struct App<'a> {
+    pdb_info: &'a ParsedPdb,
+    active: Pane,
+    modules: PaneList,
+    symbols: PaneList,
+    types: PaneList,
+    query: String,
+    searching: bool,
+}
+
+impl<'a> App<'a> {
+    fn new(pdb_info: &'a ParsedPdb) -> Self {
+        let modules = pdb_info
+            .debug_modules
+            .iter()
+            .enumerate()
+            .map(|(i, module)| (module.name.clone(), Item::Module(i)))
+            .collect();
+
+        let symbols = pdb_info
+            .procedures
+            .iter()
+            .enumerate()
+            .map(|(i, procedure)| (procedure.name.clone(), Item::Procedure(i)))
+            .chain(
+                pdb_info
+                    .public_symbols
+                    .iter()
+                    .enumerate()
+                    .map(|(i, symbol)| (symbol.name.clone(), Item::Public(i))),
+            )
+            .chain(
+                pdb_info
+                    .global_data
+                    .iter()
+                    .enumerate()
+                    .map(|(i, data)| (data.name.clone(), Item::Data(i))),
+            )
+            .collect();
+
+        let types = pdb_info
+            .types
+            .iter()
+            .filter_map(|(index, ty)| {
+                let name = match &*ty.as_ref().borrow() {
+                    Type::Class(class) if !class.properties.forward_reference => class.name.clone(),
+                    Type::Union(union) if !union.properties.forward_reference => union.name.clone(),
+                    Type::Enumeration(e) if !e.properties.forward_reference => e.name.clone(),
+                    _ => return None,
+                };
+
+                Some((name, Item::Type(*index)))
+            })
+            .collect();
+
+        App {
+            pdb_info,
+            active: Pane::Symbols,
+            modules: PaneList::new(modules),
+            symbols: PaneList::new(symbols),
+            types: PaneList::new(types),
+            query: String::new(),
+            searching: false,
+        }
+    }
+
+    fn active_list(&mut self) -> &mut PaneList {
+        match self.active {
+            Pane::Modules => &mut self.modules,
+            Pane::Symbols => &mut self.symbols,
+            Pane::Types => &mut self.types,
+        }
+    }
+
+    fn apply_query(&mut self) {
+        let query = self.query.clone();
+        self.active_list().filter(&query);
+    }
+
+    /// Handles a key press. Returns `false` when the application should exit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.searching {
+            match key.code {
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.query.clear();
+                    self.apply_query();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.apply_query();
+                }
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.apply_query();
+                }
+                _ => {}
+            }
+
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.query.clear();
+            }
+            KeyCode::Tab | KeyCode::Right => self.switch_pane(self.active.next()),
+            KeyCode::BackTab | KeyCode::Left => self.switch_pane(self.active.previous()),
+            KeyCode::Down | KeyCode::Char('j') => self.active_list().move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.active_list().move_by(-1),
+            KeyCode::PageDown => self.active_list().move_by(PAGE_SIZE as isize),
+            KeyCode::PageUp => self.active_list().move_by(-(PAGE_SIZE as isize)),
+            _ => {}
+        }
+
+        true
+    }
+
+    fn switch_pane(&mut self, pane: Pane) {
+        // Searches apply to a single pane, so reset the one we're leaving
+        if !self.query.is_empty() {
+            self.query.clear();
+            self.apply_query();
+        }
+        self.active = pane;
+    }
+
+    fn detail(&mut self) -> String {
+        match self.active_list().selected() {
+            Some(item) => render_detail(self.pdb_info, item),
+            None => String::new(),
+        }
+    }
+}
+
+fn format_offset(offset: Option<usize>) -> String {
+    offset
+        .map(|offset| format!("0x{:X}", offset))
+        .unwrap_or_else(|| "<unknown>".to_string())
+}
+
+fn render_detail(pdb_info: &ParsedPdb, item: Item) -> String {
+    let mut text = String::new();
+
+    match item {
+        Item::Module(i) => {
+            let module = &pdb_info.debug_modules[i];
+            let _ = writeln!(text, "Module: {}", module.name);
+            let _ = writeln!(text, "Object file: {}", module.object_file_name);
+            let _ = writeln!(text, "Line records: {}", module.lines.len());
+            if let Some(files) = &module.source_files {
+                let _ = writeln!(text, "\nSource files:");
+                for file in files {
+                    let _ = writeln!(text, "\t{}", file.name);
+                }
+            }
+        }
+        Item::Procedure(i) => {
+            let procedure = &pdb_info.procedures[i];
+            let _ = writeln!(text, "Procedure: {}", procedure.name);
+            if let Some(signature) = &procedure.signature {
+                let _ = writeln!(text, "Signature: {}", signature);
+            }
+            let _ = writeln!(text, "Address: {}", format_offset(procedure.offset));
+            let _ = writeln!(text, "Length: 0x{:X}", procedure.len);
+            let _ = writeln!(text, "Global: {}", procedure.is_global);
+            if let Some(offset) = procedure.offset {
+                if let Some(line) = pdb_info.line_for_address(offset) {
+                    let _ = writeln!(text, "Source: {}:{}", line.file, line.line_start);
+                }
+            }
+            if !procedure.locals.is_empty() {
+                let _ = writeln!(text, "\nLocals:");
+                for local in &procedure.locals {
+                    let ty = pdb_info
+                        .types
+                        .get(&local.type_index)
+                        .map(|ty| output::format_type_name(&*ty.as_ref().borrow()))
+                        .unwrap_or_default();
+                    let _ = writeln!(text, "\t{} {}", ty, local.name);
+                }
+            }
+        }
+        Item::Public(i) => {
+            let symbol = &pdb_info.public_symbols[i];
+            let _ = writeln!(text, "Public symbol: {}", symbol.name);
+            let _ = writeln!(text, "Address: {}", format_offset(symbol.offset));
+            let _ = writeln!(text, "Code: {}", symbol.is_code);
+            let _ = writeln!(text, "Function: {}", symbol.is_function);
+            let _ = writeln!(text, "Managed: {}", symbol.is_managed);
+        }
+        Item::Data(i) => {
+            let data = &pdb_info.global_data[i];
+            let _ = writeln!(text, "Data: {}", data.name);
+            let _ = writeln!(text, "Address: {}", format_offset(data.offset));
+            let _ = writeln!(text, "Global: {}", data.is_global);
+            let _ = writeln!(
+                text,
+                "Type: {}",
+                output::format_type_name(&*data.ty.as_ref().borrow())
+            );
+        }
+        Item::Type(index) => {
+            let ty = pdb_info.types[&index].as_ref().borrow();
+            let _ = writeln!(text, "Type index: 0x{:X}", index);
+            let _ = writeln!(text, "Size: 0x{:X}\n", ty.type_size(pdb_info));
+            match cdecl::c_definition(&*ty, pdb_info) {
+                Some(definition) => text.push_str(&definition),
+                None => text.push_str(&output::format_type_name(&*ty)),
+            }
+        }
+    }
+
+    text
+}
+
+fn draw_list<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    title: &str,
+    list: &mut PaneList,
+    active: bool,
+) {
+    let items: Vec<ListItem> = list
+        .visible
+        .iter()
+        .map(|i| ListItem::new(list.items[*i].0.as_str()))
+        .collect();
+
+    let border_style = if active {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+
+    let widget = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(format!("{} ({})", title, list.visible.len())),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    f.render_stateful_widget(widget, area, &mut list.state);
+}
+
+fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
+        .split(f.size());
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(rows[0]);
+
+    let panes = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(25),
+                Constraint::Percentage(45),
+                Constraint::Percentage(30),
+            ]
+            .as_ref(),
+        )
+        .split(columns[0]);
+
+    let active = app.active;
+    draw_list(
+        f,
+        panes[0],
+        "Modules",
+        &mut app.modules,
+        active == Pane::Modules,
+    );
+    draw_list(
+        f,
+        panes[1],
+        "Symbols",
+        &mut app.symbols,
+        active == Pane::Symbols,
+    );
+    draw_list(f, panes[2], "Types", &mut app.types, active == Pane::Types);
+
+    let detail = Paragraph::new(app.detail())
+        .block(Block::default().borders(Borders::ALL).title("Detail"))
+        .wrap(Wrap { trim: false });
+    f.render_widget(detail, columns[1]);
+
+    let status = if app.searching {
+        format!("/{}", app.query)
+    } else {
+        "q: quit  tab: switch pane  up/down: select  /: search".to_string()
+    };
+    f.render_widget(Paragraph::new(status), rows[1]);
+}
+
+/// Runs an interactive browser over the parsed PDB until the user quits
+pub fn run(pdb_info: &ParsedPdb) -> anyhow::Result<()> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let mut app = App::new(pdb_info);
+    let result = (|| -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|f| draw(f, &mut app))?;
+
+            if let Event::Key(key) = event::read()? {
+                if !app.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+
+    // Always restore the terminal, even if drawing failed
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
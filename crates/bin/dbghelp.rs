@@ -0,0 +1,377 @@
+//! Reading a PDB through Windows' dbghelp, which is backed by DIA, and comparing what it
+//! reports to pdbview's own parse. Only built on Windows with the `dbghelp` feature
+
+use ezpdb::symbol_types::ParsedPdb;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::c_void;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+
+const SYMOPT_FAIL_CRITICAL_ERRORS: u32 = 0x0000_0200;
+const SYMOPT_NO_PROMPTS: u32 = 0x0008_0000;
+
+const SYM_TAG_FUNCTION: u32 = 5;
+const SYM_TAG_DATA: u32 = 7;
+const SYM_TAG_PUBLIC_SYMBOL: u32 = 10;
+
+/// Address the PDB is loaded at. Any address works since only RVAs are compared
+const LOAD_BASE: u64 = 0x1000_0000;
+const LOAD_SIZE: u32 = 0x7000_0000;
+
+#[repr(C)]
+struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+/// `SYMBOL_INFOW`
+#[repr(C)]
+#[allow(dead_code)]
+struct SymbolInfo {
+    size_of_struct: u32,
+    type_index: u32,
+    reserved: [u64; 2],
+    index: u32,
+    size: u32,
+    mod_base: u64,
+    flags: u32,
+    value: u64,
+    address: u64,
+    register: u32,
+    scope: u32,
+    tag: u32,
+    name_len: u32,
+    max_name_len: u32,
+    name: [u16; 1],
+}
+
+/// `IMAGEHLP_MODULEW64`
+#[repr(C)]
+#[allow(dead_code)]
+struct ModuleInfo {
+    size_of_struct: u32,
+    base_of_image: u64,
+    image_size: u32,
+    time_date_stamp: u32,
+    check_sum: u32,
+    num_syms: u32,
+    sym_type: u32,
+    module_name: [u16; 32],
+    image_name: [u16; 256],
+    loaded_image_name: [u16; 256],
+    loaded_pdb_name: [u16; 256],
+    cv_sig: u32,
+    cv_data: [u16; 780],
+    pdb_sig: u32,
+    pdb_sig70: Guid,
+    pdb_age: u32,
+    pdb_unmatched: i32,
+    dbg_unmatched: i32,
+    line_numbers: i32,
+    global_symbols: i32,
+    type_info: i32,
+    source_indexed: i32,
+    publics: i32,
+    machine_type: u32,
+    reserved: u32,
+}
+
+type EnumSymbolsCallback =
+    unsafe extern "system" fn(symbol: *const SymbolInfo, size: u32, context: *mut c_void) -> i32;
+
+#[link(name = "dbghelp")]
+extern "system" {
+    fn SymSetOptions(options: u32) -> u32;
+    fn SymInitializeW(process: *mut c_void, search_path: *const u16, invade: i32) -> i32;
+    fn SymCleanup(process: *mut c_void) -> i32;
+    fn SymLoadModuleExW(
+        process: *mut c_void,
+        file: *mut c_void,
+        image_name: *const u16,
+        module_name: *const u16,
+        base_of_dll: u64,
+        dll_size: u32,
+        data: *mut c_void,
+        flags: u32,
+    ) -> u64;
+    fn SymGetModuleInfoW64(process: *mut c_void, address: u64, info: *mut ModuleInfo) -> i32;
+    fn SymEnumSymbolsW(
+        process: *mut c_void,
+        base_of_dll: u64,
+        mask: *const u16,
+        callback: EnumSymbolsCallback,
+        context: *mut c_void,
+    ) -> i32;
+}
+
+/// A symbol as reported by dbghelp
+#[derive(Debug)]
+pub struct ReferenceSymbol {
+    pub kind: &'static str,
+    pub name: String,
+    pub rva: usize,
+    /// Size in BYTES, when dbghelp knows it
+    pub size: usize,
+}
+
+/// Everything read from the PDB through dbghelp
+#[derive(Debug)]
+pub struct Reference {
+    pub guid: uuid::Uuid,
+    pub age: u32,
+    pub symbols: Vec<ReferenceSymbol>,
+}
+
+fn wide(s: &std::ffi::OsStr) -> Vec<u16> {
+    s.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+unsafe extern "system" fn collect_symbol(
+    symbol: *const SymbolInfo,
+    _size: u32,
+    context: *mut c_void,
+) -> i32 {
+    let symbols = &mut *(context as *mut Vec<ReferenceSymbol>);
+    let symbol = &*symbol;
+    let kind = match symbol.tag {
+        SYM_TAG_FUNCTION => "procedure",
+        SYM_TAG_DATA => "data",
+        SYM_TAG_PUBLIC_SYMBOL => "public",
+        _ => return 1,
+    };
+    let name = std::slice::from_raw_parts(symbol.name.as_ptr(), symbol.name_len as usize);
+
+    symbols.push(ReferenceSymbol {
+        kind,
+        name: String::from_utf16_lossy(name),
+        rva: (symbol.address - symbol.mod_base) as usize,
+        size: symbol.size as usize,
+    });
+
+    1
+}
+
+/// Loads the PDB at `path` through dbghelp and reads its identity and every procedure,
+/// global, and public symbol
+pub fn load(path: &Path) -> anyhow::Result<Reference> {
+    // dbghelp accepts any unique value as the process handle when it isn't asked to
+    // enumerate the process's modules
+    let mut session = 0u8;
+    let process = &mut session as *mut u8 as *mut c_void;
+    let image_name = wide(path.as_os_str());
+    let mask = wide("*".as_ref());
+
+    unsafe {
+        // Names are compared as stored, so leave C++ names decorated
+        SymSetOptions(SYMOPT_FAIL_CRITICAL_ERRORS | SYMOPT_NO_PROMPTS);
+        if SymInitializeW(process, std::ptr::null(), 0) == 0 {
+            anyhow::bail!(
+                "failed to initialize dbghelp: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+
+        let result = (|| {
+            let base = SymLoadModuleExW(
+                process,
+                std::ptr::null_mut(),
+                image_name.as_ptr(),
+                std::ptr::null(),
+                LOAD_BASE,
+                LOAD_SIZE,
+                std::ptr::null_mut(),
+                0,
+            );
+            if base == 0 {
+                anyhow::bail!(
+                    "dbghelp failed to load {}: {}",
+                    path.display(),
+                    std::io::Error::last_os_error()
+                );
+            }
+
+            let mut info: ModuleInfo = std::mem::zeroed();
+            info.size_of_struct = std::mem::size_of::<ModuleInfo>() as u32;
+            if SymGetModuleInfoW64(process, base, &mut info) == 0 {
+                anyhow::bail!(
+                    "dbghelp failed to describe {}: {}",
+                    path.display(),
+                    std::io::Error::last_os_error()
+                );
+            }
+
+            let mut symbols: Vec<ReferenceSymbol> = vec![];
+            if SymEnumSymbolsW(
+                process,
+                base,
+                mask.as_ptr(),
+                collect_symbol,
+                &mut symbols as *mut Vec<ReferenceSymbol> as *mut c_void,
+            ) == 0
+            {
+                anyhow::bail!(
+                    "dbghelp failed to enumerate the symbols of {}: {}",
+                    path.display(),
+                    std::io::Error::last_os_error()
+                );
+            }
+
+            let guid = &info.pdb_sig70;
+            Ok(Reference {
+                guid: uuid::Uuid::from_fields(guid.data1, guid.data2, guid.data3, &guid.data4)?,
+                age: info.pdb_age,
+                symbols,
+            })
+        })();
+
+        SymCleanup(process);
+        result
+    }
+}
+
+/// A difference between pdbview's parse and dbghelp's
+#[derive(Debug, Serialize)]
+pub struct Discrepancy {
+    /// `identity`, `procedure`, `data`, or `public`
+    pub kind: &'static str,
+    pub name: String,
+    /// What differs: `guid`, `age`, `address`, or `size`
+    pub field: &'static str,
+    /// pdbview's value, absent if pdbview didn't report the symbol
+    pub native: Option<String>,
+    /// dbghelp's value, absent if dbghelp didn't report the symbol
+    pub dbghelp: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrossCheckReport {
+    /// Number of symbols of each kind compared
+    pub compared: BTreeMap<&'static str, usize>,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+fn format_addresses(addresses: &BTreeSet<usize>) -> Option<String> {
+    if addresses.is_empty() {
+        return None;
+    }
+
+    Some(
+        addresses
+            .iter()
+            .map(|address| format!("0x{:X}", address))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// Compares the identity and symbols of `pdb_info`, which must not be rebased, to what
+/// dbghelp reported. Symbols are matched by kind and name, and a name is reported when
+/// the set of addresses either side gives it differs. Procedures found at the same
+/// single address on both sides also have their sizes compared
+pub fn compare(pdb_info: &ParsedPdb, reference: &Reference) -> CrossCheckReport {
+    let mut discrepancies = vec![];
+    if pdb_info.guid != reference.guid {
+        discrepancies.push(Discrepancy {
+            kind: "identity",
+            name: String::new(),
+            field: "guid",
+            native: Some(pdb_info.guid.to_string()),
+            dbghelp: Some(reference.guid.to_string()),
+        });
+    }
+    if pdb_info.age != reference.age {
+        discrepancies.push(Discrepancy {
+            kind: "identity",
+            name: String::new(),
+            field: "age",
+            native: Some(pdb_info.age.to_string()),
+            dbghelp: Some(reference.age.to_string()),
+        });
+    }
+
+    // kind -> name -> (pdbview's addresses, dbghelp's addresses)
+    type Addresses = (BTreeSet<usize>, BTreeSet<usize>);
+    let mut symbols: BTreeMap<&'static str, BTreeMap<&str, Addresses>> = BTreeMap::new();
+    let native = pdb_info
+        .procedures
+        .iter()
+        .map(|procedure| ("procedure", procedure.name.as_str(), procedure.offset))
+        .chain(
+            pdb_info
+                .global_data
+                .iter()
+                .map(|data| ("data", data.name.as_str(), data.offset)),
+        )
+        .chain(
+            pdb_info
+                .public_symbols
+                .iter()
+                .map(|symbol| ("public", symbol.name.as_str(), symbol.offset)),
+        );
+    for (kind, name, offset) in native {
+        let entry = symbols.entry(kind).or_default().entry(name).or_default();
+        entry.0.extend(offset);
+    }
+    for symbol in &reference.symbols {
+        let entry = symbols
+            .entry(symbol.kind)
+            .or_default()
+            .entry(symbol.name.as_str())
+            .or_default();
+        entry.1.insert(symbol.rva);
+    }
+
+    let mut compared = BTreeMap::new();
+    for (kind, names) in &symbols {
+        compared.insert(*kind, names.len());
+        for (name, (native, dbghelp)) in names {
+            if native != dbghelp {
+                discrepancies.push(Discrepancy {
+                    kind: *kind,
+                    name: name.to_string(),
+                    field: "address",
+                    native: format_addresses(native),
+                    dbghelp: format_addresses(dbghelp),
+                });
+            }
+        }
+    }
+
+    // Sizes are only comparable when both sides agree on which single procedure it is
+    let native_sizes: BTreeMap<(&str, usize), usize> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| Some(((procedure.name.as_str(), procedure.offset?), procedure.len)))
+        .collect();
+    for symbol in reference
+        .symbols
+        .iter()
+        .filter(|symbol| symbol.kind == "procedure")
+    {
+        let unique = symbols["procedure"]
+            .get(symbol.name.as_str())
+            .map_or(false, |(native, dbghelp)| {
+                native.len() == 1 && native == dbghelp
+            });
+        let native_size = native_sizes.get(&(symbol.name.as_str(), symbol.rva));
+        if let (true, Some(native_size)) = (unique, native_size) {
+            if *native_size != symbol.size && symbol.size != 0 {
+                discrepancies.push(Discrepancy {
+                    kind: "procedure",
+                    name: symbol.name.clone(),
+                    field: "size",
+                    native: Some(native_size.to_string()),
+                    dbghelp: Some(symbol.size.to_string()),
+                });
+            }
+        }
+    }
+
+    CrossCheckReport {
+        compared,
+        discrepancies,
+    }
+}
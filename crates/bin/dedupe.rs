@@ -0,0 +1,127 @@
+use ezpdb::symbol_types::{ParsedPdb, TypeIndexNumber, TypeRef};
+use ezpdb::type_info::Type;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::mem::{discriminant, Discriminant};
+use std::rc::Rc;
+
+/// Removes duplicate classes, unions, and enums from the PDB's types, recording each
+/// removed index in [ParsedPdb::type_aliases] along with the index kept in its place.
+/// Definitions are duplicates if they have the same name and serialize identically,
+/// and the lowest index among them is kept. Forward references collapse into the kept
+/// definition of the same kind and name, or into the first forward reference if there's
+/// none. References to removed types are repointed to the types kept in their place
+pub fn collapse_duplicate_types(pdb_info: &mut ParsedPdb) -> anyhow::Result<()> {
+    let mut canonical: HashMap<(String, Vec<u8>), TypeIndexNumber> = HashMap::new();
+    // Keyed by kind as well since a class and an enum may share a name
+    let mut definitions: HashMap<(Discriminant<Type>, String), TypeIndexNumber> = HashMap::new();
+    let mut forward_references: Vec<((Discriminant<Type>, String), TypeIndexNumber)> = vec![];
+    let mut aliases = BTreeMap::new();
+
+    // Types are visited in index order, so the first of each group is the lowest index
+    for (index, ty) in &pdb_info.types {
+        let ty = ty.as_ref().borrow();
+        let (name, is_forward_reference) = match ty.definition_key() {
+            Some(key) => key,
+            None => continue,
+        };
+
+        if is_forward_reference {
+            forward_references.push(((discriminant(&*ty), name.to_string()), *index));
+            continue;
+        }
+
+        let hash = Sha256::digest(&serde_json::to_vec(&without_keys(&ty))?).to_vec();
+        match canonical.entry((name.to_string(), hash)) {
+            Entry::Occupied(kept) => {
+                aliases.insert(*index, *kept.get());
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(*index);
+                definitions
+                    .entry((discriminant(&*ty), name.to_string()))
+                    .or_insert(*index);
+            }
+        }
+    }
+
+    for (key, index) in forward_references {
+        match definitions.entry(key) {
+            Entry::Occupied(kept) => {
+                aliases.insert(index, *kept.get());
+            }
+            // No definition, so this forward reference stands in for the others
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+            }
+        }
+    }
+
+    // The removed types are held until the end so their addresses aren't reused
+    let removed: Vec<(TypeRef, TypeIndexNumber)> = aliases
+        .iter()
+        .filter_map(|(index, kept)| Some((pdb_info.types.remove(index)?, *kept)))
+        .collect();
+    let replacements: HashMap<*const RefCell<Type>, TypeRef> = removed
+        .iter()
+        .map(|(removed, kept)| (Rc::as_ptr(removed), Rc::clone(&pdb_info.types[kept])))
+        .collect();
+    pdb_info.type_aliases.extend(aliases);
+
+    let mut visited = HashSet::new();
+    for ty in pdb_info.types.values() {
+        repoint_references(ty, &replacements, &mut visited);
+    }
+    for data in &mut pdb_info.global_data {
+        if let Some(kept) = replacements.get(&Rc::as_ptr(&data.ty)) {
+            data.ty = Rc::clone(kept);
+        }
+        repoint_references(&data.ty, &replacements, &mut visited);
+    }
+
+    Ok(())
+}
+
+/// Replaces the references `ty` and the types it refers to hold to removed types with
+/// the types kept in their place
+fn repoint_references(
+    ty: &TypeRef,
+    replacements: &HashMap<*const RefCell<Type>, TypeRef>,
+    visited: &mut HashSet<*const RefCell<Type>>,
+) {
+    if !visited.insert(Rc::as_ptr(ty)) {
+        return;
+    }
+
+    for reference in ty.as_ref().borrow_mut().references_mut() {
+        match replacements.get(&Rc::as_ptr(reference)) {
+            Some(kept) => *reference = Rc::clone(kept),
+            None => repoint_references(reference, replacements, visited),
+        }
+    }
+}
+
+/// Returns a copy of `ty` without the fields holding keys of [ParsedPdb::types], which
+/// differ between otherwise identical copies of a type
+fn without_keys(ty: &Type) -> Type {
+    let mut ty = ty.clone();
+    match &mut ty {
+        Type::Class(class) => {
+            class.parent = None;
+            class.synthesized_name = None;
+        }
+        Type::Union(union) => {
+            union.parent = None;
+            union.synthesized_name = None;
+        }
+        Type::Enumeration(e) => {
+            e.parent = None;
+            e.synthesized_name = None;
+        }
+        _ => {}
+    }
+
+    ty
+}
@@ -0,0 +1,26 @@
+use ezpdb::ParsedPdb;
+
+/// Returns the demangled form of a Rust symbol name (legacy `_ZN` or v0 `_R` mangling)
+/// without its trailing hash, or `None` if `name` isn't a mangled Rust name
+pub fn demangle_rust(name: &str) -> Option<String> {
+    rustc_demangle::try_demangle(name)
+        .ok()
+        .map(|demangled| format!("{:#}", demangled))
+}
+
+/// Replaces the Rust-mangled names of public symbols and procedures with their demangled
+/// form. Names using other manglings, such as MSVC's, are left as-is
+pub fn demangle_names(pdb_info: &mut ParsedPdb) {
+    let demangle = |name: &mut String| {
+        if let Some(demangled) = demangle_rust(name) {
+            *name = demangled;
+        }
+    };
+
+    for symbol in &mut pdb_info.public_symbols {
+        demangle(&mut symbol.name);
+    }
+    for procedure in &mut pdb_info.procedures {
+        demangle(&mut procedure.name);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::filter::wildcard_match;
+use crate::output;
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use iced_x86::{Decoder, DecoderOptions, Formatter, Instruction, IntelFormatter, Register};
+use std::io::Write;
+
+/// Maps the CodeView name of a frame register to its iced register
+fn frame_register(name: &str) -> Option<Register> {
+    match name {
+        "esp" => Some(Register::ESP),
+        "ebp" => Some(Register::EBP),
+        "rsp" => Some(Register::RSP),
+        "rbp" => Some(Register::RBP),
+        _ => None,
+    }
+}
+
+/// Returns the local variable `instruction` addresses, if any
+fn referenced_local<'a>(
+    instruction: &Instruction,
+    locals: &'a [LocalVariable],
+) -> Option<&'a LocalVariable> {
+    let base = instruction.memory_base();
+    if base == Register::None {
+        return None;
+    }
+
+    let displacement = instruction.memory_displacement64() as u32 as i32;
+    locals.iter().find(|local| {
+        local.register.as_deref().and_then(frame_register) == Some(base)
+            && local.register_offset == Some(displacement)
+    })
+}
+
+fn local_type_name(pdb_info: &ParsedPdb, local: &LocalVariable) -> String {
+    pdb_info
+        .types
+        .get(&local.type_index)
+        .map(|ty| output::format_type_name(&*ty.as_ref().borrow()))
+        .unwrap_or_else(|| format!("<type 0x{:X}>", local.type_index))
+}
+
+fn print_procedure(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: &PeInfo,
+    procedure: &Procedure,
+) -> anyhow::Result<()> {
+    let offset = match procedure.offset {
+        Some(offset) => offset,
+        None => anyhow::bail!("{} does not have an address", procedure.name),
+    };
+    let rva = offset - pdb_info.base_address;
+    let bytes = pe.read_rva(rva, procedure.len).ok_or_else(|| {
+        anyhow::anyhow!(
+            "bytes for {} (RVA 0x{:X}) are not present in the PE",
+            procedure.name,
+            rva
+        )
+    })?;
+
+    writeln!(output, "; {}", procedure.name)?;
+    writeln!(output, "; 0x{:X} - 0x{:X}", offset, offset + procedure.len)?;
+    for local in &procedure.locals {
+        let kind = if local.is_parameter { "param" } else { "local" };
+        match (&local.register, local.register_offset) {
+            (Some(register), Some(register_offset)) => writeln!(
+                output,
+                "; {} {} {} @ [{}{}0x{:X}]",
+                kind,
+                local_type_name(pdb_info, local),
+                local.name,
+                register,
+                if register_offset < 0 { '-' } else { '+' },
+                register_offset.unsigned_abs()
+            )?,
+            _ => writeln!(
+                output,
+                "; {} {} {}",
+                kind,
+                local_type_name(pdb_info, local),
+                local.name
+            )?,
+        }
+    }
+    writeln!(output)?;
+
+    let bitness = if pe.is_64 { 64 } else { 32 };
+    let mut decoder = Decoder::with_ip(bitness, bytes, offset as u64, DecoderOptions::NONE);
+    let mut formatter = IntelFormatter::new();
+    let mut instruction = Instruction::default();
+    let mut text = String::new();
+    let mut last_line: Option<(&str, u32)> = None;
+
+    while decoder.can_decode() {
+        decoder.decode_out(&mut instruction);
+        let address = instruction.ip() as usize;
+
+        if let Some(line) = pdb_info.line_for_address(address) {
+            let current = (line.file.as_str(), line.line_start);
+            if last_line != Some(current) {
+                writeln!(output, "; {}:{}", line.file, line.line_start)?;
+                last_line = Some(current);
+            }
+        }
+
+        text.clear();
+        formatter.format(&instruction, &mut text);
+
+        let start = address - offset;
+        let instruction_bytes: String = bytes[start..start + instruction.len()]
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+
+        match referenced_local(&instruction, &procedure.locals) {
+            Some(local) => writeln!(
+                output,
+                "  {:016X}  {:<24} {:<40} ; {}",
+                address, instruction_bytes, text, local.name
+            )?,
+            None => writeln!(
+                output,
+                "  {:016X}  {:<24} {}",
+                address, instruction_bytes, text
+            )?,
+        }
+    }
+
+    writeln!(output)?;
+
+    Ok(())
+}
+
+/// Disassembles every procedure matching the `function` wildcard pattern, annotating
+/// instructions with source lines and the locals they reference
+pub fn print_disassembly(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: &PeInfo,
+    function: &str,
+) -> anyhow::Result<()> {
+    let procedures: Vec<&Procedure> = pdb_info
+        .procedures
+        .iter()
+        .filter(|procedure| wildcard_match(function, &procedure.name))
+        .collect();
+
+    if procedures.is_empty() {
+        anyhow::bail!("no procedure matches {:?}", function);
+    }
+
+    for procedure in procedures {
+        print_procedure(output, pdb_info, pe, procedure)?;
+    }
+
+    Ok(())
+}
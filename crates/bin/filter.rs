@@ -0,0 +1,276 @@
+use crate::config::Redaction;
+use crate::CliArgumentError;
+pub use ezpdb::options::wildcard_match;
+use ezpdb::options::{ParseOptions, SymbolKind};
+use ezpdb::ParsedPdb;
+use regex::Regex;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Removes all named symbols which do not match any of the provided `patterns`.
+/// No symbols are removed if `patterns` is empty, but raw records are always reduced to
+/// those which failed to decode or match.
+pub fn retain_matching_names(pdb_info: &mut ParsedPdb, patterns: &[String]) {
+    let matches = |name: &str| patterns.iter().any(|pattern| wildcard_match(pattern, name));
+
+    // Raw bytes of records which decoded fine are only kept for the names asked for
+    pdb_info
+        .raw_records
+        .retain(|record| record.error.is_some() || record.name.as_deref().map_or(false, matches));
+
+    if patterns.is_empty() {
+        return;
+    }
+
+    pdb_info
+        .public_symbols
+        .retain(|symbol| matches(&symbol.name));
+    pdb_info
+        .procedures
+        .retain(|procedure| matches(&procedure.name));
+    pdb_info.global_data.retain(|data| matches(&data.name));
+}
+
+/// Patterns for CRT, STL, and compiler-generated symbols
+const BUILTIN_EXCLUDE: &str = include_str!("builtin_exclude.txt");
+
+/// Reads wildcard patterns from each file in `sources`, one per line. Blank lines and
+/// lines starting with `#` are ignored. The source `builtin` stands for a list covering
+/// CRT, STL, and compiler-generated symbols
+pub fn load_exclude_patterns(sources: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut patterns = vec![];
+    for source in sources {
+        let contents = if source == "builtin" {
+            BUILTIN_EXCLUDE.to_string()
+        } else {
+            std::fs::read_to_string(source)
+                .map_err(|e| anyhow::anyhow!("failed to read exclude file {}: {}", source, e))?
+        };
+
+        patterns.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+
+    Ok(patterns)
+}
+
+/// Removes all named symbols which match any of the provided `patterns`
+pub fn remove_matching_names(pdb_info: &mut ParsedPdb, patterns: &[String]) {
+    if patterns.is_empty() {
+        return;
+    }
+
+    let keep = |name: &str| !patterns.iter().any(|pattern| wildcard_match(pattern, name));
+
+    pdb_info.public_symbols.retain(|symbol| keep(&symbol.name));
+    pdb_info
+        .procedures
+        .retain(|procedure| keep(&procedure.name));
+    pdb_info.global_data.retain(|data| keep(&data.name));
+    pdb_info.thunks.retain(|thunk| keep(&thunk.name));
+}
+
+/// Applies the configured [Redaction] to every path embedded in the PDB
+pub fn redact(pdb_info: &mut ParsedPdb, redaction: &Redaction) -> anyhow::Result<()> {
+    if !redaction.strip_directories && redaction.patterns.is_empty() {
+        return Ok(());
+    }
+
+    let patterns = redaction
+        .patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let redact_path = |path: &str| -> String {
+        let mut path = if redaction.strip_directories {
+            path.rsplit(|c| c == '\\' || c == '/')
+                .next()
+                .unwrap_or(path)
+                .to_string()
+        } else {
+            path.to_string()
+        };
+
+        for pattern in &patterns {
+            path = pattern.replace_all(&path, "<redacted>").into_owned();
+        }
+
+        path
+    };
+
+    pdb_info.path = redact_path(&pdb_info.path.to_string_lossy()).into();
+    if let Some(identity) = pdb_info.identity.as_mut() {
+        for hashes in std::iter::once(&mut identity.pdb).chain(identity.pe.as_mut()) {
+            hashes.path = redact_path(&hashes.path.to_string_lossy()).into();
+        }
+    }
+    for module in &mut pdb_info.debug_modules {
+        module.name = redact_path(&module.name);
+        module.object_file_name = redact_path(&module.object_file_name);
+        for file in module.source_files.iter_mut().flatten() {
+            file.name = redact_path(&file.name);
+        }
+        for line in &mut module.lines {
+            line.file = redact_path(&line.file);
+        }
+    }
+
+    Ok(())
+}
+
+/// A `from=to` rule which rewrites paths starting with `from` to start with `to`.
+/// Matching ignores ASCII case and treats `\` and `/` as the same separator since PDBs
+/// built on Windows are often inspected elsewhere
+#[derive(Debug, Clone)]
+pub struct PathMapping {
+    to: String,
+    /// Matches `from` anywhere in a string
+    pattern: Regex,
+}
+
+impl FromStr for PathMapping {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() => (from, to),
+            _ => return Err(CliArgumentError::InvalidValue("path-map", s.to_string())),
+        };
+
+        let from: Vec<String> = from
+            .split(|c| c == '\\' || c == '/')
+            .map(regex::escape)
+            .collect();
+        let pattern = Regex::new(&format!("(?i){}", from.join(r"[\\/]")))
+            .map_err(|_| CliArgumentError::InvalidValue("path-map", s.to_string()))?;
+
+        Ok(PathMapping {
+            to: to.to_string(),
+            pattern,
+        })
+    }
+}
+
+impl PathMapping {
+    /// Rewrites `path` if it starts with this mapping's prefix
+    fn map_prefix(&self, path: &str) -> Option<String> {
+        self.pattern
+            .find(path)
+            .filter(|found| found.start() == 0)
+            .map(|found| format!("{}{}", self.to, &path[found.end()..]))
+    }
+
+    /// Rewrites every occurrence of this mapping's prefix in `text`, e.g. paths
+    /// embedded in a command line
+    fn map_all(&self, text: &str) -> String {
+        self.pattern
+            .replace_all(text, regex::NoExpand(&self.to))
+            .into_owned()
+    }
+}
+
+/// Rewrites source paths in line info, module lists, and build info using the first
+/// matching mapping in `mappings`
+pub fn remap_paths(pdb_info: &mut ParsedPdb, mappings: &[PathMapping]) {
+    if mappings.is_empty() {
+        return;
+    }
+
+    let map_path = |path: &mut String| {
+        if let Some(mapped) = mappings.iter().find_map(|mapping| mapping.map_prefix(path)) {
+            *path = mapped;
+        }
+    };
+
+    for module in &mut pdb_info.debug_modules {
+        map_path(&mut module.name);
+        map_path(&mut module.object_file_name);
+        for file in module.source_files.iter_mut().flatten() {
+            map_path(&mut file.name);
+        }
+        for line in &mut module.lines {
+            map_path(&mut line.file);
+        }
+
+        if let Some(build_info) = module.build_info.as_mut() {
+            for argument in build_info.arguments_mut() {
+                for mapping in mappings {
+                    *argument = mapping.map_all(argument);
+                }
+            }
+        }
+    }
+
+    if let Some(build_info) = pdb_info.assembly_info.build_info.as_mut() {
+        for argument in build_info.arguments_mut() {
+            for mapping in mappings {
+                *argument = mapping.map_all(argument);
+            }
+        }
+    }
+    for typ in pdb_info.types.values() {
+        if let Some(source) = typ.as_ref().borrow_mut().source_mut() {
+            map_path(&mut source.file);
+        }
+    }
+}
+
+/// Comma-separated symbol kinds to include, e.g. `function,data`. Kinds prefixed with
+/// `-` are excluded instead, starting from every kind if nothing is explicitly included
+#[derive(Debug, Clone)]
+pub struct KindFilter(pub HashSet<SymbolKind>);
+
+impl FromStr for KindFilter {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut included = HashSet::new();
+        let mut excluded = HashSet::new();
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let (set, name) = match name.strip_prefix('-') {
+                Some(name) => (&mut excluded, name),
+                None => (&mut included, name),
+            };
+            let kind = SymbolKind::from_name(name)
+                .ok_or_else(|| CliArgumentError::InvalidValue("kinds", name.to_string()))?;
+            set.insert(kind);
+        }
+
+        if included.is_empty() {
+            included.extend(SymbolKind::ALL.iter().copied());
+        }
+
+        Ok(KindFilter(&included - &excluded))
+    }
+}
+
+/// Removes symbols of kinds `options` excludes. PDBs are filtered while parsing, but
+/// previously exported JSON has to be filtered after loading
+pub fn retain_kinds(pdb_info: &mut ParsedPdb, options: &ParseOptions) {
+    if !options.includes(SymbolKind::Function) {
+        pdb_info.procedures.clear();
+    }
+    if !options.includes(SymbolKind::Local) {
+        for procedure in &mut pdb_info.procedures {
+            procedure.locals.clear();
+        }
+    }
+    if !options.includes(SymbolKind::Data) {
+        pdb_info.global_data.clear();
+    }
+    if !options.includes(SymbolKind::Public) {
+        pdb_info.public_symbols.clear();
+    }
+    if !options.includes(SymbolKind::Thunk) {
+        pdb_info.thunks.clear();
+    }
+    if !options.includes(SymbolKind::Export) {
+        pdb_info.exports.clear();
+    }
+}
@@ -0,0 +1,113 @@
+use crate::CliArgumentError;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// Ways of grouping output collections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Attach procedures, globals, and used types to the module which contributed them
+    Module,
+}
+
+impl FromStr for GroupBy {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_ref() {
+            "module" => Ok(GroupBy::Module),
+            _ => Err(CliArgumentError::InvalidValue("group-by", s.to_string())),
+        }
+    }
+}
+
+/// A debug module along with the symbols attributed to it
+#[derive(Debug, Serialize)]
+pub struct ModuleGroup<'a> {
+    #[serde(flatten)]
+    pub module: &'a DebugModule,
+    pub procedures: Vec<&'a Procedure>,
+    pub globals: Vec<&'a Data>,
+    /// Indices of the types referenced by the module's procedures and their locals
+    pub types_used: BTreeSet<TypeIndexNumber>,
+}
+
+/// Maps addresses to the index of the module whose section contribution contains them
+pub struct ModuleRanges {
+    /// `(start, end, module index)` sorted by start
+    ranges: Vec<(usize, usize, usize)>,
+}
+
+impl ModuleRanges {
+    pub fn new(pdb_info: &ParsedPdb) -> Self {
+        let mut ranges: Vec<(usize, usize, usize)> = pdb_info
+            .section_contributions
+            .iter()
+            .filter_map(|contribution| {
+                contribution.offset.map(|offset| {
+                    (
+                        offset,
+                        offset + contribution.size,
+                        contribution.module_index,
+                    )
+                })
+            })
+            .collect();
+        ranges.sort_unstable();
+
+        ModuleRanges { ranges }
+    }
+
+    /// Returns the index of the module which contributed the code or data at `address`
+    pub fn module_index(&self, address: usize) -> Option<usize> {
+        let idx = self
+            .ranges
+            .partition_point(|(start, _, _)| *start <= address)
+            .checked_sub(1)?;
+        let (_, end, module_index) = self.ranges[idx];
+
+        if address < end {
+            Some(module_index)
+        } else {
+            None
+        }
+    }
+}
+
+/// Groups procedures and globals by the module whose section contributions contain
+/// their addresses. Symbols without an address or outside every contribution are left
+/// out of all groups
+pub fn module_groups(pdb_info: &ParsedPdb) -> Vec<ModuleGroup<'_>> {
+    let mut groups: Vec<ModuleGroup<'_>> = pdb_info
+        .debug_modules
+        .iter()
+        .map(|module| ModuleGroup {
+            module,
+            procedures: vec![],
+            globals: vec![],
+            types_used: BTreeSet::new(),
+        })
+        .collect();
+
+    let ranges = ModuleRanges::new(pdb_info);
+    let group_for = |offset: Option<usize>| offset.and_then(|offset| ranges.module_index(offset));
+
+    for procedure in &pdb_info.procedures {
+        if let Some(group) = group_for(procedure.offset).and_then(|i| groups.get_mut(i)) {
+            group.procedures.push(procedure);
+            group.types_used.insert(procedure.type_index);
+            group
+                .types_used
+                .extend(procedure.locals.iter().map(|local| local.type_index));
+        }
+    }
+
+    for data in &pdb_info.global_data {
+        if let Some(group) = group_for(data.offset).and_then(|i| groups.get_mut(i)) {
+            group.globals.push(data);
+        }
+    }
+
+    groups
+}
@@ -0,0 +1,300 @@
+//! gRPC interface to a loaded PDB, defined in `proto/pdbview.proto`.
+//!
+//! [ParsedPdb] shares types through `Rc`, so it can't be used from the server's worker
+//! threads. The PDB is instead loaded and queried on a thread of its own, and requests
+//! send it closures to run. A [NameIndex] of the PDB is built on that thread as well to
+//! answer symbol pattern queries
+
+use crate::index::{NameIndex, NameRef};
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::Typed;
+use std::net::SocketAddr;
+use tokio::sync::{mpsc, oneshot};
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("pdbview");
+}
+
+use proto::pdbview_server::{Pdbview, PdbviewServer};
+use proto::SymbolKind;
+
+/// Number of queries waiting for the PDB's thread before further requests wait to be
+/// queued, so a burst of requests can't grow memory without bound
+const JOB_QUEUE_LEN: usize = 64;
+
+/// A query run on the thread which owns the PDB and its index of names
+type Job = Box<dyn FnOnce(&ParsedPdb, &NameIndex) + Send>;
+
+struct Service {
+    jobs: mpsc::Sender<Job>,
+}
+
+impl Service {
+    /// Runs `query` against the PDB and returns its result
+    async fn query<T: Send + 'static>(
+        &self,
+        query: impl FnOnce(&ParsedPdb) -> Result<T, Status> + Send + 'static,
+    ) -> Result<T, Status> {
+        self.query_indexed(move |pdb_info, _names| query(pdb_info))
+            .await
+    }
+
+    /// Runs `query` against the PDB and the index of its names and returns its result
+    async fn query_indexed<T: Send + 'static>(
+        &self,
+        query: impl FnOnce(&ParsedPdb, &NameIndex) -> Result<T, Status> + Send + 'static,
+    ) -> Result<T, Status> {
+        let (reply, result) = oneshot::channel();
+        self.jobs
+            .send(Box::new(move |pdb_info, names| {
+                let _ = reply.send(query(pdb_info, names));
+            }))
+            .await
+            .map_err(|_| Status::unavailable("the PDB is no longer loaded"))?;
+
+        result
+            .await
+            .map_err(|_| Status::internal("the query failed unexpectedly"))?
+    }
+}
+
+fn procedure_symbol(procedure: &Procedure) -> proto::Symbol {
+    proto::Symbol {
+        kind: SymbolKind::Procedure as i32,
+        name: procedure.name.clone(),
+        address: procedure.offset.unwrap_or_default() as u64,
+        has_address: procedure.offset.is_some(),
+        size: procedure.len as u64,
+        signature: procedure.signature.clone().unwrap_or_default(),
+    }
+}
+
+fn public_symbol(symbol: &PublicSymbol) -> proto::Symbol {
+    proto::Symbol {
+        kind: SymbolKind::Public as i32,
+        name: symbol.name.clone(),
+        address: symbol.offset.unwrap_or_default() as u64,
+        has_address: symbol.offset.is_some(),
+        size: 0,
+        signature: String::new(),
+    }
+}
+
+fn data_symbol(pdb_info: &ParsedPdb, data: &Data) -> proto::Symbol {
+    proto::Symbol {
+        kind: SymbolKind::Data as i32,
+        name: data.name.clone(),
+        address: data.offset.unwrap_or_default() as u64,
+        has_address: data.offset.is_some(),
+        size: data.ty.borrow().type_size(pdb_info) as u64,
+        signature: String::new(),
+    }
+}
+
+/// Returns the symbols of `kinds`, or of every kind if empty, whose name matches
+/// `pattern`, or every symbol if it is empty. Procedures come first, then public
+/// symbols, then data
+fn matching_symbols(
+    pdb_info: &ParsedPdb,
+    names: &NameIndex,
+    kinds: &[SymbolKind],
+    pattern: &str,
+) -> Vec<proto::Symbol> {
+    let wanted = |kind: SymbolKind| kinds.is_empty() || kinds.contains(&kind);
+
+    if pattern.is_empty() {
+        let procedures = (0..pdb_info.procedures.len()).map(NameRef::Procedure);
+        let publics = (0..pdb_info.public_symbols.len()).map(NameRef::Public);
+        let data = (0..pdb_info.global_data.len()).map(NameRef::Data);
+        return procedures
+            .chain(publics)
+            .chain(data)
+            .filter_map(|name| symbol(pdb_info, name))
+            .filter(|symbol| wanted(symbol.kind()))
+            .collect();
+    }
+
+    names
+        .matches(pattern)
+        .into_iter()
+        .filter_map(|name| symbol(pdb_info, name))
+        .filter(|symbol| wanted(symbol.kind()))
+        .collect()
+}
+
+/// Returns the symbol `name` refers to, or `None` if it's a type
+fn symbol(pdb_info: &ParsedPdb, name: NameRef) -> Option<proto::Symbol> {
+    match name {
+        NameRef::Procedure(i) => Some(procedure_symbol(&pdb_info.procedures[i])),
+        NameRef::Public(i) => Some(public_symbol(&pdb_info.public_symbols[i])),
+        NameRef::Data(i) => Some(data_symbol(pdb_info, &pdb_info.global_data[i])),
+        NameRef::Type(_) => None,
+    }
+}
+
+#[tonic::async_trait]
+impl Pdbview for Service {
+    async fn get_identity(
+        &self,
+        _request: Request<proto::GetIdentityRequest>,
+    ) -> Result<Response<proto::Identity>, Status> {
+        let identity = self
+            .query(|pdb_info| {
+                Ok(proto::Identity {
+                    path: pdb_info.path.display().to_string(),
+                    guid: pdb_info.guid.to_string(),
+                    age: pdb_info.age,
+                    timestamp: pdb_info.timestamp,
+                    debug_identifier: pdb_info.debug_identifier(),
+                    machine_type: pdb_info
+                        .machine_type
+                        .as_ref()
+                        .map(|machine_type| format!("{:?}", machine_type))
+                        .unwrap_or_default(),
+                    base_address: pdb_info.base_address as u64,
+                })
+            })
+            .await?;
+
+        Ok(Response::new(identity))
+    }
+
+    async fn find_symbol(
+        &self,
+        request: Request<proto::FindSymbolRequest>,
+    ) -> Result<Response<proto::FindSymbolResponse>, Status> {
+        let pattern = request.into_inner().pattern;
+        if pattern.is_empty() {
+            return Err(Status::invalid_argument("a pattern is required"));
+        }
+
+        let symbols = self
+            .query_indexed(move |pdb_info, names| {
+                Ok(matching_symbols(pdb_info, names, &[], &pattern))
+            })
+            .await?;
+
+        Ok(Response::new(proto::FindSymbolResponse { symbols }))
+    }
+
+    async fn resolve_address(
+        &self,
+        request: Request<proto::ResolveAddressRequest>,
+    ) -> Result<Response<proto::ResolveAddressResponse>, Status> {
+        let address = request.into_inner().address as usize;
+        let response = self
+            .query(move |pdb_info| {
+                let lookup = pdb_info.lookup_address(address);
+
+                Ok(proto::ResolveAddressResponse {
+                    procedure: lookup.procedure.map(procedure_symbol),
+                    module: lookup
+                        .module
+                        .map(|module| module.name.clone())
+                        .unwrap_or_default(),
+                    nearest_public: lookup
+                        .nearest_public
+                        .map(|(symbol, _displacement)| public_symbol(symbol)),
+                    displacement: lookup
+                        .nearest_public
+                        .map_or(0, |(_symbol, displacement)| displacement as u64),
+                })
+            })
+            .await?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_type(
+        &self,
+        request: Request<proto::GetTypeRequest>,
+    ) -> Result<Response<proto::GetTypeResponse>, Status> {
+        let name = request.into_inner().name;
+        let json = self
+            .query(move |pdb_info| {
+                let definition = pdb_info.find_type_definition(&name).ok_or_else(|| {
+                    Status::not_found(format!("no class, union, or enum named {:?}", name))
+                })?;
+
+                serde_json::to_string(&definition).map_err(|e| Status::internal(e.to_string()))
+            })
+            .await?;
+
+        Ok(Response::new(proto::GetTypeResponse { json }))
+    }
+
+    type StreamSymbolsStream =
+        tokio_stream::Iter<std::vec::IntoIter<Result<proto::Symbol, Status>>>;
+
+    async fn stream_symbols(
+        &self,
+        request: Request<proto::StreamSymbolsRequest>,
+    ) -> Result<Response<Self::StreamSymbolsStream>, Status> {
+        let request = request.into_inner();
+        let kinds = request
+            .kinds
+            .iter()
+            .map(|kind| {
+                SymbolKind::from_i32(*kind)
+                    .filter(|kind| *kind != SymbolKind::Unspecified)
+                    .ok_or_else(|| Status::invalid_argument(format!("unknown kind {}", kind)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let symbols = self
+            .query_indexed(move |pdb_info, names| {
+                Ok(matching_symbols(pdb_info, names, &kinds, &request.pattern))
+            })
+            .await?;
+
+        Ok(Response::new(tokio_stream::iter(
+            symbols.into_iter().map(Ok).collect::<Vec<_>>(),
+        )))
+    }
+}
+
+/// Serves the PDB returned by `load` on `listen` until the server fails. `load` runs on
+/// the thread which answers queries, since the PDB can't be moved between threads
+pub fn serve(
+    listen: SocketAddr,
+    load: impl FnOnce() -> anyhow::Result<ParsedPdb> + Send,
+) -> anyhow::Result<()> {
+    let (jobs, mut queue) = mpsc::channel::<Job>(JOB_QUEUE_LEN);
+
+    std::thread::scope(|scope| {
+        let (loaded, load_result) = std::sync::mpsc::channel();
+        scope.spawn(move || {
+            let loaded_pdb = load().and_then(|pdb_info| {
+                let names = NameIndex::new(&pdb_info)?;
+                Ok((pdb_info, names))
+            });
+            let (pdb_info, names) = match loaded_pdb {
+                Ok(loaded_pdb) => {
+                    let _ = loaded.send(Ok(()));
+                    loaded_pdb
+                }
+                Err(e) => {
+                    let _ = loaded.send(Err(e));
+                    return;
+                }
+            };
+
+            // Runs until the server shuts down and drops its sender
+            while let Some(job) = queue.blocking_recv() {
+                job(&pdb_info, &names);
+            }
+        });
+        load_result.recv()??;
+
+        log::info!("serving gRPC on {}", listen);
+        let runtime = tokio::runtime::Runtime::new()?;
+        runtime.block_on(
+            tonic::transport::Server::builder()
+                .add_service(PdbviewServer::new(Service { jobs }))
+                .serve(listen),
+        )?;
+
+        Ok(())
+    })
+}
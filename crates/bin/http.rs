@@ -0,0 +1,200 @@
+//! HTTP client for symbol servers and webhooks, configured with the proxy, certificate
+//! authorities, and credentials from [crate::config::Http]
+
+use crate::config::{Credentials, Http};
+use std::io::BufReader;
+use std::sync::Arc;
+use url::Url;
+
+pub struct HttpClient {
+    agent: ureq::Agent,
+    /// Credentials with their parsed `server`, which is `None` when they apply to every
+    /// server
+    credentials: Vec<(Option<Url>, Credentials)>,
+}
+
+/// Resolves `env:NAME` to the value of the environment variable `NAME`
+fn resolve_secret(value: &str) -> anyhow::Result<String> {
+    match value.strip_prefix("env:") {
+        Some(name) => std::env::var(name)
+            .map_err(|_| anyhow::anyhow!("environment variable {} is not set", name)),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Returns `url` with any user name and password replaced, for logging. Proxies are
+/// often given without a scheme, so this doesn't rely on `url` parsing as a URL
+pub fn redact_userinfo(url: &str) -> String {
+    let authority_start = url.find("://").map_or(0, |scheme_end| scheme_end + 3);
+    let authority_end = url[authority_start..]
+        .find('/')
+        .map_or(url.len(), |end| authority_start + end);
+    match url[authority_start..authority_end].rfind('@') {
+        Some(at) => format!(
+            "{}<redacted>{}",
+            &url[..authority_start],
+            &url[authority_start + at..]
+        ),
+        None => url.to_string(),
+    }
+}
+
+/// Parses the `server` of `credentials` and resolves their secrets
+fn resolve_credentials(credentials: &Credentials) -> anyhow::Result<(Option<Url>, Credentials)> {
+    let server = if credentials.server.is_empty() {
+        None
+    } else {
+        Some(Url::parse(&credentials.server).map_err(|e| {
+            anyhow::anyhow!(
+                "credentials server {:?} is not a URL: {}",
+                credentials.server,
+                e
+            )
+        })?)
+    };
+
+    let resolved = Credentials {
+        password: credentials
+            .password
+            .as_deref()
+            .map(resolve_secret)
+            .transpose()?,
+        token: credentials
+            .token
+            .as_deref()
+            .map(resolve_secret)
+            .transpose()?,
+        ..credentials.clone()
+    };
+
+    Ok((server, resolved))
+}
+
+/// Returns whether credentials for `server` are sent to `url`. The scheme, host, and
+/// port must match exactly, and `server`'s path must be a prefix of `url`'s ending on a
+/// `/` boundary, so `https://symbols.corp/private` doesn't match
+/// `https://symbols.corp/private-other` or `https://symbols.corp.evil`
+fn server_matches(server: &Url, url: &Url) -> bool {
+    if server.scheme() != url.scheme()
+        || server.host_str() != url.host_str()
+        || server.port_or_known_default() != url.port_or_known_default()
+    {
+        return false;
+    }
+
+    let prefix = server.path().trim_end_matches('/');
+    match url.path().strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Returns the TLS configuration trusting the built-in roots and those in the PEM file
+/// at `ca_bundle`
+fn tls_config(ca_bundle: &std::path::Path) -> anyhow::Result<rustls::ClientConfig> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|anchor| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+
+    let mut reader = BufReader::new(std::fs::File::open(ca_bundle)?);
+    let certificates = rustls_pemfile::certs(&mut reader)?;
+    if certificates.is_empty() {
+        anyhow::bail!("{} contains no certificates", ca_bundle.display());
+    }
+    for certificate in certificates {
+        roots.add(&rustls::Certificate(certificate)).map_err(|e| {
+            anyhow::anyhow!("invalid certificate in {}: {}", ca_bundle.display(), e)
+        })?;
+    }
+
+    Ok(rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth())
+}
+
+impl HttpClient {
+    pub fn new(settings: &Http) -> anyhow::Result<HttpClient> {
+        let mut builder = ureq::AgentBuilder::new();
+
+        let proxy = settings.proxy.clone().or_else(|| {
+            ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+                .iter()
+                .find_map(|name| std::env::var(name).ok())
+                .filter(|proxy| !proxy.is_empty())
+        });
+        if let Some(proxy) = proxy {
+            log::debug!("using proxy {}", redact_userinfo(&proxy));
+            builder = builder.proxy(ureq::Proxy::new(&proxy)?);
+        }
+        if let Some(ca_bundle) = settings.ca_bundle.as_ref() {
+            builder = builder.tls_config(Arc::new(tls_config(ca_bundle)?));
+        }
+
+        // Resolve secrets up front so a missing variable is reported before any request
+        let credentials = settings
+            .credentials
+            .iter()
+            .map(resolve_credentials)
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(HttpClient {
+            agent: builder.build(),
+            credentials,
+        })
+    }
+
+    /// Adds the headers of the credentials whose `server` matches `url`. Nothing is added
+    /// to URLs that don't parse
+    fn authenticate(&self, mut request: ureq::Request, url: &str) -> ureq::Request {
+        let url = match Url::parse(url) {
+            Ok(url) => url,
+            Err(_) => return request,
+        };
+        let applicable = self
+            .credentials
+            .iter()
+            .filter(|(server, _)| {
+                server
+                    .as_ref()
+                    .map_or(true, |server| server_matches(server, &url))
+            })
+            .map(|(_, credentials)| credentials);
+        for credentials in applicable {
+            if let Some(username) = credentials.username.as_ref() {
+                let password = credentials.password.as_deref().unwrap_or_default();
+                let encoded = base64::encode(format!("{}:{}", username, password));
+                request = request.set("Authorization", &format!("Basic {}", encoded));
+            }
+            if let Some(token) = credentials.token.as_ref() {
+                request = request.set("Authorization", &format!("Bearer {}", token));
+            }
+            for header in &credentials.headers {
+                if let Some((name, value)) = header.split_once(':') {
+                    request = request.set(name.trim(), value.trim());
+                }
+            }
+        }
+
+        request
+    }
+
+    pub fn get(&self, url: &str) -> ureq::Request {
+        self.authenticate(self.agent.get(url), url)
+    }
+
+    pub fn head(&self, url: &str) -> ureq::Request {
+        self.authenticate(self.agent.head(url), url)
+    }
+
+    /// POSTs without credentials, which are only meant for symbol servers. Used for
+    /// webhooks, which are often third-party services
+    pub fn post(&self, url: &str) -> ureq::Request {
+        self.agent.post(url)
+    }
+}
@@ -0,0 +1,55 @@
+//! Hashes of the input files, so reports record exactly which sample they describe
+
+use ezpdb::symbol_types::{FileHashes, Identity, ParsedPdb};
+use sha2::Digest;
+use std::io::Read;
+use std::path::Path;
+
+/// Computes the MD5, SHA1, and SHA256 of the file at `path` in a single pass
+pub fn hash_file(path: &Path) -> anyhow::Result<FileHashes> {
+    let mut file = std::fs::File::open(path)?;
+    let mut md5 = md5::Md5::new();
+    let mut sha1 = sha1::Sha1::new();
+    let mut sha256 = sha2::Sha256::new();
+    let mut size = 0u64;
+
+    let mut buffer = vec![0u8; 1 << 16];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        md5.update(&buffer[..read]);
+        sha1.update(&buffer[..read]);
+        sha256.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok(FileHashes {
+        path: path.to_path_buf(),
+        size,
+        md5: md5.finalize().to_vec(),
+        sha1: sha1.finalize().to_vec(),
+        sha256: sha256.finalize().to_vec(),
+    })
+}
+
+/// Records the hashes of the PDB at `path` in `pdb_info`'s identity
+pub fn identify_pdb(pdb_info: &mut ParsedPdb, path: &Path) -> anyhow::Result<()> {
+    pdb_info.identity = Some(Identity {
+        pdb: hash_file(path)?,
+        pe: None,
+    });
+
+    Ok(())
+}
+
+/// Records the hashes of the image at `path` alongside the PDB's. PDBs loaded from
+/// JSON exported without an identity are left as they are
+pub fn identify_pe(pdb_info: &mut ParsedPdb, path: &Path) -> anyhow::Result<()> {
+    if let Some(identity) = pdb_info.identity.as_mut() {
+        identity.pe = Some(hash_file(path)?);
+    }
+
+    Ok(())
+}
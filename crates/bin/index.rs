@@ -0,0 +1,195 @@
+//! Finite-state transducer index of symbol and type names, so wildcard queries over PDBs
+//! with millions of symbols only visit the names sharing the pattern's literal parts
+
+use crate::filter::wildcard_match;
+use ezpdb::symbol_types::{ParsedPdb, TypeIndexNumber};
+use ezpdb::type_info::Type;
+use fst::{Automaton, IntoStreamer, Map, MapBuilder, Streamer};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// An item with an indexed name. Ordered so that sorting matches produces the order
+/// items are listed in without an index: procedures, public symbols, data, then types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NameRef {
+    /// Index into [ParsedPdb::procedures]
+    Procedure(usize),
+    /// Index into [ParsedPdb::public_symbols]
+    Public(usize),
+    /// Index into [ParsedPdb::global_data]
+    Data(usize),
+    /// Key of a class, union, or enum in [ParsedPdb::types]
+    Type(TypeIndexNumber),
+}
+
+pub struct NameIndex {
+    /// Maps each distinct name to its position in `postings`
+    names: Map<Vec<u8>>,
+    postings: Vec<Vec<NameRef>>,
+}
+
+impl NameIndex {
+    /// Indexes the names of the PDB's procedures, public symbols, global data, and
+    /// classes, unions, and enums, including the synthesized names of anonymous types
+    pub fn new(pdb_info: &ParsedPdb) -> anyhow::Result<NameIndex> {
+        let start = Instant::now();
+
+        // The FST builder requires keys in sorted order
+        let mut by_name: BTreeMap<&str, Vec<NameRef>> = BTreeMap::new();
+        for (i, procedure) in pdb_info.procedures.iter().enumerate() {
+            by_name
+                .entry(&procedure.name)
+                .or_default()
+                .push(NameRef::Procedure(i));
+        }
+        for (i, symbol) in pdb_info.public_symbols.iter().enumerate() {
+            by_name
+                .entry(&symbol.name)
+                .or_default()
+                .push(NameRef::Public(i));
+        }
+        for (i, data) in pdb_info.global_data.iter().enumerate() {
+            by_name
+                .entry(&data.name)
+                .or_default()
+                .push(NameRef::Data(i));
+        }
+
+        // Anonymous types are indexed under their synthesized name as well
+        let types = pdb_info.types.iter().flat_map(|(index, ty)| {
+            let names = match &*ty.as_ref().borrow() {
+                Type::Class(class) => {
+                    vec![Some(class.name.clone()), class.synthesized_name.clone()]
+                }
+                Type::Union(union) => {
+                    vec![Some(union.name.clone()), union.synthesized_name.clone()]
+                }
+                Type::Enumeration(e) => vec![Some(e.name.clone()), e.synthesized_name.clone()],
+                _ => vec![],
+            };
+            names.into_iter().flatten().map(move |name| (name, *index))
+        });
+        // Type names are owned since they're behind a `RefCell`
+        let mut type_names: BTreeMap<String, Vec<NameRef>> = BTreeMap::new();
+        for (name, index) in types {
+            type_names
+                .entry(name)
+                .or_default()
+                .push(NameRef::Type(index));
+        }
+        for (name, refs) in &type_names {
+            by_name.entry(name.as_str()).or_default().extend(refs);
+        }
+
+        let mut builder = MapBuilder::memory();
+        let mut postings = Vec::with_capacity(by_name.len());
+        for (name, refs) in by_name {
+            builder.insert(name, postings.len() as u64)?;
+            postings.push(refs);
+        }
+        let names = Map::new(builder.into_inner()?)?;
+
+        log::debug!(
+            "indexed {} distinct names in {:?}",
+            postings.len(),
+            start.elapsed()
+        );
+
+        Ok(NameIndex { names, postings })
+    }
+
+    /// Returns the items whose name matches the wildcard `pattern`, in the order
+    /// described by [NameRef]
+    pub fn matches(&self, pattern: &str) -> Vec<NameRef> {
+        let mut matches = vec![];
+        let mut stream = self
+            .names
+            .search(WildcardAutomaton::new(pattern))
+            .into_stream();
+        while let Some((name, posting)) = stream.next() {
+            // The automaton treats `?` like `*`, so confirm the match
+            let matched =
+                std::str::from_utf8(name).map_or(false, |name| wildcard_match(pattern, name));
+            if matched {
+                matches.extend(&self.postings[posting as usize]);
+            }
+        }
+
+        matches.sort_unstable();
+        matches
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Byte(u8),
+    Any,
+}
+
+/// Accepts every name matching a wildcard pattern, plus some which don't: `?` matches a
+/// single character, which may be several UTF-8 bytes, so it's treated like `*`
+struct WildcardAutomaton {
+    tokens: Vec<Token>,
+}
+
+impl WildcardAutomaton {
+    fn new(pattern: &str) -> WildcardAutomaton {
+        let tokens = pattern
+            .bytes()
+            .map(|byte| match byte {
+                b'*' | b'?' => Token::Any,
+                byte => Token::Byte(byte),
+            })
+            .collect();
+
+        WildcardAutomaton { tokens }
+    }
+
+    /// Adds the positions reachable from `positions` by matching `*` against nothing
+    fn close(&self, mut positions: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < positions.len() {
+            let position = positions[i];
+            if self.tokens.get(position) == Some(&Token::Any)
+                && !positions.contains(&(position + 1))
+            {
+                positions.push(position + 1);
+            }
+            i += 1;
+        }
+
+        positions.sort_unstable();
+        positions
+    }
+}
+
+impl Automaton for WildcardAutomaton {
+    /// Positions in the pattern the input so far could have reached
+    type State = Vec<usize>;
+
+    fn start(&self) -> Vec<usize> {
+        self.close(vec![0])
+    }
+
+    fn is_match(&self, state: &Vec<usize>) -> bool {
+        state.contains(&self.tokens.len())
+    }
+
+    fn can_match(&self, state: &Vec<usize>) -> bool {
+        !state.is_empty()
+    }
+
+    fn accept(&self, state: &Vec<usize>, byte: u8) -> Vec<usize> {
+        let mut next = vec![];
+        for &position in state {
+            match self.tokens.get(position) {
+                Some(Token::Any) => next.push(position),
+                Some(Token::Byte(expected)) if *expected == byte => next.push(position + 1),
+                _ => {}
+            }
+        }
+        next.dedup();
+
+        self.close(next)
+    }
+}
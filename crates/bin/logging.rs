@@ -0,0 +1,74 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::cell::RefCell;
+
+thread_local! {
+    /// Warnings captured for inclusion in the output document. Kept per thread since
+    /// `--jobs` parses several PDBs at once
+    static CAPTURED_WARNINGS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+struct Logger {
+    level: LevelFilter,
+    json: bool,
+    capture_warnings: bool,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level || (self.capture_warnings && metadata.level() <= Level::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.capture_warnings && record.level() <= Level::Warn {
+            CAPTURED_WARNINGS
+                .with(|warnings| warnings.borrow_mut().push(record.args().to_string()));
+        }
+
+        if record.level() > self.level {
+            return;
+        }
+
+        if self.json {
+            let entry = serde_json::json!({
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            eprintln!("{}", entry);
+        } else {
+            eprintln!(
+                "[{}] {}: {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the global logger. Records at or above `level` are written to stderr as
+/// text or JSON lines. If `capture_warnings` is set, warnings are also retained so they
+/// can be retrieved with [take_warnings] regardless of `level`
+pub fn init(level: LevelFilter, json: bool, capture_warnings: bool) -> anyhow::Result<()> {
+    let max_level = if capture_warnings {
+        level.max(LevelFilter::Warn)
+    } else {
+        level
+    };
+
+    log::set_boxed_logger(Box::new(Logger {
+        level,
+        json,
+        capture_warnings,
+    }))?;
+    log::set_max_level(max_level);
+
+    Ok(())
+}
+
+/// Returns and clears the warnings captured on this thread since the last call
+pub fn take_warnings() -> Vec<String> {
+    CAPTURED_WARNINGS.with(|warnings| std::mem::take(&mut *warnings.borrow_mut()))
+}
@@ -1,9 +1,48 @@
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use structopt::clap::AppSettings;
 use structopt::StructOpt;
 use thiserror::Error;
 
+mod batch;
+mod browser;
+mod config;
+#[cfg(all(windows, feature = "dbghelp"))]
+mod dbghelp;
+mod dedupe;
+mod demangle;
+mod disasm;
+mod filter;
+mod group;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod http;
+mod identity;
+mod index;
+mod logging;
+mod namespace;
 mod output;
+mod pe;
+mod pe_only;
+mod profile;
+mod progress;
+mod query;
+mod remote;
+mod repl;
+mod rtti;
+mod signature;
+mod sort;
+mod spill;
+mod symsrv;
+mod symstore;
+mod template;
+mod watch;
+mod writer;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: profile::CountingAllocator = profile::CountingAllocator;
 
 #[derive(Error, Debug)]
 pub enum CliArgumentError {
@@ -12,30 +51,804 @@ pub enum CliArgumentError {
 }
 
 #[derive(StructOpt, Debug)]
-#[structopt(name = "pdbview")]
+#[structopt(name = "pdbview", setting = AppSettings::SubcommandsNegateReqs)]
 struct Opt {
-    /// Print debug information
-    #[structopt(short, long)]
+    /// Print debug information. Equivalent to `--log-level debug`
+    #[structopt(short, long, global = true)]
     debug: bool,
 
-    /// Output format type. Options include: plain, json
-    #[structopt(short, long, default_value = "plain")]
+    /// Minimum level of log messages written to stderr: off, error, warn, info, debug,
+    /// or trace
+    #[structopt(long, default_value = "off", global = true)]
+    log_level: log::LevelFilter,
+
+    /// Write log messages to stderr as JSON lines
+    #[structopt(long, global = true)]
+    log_json: bool,
+
+    /// Include warnings raised while parsing in the output's `warnings` field so
+    /// consumers can detect incomplete results
+    #[structopt(long, global = true)]
+    warnings_in_output: bool,
+
+    /// Output format type. Options include: plain, json, table, html, markdown, map, def,
+    /// x64dbg, windbg, windbg-js, radare2, frida, frida-json, gsym, breakpad, boundaries,
+    /// drcov, pat, dot-callgraph, parquet (with the `parquet-format` feature), xml, ctags,
+    /// etags, compile-commands, namespace-tree, rust, ctypes, isf
+    /// [default: plain]
+    #[structopt(short, long = "format", global = true)]
+    format_arg: Option<OutputFormatType>,
+
+    /// Output format after applying the config file's default
+    #[structopt(skip)]
     format: OutputFormatType,
 
-    /// Base address of module in-memory. If provided, all "offset" fields
-    /// will be added to the provided base address
-    #[structopt(short, long)]
-    base_address: Option<usize>,
+    /// Order of symbols, modules' lines, and table rows in every output: address
+    /// (the default, ties broken by name), name, or size
+    #[structopt(long, default_value = "address", global = true)]
+    sort: sort::SortKey,
+
+    /// Reverse the sort order
+    #[structopt(long, global = true)]
+    reverse: bool,
+
+    /// Nest procedures, globals, and used types under the module which contributed
+    /// them in plain and JSON output. Options include: module
+    #[structopt(long, global = true)]
+    group_by: Option<group::GroupBy>,
+
+    /// Whether to color table output: auto, always, or never
+    #[structopt(long, default_value = "auto", global = true)]
+    color: ColorChoice,
+
+    /// Only include functions reachable from functions matching this wildcard pattern
+    /// in the dot-callgraph output
+    #[structopt(long, global = true)]
+    root: Option<String>,
+
+    /// Maximum number of calls to follow from `--root` in the dot-callgraph output
+    #[structopt(long, global = true, requires = "root")]
+    depth: Option<usize>,
+
+    /// Configuration file to load instead of `./pdbview.toml` or
+    /// `~/.config/pdbview/pdbview.toml`
+    #[structopt(long, parse(from_os_str), global = true)]
+    config: Option<PathBuf>,
+
+    #[structopt(skip)]
+    settings: config::Config,
+
+    /// Proxy to reach symbol servers through, e.g. `http://proxy:8080`
+    #[structopt(long, global = true)]
+    proxy: Option<String>,
+
+    /// PEM file of certificate authorities to trust for symbol servers, in addition to
+    /// the built-in ones
+    #[structopt(long, parse(from_os_str), global = true)]
+    ca_bundle: Option<PathBuf>,
+
+    /// `user:password` for HTTP basic authentication with symbol servers. Sent to every
+    /// server queried; use `credentials` in `pdbview.toml` to limit it to some
+    #[structopt(long, global = true)]
+    server_auth: Option<String>,
+
+    /// Bearer token for symbol servers, sent to every server queried
+    #[structopt(long, global = true)]
+    server_token: Option<String>,
+
+    /// Header such as `X-Api-Key: secret` to send to every symbol server queried. May be
+    /// repeated
+    #[structopt(long, global = true)]
+    server_header: Vec<String>,
+
+    /// Address the image is loaded at, e.g. `0x140000000`. Every address in the output
+    /// is rebased to it. Without this, PDBs produce RVAs and JSON input keeps the base
+    /// it was exported with
+    #[structopt(
+        short,
+        long,
+        alias = "base-address",
+        parse(try_from_str = parse_number),
+        conflicts_with = "rva-only",
+        global = true
+    )]
+    base: Option<usize>,
+
+    /// Output RVAs, including for JSON input which was exported with `--base`
+    #[structopt(long, global = true)]
+    rva_only: bool,
+
+    /// Layout version of JSON output. Older versions drop fields added since then
+    /// so existing consumers keep working. Defaults to the latest version
+    #[structopt(long, global = true)]
+    format_version: Option<u32>,
+
+    /// Report parsing progress on stderr as a progress bar (`bar`) or as JSON events
+    /// (`json`)
+    #[structopt(long, global = true)]
+    progress: Option<progress::ProgressKind>,
+
+    /// Write output to this file instead of stdout. Output is compressed if the file
+    /// name ends in `.gz` (gzip) or `.zst` (zstd)
+    #[structopt(short, long, parse(from_os_str), global = true)]
+    output: Option<PathBuf>,
+
+    /// PE image the PDB belongs to. Enables output which requires data from the image
+    #[structopt(long, parse(from_os_str), global = true)]
+    pe: Option<PathBuf>,
+
+    /// Only output symbols whose name matches this wildcard pattern (`*` and `?`).
+    /// May be provided multiple times
+    #[structopt(short, long = "name", global = true, number_of_values = 1)]
+    names: Vec<String>,
+
+    /// Only parse these kinds of symbols: function, data, public, thunk, local, export.
+    /// Separate kinds with commas and prefix a kind with `-` to exclude it instead,
+    /// e.g. `--kinds data` or `--kinds=-public,-local`
+    #[structopt(long, global = true, allow_hyphen_values = true)]
+    kinds: Option<filter::KindFilter>,
+
+    /// Only parse the symbols and line information of modules whose name or object file
+    /// matches this wildcard pattern, e.g. `--module '*mydriver.obj'`. The module info
+    /// streams of all other modules are never read. May be provided multiple times
+    #[structopt(long = "module", global = true, number_of_values = 1)]
+    modules: Vec<String>,
+
+    /// Only convert the types referenced by parsed symbols, the types matching `--name`,
+    /// and the types named by `dt` and `offsets`, instead of the whole type stream.
+    /// Speeds up targeted lookups in large PDBs, especially with `--module`
+    #[structopt(long, global = true)]
+    lazy_types: bool,
+
+    /// Output each class, union, and enum once. Identical definitions and forward
+    /// references to a definition are left out of `types` and listed in `type_aliases`
+    /// with the index of the type kept in their place
+    #[structopt(long, global = true)]
+    dedupe_types: bool,
 
-    /// PDB file to process
-    #[structopt(name = "FILE", parse(from_os_str))]
-    file: PathBuf,
+    /// List exception handling and unwind funclets, such as `catch$0` and `dtor$2`
+    /// blocks, under the procedure they were split out of instead of as procedures of
+    /// their own
+    #[structopt(long, global = true)]
+    nest_funclets: bool,
+
+    /// Keep Rust-mangled (`_ZN...`/`_R...`) public and procedure names as they appear in
+    /// the PDB instead of demangling them
+    #[structopt(long, global = true)]
+    no_demangle: bool,
+
+    /// Attach the undecoded bytes, as hex, of records which failed to parse and of
+    /// records matching `--name`
+    #[structopt(long, global = true)]
+    raw: bool,
+
+    /// Include the strings of the PDB's `/names` string table, which often holds paths
+    /// and identifiers no parsed record refers to
+    #[structopt(long, global = true)]
+    string_table: bool,
+
+    /// Leave out symbols matching the wildcard patterns in this file, one per line.
+    /// `builtin` selects a list of CRT, STL, and compiler-generated symbols. May be
+    /// provided multiple times
+    #[structopt(long, global = true, number_of_values = 1)]
+    exclude: Vec<String>,
+
+    #[structopt(skip)]
+    exclude_patterns: Vec<String>,
+
+    /// Rewrite source paths starting with `from` to start with `to` instead, e.g.
+    /// `--path-map 'D:\a\_work=/src'`. May be provided multiple times; the first
+    /// matching rule applies
+    #[structopt(long, global = true, number_of_values = 1)]
+    path_map: Vec<filter::PathMapping>,
+
+    /// Only output the items selected by this query, e.g.
+    /// `procedures[len > 0x1000 && name =~ /Crypt/]`. Fields may be compared with
+    /// `==`, `!=`, `<`, `<=`, `>`, `>=` and matched against regexes with `=~` and `!~`
+    #[structopt(long)]
+    query: Option<query::Query>,
+
+    /// File listing additional PDBs to process, one path per line. Blank lines and lines
+    /// starting with `#` are ignored and relative paths are relative to the manifest
+    #[structopt(long, parse(from_os_str))]
+    manifest: Option<PathBuf>,
+
+    /// With multiple PDBs or `watch`, write each PDB's output to its own file in this
+    /// directory
+    #[structopt(long, parse(from_os_str), conflicts_with = "combined", global = true)]
+    output_dir: Option<PathBuf>,
+
+    /// With multiple PDBs, emit a single JSON document keyed by module name
+    #[structopt(long)]
+    combined: bool,
+
+    /// Keep at most about this many bytes in memory while parsing, e.g. `4G`. Past the
+    /// limit, the procedures and lines of each remaining module are written to a
+    /// temporary file and streamed back into the output. Requires the json format and a
+    /// build with the `alloc-stats` feature, and spilled procedures are written in PDB
+    /// order after the sorted ones
+    #[structopt(
+        long,
+        parse(try_from_str = parse_size),
+        conflicts_with_all = &["combined", "output-dir"]
+    )]
+    max_memory: Option<u64>,
+
+    /// With multiple PDBs, parse and write this many at once. Each PDB being processed
+    /// is held in memory, and without `--output-dir` so is its finished output until
+    /// the PDBs before it are written
+    #[structopt(short, long, default_value = "1", conflicts_with = "combined")]
+    jobs: usize,
+
+    /// PDB files to process. JSON previously exported by `--format json` is also accepted,
+    /// including gzip or zstd compressed as `.json.gz` or `.json.zst`
+    #[structopt(name = "FILE", parse(from_os_str), required_unless = "manifest")]
+    files: Vec<PathBuf>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
 }
 
+impl Opt {
+    /// Options for parsing PDBs
+    fn parse_options(&self) -> ezpdb::ParseOptions {
+        let mut kinds = self.kinds.as_ref().map(|kinds| kinds.0.clone());
+        // Skip parsing the symbols a query would discard
+        if let Some(query) = self.query.as_ref() {
+            let queried = query.symbol_kinds();
+            kinds = Some(match kinds {
+                Some(kinds) => kinds.intersection(&queried).copied().collect(),
+                None => queried,
+            });
+        }
+
+        ezpdb::ParseOptions {
+            kinds,
+            raw: self.raw,
+            string_table: self.string_table,
+            modules: self.modules.clone(),
+            lazy_types: self.lazy_types,
+            type_names: self.requested_type_names(),
+        }
+    }
+
+    /// Names of the types output was asked for, which are resolved even with `--lazy-types`
+    fn requested_type_names(&self) -> Vec<String> {
+        let mut names = self.names.clone();
+        match &self.command {
+            Some(Command::Dt { type_name, .. }) => names.push(type_name.clone()),
+            // Items are either a class or `Class::member`
+            Some(Command::Offsets { items, .. }) => {
+                for item in items {
+                    names.push(item.clone());
+                    if let Some(idx) = item.rfind("::") {
+                        names.push(item[..idx].to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        names
+    }
+
+    /// Base address to rebase loaded PDBs to, if any
+    fn base_address(&self) -> Option<usize> {
+        if self.rva_only {
+            Some(0)
+        } else {
+            self.base
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Resolve an address to its owning procedure, module, and nearest public symbol
+    Lookup {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Address to resolve. Accepts decimal or `0x`-prefixed hexadecimal
+        #[structopt(name = "ADDRESS", parse(try_from_str = parse_number))]
+        address: usize,
+    },
+    /// Disassemble a procedure, annotated with source lines and local variables. Requires --pe
+    Disasm {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Name of the procedure to disassemble. Accepts `*` and `?` wildcards
+        #[structopt(name = "FUNCTION")]
+        function: String,
+    },
+    /// Combine several PDBs loaded at different base addresses into a single address space.
+    /// Prints every symbol sorted by address, or symbolizes a list of addresses
+    Merge {
+        /// PDBs and the base address their image is loaded at, as `PATH@BASE`
+        #[structopt(name = "PDB@BASE", required = true)]
+        modules: Vec<ModuleSpec>,
+
+        /// File containing one address per line to symbolize, or `-` for stdin
+        #[structopt(long, parse(from_os_str))]
+        symbolize: Option<PathBuf>,
+    },
+    /// Map the addresses of procedures, public symbols, and globals in the first PDB to
+    /// their addresses in later builds, for porting annotations, hooks, and offsets
+    Drift {
+        /// PDBs of the same project, oldest first
+        #[structopt(name = "FILE", parse(from_os_str), min_values = 2, required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Print the layout of a class, union, or enum like WinDbg's `dt`. With an address and
+    /// `--mem`, the bytes at that offset of the file are displayed as an instance of the type
+    Dt {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Name of the type, optionally prefixed with `module!`
+        #[structopt(name = "TYPE")]
+        type_name: String,
+
+        /// Offset of the instance within the `--mem` file
+        #[structopt(name = "ADDRESS", parse(try_from_str = parse_number), requires = "mem")]
+        address: Option<usize>,
+
+        /// Memory dump or other file holding the instance
+        #[structopt(long, parse(from_os_str), requires = "ADDRESS")]
+        mem: Option<PathBuf>,
+
+        /// Expand nested classes and unions this many levels deep, like `dt -r`
+        #[structopt(short, long, default_value = "0")]
+        recurse: usize,
+    },
+    /// Generate a C++ or Rust header of member offsets, vtable indices, and sizes for the
+    /// given classes, to be regenerated whenever the application is updated
+    Offsets {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Classes to include, or single members and virtual methods as `Class::member`
+        #[structopt(name = "ITEM", required = true)]
+        items: Vec<String>,
+
+        /// Language of the header: cpp or rust
+        #[structopt(long, default_value = "cpp")]
+        lang: output::HeaderLanguage,
+    },
+    /// Categorize the procedures of a kernel or driver PDB: system services with both `Nt`
+    /// and `Zw` entry points, DPC routines, and candidate IRP dispatch routines
+    Kernel {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// List the system call numbers found in the `Nt`/`Zw` stubs of ntdll or the kernel
+    /// image, along with their names and RVAs. Requires `--pe`
+    Syscalls {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Cross-reference the image's Control Flow Guard function table with the PDB's
+    /// symbols and list valid indirect call targets with no symbol. Requires `--pe`
+    Cfg {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Report the ranges of executable sections no procedure covers, such as hand-written
+    /// stubs, packed code, or modules without debug info. Requires `--pe`
+    Gaps {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Smallest gap to report, in bytes. Alignment padding is never reported
+        #[structopt(long, default_value = "16")]
+        min_size: usize,
+    },
+    /// Cross-reference the image's exports and imports with the PDB's symbols, listing
+    /// exports without symbols and imports by DLL. Requires `--pe`
+    Interface {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Summarize security-relevant build settings as pass/fail checks: `/GS`, `/sdl`, and
+    /// `/guard:cf` per module, and Control Flow Guard, SafeSEH, and sanitizer runtimes
+    /// for the image. Image checks are more reliable with `--pe`
+    Audit {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// List every timestamp reachable through the PDB, and through the image when `--pe`
+    /// is given, from oldest to newest
+    Timeline {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Group procedures by the class or function template they were instantiated from,
+    /// with the number of functions and bytes each template and instantiation accounts for
+    Templates {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Only include instantiations whose normalized name matches this wildcard
+        /// pattern, e.g. `std::vector<*>`
+        #[structopt(long)]
+        matching: Option<String>,
+    },
+    /// List each class's methods with their signatures, virtuality, vtable slots, and the
+    /// procedures implementing them
+    Methods {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Only include classes whose name matches this wildcard pattern
+        #[structopt(long)]
+        class: Option<String>,
+
+        /// Only include virtual methods
+        #[structopt(long = "virtual")]
+        virtual_only: bool,
+
+        /// Only include methods with at least one implementing procedure
+        #[structopt(long = "implemented")]
+        implemented_only: bool,
+    },
+    /// Recover the vtables of polymorphic classes from the MSVC RTTI in the PE image and
+    /// correlate them with the PDB's classes. Requires `--pe`
+    Rtti {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Only include vtables which no public symbol names
+        #[structopt(long)]
+        unnamed_only: bool,
+    },
+    /// List the dynamic initializers and atexit destructors of globals in the order they
+    /// run. With `--pe`, initializer table entries are resolved through the image
+    Initializers {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Hash the local copies of the PDB's source files and compare them to the checksums
+    /// recorded at build time
+    VerifySrc {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Directory the sources are checked out in. Files are found by the trailing
+        /// components of the paths recorded in the PDB
+        #[structopt(long, parse(from_os_str))]
+        src_root: PathBuf,
+    },
+    /// Manage a symbol store in the directory layout of `symstore.exe`
+    Store(StoreCommand),
+    /// Query symbol servers for symbols
+    Srv(SrvCommand),
+    /// Parse the PDB with both pdbview and Windows' dbghelp and report where their
+    /// identities, symbol names, addresses, or procedure sizes disagree. Exits with an
+    /// error if any do
+    #[cfg(all(windows, feature = "dbghelp"))]
+    CrossCheck {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Serve the PDB over the gRPC API defined in `proto/pdbview.proto` until stopped
+    #[cfg(feature = "grpc")]
+    ServeGrpc {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Address to listen on
+        #[structopt(long, default_value = "127.0.0.1:50051")]
+        listen: std::net::SocketAddr,
+    },
+    /// Watch a directory for PDBs and PE images, and parse each as it arrives. Output is
+    /// written to stdout, or with `--output-dir` to a file per input, and a summary of
+    /// each file is POSTed to the webhook if one is configured
+    Watch {
+        /// Directory to watch, including its subdirectories
+        #[structopt(name = "DIR", parse(from_os_str))]
+        dir: PathBuf,
+
+        /// URL to POST a JSON summary of each parsed file to. Overrides `watch.webhook`
+        /// in `pdbview.toml`
+        #[structopt(long)]
+        webhook: Option<String>,
+
+        /// Seconds a file must go unmodified before it is parsed, so files which are
+        /// still being copied aren't read
+        #[structopt(long, default_value = "2")]
+        settle: u64,
+    },
+    /// Report which static libraries contributed which object files, and the number of
+    /// functions and bytes each accounts for
+    Libs {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Validate the MSF container's superblock, free page map, stream directory, and
+    /// stream block lists. Exits with an error if the PDB cannot be read correctly
+    Check {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Write the contents of an MSF stream verbatim, e.g. to `-o FILE`
+    DumpStream {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// Stream index, one of `pdb`, `tpi`, `dbi`, or `ipi`, or a named stream such as
+        /// `/names`
+        #[structopt(name = "STREAM")]
+        stream: String,
+    },
+    /// Report the number and total size of functions in each namespace, class, or module,
+    /// largest first
+    SizeReport {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+
+        /// What to group functions by: namespace, class, or module
+        #[structopt(long, default_value = "namespace")]
+        by: namespace::SizeGroup,
+
+        /// Write CSV instead of a table. `--format json` writes JSON
+        #[structopt(long)]
+        csv: bool,
+    },
+    /// Report the time and memory allocated while parsing each part of the PDB. Memory
+    /// is only counted when built with the `alloc-stats` feature
+    Profile {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Report record counts and sizes by leaf kind for the TPI and IPI streams, along
+    /// with their header and hash information
+    TypeStats {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Explore the PDB with interactive queries such as `type`, `sym`, `addr`, and `module`
+    Repl {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+    /// Print the configuration loaded from `pdbview.toml`
+    Config,
+    /// Print a JSON Schema describing the output of `--format json`
+    Schema,
+    /// Browse modules, symbols, and types in an interactive terminal UI
+    Tui {
+        /// PDB file to process
+        #[structopt(name = "FILE", parse(from_os_str))]
+        file: PathBuf,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum SrvCommand {
+    /// Report which of the configured symbol servers hold the symbols for a PE image, a
+    /// PDB, or an index path such as `foo.pdb/<GUID><age>`, without downloading them.
+    /// Checks the cache directory first, then each server in order
+    Check {
+        /// PE image, PDB, or index path to look up
+        #[structopt(name = "TARGET")]
+        target: String,
+
+        /// Symbol server URL or directory to check instead of those in `pdbview.toml`.
+        /// May be repeated
+        #[structopt(long)]
+        server: Vec<String>,
+    },
+    /// Download the symbols for PE images, PDBs, or index paths such as
+    /// `foo.pdb/<GUID><age>` from the first symbol server which has them. Downloads run
+    /// `--jobs` at a time, are retried on transient failures, continue from where an
+    /// interrupted run stopped, and are checked against the requested GUID and age or
+    /// timestamp and image size before being kept
+    Fetch {
+        /// PE images, PDBs, or index paths to download symbols for
+        #[structopt(name = "TARGET")]
+        targets: Vec<String>,
+
+        /// File listing additional targets, one per line
+        #[structopt(long, parse(from_os_str))]
+        list: Option<PathBuf>,
+
+        /// Symbol server URL or directory to download from instead of those in
+        /// `pdbview.toml`. May be repeated
+        #[structopt(long)]
+        server: Vec<String>,
+
+        /// Store to download into. Defaults to the configured cache directory
+        #[structopt(long, parse(from_os_str))]
+        destination: Option<PathBuf>,
+
+        /// Times to retry a download after a transient failure
+        #[structopt(long, default_value = "3")]
+        retries: u32,
+    },
+    /// Read the identity of a PDB on a symbol server, and optionally look up public
+    /// symbols, using HTTP range requests so only the parts needed are downloaded
+    Probe {
+        /// URL of the PDB, or an index path such as `foo.pdb/<GUID><age>` to find on the
+        /// configured symbol servers
+        #[structopt(name = "TARGET")]
+        target: String,
+
+        /// Look up the public symbols whose name matches this wildcard pattern. Reads
+        /// the PDB's symbol records stream
+        #[structopt(long)]
+        public: Option<String>,
+
+        /// Symbol server URL to look the index path up on instead of those in
+        /// `pdbview.toml`. May be repeated
+        #[structopt(long)]
+        server: Vec<String>,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+enum StoreCommand {
+    /// Copy PDBs and PE images into a symbol store so symbol servers and debuggers can
+    /// find them by their GUID and age or timestamp and image size
+    Add {
+        /// PDB or PE files to add
+        #[structopt(name = "FILE", parse(from_os_str), required = true)]
+        files: Vec<PathBuf>,
+
+        /// Root directory of the symbol store. Created if it doesn't exist
+        #[structopt(long, parse(from_os_str))]
+        store: PathBuf,
+
+        /// Product name recorded with the transaction
+        #[structopt(long, default_value = "")]
+        product: String,
+
+        /// Product version recorded with the transaction
+        #[structopt(long = "product-version", default_value = "")]
+        product_version: String,
+
+        /// Comment recorded with the transaction
+        #[structopt(long, default_value = "")]
+        comment: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_ref() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(CliArgumentError::InvalidValue("color", s.to_string())),
+        }
+    }
+}
+
+/// A PDB and the base address of its image, parsed from `PATH@BASE`
 #[derive(Debug)]
+struct ModuleSpec {
+    path: PathBuf,
+    base_address: usize,
+}
+
+impl FromStr for ModuleSpec {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let idx = s
+            .rfind('@')
+            .ok_or_else(|| CliArgumentError::InvalidValue("PDB@BASE", s.to_string()))?;
+
+        Ok(ModuleSpec {
+            path: PathBuf::from(&s[..idx]),
+            base_address: parse_number(&s[idx + 1..])?,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
 enum OutputFormatType {
+    #[default]
     Plain,
     Json,
+    Table,
+    Html,
+    Markdown,
+    Map,
+    Def,
+    X64dbg,
+    Windbg,
+    WindbgJs,
+    Radare2,
+    Frida,
+    FridaJson,
+    Gsym,
+    Breakpad,
+    Boundaries,
+    Drcov,
+    Pat,
+    DotCallgraph,
+    #[cfg(feature = "parquet-format")]
+    Parquet,
+    Xml,
+    Ctags,
+    Etags,
+    CompileCommands,
+    NamespaceTree,
+    Rust,
+    Ctypes,
+    Isf,
+}
+
+impl OutputFormatType {
+    /// File extension used when writing this format to a file
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormatType::Plain => "txt",
+            OutputFormatType::Json => "json",
+            OutputFormatType::Table => "txt",
+            OutputFormatType::Html => "html",
+            OutputFormatType::Markdown => "md",
+            OutputFormatType::Map => "map",
+            OutputFormatType::Def => "def",
+            OutputFormatType::X64dbg => "dd64",
+            OutputFormatType::Windbg => "wds",
+            OutputFormatType::WindbgJs => "js",
+            OutputFormatType::Radare2 => "r2",
+            OutputFormatType::Frida => "ts",
+            OutputFormatType::FridaJson => "frida.json",
+            OutputFormatType::Gsym => "gsym",
+            OutputFormatType::Breakpad => "sym",
+            OutputFormatType::Boundaries => "tsv",
+            OutputFormatType::Drcov => "drcov.log",
+            OutputFormatType::Pat => "pat",
+            OutputFormatType::DotCallgraph => "dot",
+            #[cfg(feature = "parquet-format")]
+            OutputFormatType::Parquet => "parquet",
+            OutputFormatType::Xml => "xml",
+            OutputFormatType::Ctags => "tags",
+            OutputFormatType::Etags => "TAGS",
+            OutputFormatType::CompileCommands => "json",
+            OutputFormatType::NamespaceTree => "txt",
+            OutputFormatType::Rust => "rs",
+            OutputFormatType::Ctypes => "py",
+            OutputFormatType::Isf => "json",
+        }
+    }
 }
 
 impl FromStr for OutputFormatType {
@@ -45,6 +858,33 @@ impl FromStr for OutputFormatType {
         let result = match s.to_ascii_lowercase().as_ref() {
             "plain" => OutputFormatType::Plain,
             "json" => OutputFormatType::Json,
+            "table" => OutputFormatType::Table,
+            "html" => OutputFormatType::Html,
+            "markdown" | "md" => OutputFormatType::Markdown,
+            "map" => OutputFormatType::Map,
+            "def" => OutputFormatType::Def,
+            "x64dbg" => OutputFormatType::X64dbg,
+            "windbg" => OutputFormatType::Windbg,
+            "windbg-js" => OutputFormatType::WindbgJs,
+            "radare2" | "rizin" => OutputFormatType::Radare2,
+            "frida" => OutputFormatType::Frida,
+            "frida-json" => OutputFormatType::FridaJson,
+            "gsym" => OutputFormatType::Gsym,
+            "breakpad" | "sym" => OutputFormatType::Breakpad,
+            "boundaries" => OutputFormatType::Boundaries,
+            "drcov" => OutputFormatType::Drcov,
+            "pat" => OutputFormatType::Pat,
+            "dot-callgraph" => OutputFormatType::DotCallgraph,
+            #[cfg(feature = "parquet-format")]
+            "parquet" => OutputFormatType::Parquet,
+            "xml" => OutputFormatType::Xml,
+            "ctags" => OutputFormatType::Ctags,
+            "etags" => OutputFormatType::Etags,
+            "compile-commands" => OutputFormatType::CompileCommands,
+            "namespace-tree" => OutputFormatType::NamespaceTree,
+            "rust" => OutputFormatType::Rust,
+            "ctypes" => OutputFormatType::Ctypes,
+            "isf" => OutputFormatType::Isf,
             _ => return Err(CliArgumentError::InvalidValue("format", s.to_string())),
         };
 
@@ -52,22 +892,953 @@ impl FromStr for OutputFormatType {
     }
 }
 
-fn main() -> anyhow::Result<()> {
-    let opt = Opt::from_args();
+/// Parses a number which may be provided in decimal or `0x`-prefixed hexadecimal
+fn parse_number(s: &str) -> Result<usize, CliArgumentError> {
+    let result = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
 
-    if opt.debug {
-        simplelog::SimpleLogger::init(log::LevelFilter::Debug, simplelog::Config::default())?;
+    result.map_err(|_| CliArgumentError::InvalidValue("number", s.to_string()))
+}
+
+/// Parses a byte count with an optional binary `K`, `M`, or `G` suffix, e.g. `512M`
+fn parse_size(s: &str) -> Result<u64, CliArgumentError> {
+    let invalid = || CliArgumentError::InvalidValue("size", s.to_string());
+    let (number, shift) = match s.char_indices().last() {
+        Some((i, 'k')) | Some((i, 'K')) => (&s[..i], 10),
+        Some((i, 'm')) | Some((i, 'M')) => (&s[..i], 20),
+        Some((i, 'g')) | Some((i, 'G')) => (&s[..i], 30),
+        _ => (s, 0),
+    };
+
+    let number: u64 = number.trim().parse().map_err(|_| invalid())?;
+    number.checked_mul(1 << shift).ok_or_else(invalid)
+}
+
+/// Whether `path` is named like JSON output, optionally compressed by `-o`
+fn is_exported_json(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    [".json", ".json.gz", ".json.zst"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+}
+
+/// Parses `path` as a PDB, or loads it as previously exported JSON output if it has a
+/// `.json`, `.json.gz`, or `.json.zst` extension, or recovers what it can from a PE image if given one instead of
+/// its PDB, then rebases it to `base_address` if provided
+fn load_pdb(
+    path: &Path,
+    base_address: Option<usize>,
+    opt: &Opt,
+) -> anyhow::Result<ezpdb::ParsedPdb> {
+    let options = opt.parse_options();
+    let mut parsed_pdb = if is_exported_json(path) {
+        let file = writer::open_decompressed(path)?;
+        let mut parsed_pdb: ezpdb::ParsedPdb = serde_json::from_reader(file)
+            .map_err(|e| anyhow::anyhow!("invalid JSON in {}: {}", path.display(), e))?;
+        filter::retain_kinds(&mut parsed_pdb, &options);
+
+        parsed_pdb
+    } else if pe::is_pe(path)? {
+        let mut parsed_pdb = pe_only::parse_pe(path)?;
+        filter::retain_kinds(&mut parsed_pdb, &options);
+
+        parsed_pdb
+    } else {
+        let mut parsed_pdb = match opt.progress {
+            Some(kind) => {
+                ezpdb::parse_pdb_with_options(path, &options, &mut progress::reporter(kind))?
+            }
+            None => ezpdb::parse_pdb_with_options(path, &options, &mut |_event| {})?,
+        };
+        // Only populated with --warnings-in-output
+        parsed_pdb.warnings = logging::take_warnings();
+        identity::identify_pdb(&mut parsed_pdb, path)?;
+
+        parsed_pdb
+    };
+
+    if let Some(base_address) = base_address {
+        parsed_pdb.rebase(base_address);
+    }
+
+    Ok(parsed_pdb)
+}
+
+/// Reads the PDB paths listed in a manifest file
+fn read_manifest(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    let contents = std::fs::read_to_string(path)?;
+
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect())
+}
+
+/// Loads a PDB and applies the filters requested on the command line
+fn load_and_filter(
+    opt: &Opt,
+    file: &Path,
+    pe: Option<&pe::PeInfo>,
+) -> anyhow::Result<ezpdb::ParsedPdb> {
+    let mut parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+    filter_pdb(opt, &mut parsed_pdb, pe)?;
+
+    Ok(parsed_pdb)
+}
+
+/// Applies the filters requested on the command line to a loaded PDB
+fn filter_pdb(
+    opt: &Opt,
+    parsed_pdb: &mut ezpdb::ParsedPdb,
+    pe: Option<&pe::PeInfo>,
+) -> anyhow::Result<()> {
+    if let Some(pe) = pe {
+        identity::identify_pe(parsed_pdb, &pe.path)?;
+    }
+    if opt.dedupe_types {
+        dedupe::collapse_duplicate_types(parsed_pdb)?;
+    }
+    if opt.nest_funclets {
+        let nested = parsed_pdb.nest_funclets();
+        log::debug!("nested {} funclets under their parent procedures", nested);
+    }
+    // IDs are derived from the names as stored in the PDB, so assign them before demangling
+    signature::assign_stable_ids(parsed_pdb);
+    if !opt.no_demangle {
+        demangle::demangle_names(parsed_pdb);
+    }
+    filter::retain_matching_names(parsed_pdb, &opt.names);
+    filter::remove_matching_names(parsed_pdb, &opt.exclude_patterns);
+    filter::remap_paths(parsed_pdb, &opt.path_map);
+    filter::redact(parsed_pdb, &opt.settings.redaction)?;
+    if let Some(query) = opt.query.as_ref() {
+        query.apply(parsed_pdb);
+    }
+    if let Some(pe) = pe {
+        signature::hash_functions(parsed_pdb, pe);
+        signature::mark_cfg_targets(parsed_pdb, pe);
     }
+    sort::sort_pdb(parsed_pdb, opt.sort, opt.reverse);
 
-    let parsed_pdb = ezpdb::parse_pdb(&opt.file, opt.base_address)?;
-    assert!(!parsed_pdb.global_data.is_empty());
-    let stdout = std::io::stdout();
-    let mut stdout_lock = stdout.lock();
+    Ok(())
+}
+
+/// Applies the filters which only look at individual procedures to those `--max-memory`
+/// moved out of the PDB while parsing
+fn filter_spilled(opt: &Opt, chunk: &mut ezpdb::ParsedPdb) {
+    if !opt.no_demangle {
+        demangle::demangle_names(chunk);
+    }
+    filter::retain_matching_names(chunk, &opt.names);
+    filter::remove_matching_names(chunk, &opt.exclude_patterns);
+}
+
+/// Parses a PDB while keeping memory use near `limit`, then writes it as JSON
+fn print_bounded(
+    out: &mut impl Write,
+    opt: &Opt,
+    file: &Path,
+    limit: u64,
+    format_version: u32,
+) -> anyhow::Result<()> {
+    // Previously exported JSON is read in one piece anyway, and images alone have
+    // little to parse
+    if is_exported_json(file) || pe::is_pe(file)? {
+        let parsed_pdb = load_and_filter(opt, file, None)?;
+        return Ok(output::print_json(out, &parsed_pdb, format_version)?);
+    }
+
+    let options = opt.parse_options();
+    let mut spill = spill::Spill::new(limit, opt.base_address());
+    let mut sink = |pdb: &mut ezpdb::ParsedPdb,
+                    module: ezpdb::ParsedModule|
+     -> Result<(), ezpdb::error::Error> {
+        Ok(spill.module_parsed(pdb, module, |chunk| filter_spilled(opt, chunk))?)
+    };
+    let mut parsed_pdb = match opt.progress {
+        Some(kind) => {
+            ezpdb::parse_pdb_with_sink(file, &options, &mut progress::reporter(kind), &mut sink)?
+        }
+        None => ezpdb::parse_pdb_with_sink(file, &options, &mut |_event| {}, &mut sink)?,
+    };
+    parsed_pdb.warnings = logging::take_warnings();
+    identity::identify_pdb(&mut parsed_pdb, file)?;
+    if let Some(base_address) = opt.base_address() {
+        parsed_pdb.rebase(base_address);
+    }
+    filter_pdb(opt, &mut parsed_pdb, None)?;
+
+    spill.print_json(out, parsed_pdb, format_version)
+}
+
+/// Writes a single JSON object mapping each PDB's module name to its JSON output.
+/// PDBs are parsed one at a time so only one is held in memory.
+fn print_combined(
+    out: &mut impl Write,
+    opt: &Opt,
+    files: &[PathBuf],
+    format_version: u32,
+) -> anyhow::Result<()> {
+    let mut seen = std::collections::HashSet::new();
+
+    write!(out, "{{")?;
+    for (i, file) in files.iter().enumerate() {
+        let parsed_pdb = load_and_filter(opt, file, None)?;
+
+        let stem = file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // Fall back to the full path if two PDBs share a module name
+        let key = if seen.insert(stem.clone()) {
+            stem
+        } else {
+            file.display().to_string()
+        };
 
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{}:", serde_json::to_string(&key)?)?;
+        output::print_json(out, &parsed_pdb, format_version)?;
+    }
+    write!(out, "}}")?;
+
+    Ok(())
+}
+
+/// Writes `parsed_pdb` in the requested output format
+fn print_pdb(
+    out: &mut impl Write,
+    opt: &Opt,
+    parsed_pdb: &ezpdb::ParsedPdb,
+    pe: Option<&pe::PeInfo>,
+    format_version: u32,
+) -> anyhow::Result<()> {
     match opt.format {
-        OutputFormatType::Plain => output::print_plain(&mut stdout_lock, &parsed_pdb)?,
-        OutputFormatType::Json => output::print_json(&mut stdout_lock, &parsed_pdb)?,
+        OutputFormatType::Plain if opt.group_by.is_some() => {
+            output::print_grouped_plain(out, parsed_pdb)?
+        }
+        OutputFormatType::Json if opt.group_by.is_some() => {
+            output::print_grouped_json(out, parsed_pdb, format_version)?
+        }
+        OutputFormatType::Plain => output::print_plain(out, parsed_pdb)?,
+        OutputFormatType::Json => output::print_json(out, parsed_pdb, format_version)?,
+        _ if opt.group_by.is_some() => {
+            anyhow::bail!("--group-by is only supported by the plain and json formats")
+        }
+        OutputFormatType::Table => {
+            let color = match opt.color {
+                ColorChoice::Always => true,
+                ColorChoice::Never => false,
+                ColorChoice::Auto => opt.output.is_none() && std::io::stdout().is_terminal(),
+            };
+            output::print_table(out, parsed_pdb, opt.sort, opt.reverse, color)?
+        }
+        OutputFormatType::Html => output::print_html(out, parsed_pdb)?,
+        OutputFormatType::Markdown => output::print_markdown(out, parsed_pdb)?,
+        OutputFormatType::Map => output::print_map(out, parsed_pdb)?,
+        OutputFormatType::Def => output::print_def(out, parsed_pdb, pe)?,
+        OutputFormatType::X64dbg => output::print_x64dbg(out, parsed_pdb, pe)?,
+        OutputFormatType::Windbg => output::print_windbg_script(out, parsed_pdb, pe)?,
+        OutputFormatType::WindbgJs => output::print_windbg_js(out, parsed_pdb, pe)?,
+        OutputFormatType::Radare2 => output::print_radare2(out, parsed_pdb)?,
+        OutputFormatType::Frida => output::print_frida_ts(out, parsed_pdb, pe)?,
+        OutputFormatType::FridaJson => output::print_frida_json(out, parsed_pdb, pe)?,
+        OutputFormatType::Gsym => output::print_gsym(out, parsed_pdb)?,
+        OutputFormatType::Breakpad => output::print_breakpad(out, parsed_pdb, pe)?,
+        OutputFormatType::Boundaries => output::print_boundaries(out, parsed_pdb)?,
+        OutputFormatType::Drcov => output::print_drcov(out, parsed_pdb, pe)?,
+        OutputFormatType::Pat => {
+            let pe = pe.ok_or_else(|| anyhow::anyhow!("the pat format requires --pe"))?;
+            output::print_pat(out, parsed_pdb, pe)?
+        }
+        OutputFormatType::DotCallgraph => {
+            let pe = pe.ok_or_else(|| anyhow::anyhow!("the dot-callgraph format requires --pe"))?;
+            output::print_dot_callgraph(out, parsed_pdb, pe, opt.root.as_deref(), opt.depth)?
+        }
+        #[cfg(feature = "parquet-format")]
+        OutputFormatType::Parquet => output::print_parquet(out, parsed_pdb)?,
+        OutputFormatType::Xml => output::print_xml(out, parsed_pdb, format_version)?,
+        OutputFormatType::Ctags => output::print_ctags(out, parsed_pdb)?,
+        OutputFormatType::Etags => output::print_etags(out, parsed_pdb)?,
+        OutputFormatType::CompileCommands => output::print_compile_commands(out, parsed_pdb)?,
+        OutputFormatType::NamespaceTree => output::print_namespace_tree(out, parsed_pdb)?,
+        OutputFormatType::Rust => output::print_rust(out, parsed_pdb, &opt.names)?,
+        OutputFormatType::Ctypes => output::print_ctypes(out, parsed_pdb, &opt.names)?,
+        OutputFormatType::Isf => output::print_isf(out, parsed_pdb)?,
+    }
+
+    Ok(())
+}
+
+/// Fails if two of `files` share a file stem, since their `--output-dir` files would
+/// overwrite each other
+fn check_output_stems(files: &[PathBuf]) -> anyhow::Result<()> {
+    let mut seen = std::collections::HashMap::new();
+    for file in files {
+        let stem = file.file_stem().unwrap_or_else(|| file.as_os_str());
+        if let Some(previous) = seen.insert(stem, file) {
+            anyhow::bail!(
+                "{} and {} are both named {}, so their --output-dir files would collide",
+                previous.display(),
+                file.display(),
+                stem.to_string_lossy()
+            );
+        }
     }
 
     Ok(())
 }
+
+/// Loads a PDB given in batch mode and writes it to its own file under `--output-dir`,
+/// or otherwise to `out`. Returns the path of the file written, if any
+fn print_batch_pdb(
+    out: &mut impl Write,
+    opt: &Opt,
+    file: &Path,
+    pe: Option<&pe::PeInfo>,
+    format_version: u32,
+) -> anyhow::Result<Option<PathBuf>> {
+    let parsed_pdb = load_and_filter(opt, file, pe)?;
+
+    match opt.output_dir.as_ref() {
+        Some(output_dir) => {
+            let stem = file.file_stem().unwrap_or_else(|| file.as_os_str());
+            let path = output_dir.join(format!(
+                "{}.{}",
+                stem.to_string_lossy(),
+                opt.format.extension()
+            ));
+            let mut file_out = writer::OutputWriter::create(Some(&path))?;
+            print_pdb(&mut file_out, opt, &parsed_pdb, pe, format_version)?;
+            file_out.finish()?;
+
+            Ok(Some(path))
+        }
+        None => {
+            print_pdb(out, opt, &parsed_pdb, pe, format_version)?;
+            // Separate concatenated JSON documents so the output is JSON lines
+            if matches!(opt.format, OutputFormatType::Json) {
+                writeln!(out)?;
+            }
+
+            Ok(None)
+        }
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut opt = Opt::from_args();
+
+    opt.settings = config::Config::load(opt.config.as_deref())?;
+    opt.format = match (opt.format_arg.take(), opt.settings.format.as_ref()) {
+        (Some(format), _) => format,
+        (None, Some(format)) => format.parse()?,
+        (None, None) => OutputFormatType::Plain,
+    };
+    if opt.names.is_empty() {
+        opt.names = opt.settings.names.clone();
+    }
+    if let Some(proxy) = opt.proxy.take() {
+        opt.settings.http.proxy = Some(proxy);
+    }
+    if let Some(ca_bundle) = opt.ca_bundle.take() {
+        opt.settings.http.ca_bundle = Some(ca_bundle);
+    }
+    if opt.server_auth.is_some() || opt.server_token.is_some() || !opt.server_header.is_empty() {
+        let (username, password) = match opt.server_auth.as_deref().map(|auth| auth.split_once(':'))
+        {
+            Some(Some((username, password))) => {
+                (Some(username.to_string()), Some(password.to_string()))
+            }
+            Some(None) => anyhow::bail!("--server-auth must be user:password"),
+            None => (None, None),
+        };
+        opt.settings.http.credentials.push(config::Credentials {
+            server: String::new(),
+            username,
+            password,
+            token: opt.server_token.clone(),
+            headers: opt.server_header.clone(),
+        });
+    }
+    opt.exclude_patterns = filter::load_exclude_patterns(&opt.exclude)?;
+
+    let log_level = if opt.debug {
+        opt.log_level.max(log::LevelFilter::Debug)
+    } else {
+        opt.log_level
+    };
+    if log_level != log::LevelFilter::Off || opt.warnings_in_output {
+        logging::init(log_level, opt.log_json, opt.warnings_in_output)?;
+    }
+
+    let format_version = opt
+        .format_version
+        .unwrap_or(ezpdb::symbol_types::FORMAT_VERSION);
+    if !output::compat::is_supported(format_version) {
+        return Err(
+            CliArgumentError::InvalidValue("format-version", format_version.to_string()).into(),
+        );
+    }
+
+    // The options are settled from here on, and the helpers below borrow them
+    let opt = &opt;
+    let pe = opt.pe.as_ref().map(pe::PeInfo::open).transpose()?;
+
+    let mut out = writer::OutputWriter::create(opt.output.as_deref())?;
+
+    match &opt.command {
+        Some(Command::Lookup { file, address }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+            let lookup = parsed_pdb.lookup_address(*address);
+
+            match opt.format {
+                OutputFormatType::Json => output::print_lookup_json(&mut out, &lookup)?,
+                _ => output::print_lookup_plain(&mut out, &lookup)?,
+            }
+        }
+        Some(Command::Disasm { file, function }) => {
+            let pe = pe
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("disasm requires --pe"))?;
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            disasm::print_disassembly(&mut out, &parsed_pdb, pe, function)?;
+        }
+        Some(Command::Merge { modules, symbolize }) => {
+            let modules = modules
+                .iter()
+                .map(|module| load_pdb(&module.path, Some(module.base_address), opt))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let space = ezpdb::lookup::AddressSpace::new(modules);
+
+            match symbolize {
+                Some(path) if path.as_os_str() == "-" => {
+                    output::print_symbolized(&mut out, &space, std::io::stdin().lock())?
+                }
+                Some(path) => {
+                    let input = std::io::BufReader::new(std::fs::File::open(path)?);
+                    output::print_symbolized(&mut out, &space, input)?
+                }
+                None => output::print_merged_symbols(
+                    &mut out,
+                    &space,
+                    matches!(opt.format, OutputFormatType::Json),
+                )?,
+            }
+        }
+        Some(Command::Drift { files }) => {
+            let pdbs = files
+                .iter()
+                .map(|file| load_and_filter(opt, file, None))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            output::print_drift(
+                &mut out,
+                &pdbs,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Dt {
+            file,
+            type_name,
+            address,
+            mem,
+            recurse,
+        }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+            let instance = match (mem, address) {
+                (Some(mem), Some(address)) => Some((mem.as_path(), *address as u64)),
+                _ => None,
+            };
+
+            output::print_dt(&mut out, &parsed_pdb, type_name, instance, *recurse)?
+        }
+        Some(Command::Offsets { file, items, lang }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_offsets_header(&mut out, &parsed_pdb, items, *lang)?
+        }
+        Some(Command::Kernel { file }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_kernel_report(
+                &mut out,
+                &parsed_pdb,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Cfg { file }) => {
+            let pe = pe
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("cfg requires --pe"))?;
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_cfg_report(
+                &mut out,
+                &parsed_pdb,
+                pe,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Gaps { file, min_size }) => {
+            let pe = pe
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("gaps requires --pe"))?;
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_code_gaps(
+                &mut out,
+                &parsed_pdb,
+                pe,
+                *min_size,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Interface { file }) => {
+            let pe = pe
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("interface requires --pe"))?;
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_interface_report(
+                &mut out,
+                &parsed_pdb,
+                pe,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Audit { file }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_audit(
+                &mut out,
+                &parsed_pdb,
+                pe.as_ref(),
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Timeline { file }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_timeline(
+                &mut out,
+                &parsed_pdb,
+                pe.as_ref(),
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Templates { file, matching }) => {
+            let parsed_pdb = load_and_filter(opt, file, None)?;
+
+            output::print_template_report(
+                &mut out,
+                &parsed_pdb,
+                matching.as_deref(),
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Methods {
+            file,
+            class,
+            virtual_only,
+            implemented_only,
+        }) => {
+            let parsed_pdb = load_and_filter(opt, file, None)?;
+
+            output::print_methods(
+                &mut out,
+                &parsed_pdb,
+                &output::MethodFilter {
+                    class: class.as_deref(),
+                    virtual_only: *virtual_only,
+                    implemented_only: *implemented_only,
+                },
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Rtti { file, unnamed_only }) => {
+            let pe = pe
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("rtti requires --pe"))?;
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_rtti(
+                &mut out,
+                rtti::recover_rtti(&parsed_pdb, pe),
+                *unnamed_only,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Initializers { file }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_initializers(
+                &mut out,
+                &parsed_pdb,
+                pe.as_ref(),
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::VerifySrc { file, src_root }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_source_verification(
+                &mut out,
+                &parsed_pdb,
+                src_root,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Store(StoreCommand::Add {
+            files,
+            store,
+            product,
+            product_version,
+            comment,
+        })) => {
+            let info = symstore::TransactionInfo {
+                product: product.clone(),
+                version: product_version.clone(),
+                comment: comment.clone(),
+            };
+            let transaction = symstore::add_files(store, files, &info)?;
+
+            output::print_store_transaction(
+                &mut out,
+                &transaction,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Srv(SrvCommand::Check { target, server })) => {
+            let servers = if server.is_empty() {
+                &opt.settings.symbol_servers
+            } else {
+                server
+            };
+            let tiers: Vec<String> = opt
+                .settings
+                .cache_dir
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .chain(symsrv::tiers(servers))
+                .collect();
+            if tiers.is_empty() {
+                anyhow::bail!("no symbol servers configured; pass --server or set symbol_servers in pdbview.toml");
+            }
+            let client = http::HttpClient::new(&opt.settings.http)?;
+            let report =
+                symsrv::check_availability(&client, symsrv::SymbolId::parse(target)?, &tiers);
+
+            output::print_availability(
+                &mut out,
+                &report,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Srv(SrvCommand::Fetch {
+            targets,
+            list,
+            server,
+            destination,
+            retries,
+        })) => {
+            let mut targets = targets.clone();
+            if let Some(list) = list {
+                targets.extend(
+                    std::fs::read_to_string(list)?
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                        .map(str::to_string),
+                );
+            }
+            let servers = if server.is_empty() {
+                &opt.settings.symbol_servers
+            } else {
+                server
+            };
+            let tiers = symsrv::tiers(servers);
+            if tiers.is_empty() {
+                anyhow::bail!(
+                    "no symbol servers configured; pass --server or set symbol_servers in pdbview.toml"
+                );
+            }
+            let options = symsrv::FetchOptions {
+                destination: destination
+                    .clone()
+                    .or_else(|| opt.settings.cache_dir.clone())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("pass --destination or set cache_dir in pdbview.toml")
+                    })?,
+                retries: *retries,
+            };
+            let client = http::HttpClient::new(&opt.settings.http)?;
+
+            let mut results = vec![];
+            batch::run_ordered(
+                targets.len(),
+                opt.jobs.clamp(1, targets.len().max(1)),
+                |index| {
+                    let symbol = symsrv::SymbolId::parse(&targets[index])?;
+                    Ok(symsrv::fetch(&client, symbol, &tiers, &options))
+                },
+                |result| {
+                    results.push(result);
+                    Ok(())
+                },
+            )?;
+
+            output::print_fetch_results(
+                &mut out,
+                &results,
+                matches!(opt.format, OutputFormatType::Json),
+            )?;
+            let failed = results
+                .iter()
+                .filter(|result| result.error.is_some())
+                .count();
+            if failed > 0 {
+                out.finish()?;
+                anyhow::bail!("{} of {} downloads failed", failed, results.len());
+            }
+        }
+        Some(Command::Srv(SrvCommand::Probe {
+            target,
+            public,
+            server,
+        })) => {
+            let client = http::HttpClient::new(&opt.settings.http)?;
+            let url = if symsrv::is_url(target) {
+                target.clone()
+            } else {
+                let servers = if server.is_empty() {
+                    &opt.settings.symbol_servers
+                } else {
+                    server
+                };
+                let symbol = symsrv::SymbolId::parse(target)?;
+                symsrv::locate(&client, &symbol, &symsrv::tiers(servers)).ok_or_else(|| {
+                    anyhow::anyhow!("no symbol server has an uncompressed copy of {}", target)
+                })?
+            };
+            let report =
+                remote::probe(remote::RangeReader::open(&client, &url)?, public.as_deref())?;
+
+            output::print_probe(
+                &mut out,
+                &report,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Watch {
+            dir,
+            webhook,
+            settle,
+        }) => {
+            let webhook = webhook.as_ref().or(opt.settings.watch.webhook.as_ref());
+            let client = webhook
+                .map(|_| http::HttpClient::new(&opt.settings.http))
+                .transpose()?;
+
+            watch::watch(dir, std::time::Duration::from_secs(*settle), |path| {
+                let result =
+                    print_batch_pdb(&mut out, opt, path, None, format_version).and_then(|output| {
+                        out.flush()?;
+                        Ok(output)
+                    });
+                let event = watch::WatchEvent {
+                    path: path.to_path_buf(),
+                    symbol_id: symsrv::SymbolId::parse(&path.to_string_lossy())
+                        .ok()
+                        .map(|symbol| format!("{}/{}", symbol.name, symbol.key)),
+                    output: result.as_ref().ok().cloned().flatten(),
+                    error: result.as_ref().err().map(|e| e.to_string()),
+                };
+                if let Some(error) = event.error.as_ref() {
+                    log::warn!("failed to parse {}: {}", path.display(), error);
+                }
+
+                if let (Some(client), Some(webhook)) = (client.as_ref(), webhook) {
+                    if let Err(e) = watch::post(client, webhook, &event) {
+                        log::warn!("failed to notify {}: {}", webhook, e);
+                    }
+                }
+            })?
+        }
+        #[cfg(feature = "grpc")]
+        Some(Command::ServeGrpc { file, listen }) => {
+            grpc::serve(*listen, || load_pdb(file, opt.base_address(), opt))?
+        }
+        #[cfg(all(windows, feature = "dbghelp"))]
+        Some(Command::CrossCheck { file }) => {
+            // Compare RVAs, which is what dbghelp reports relative to the module base
+            let parsed_pdb = load_pdb(file, None, opt)?;
+            let reference = dbghelp::load(file)?;
+            let report = dbghelp::compare(&parsed_pdb, &reference);
+
+            output::print_cross_check(
+                &mut out,
+                &report,
+                matches!(opt.format, OutputFormatType::Json),
+            )?;
+            if !report.discrepancies.is_empty() {
+                out.finish()?;
+                anyhow::bail!(
+                    "{} discrepancies between pdbview and dbghelp",
+                    report.discrepancies.len()
+                );
+            }
+        }
+        Some(Command::Syscalls { file }) => {
+            let pe = pe
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("syscalls requires --pe"))?;
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_syscalls(
+                &mut out,
+                &parsed_pdb,
+                pe,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Libs { file }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            output::print_library_report(
+                &mut out,
+                &parsed_pdb,
+                matches!(opt.format, OutputFormatType::Json),
+            )?
+        }
+        Some(Command::Check { file }) => {
+            let report = ezpdb::msf::check_msf(file)?;
+            output::print_msf_report(
+                &mut out,
+                &report,
+                matches!(opt.format, OutputFormatType::Json),
+            )?;
+
+            if report.has_errors() {
+                out.finish()?;
+                anyhow::bail!("{} is not a valid MSF file", file.display());
+            }
+        }
+        Some(Command::DumpStream { file, stream }) => {
+            out.write_all(&ezpdb::streams::read_stream(file, stream)?)?
+        }
+        Some(Command::SizeReport { file, by, csv }) => {
+            let parsed_pdb = load_and_filter(opt, file, None)?;
+
+            output::print_size_report(
+                &mut out,
+                &parsed_pdb,
+                *by,
+                matches!(opt.format, OutputFormatType::Json),
+                *csv,
+            )?
+        }
+        Some(Command::Profile { file }) => {
+            profile::print_profile(&mut out, file, matches!(opt.format, OutputFormatType::Json))?
+        }
+        Some(Command::TypeStats { file }) => output::print_type_stats(
+            &mut out,
+            &ezpdb::stats::type_stream_stats(file)?,
+            matches!(opt.format, OutputFormatType::Json),
+        )?,
+        Some(Command::Repl { file }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            // The REPL is interactive so it always talks to the terminal
+            repl::run(&mut std::io::stdout().lock(), &parsed_pdb)?;
+        }
+        Some(Command::Config) => {
+            write!(out, "{}", toml::to_string_pretty(&opt.settings.redacted())?)?
+        }
+        Some(Command::Schema) => output::print_json_schema(&mut out)?,
+        Some(Command::Tui { file }) => {
+            let parsed_pdb = load_pdb(file, opt.base_address(), opt)?;
+
+            browser::run(&parsed_pdb)?;
+        }
+        None => {
+            let mut files = opt.files.clone();
+            if let Some(manifest) = opt.manifest.as_ref() {
+                files.extend(read_manifest(manifest)?);
+            }
+
+            if files.len() > 1 && pe.is_some() {
+                anyhow::bail!("--pe cannot be used with multiple PDBs");
+            }
+            if opt.output_dir.is_some() {
+                check_output_stems(&files)?;
+            }
+
+            if opt.combined {
+                if !matches!(opt.format, OutputFormatType::Json) {
+                    anyhow::bail!("--combined requires the json format");
+                }
+
+                print_combined(&mut out, opt, &files, format_version)?;
+            } else if let Some(limit) = opt.max_memory {
+                if !cfg!(feature = "alloc-stats") {
+                    anyhow::bail!(
+                        "--max-memory requires pdbview built with --features alloc-stats"
+                    );
+                }
+                if !matches!(opt.format, OutputFormatType::Json) {
+                    anyhow::bail!("--max-memory requires the json format");
+                }
+                let redacts = opt.settings.redaction.strip_directories
+                    || !opt.settings.redaction.patterns.is_empty();
+                if opt.group_by.is_some()
+                    || opt.query.is_some()
+                    || pe.is_some()
+                    || !opt.path_map.is_empty()
+                    || redacts
+                {
+                    anyhow::bail!(
+                        "--max-memory cannot be used with --group-by, --query, --pe, \
+                        --path-map, or redaction, which need every record in memory"
+                    );
+                }
+
+                for file in &files {
+                    print_bounded(&mut out, opt, file, limit, format_version)?;
+                    writeln!(out)?;
+                }
+            } else if opt.jobs > 1 && files.len() > 1 {
+                batch::run_ordered(
+                    files.len(),
+                    opt.jobs.min(files.len()),
+                    |index| {
+                        let mut buffer = vec![];
+                        print_batch_pdb(&mut buffer, opt, &files[index], None, format_version)?;
+                        Ok(buffer)
+                    },
+                    |buffer| Ok(out.write_all(&buffer)?),
+                )?;
+            } else {
+                for file in &files {
+                    print_batch_pdb(&mut out, opt, file, pe.as_ref(), format_version)?;
+                }
+            }
+        }
+    }
+
+    out.finish()?;
+
+    Ok(())
+}
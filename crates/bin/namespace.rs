@@ -0,0 +1,120 @@
+use crate::CliArgumentError;
+use ezpdb::ParsedPdb;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// What `size-report` aggregates function sizes by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeGroup {
+    /// The namespaces enclosing each function, excluding classes
+    #[default]
+    Namespace,
+    /// The class, struct, or union a member function belongs to
+    Class,
+    /// The module which contributed each function
+    Module,
+}
+
+impl FromStr for SizeGroup {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_ref() {
+            "namespace" => Ok(SizeGroup::Namespace),
+            "class" => Ok(SizeGroup::Class),
+            "module" => Ok(SizeGroup::Module),
+            _ => Err(CliArgumentError::InvalidValue("by", s.to_string())),
+        }
+    }
+}
+
+/// Splits a qualified C++ or Rust name into its scopes, e.g. `std::vector<int>::push_back`
+/// into `std`, `vector<int>`, and `push_back`. Separators inside template arguments or
+/// parameter lists don't split, and operators such as `operator<` end the name
+pub fn split_scopes(name: &str) -> Vec<&str> {
+    let bytes = name.as_bytes();
+    let mut scopes = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if depth == 0 && i == start && name[start..].starts_with("operator") {
+            break;
+        }
+
+        match bytes[i] {
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                scopes.push(&name[start..i]);
+                i += 2;
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    scopes.push(&name[start..]);
+
+    scopes
+}
+
+/// Replaces the template arguments of a scope with `<T>` so every instantiation of a
+/// class template is grouped together, e.g. `vector<int>` becomes `vector<T>`
+pub fn collapse_templates(scope: &str) -> String {
+    match scope.find('<') {
+        Some(start) if start > 0 && scope.ends_with('>') => format!("{}<T>", &scope[..start]),
+        _ => scope.to_string(),
+    }
+}
+
+/// A namespace, class, or function in a tree of procedure names, along with the total
+/// size of the functions beneath it
+#[derive(Debug, Default, Serialize)]
+pub struct NamespaceNode {
+    /// Total size of this scope's functions in BYTES
+    pub bytes: usize,
+    pub functions: usize,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub children: BTreeMap<String, NamespaceNode>,
+}
+
+impl NamespaceNode {
+    /// Builds a tree of every procedure, nested by the scopes of its name. Functions
+    /// with the same name (e.g. overloads) share a leaf
+    pub fn from_procedures(pdb_info: &ParsedPdb) -> Self {
+        let mut root = NamespaceNode::default();
+        for procedure in &pdb_info.procedures {
+            root.insert(&split_scopes(&procedure.name), procedure.len);
+        }
+
+        root
+    }
+
+    fn insert(&mut self, scopes: &[&str], len: usize) {
+        self.bytes += len;
+        self.functions += 1;
+
+        if let Some((scope, rest)) = scopes.split_first() {
+            let key = if rest.is_empty() {
+                scope.to_string()
+            } else {
+                collapse_templates(scope)
+            };
+            self.children.entry(key).or_default().insert(rest, len);
+        }
+    }
+
+    /// Children ordered largest first, then by name
+    pub fn sorted_children(&self) -> Vec<(&String, &NamespaceNode)> {
+        let mut children: Vec<_> = self.children.iter().collect();
+        children.sort_by(|(a_name, a), (b_name, b)| {
+            b.bytes.cmp(&a.bytes).then_with(|| a_name.cmp(b_name))
+        });
+
+        children
+    }
+}
@@ -1,8 +1,146 @@
+use crate::signature::hex_digest;
+use ezpdb::lookup::AddressLookup;
 use ezpdb::symbol_types::*;
 use ezpdb::type_info::*;
 use log::{debug, warn};
 use std::io::{self, Write};
 
+mod audit;
+mod breakpad;
+mod callgraph;
+pub(crate) mod cdecl;
+mod cfg;
+pub(crate) mod compat;
+mod compile_commands;
+mod coverage;
+#[cfg(all(windows, feature = "dbghelp"))]
+mod crosscheck;
+mod ctypes;
+mod def;
+mod drift;
+mod dt;
+mod frida;
+mod gaps;
+mod grouped;
+mod gsym;
+mod html;
+mod initializers;
+mod interface;
+mod isf;
+mod kernel;
+mod layout;
+mod libraries;
+mod map;
+mod markdown;
+mod merge;
+mod methods;
+mod msf;
+mod offsets;
+#[cfg(feature = "parquet-format")]
+mod parquet;
+mod pat;
+mod radare2;
+mod rtti;
+mod rust;
+mod size_report;
+mod symsrv;
+mod symstore;
+mod syscalls;
+mod table;
+mod tags;
+mod templates;
+mod timeline;
+mod tree;
+mod type_stats;
+mod verify_src;
+mod windbg;
+mod x64dbg;
+mod xml;
+
+#[cfg(feature = "parquet-format")]
+pub use self::parquet::print_parquet;
+pub use audit::print_audit;
+pub use breakpad::print_breakpad;
+pub use callgraph::print_dot_callgraph;
+pub use cfg::print_cfg_report;
+pub use compile_commands::print_compile_commands;
+pub use coverage::{print_boundaries, print_drcov};
+#[cfg(all(windows, feature = "dbghelp"))]
+pub use crosscheck::print_cross_check;
+pub use ctypes::print_ctypes;
+pub use def::print_def;
+pub use drift::print_drift;
+pub use dt::print_dt;
+pub use frida::{print_frida_json, print_frida_ts};
+pub use gaps::print_code_gaps;
+pub use grouped::{print_grouped_json, print_grouped_plain};
+pub use gsym::print_gsym;
+pub use html::print_html;
+pub use initializers::print_initializers;
+pub use interface::print_interface_report;
+pub use isf::print_isf;
+pub use kernel::print_kernel_report;
+pub use libraries::print_library_report;
+pub use map::print_map;
+pub use markdown::print_markdown;
+pub use merge::{print_merged_symbols, print_symbolized};
+pub use methods::{print_methods, MethodFilter};
+pub use msf::print_msf_report;
+pub use offsets::{print_offsets_header, HeaderLanguage};
+pub use pat::print_pat;
+pub use radare2::print_radare2;
+pub use rtti::print_rtti;
+pub use rust::print_rust;
+pub use size_report::print_size_report;
+pub use symsrv::{print_availability, print_fetch_results, print_probe};
+pub use symstore::print_store_transaction;
+pub use syscalls::print_syscalls;
+pub use table::print_table;
+pub use tags::{print_ctags, print_etags};
+pub use templates::print_template_report;
+pub use timeline::print_timeline;
+pub use tree::print_namespace_tree;
+pub use type_stats::print_type_stats;
+pub use verify_src::print_source_verification;
+pub use windbg::{print_windbg_js, print_windbg_script};
+pub use x64dbg::print_x64dbg;
+pub use xml::print_xml;
+
+/// Returns the file name of the image described by the PDB, e.g. `foo.dll`. Uses the PE's
+/// name if available and otherwise guesses from the PDB's file name.
+pub(crate) fn image_name(pdb_info: &ParsedPdb, pe: Option<&crate::pe::PeInfo>) -> String {
+    match pe {
+        Some(pe) => pe.file_name(),
+        None => pdb_info
+            .path
+            .file_stem()
+            .map(|stem| format!("{}.dll", stem.to_string_lossy()))
+            .unwrap_or_default(),
+    }
+}
+
+/// Size of a pointer in the image described by the PDB, in BYTES
+pub(crate) fn pointer_size(pdb_info: &ParsedPdb) -> usize {
+    match pdb_info.machine_type {
+        Some(MachineType::Amd64) | Some(MachineType::Arm64) | Some(MachineType::Ia64) => 8,
+        _ => 4,
+    }
+}
+
+/// Fills the offset column of a symbol without an offset. At most 10 characters wide
+fn format_location(location: &Location) -> String {
+    format!("<{}>", location.name())
+}
+
+/// Value of an absolute or constant symbol, to follow its name
+fn format_location_value(location: &Location) -> String {
+    match location {
+        Location::Absolute { value } => format!(" = 0x{:X}", value),
+        Location::Constant { value } => format!(" = {}", value),
+        _ => String::new(),
+    }
+}
+
 pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
     // region: Header info
     // Print header information
@@ -18,6 +156,26 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
             .map(|ty| format!("{:?}", ty))
             .unwrap_or_else(|| "Unknown".to_string())
     )?;
+    if let Some(identity) = &pdb_info.identity {
+        for (label, hashes) in std::iter::once(("PDB", &identity.pdb))
+            .chain(identity.pe.as_ref().map(|hashes| ("PE", hashes)))
+        {
+            writeln!(
+                output,
+                "{} Hashes ({:?}, {} bytes):",
+                label, hashes.path, hashes.size
+            )?;
+            writeln!(output, "\tMD5: {}", hex_digest(&hashes.md5))?;
+            writeln!(output, "\tSHA1: {}", hex_digest(&hashes.sha1))?;
+            writeln!(output, "\tSHA256: {}", hex_digest(&hashes.sha256))?;
+        }
+    }
+    if pdb_info.is_fastlink {
+        writeln!(
+            output,
+            "Linked with /DEBUG:FASTLINK: types and private symbols are in the object files"
+        )?;
+    }
 
     writeln!(output, "Assembly Info:")?;
 
@@ -166,6 +324,13 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
             compiler_info.version_string
         )?;
     }
+
+    if !pdb_info.assembly_info.instrumentation.is_empty() {
+        writeln!(output, "\tInstrumentation:")?;
+        for instrumentation in &pdb_info.assembly_info.instrumentation {
+            writeln!(output, "\t\t{:?}", instrumentation)?;
+        }
+    }
     // endregion
 
     // region: Public symbols
@@ -176,9 +341,14 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
         if let Some(offset) = symbol.offset {
             write!(output, "0x{:08X} ", offset)?;
         } else {
-            write!(output, "{:<10} ", "")?;
+            write!(output, "{:<10} ", format_location(&symbol.location))?;
         }
-        writeln!(output, "{}", symbol.name)?;
+        writeln!(
+            output,
+            "{}{}",
+            symbol.name,
+            format_location_value(&symbol.location)
+        )?;
     }
     // endregion
 
@@ -195,7 +365,7 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
         if let Some(offset) = procedure.offset {
             write!(output, "0x{:08X} ", offset)?;
         } else {
-            write!(output, "{:<10} ", "")?;
+            write!(output, "{:<10} ", format_location(&procedure.location))?;
         }
 
         write!(output, "0x{:08X} ", procedure.len)?;
@@ -210,6 +380,60 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
             format!("0x{:08X} ", procedure.epilogue_start)
         )?;
         writeln!(output, "{}", procedure.name)?;
+
+        if let Some(hashes) = &procedure.hashes {
+            writeln!(output, "\t\tSHA256:        {}", hashes.sha256)?;
+            writeln!(output, "\t\tMasked SHA256: {}", hashes.masked_sha256)?;
+        }
+        if let Some(cfg_target) = procedure.cfg_target {
+            writeln!(output, "\t\tCFG target:    {}", cfg_target)?;
+        }
+        if let Some(class) = procedure.declaring_class {
+            writeln!(
+                output,
+                "\t\tDeclared by:   {}",
+                type_display_name(pdb_info, class)
+            )?;
+        }
+        for inlinee in &procedure.inlinees {
+            writeln!(output, "\t\tInlines: {}", inlinee)?;
+        }
+        for funclet in &procedure.funclets {
+            writeln!(
+                output,
+                "\t\tFunclet: {:<10} 0x{:08X} {:?} {}",
+                funclet
+                    .offset
+                    .map_or_else(String::new, |offset| format!("0x{:08X}", offset)),
+                funclet.len,
+                funclet.kind,
+                funclet.name
+            )?;
+        }
+    }
+    // endregion
+
+    // region: Thunks
+    if !pdb_info.thunks.is_empty() {
+        writeln!(output, "Thunks:")?;
+        writeln!(
+            output,
+            "\t{:<10} {:<10} {:<10} Name",
+            "Offset", "Length", "Kind"
+        )?;
+        for thunk in &pdb_info.thunks {
+            write!(output, "\t")?;
+            if let Some(offset) = thunk.offset {
+                write!(output, "0x{:08X} ", offset)?;
+            } else {
+                write!(output, "{:<10} ", format_location(&thunk.location))?;
+            }
+            writeln!(
+                output,
+                "0x{:08X} {:<10} {}",
+                thunk.len, thunk.kind, thunk.name
+            )?;
+        }
     }
     // endregion
 
@@ -222,9 +446,14 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
         if let Some(offset) = global.offset {
             write!(output, "0x{:08X} ", offset)?;
         } else {
-            write!(output, "{:<10} ", "")?;
+            write!(output, "{:<10} ", format_location(&global.location))?;
         }
-        writeln!(output, "{}", global.name)?;
+        writeln!(
+            output,
+            "{}{}",
+            global.name,
+            format_location_value(&global.location)
+        )?;
 
         let ty: &Type = &*global.ty.as_ref().borrow();
         writeln!(output, "\t\tType: {}", format_type_name(ty))?;
@@ -250,11 +479,17 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
                     output,
                     "\t{:width$} {} {}",
                     class.kind,
-                    class.name,
+                    ty.display_name().unwrap_or_default(),
                     class.unique_name.as_ref().map(String::as_ref).unwrap_or(""),
                     width = 10
                 )?;
                 writeln!(output, "\tSize: 0x{:X}", class.size)?;
+                if let Some(source) = &class.source {
+                    writeln!(output, "\tSource: {}:{}", source.file, source.line)?;
+                }
+                if let Some(parent) = class.parent {
+                    writeln!(output, "\tParent: {}", type_display_name(pdb_info, parent))?;
+                }
                 // writeln!(
                 //     output,
                 //     "\t\t{:width$} {}",
@@ -327,10 +562,16 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
                 writeln!(
                     output,
                     "\tUnion {} {}",
-                    union.name,
+                    ty.display_name().unwrap_or_default(),
                     union.unique_name.as_ref().map(String::as_ref).unwrap_or(""),
                 )?;
                 writeln!(output, "\tSize: 0x{:X}", union.size)?;
+                if let Some(source) = &union.source {
+                    writeln!(output, "\tSource: {}:{}", source.file, source.line)?;
+                }
+                if let Some(parent) = union.parent {
+                    writeln!(output, "\tParent: {}", type_display_name(pdb_info, parent))?;
+                }
                 // writeln!(
                 //     output,
                 //     "\t\t{:width$} {}",
@@ -404,12 +645,18 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
                 writeln!(
                     output,
                     "\tEnum {} {}",
-                    e.name,
+                    ty.display_name().unwrap_or_default(),
                     e.unique_name.as_ref().map(String::as_ref).unwrap_or(""),
                 )?;
                 if let Type::Primitive(primitive) = &*e.underlying_type.borrow() {
                     writeln!(output, "\tSize: 0x{:X}", primitive.size())?;
                 }
+                if let Some(source) = &e.source {
+                    writeln!(output, "\tSource: {}:{}", source.file, source.line)?;
+                }
+                if let Some(parent) = e.parent {
+                    writeln!(output, "\tParent: {}", type_display_name(pdb_info, parent))?;
+                }
                 let underlying_type = e.underlying_type.borrow();
                 writeln!(output, "\tType: {}", format_type_name(&*underlying_type))?;
                 writeln!(output, "\tVariants:")?;
@@ -436,13 +683,61 @@ pub fn print_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<
     }
     // endregion
 
+    // region: String table
+    if !pdb_info.string_table.is_empty() {
+        writeln!(output, "String table:")?;
+        for entry in &pdb_info.string_table {
+            writeln!(output, "\t0x{:08X} {}", entry.offset, entry.value)?;
+        }
+    }
+    // endregion
+
+    // region: Raw records
+    if !pdb_info.raw_records.is_empty() {
+        writeln!(output, "Raw records:")?;
+        for record in &pdb_info.raw_records {
+            writeln!(
+                output,
+                "\t{} 0x{:08X} kind 0x{:04X} {}",
+                record.stream,
+                record.index,
+                record.kind,
+                record.name.as_deref().unwrap_or("<unnamed>")
+            )?;
+            if let Some(error) = &record.error {
+                writeln!(output, "\t\tError: {}", error)?;
+            }
+            writeln!(output, "\t\t{}", record.bytes)?;
+        }
+    }
+    // endregion
+
+    // region: Warnings
+    if !pdb_info.warnings.is_empty() {
+        writeln!(output, "Warnings:")?;
+        for warning in &pdb_info.warnings {
+            writeln!(output, "\t{}", warning)?;
+        }
+    }
+    // endregion
+
     Ok(())
 }
 
-fn format_type_name(ty: &Type) -> String {
+/// Returns the name of the class, union, or enum at `index`, or its key if it's missing
+fn type_display_name(pdb_info: &ParsedPdb, index: TypeIndexNumber) -> String {
+    pdb_info
+        .types
+        .get(&index)
+        .and_then(|ty| ty.as_ref().borrow().display_name().map(str::to_string))
+        .unwrap_or_else(|| format!("{:#x}", index))
+}
+
+pub(crate) fn format_type_name(ty: &Type) -> String {
     match ty {
-        Type::Class(class) => class.name.clone(),
-        Type::Union(union) => union.name.clone(),
+        Type::Class(_) | Type::Union(_) | Type::Enumeration(_) => {
+            ty.display_name().unwrap_or_default().to_string()
+        }
         Type::Array(array) => format!(
             "{}{}",
             format_type_name(&*array.element_type.as_ref().borrow()),
@@ -457,9 +752,15 @@ fn format_type_name(ty: &Type) -> String {
         Type::Pointer(pointer) => {
             // TODO: Attributes
             match pointer.underlying_type.as_ref() {
-                Some(underlying_type) => {
-                    format!("{}*", format_type_name(&*underlying_type.as_ref().borrow()))
-                }
+                Some(underlying_type) => format!(
+                    "{}{}",
+                    format_type_name(&*underlying_type.as_ref().borrow()),
+                    if pointer.attributes.is_reference {
+                        "&"
+                    } else {
+                        "*"
+                    }
+                ),
                 None => "<UNRESOLVED_POINTER_TYPE>".to_string(),
             }
         }
@@ -505,7 +806,6 @@ fn format_type_name(ty: &Type) -> String {
                     )
                 })
         ),
-        Type::Enumeration(e) => e.name.clone(),
         Type::MemberFunction(member) => {
             format!(
                 "{} (*function){}",
@@ -527,6 +827,65 @@ fn format_type_name(ty: &Type) -> String {
     }
 }
 
-pub fn print_json(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
-    write!(output, "{}", serde_json::to_string(pdb_info)?)
+pub fn print_json(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    format_version: u32,
+) -> io::Result<()> {
+    // Serialize straight to the output since full dumps can be several gigabytes
+    if format_version == FORMAT_VERSION {
+        return Ok(serde_json::to_writer(output, pdb_info)?);
+    }
+
+    let mut value = serde_json::to_value(pdb_info)?;
+    compat::downgrade(&mut value, format_version);
+
+    Ok(serde_json::to_writer(output, &value)?)
+}
+
+pub fn print_json_schema(output: &mut impl Write) -> io::Result<()> {
+    let schema = schemars::schema_for!(ParsedPdb);
+    writeln!(output, "{}", serde_json::to_string_pretty(&schema)?)
+}
+
+pub fn print_lookup_plain(output: &mut impl Write, lookup: &AddressLookup<'_>) -> io::Result<()> {
+    writeln!(output, "Address: 0x{:08X}", lookup.address)?;
+
+    write!(output, "Procedure: ")?;
+    match lookup.procedure {
+        Some(procedure) => {
+            let offset = procedure.offset.unwrap_or_default();
+            if lookup.address >= offset && lookup.address - offset < procedure.len {
+                write!(output, "{}+0x{:X}", procedure.name, lookup.address - offset)?;
+            } else {
+                // The address is inside a separated code block of this procedure
+                write!(output, "{} <separated code>", procedure.name)?;
+            }
+            writeln!(
+                output,
+                " (0x{:08X} - 0x{:08X})",
+                offset,
+                offset + procedure.len
+            )?;
+        }
+        None => writeln!(output, "<none>")?,
+    }
+
+    write!(output, "Module: ")?;
+    match lookup.module {
+        Some(module) => writeln!(output, "{} ({})", module.name, module.object_file_name)?,
+        None => writeln!(output, "<none>")?,
+    }
+
+    write!(output, "Nearest public: ")?;
+    match lookup.nearest_public {
+        Some((symbol, displacement)) => writeln!(output, "{}+0x{:X}", symbol.name, displacement)?,
+        None => writeln!(output, "<none>")?,
+    }
+
+    Ok(())
+}
+
+pub fn print_lookup_json(output: &mut impl Write, lookup: &AddressLookup<'_>) -> io::Result<()> {
+    write!(output, "{}", serde_json::to_string(lookup)?)
 }
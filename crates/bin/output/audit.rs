@@ -0,0 +1,229 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::io::Write;
+
+const IMAGE_DLLCHARACTERISTICS_NO_SEH: u16 = 0x0400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Pass,
+    Fail,
+    /// The PDB doesn't record enough to tell
+    Unknown,
+    NotApplicable,
+}
+
+impl Status {
+    fn from_bool(pass: bool) -> Status {
+        if pass {
+            Status::Pass
+        } else {
+            Status::Fail
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Status::Pass => "PASS",
+            Status::Fail => "FAIL",
+            Status::Unknown => "?",
+            Status::NotApplicable => "n/a",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ModuleAudit<'a> {
+    name: &'a str,
+    /// `/GS` buffer security checks
+    gs: Status,
+    /// `/sdl` additional security checks
+    sdl: Status,
+    /// `/guard:cf` on the compiler command line
+    guard_cf: Status,
+    hotpatch: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ImageAudit {
+    /// Linked with a Control Flow Guard function table
+    cfg: Status,
+    /// x86 only: linked with a SafeSEH handler table, or marked as using no SEH
+    safe_seh: Status,
+    /// Passes if no sanitizer instrumentation was detected
+    no_sanitizers: Status,
+    sanitizers: Vec<Instrumentation>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditReport<'a> {
+    image: ImageAudit,
+    modules: Vec<ModuleAudit<'a>>,
+}
+
+/// Returns whether `arguments` contains `flag`, given with either a `/` or `-` prefix
+fn has_flag(arguments: &str, flag: &str) -> bool {
+    arguments.split_whitespace().any(|argument| {
+        argument
+            .strip_prefix('/')
+            .or_else(|| argument.strip_prefix('-'))
+            .map_or(false, |argument| argument.eq_ignore_ascii_case(flag))
+    })
+}
+
+fn audit_module(module: &DebugModule) -> Option<ModuleAudit<'_>> {
+    let compiler_info = module.compiler_info.as_ref()?;
+    // Only C and C++ modules are affected by these flags. Assembly, resources, and
+    // linker-generated modules are left out
+    let language = compiler_info.language.as_str();
+    if !["c", "cpp", "c++"]
+        .iter()
+        .any(|candidate| language.eq_ignore_ascii_case(candidate))
+    {
+        return None;
+    }
+
+    let flags = &compiler_info.flags;
+    let guard_cf = match module
+        .build_info
+        .as_ref()
+        .and_then(|build_info| build_info.command_arguments())
+    {
+        Some(arguments) => Status::from_bool(has_flag(arguments, "guard:cf")),
+        None => Status::Unknown,
+    };
+
+    Some(ModuleAudit {
+        name: &module.name,
+        gs: Status::from_bool(flags.security_checks),
+        sdl: Status::from_bool(flags.sdl),
+        guard_cf,
+        hotpatch: flags.hot_patch,
+    })
+}
+
+fn audit_image(pdb_info: &ParsedPdb, pe: Option<&PeInfo>) -> ImageAudit {
+    let names = || {
+        pdb_info
+            .public_symbols
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .chain(
+                pdb_info
+                    .procedures
+                    .iter()
+                    .map(|procedure| procedure.name.as_str()),
+            )
+    };
+    // x86 C symbols carry an extra leading underscore
+    let has_symbol =
+        |name: &str| names().any(|symbol| symbol == name || symbol.strip_prefix('_') == Some(name));
+
+    // Without the image, the linker-generated tables' symbols show the features were
+    // used, but their absence may just mean the public symbols were stripped
+    let cfg = match pe {
+        Some(pe) => Status::from_bool(pe.cfg_targets.is_some()),
+        None if has_symbol("__guard_fids_table") => Status::Pass,
+        None => Status::Unknown,
+    };
+
+    let safe_seh = match (pdb_info.machine_type.as_ref(), pe) {
+        (Some(MachineType::X86), Some(pe)) => Status::from_bool(
+            pe.safe_seh_handlers.is_some()
+                || pe.dll_characteristics & IMAGE_DLLCHARACTERISTICS_NO_SEH != 0,
+        ),
+        (Some(MachineType::X86), None) if has_symbol("__safe_se_handler_table") => Status::Pass,
+        (Some(MachineType::X86), None) | (None, _) => Status::Unknown,
+        _ => Status::NotApplicable,
+    };
+
+    let sanitizers: Vec<Instrumentation> = pdb_info
+        .assembly_info
+        .instrumentation
+        .iter()
+        .copied()
+        .filter(|instrumentation| instrumentation.is_sanitizer())
+        .collect();
+
+    ImageAudit {
+        cfg,
+        safe_seh,
+        no_sanitizers: Status::from_bool(sanitizers.is_empty()),
+        sanitizers,
+    }
+}
+
+/// Summarizes the security-relevant build settings of the image and of each C/C++
+/// module as pass/fail checks. Image-level checks are more reliable with the PE
+pub fn print_audit(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let report = AuditReport {
+        image: audit_image(pdb_info, pe),
+        modules: pdb_info
+            .debug_modules
+            .iter()
+            .filter_map(audit_module)
+            .collect(),
+    };
+
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    writeln!(output, "Image:")?;
+    writeln!(output, "\tControl Flow Guard: {}", report.image.cfg.label())?;
+    writeln!(
+        output,
+        "\tSafeSEH:            {}",
+        report.image.safe_seh.label()
+    )?;
+    writeln!(
+        output,
+        "\tNo sanitizers:      {}",
+        report.image.no_sanitizers.label()
+    )?;
+    for sanitizer in &report.image.sanitizers {
+        writeln!(output, "\t\t{:?}", sanitizer)?;
+    }
+
+    let count = |status: fn(&ModuleAudit) -> Status| {
+        report
+            .modules
+            .iter()
+            .filter(|module| status(module) == Status::Fail)
+            .count()
+    };
+    writeln!(
+        output,
+        "\nModules ({}; {} without /GS, {} without /sdl, {} without /guard:cf):",
+        report.modules.len(),
+        count(|module| module.gs),
+        count(|module| module.sdl),
+        count(|module| module.guard_cf)
+    )?;
+    writeln!(
+        output,
+        "\t{:4} {:4} {:9} {:8} Name",
+        "/GS", "/sdl", "/guard:cf", "Hotpatch"
+    )?;
+    for module in &report.modules {
+        writeln!(
+            output,
+            "\t{:4} {:4} {:9} {:8} {}",
+            module.gs.label(),
+            module.sdl.label(),
+            module.guard_cf.label(),
+            if module.hotpatch { "yes" } else { "no" },
+            module.name
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+fn architecture(machine_type: Option<&MachineType>) -> &'static str {
+    match machine_type {
+        Some(MachineType::X86) => "x86",
+        Some(MachineType::Amd64) => "x86_64",
+        Some(MachineType::Arm) | Some(MachineType::ArmNT) | Some(MachineType::Thumb) => "arm",
+        Some(MachineType::Arm64) => "arm64",
+        Some(MachineType::Ia64) => "ia64",
+        _ => "unknown",
+    }
+}
+
+/// Writes a Breakpad text symbol file. This is the documented interchange format accepted
+/// by Sentry's `symbolic` (which converts it to a SymCache) and most crash reporting pipelines.
+pub fn print_breakpad(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    let pdb_name = pdb_info
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    writeln!(
+        output,
+        "MODULE windows {} {} {}",
+        architecture(pdb_info.machine_type.as_ref()),
+        pdb_info.debug_identifier(),
+        pdb_name
+    )?;
+    if let Some(pe) = pe {
+        writeln!(
+            output,
+            "INFO CODE_ID {:08X}{:x} {}",
+            pe.timestamp,
+            pe.size_of_image,
+            pe.file_name()
+        )?;
+    }
+
+    let mut lines: Vec<&LineInfo> = pdb_info
+        .debug_modules
+        .iter()
+        .flat_map(|module| module.lines.iter())
+        .filter(|line| line.offset.is_some())
+        .collect();
+    lines.sort_by_key(|line| line.offset);
+
+    let mut files: Vec<&str> = vec![];
+    let mut file_indices: HashMap<&str, usize> = HashMap::new();
+    for line in &lines {
+        file_indices.entry(line.file.as_str()).or_insert_with(|| {
+            files.push(line.file.as_str());
+            files.len() - 1
+        });
+    }
+
+    for (i, file) in files.iter().enumerate() {
+        writeln!(output, "FILE {} {}", i, file)?;
+    }
+
+    let mut procedures: Vec<_> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| procedure.offset.map(|offset| (offset, procedure)))
+        .collect();
+    procedures.sort_by_key(|(offset, _procedure)| *offset);
+
+    for (offset, procedure) in &procedures {
+        let end = offset + procedure.len;
+        writeln!(
+            output,
+            "FUNC {:x} {:x} 0 {}",
+            offset - pdb_info.base_address,
+            procedure.len,
+            procedure.name
+        )?;
+
+        let first = lines.partition_point(|line| line.offset.unwrap() < *offset);
+        let function_lines: Vec<&LineInfo> = lines[first..]
+            .iter()
+            .take_while(|line| line.offset.unwrap() < end)
+            .copied()
+            .collect();
+
+        for (i, line) in function_lines.iter().enumerate() {
+            let line_offset = line.offset.unwrap();
+            // Lines without an explicit length extend to the next line or the end of the function
+            let len = line.len.unwrap_or_else(|| {
+                function_lines
+                    .get(i + 1)
+                    .map(|next| next.offset.unwrap())
+                    .unwrap_or(end)
+                    - line_offset
+            });
+
+            writeln!(
+                output,
+                "{:x} {:x} {} {}",
+                line_offset - pdb_info.base_address,
+                len,
+                line.line_start,
+                file_indices[line.file.as_str()]
+            )?;
+        }
+    }
+
+    let mut publics: Vec<_> = pdb_info
+        .public_symbols
+        .iter()
+        .filter_map(|symbol| symbol.offset.map(|offset| (offset, symbol)))
+        .collect();
+    publics.sort_by_key(|(offset, _symbol)| *offset);
+
+    for (offset, symbol) in publics {
+        writeln!(
+            output,
+            "PUBLIC {:x} 0 {}",
+            offset - pdb_info.base_address,
+            symbol.name
+        )?;
+    }
+
+    Ok(())
+}
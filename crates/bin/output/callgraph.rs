@@ -0,0 +1,155 @@
+use crate::filter::wildcard_match;
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use iced_x86::{Decoder, DecoderOptions, Instruction, OpKind};
+use std::collections::{BTreeSet, VecDeque};
+use std::io::{self, Write};
+
+/// Returns the index of the procedure whose address range contains `address`.
+/// `ranges` holds `(start, end, procedure index)` sorted by start
+fn procedure_at(ranges: &[(usize, usize, usize)], address: usize) -> Option<usize> {
+    let idx = match ranges.binary_search_by_key(&address, |(start, _, _)| *start) {
+        Ok(idx) => idx,
+        Err(0) => return None,
+        Err(idx) => idx - 1,
+    };
+
+    let (_, end, procedure) = ranges[idx];
+    if address < end {
+        Some(procedure)
+    } else {
+        None
+    }
+}
+
+/// Disassembles every procedure and returns `(caller, callee)` procedure index pairs
+/// for direct calls, and for jumps leaving the procedure (tail calls)
+fn call_edges(pdb_info: &ParsedPdb, pe: &PeInfo) -> BTreeSet<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize, usize)> = pdb_info
+        .procedures
+        .iter()
+        .enumerate()
+        .filter_map(|(i, procedure)| {
+            procedure
+                .offset
+                .filter(|_| procedure.len > 0)
+                .map(|offset| (offset, offset + procedure.len, i))
+        })
+        .collect();
+    ranges.sort_unstable();
+
+    let bitness = if pe.is_64 { 64 } else { 32 };
+    let mut edges = BTreeSet::new();
+    let mut instruction = Instruction::default();
+
+    for &(start, end, caller) in &ranges {
+        let rva = start - pdb_info.base_address;
+        let bytes = match pe.read_rva(rva, end - start) {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+
+        let mut decoder = Decoder::with_ip(bitness, bytes, start as u64, DecoderOptions::NONE);
+        while decoder.can_decode() {
+            decoder.decode_out(&mut instruction);
+
+            let is_direct = matches!(
+                instruction.op0_kind(),
+                OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64
+            );
+            if !is_direct {
+                continue;
+            }
+
+            let target = instruction.near_branch_target() as usize;
+            let leaves_procedure = target < start || target >= end;
+            if instruction.is_call_near() || (instruction.is_jmp_near() && leaves_procedure) {
+                if let Some(callee) = procedure_at(&ranges, target) {
+                    edges.insert((caller, callee));
+                }
+            }
+        }
+    }
+
+    edges
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes a Graphviz DOT call graph built from the direct calls found by disassembling
+/// each procedure in the PE. With `root`, only procedures reachable from procedures
+/// matching the wildcard pattern within `depth` calls are included
+pub fn print_dot_callgraph(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: &PeInfo,
+    root: Option<&str>,
+    depth: Option<usize>,
+) -> io::Result<()> {
+    let edges = call_edges(pdb_info, pe);
+
+    let roots: BTreeSet<usize> = match root {
+        Some(pattern) => pdb_info
+            .procedures
+            .iter()
+            .enumerate()
+            .filter(|(_, procedure)| wildcard_match(pattern, &procedure.name))
+            .map(|(i, _)| i)
+            .collect(),
+        None => BTreeSet::new(),
+    };
+
+    let edges: BTreeSet<(usize, usize)> = if root.is_some() {
+        // Breadth-first walk from the roots so each procedure is reached at its
+        // shallowest depth
+        let mut visited = roots.clone();
+        let mut queue: VecDeque<(usize, usize)> = roots.iter().map(|root| (*root, 0)).collect();
+        let mut reachable = BTreeSet::new();
+        while let Some((caller, level)) = queue.pop_front() {
+            if depth.map_or(false, |depth| level >= depth) {
+                continue;
+            }
+
+            for &(_, callee) in edges.range((caller, 0)..=(caller, usize::MAX)) {
+                reachable.insert((caller, callee));
+                if visited.insert(callee) {
+                    queue.push_back((callee, level + 1));
+                }
+            }
+        }
+
+        reachable
+    } else {
+        edges
+    };
+
+    let mut nodes: BTreeSet<usize> = roots.clone();
+    for &(caller, callee) in &edges {
+        nodes.insert(caller);
+        nodes.insert(callee);
+    }
+
+    writeln!(output, "digraph callgraph {{")?;
+    writeln!(output, "  node [shape=box, fontname=\"monospace\"];")?;
+    for node in nodes {
+        let procedure = &pdb_info.procedures[node];
+        let style = if roots.contains(&node) {
+            ", style=bold"
+        } else {
+            ""
+        };
+        writeln!(
+            output,
+            "  p{} [label=\"{}\"{}];",
+            node,
+            escape(&procedure.name),
+            style
+        )?;
+    }
+    for (caller, callee) in edges {
+        writeln!(output, "  p{} -> p{};", caller, callee)?;
+    }
+    writeln!(output, "}}")
+}
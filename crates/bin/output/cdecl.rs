@@ -0,0 +1,228 @@
+//! Renders parsed types as C declarations and definitions
+
+use ezpdb::symbol_types::{ParsedPdb, TypeRef};
+use ezpdb::type_info::*;
+use std::fmt::Write;
+
+/// Converts `name` into a valid C identifier by replacing any unsupported characters
+pub fn c_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) || identifier.is_empty() {
+        identifier.insert(0, '_');
+    }
+
+    identifier
+}
+
+/// Returns the C spelling of a primitive type, or `None` if it has no portable spelling
+pub fn primitive_name(kind: PrimitiveKind) -> Option<&'static str> {
+    let name = match kind {
+        PrimitiveKind::NoType | PrimitiveKind::Void => "void",
+        PrimitiveKind::Char | PrimitiveKind::RChar => "char",
+        PrimitiveKind::UChar => "unsigned char",
+        PrimitiveKind::WChar => "wchar_t",
+        PrimitiveKind::RChar16 => "uint16_t",
+        PrimitiveKind::RChar32 => "uint32_t",
+        PrimitiveKind::I8 => "int8_t",
+        PrimitiveKind::U8 => "uint8_t",
+        PrimitiveKind::I16 | PrimitiveKind::Short => "int16_t",
+        PrimitiveKind::U16 | PrimitiveKind::UShort => "uint16_t",
+        PrimitiveKind::I32 | PrimitiveKind::Long | PrimitiveKind::HRESULT => "int32_t",
+        PrimitiveKind::U32 | PrimitiveKind::ULong => "uint32_t",
+        PrimitiveKind::I64 | PrimitiveKind::Quad => "int64_t",
+        PrimitiveKind::U64 | PrimitiveKind::UQuad => "uint64_t",
+        PrimitiveKind::F32 | PrimitiveKind::F32PP => "float",
+        PrimitiveKind::F64 => "double",
+        PrimitiveKind::F80 => "long double",
+        PrimitiveKind::Bool8 => "bool",
+        PrimitiveKind::Bool16 => "uint16_t",
+        PrimitiveKind::Bool32 => "uint32_t",
+        PrimitiveKind::Bool64 => "uint64_t",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Renders a declaration of `name` with type `ty`, e.g. `uint32_t name`, `struct Foo* name`,
+/// or `uint8_t name[16]`
+pub fn c_declaration(ty: &Type, name: &str, pdb_info: &ParsedPdb) -> String {
+    match ty {
+        Type::Class(class) => format!("struct {} {}", c_identifier(&class.name), name),
+        Type::Union(union) => format!("union {} {}", c_identifier(&union.name), name),
+        Type::Enumeration(e) => format!("enum {} {}", c_identifier(&e.name), name),
+        Type::Primitive(primitive) => {
+            match (primitive_name(primitive.kind), &primitive.indirection) {
+                (Some(kind), Some(_)) => format!("{}* {}", kind, name),
+                (None, Some(_)) => format!("void* {}", name),
+                (Some(kind), None) => format!("{} {}", kind, name),
+                // Types without a portable C spelling are treated as opaque blobs
+                (None, None) => format!("uint8_t {}[{}]", name, primitive.kind.size()),
+            }
+        }
+        Type::Pointer(pointer) => match pointer.underlying_type.as_ref() {
+            Some(underlying_type) => match &*underlying_type.as_ref().borrow() {
+                // Function pointers are emitted as opaque pointers
+                Type::Procedure(_) | Type::MemberFunction(_) => format!("void* {}", name),
+                other => c_declaration(other, &format!("*{}", name), pdb_info),
+            },
+            None => format!("void* {}", name),
+        },
+        Type::Array(array) => {
+            let dimensions = array
+                .dimensions_elements
+                .iter()
+                .fold(String::new(), |accum, dimension| {
+                    format!("{}[{}]", accum, dimension)
+                });
+
+            c_declaration(
+                &*array.element_type.as_ref().borrow(),
+                &format!("{}{}", name, dimensions),
+                pdb_info,
+            )
+        }
+        Type::Modifier(modifier) => {
+            let declaration =
+                c_declaration(&*modifier.underlying_type.as_ref().borrow(), name, pdb_info);
+            if modifier.constant {
+                format!("const {}", declaration)
+            } else {
+                declaration
+            }
+        }
+        Type::Bitfield(bitfield) => format!(
+            "{} : {}",
+            c_declaration(&*bitfield.underlying_type.as_ref().borrow(), name, pdb_info),
+            bitfield.len
+        ),
+        _ => format!("void* {}", name),
+    }
+}
+
+/// Returns the size of a field's type, treating bitfields as their storage type
+pub fn field_size(ty: &Type, pdb_info: &ParsedPdb) -> usize {
+    match ty {
+        Type::Bitfield(bitfield) => bitfield
+            .underlying_type
+            .as_ref()
+            .borrow()
+            .type_size(pdb_info),
+        other => other.type_size(pdb_info),
+    }
+}
+
+/// Renders the body of a class or union, inserting explicit padding so every field
+/// lands at its recorded offset
+fn c_fields(fields: &[TypeRef], size: usize, is_union: bool, pdb_info: &ParsedPdb) -> String {
+    let mut body = String::new();
+    let mut cursor = 0usize;
+    let mut last_bitfield_offset = None;
+
+    for (i, field) in fields.iter().enumerate() {
+        let (name, offset, ty) = match &*field.as_ref().borrow() {
+            Type::Member(member) => (
+                c_identifier(&member.name),
+                member.offset,
+                std::rc::Rc::clone(&member.underlying_type),
+            ),
+            Type::BaseClass(base) => (
+                format!("base_{}", i),
+                base.offset,
+                std::rc::Rc::clone(&base.base_class),
+            ),
+            _ => continue,
+        };
+        let ty = ty.as_ref().borrow();
+        let is_bitfield = matches!(&*ty, Type::Bitfield(_));
+
+        if !is_union {
+            let continues_bitfield = is_bitfield && last_bitfield_offset == Some(offset);
+            if offset < cursor && !continues_bitfield {
+                let _ = writeln!(
+                    body,
+                    "\t/* 0x{:04X} overlaps previous field: {} */",
+                    offset,
+                    c_declaration(&*ty, &name, pdb_info)
+                );
+                continue;
+            }
+
+            if offset > cursor {
+                let _ = writeln!(
+                    body,
+                    "\tuint8_t _pad_0x{:04X}[{}];",
+                    cursor,
+                    offset - cursor
+                );
+            }
+
+            if !continues_bitfield {
+                cursor = offset + field_size(&*ty, pdb_info);
+            }
+            last_bitfield_offset = if is_bitfield { Some(offset) } else { None };
+        }
+
+        let _ = writeln!(
+            body,
+            "\t{}; /* 0x{:04X} */",
+            c_declaration(&*ty, &name, pdb_info),
+            offset
+        );
+    }
+
+    if !is_union && size > cursor {
+        let _ = writeln!(body, "\tuint8_t _pad_0x{:04X}[{}];", cursor, size - cursor);
+    }
+
+    body
+}
+
+/// Renders a complete C definition for a class, union, or enumeration. Returns `None`
+/// for forward references and types which have no C definition.
+pub fn c_definition(ty: &Type, pdb_info: &ParsedPdb) -> Option<String> {
+    match ty {
+        Type::Class(class) if !class.properties.forward_reference => Some(format!(
+            "struct {} {{\n{}}};",
+            c_identifier(&class.name),
+            c_fields(&class.fields, class.size, false, pdb_info)
+        )),
+        Type::Union(union) if !union.properties.forward_reference => Some(format!(
+            "union {} {{\n{}}};",
+            c_identifier(&union.name),
+            c_fields(&union.fields, union.size, true, pdb_info)
+        )),
+        Type::Enumeration(e) if !e.properties.forward_reference => {
+            let mut body = String::new();
+            for variant in &e.variants {
+                let _ = writeln!(
+                    body,
+                    "\t{} = {},",
+                    c_identifier(&variant.name),
+                    variant_value(&variant.value)
+                );
+            }
+
+            Some(format!("enum {} {{\n{}}};", c_identifier(&e.name), body))
+        }
+        _ => None,
+    }
+}
+
+/// Returns an enum variant's value as a signed integer
+pub fn variant_value(value: &VariantValue) -> i128 {
+    match *value {
+        VariantValue::U8(v) => v as i128,
+        VariantValue::U16(v) => v as i128,
+        VariantValue::U32(v) => v as i128,
+        VariantValue::U64(v) => v as i128,
+        VariantValue::I8(v) => v as i128,
+        VariantValue::I16(v) => v as i128,
+        VariantValue::I32(v) => v as i128,
+        VariantValue::I64(v) => v as i128,
+    }
+}
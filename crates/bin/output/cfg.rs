@@ -0,0 +1,134 @@
+use crate::group::ModuleRanges;
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+
+#[derive(Debug, Serialize)]
+struct UnsymbolizedTarget<'a> {
+    rva: usize,
+    section: Option<&'a str>,
+    /// Procedure whose range covers this target, which happens for code the compiler
+    /// split off a function or for symbols with incorrect lengths
+    within: Option<&'a str>,
+    module: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct CfgReport<'a> {
+    /// Number of entries in the guard function table
+    targets: usize,
+    /// Procedures which are valid indirect call targets
+    valid_procedures: usize,
+    /// Procedures which are not in the guard function table
+    invalid_procedures: usize,
+    /// Table entries with no procedure or public symbol starting at them
+    unsymbolized: Vec<UnsymbolizedTarget<'a>>,
+}
+
+/// Cross-references the image's Control Flow Guard function table with the PDB's
+/// symbols, counting the procedures which are valid indirect call targets and listing
+/// the targets no symbol starts at
+pub fn print_cfg_report(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: &PeInfo,
+    json: bool,
+) -> anyhow::Result<()> {
+    let cfg_targets = pe.cfg_targets.as_ref().ok_or_else(|| {
+        anyhow::anyhow!(
+            "{} has no Control Flow Guard function table",
+            pe.file_name()
+        )
+    })?;
+    let base_address = pdb_info.base_address;
+
+    let mut symbol_rvas: HashSet<usize> = pdb_info
+        .public_symbols
+        .iter()
+        .filter_map(|symbol| symbol.offset)
+        .map(|offset| offset - base_address)
+        .collect();
+    // Procedures keyed by start RVA for finding the one covering a target
+    let mut procedures: BTreeMap<usize, &Procedure> = BTreeMap::new();
+    let mut valid_procedures = 0;
+    let mut invalid_procedures = 0;
+    for procedure in &pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            let rva = offset - base_address;
+            symbol_rvas.insert(rva);
+            procedures.insert(rva, procedure);
+            if cfg_targets.contains(&rva) {
+                valid_procedures += 1;
+            } else {
+                invalid_procedures += 1;
+            }
+        }
+    }
+
+    let module_ranges = ModuleRanges::new(pdb_info);
+    let unsymbolized = cfg_targets
+        .iter()
+        .filter(|rva| !symbol_rvas.contains(rva))
+        .map(|&rva| UnsymbolizedTarget {
+            rva,
+            section: pe
+                .sections
+                .iter()
+                .find(|section| {
+                    rva >= section.virtual_address
+                        && rva - section.virtual_address
+                            < section.virtual_size.max(section.raw_size)
+                })
+                .map(|section| section.name.as_str()),
+            within: procedures
+                .range(..rva)
+                .next_back()
+                .filter(|(start, procedure)| rva < *start + procedure.len)
+                .map(|(_, procedure)| procedure.name.as_str()),
+            module: module_ranges
+                .module_index(base_address + rva)
+                .and_then(|index| pdb_info.debug_modules.get(index))
+                .map(|module| module.name.as_str()),
+        })
+        .collect();
+
+    let report = CfgReport {
+        targets: cfg_targets.len(),
+        valid_procedures,
+        invalid_procedures,
+        unsymbolized,
+    };
+
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    writeln!(output, "CFG targets:        {}", report.targets)?;
+    writeln!(output, "Valid procedures:   {}", report.valid_procedures)?;
+    writeln!(output, "Invalid procedures: {}", report.invalid_procedures)?;
+    writeln!(
+        output,
+        "\nUnsymbolized targets ({}):",
+        report.unsymbolized.len()
+    )?;
+    writeln!(
+        output,
+        "\t{:>10} {:8} {:30} Module",
+        "RVA", "Section", "Within"
+    )?;
+    for target in &report.unsymbolized {
+        writeln!(
+            output,
+            "\t{:>#10X} {:8} {:30} {}",
+            target.rva,
+            target.section.unwrap_or("<none>"),
+            target.within.unwrap_or("<none>"),
+            target.module.unwrap_or("<unknown>")
+        )?;
+    }
+
+    Ok(())
+}
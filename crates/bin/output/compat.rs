@@ -0,0 +1,186 @@
+//! Conversion of the serialized `ParsedPdb` to layouts from older releases so that
+//! existing pipelines keep working after fields are added or changed.
+//!
+//! Version 1 predates the compatibility policy, so every field added since is removed
+//! when emitting it. From version 2 on, fields are only ever added within a version and
+//! are kept when emitting an older one; only removed, renamed, or repurposed fields are
+//! converted back
+
+use ezpdb::symbol_types::FORMAT_VERSION;
+use serde_json::Value;
+
+/// Fields added to the top-level object after version 1, whether in version 2 or since
+const POST_V1_TOP_LEVEL_FIELDS: &[&str] = &[
+    "format_version",
+    "base_address",
+    "exports",
+    "sections",
+    "section_contributions",
+    "separated_code",
+    "warnings",
+    "type_aliases",
+    "string_table",
+    "identity",
+];
+
+/// Fields of types and data which reference a type, along with the variants of `Type`
+/// which consist of references
+const TYPE_REFERENCE_FIELDS: &[&str] = &[
+    "derived_from",
+    "fields",
+    "base_class",
+    "base_pointer",
+    "underlying_type",
+    "element_type",
+    "indexing_type",
+    "return_type",
+    "argument_list",
+    "class_type",
+    "this_pointer_type",
+    "method_type",
+    "nested_type",
+    "method_list",
+    "field_type",
+    "ty",
+    "FieldList",
+    "ArgumentList",
+    "VTable",
+];
+
+/// Returns whether `version` is a layout this release can produce
+pub fn is_supported(version: u32) -> bool {
+    (1..=FORMAT_VERSION).contains(&version)
+}
+
+fn remove_from_each(value: &mut Value, collection: &str, fields: &[&str]) {
+    if let Some(Value::Array(items)) = value.get_mut(collection) {
+        for item in items {
+            if let Value::Object(item) = item {
+                for field in fields {
+                    item.remove(*field);
+                }
+            }
+        }
+    }
+}
+
+/// Converts a source file checksum from `{"algorithm": "md5", "digest": "0a1b.."}` back
+/// to the externally tagged `{"Md5": [10, 27, ..]}` used before version 3
+fn downgrade_checksum(checksum: &mut Value) {
+    let algorithm = match checksum.get("algorithm").and_then(Value::as_str) {
+        Some("md5") => "Md5",
+        Some("sha1") => "Sha1",
+        Some("sha256") => "Sha256",
+        _ => {
+            *checksum = Value::from("None");
+            return;
+        }
+    };
+    let digest = checksum
+        .get("digest")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let bytes: Vec<Value> = (0..digest.len() / 2)
+        .filter_map(|i| u8::from_str_radix(digest.get(i * 2..i * 2 + 2)?, 16).ok())
+        .map(Value::from)
+        .collect();
+
+    let mut legacy = serde_json::Map::new();
+    legacy.insert(algorithm.to_string(), Value::Array(bytes));
+    *checksum = Value::Object(legacy);
+}
+
+/// Replaces the type indices in `value` with the definitions they refer to in `types`
+fn inline_type_references(value: &mut Value, types: &serde_json::Map<String, Value>) {
+    match value {
+        Value::Object(object) => {
+            for (field, child) in object.iter_mut() {
+                if TYPE_REFERENCE_FIELDS.contains(&field.as_str()) {
+                    inline_type_reference(child, types);
+                } else {
+                    inline_type_references(child, types);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                inline_type_references(item, types);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces `reference`, or each reference in it if it's an array, with its definition
+/// if it's an index
+fn inline_type_reference(reference: &mut Value, types: &serde_json::Map<String, Value>) {
+    match reference {
+        Value::Number(index) => {
+            if let Some(definition) = types.get(&index.to_string()) {
+                *reference = definition.clone();
+                inline_type_references(reference, types);
+            }
+        }
+        Value::Array(references) => {
+            for reference in references {
+                inline_type_reference(reference, types);
+            }
+        }
+        _ => inline_type_references(reference, types),
+    }
+}
+
+/// Rewrites `value`, a serialized `ParsedPdb` in the current layout, into the layout
+/// used by `version`
+pub fn downgrade(value: &mut Value, version: u32) {
+    if version >= 2 {
+        if let Value::Object(object) = value {
+            object.insert("format_version".to_string(), Value::from(version));
+        }
+    }
+
+    if version < 4 {
+        if let Some(Value::Object(types)) = value.get("types") {
+            let types = types.clone();
+            for collection in &["types", "global_data"] {
+                if let Some(collection) = value.get_mut(*collection) {
+                    inline_type_references(collection, &types);
+                }
+            }
+        }
+    }
+
+    if version < 3 {
+        if let Some(Value::Array(modules)) = value.get_mut("debug_modules") {
+            let files = modules
+                .iter_mut()
+                .filter_map(|module| module.get_mut("source_files"))
+                .filter_map(Value::as_array_mut)
+                .flatten();
+            for file in files {
+                if let Some(checksum) = file.get_mut("checksum") {
+                    downgrade_checksum(checksum);
+                }
+            }
+        }
+    }
+
+    // Version 1 consumers predate additive fields, so strip everything added since
+    if version < 2 {
+        if let Value::Object(object) = value {
+            for field in POST_V1_TOP_LEVEL_FIELDS {
+                object.remove(*field);
+            }
+        }
+
+        remove_from_each(value, "debug_modules", &["lines"]);
+        remove_from_each(
+            value,
+            "procedures",
+            &["locals", "hashes", "cfg_target", "location"],
+        );
+        for collection in &["public_symbols", "global_data", "thunks"] {
+            remove_from_each(value, collection, &["location"]);
+        }
+    }
+}
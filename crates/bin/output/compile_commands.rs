@@ -0,0 +1,83 @@
+use ezpdb::symbol_types::*;
+use serde_json::json;
+use std::io::{self, Write};
+
+/// Splits a Windows command line into arguments following the MSVC runtime's rules:
+/// whitespace separates arguments unless quoted, and backslashes are literal unless they
+/// precede a quote
+fn split_command_line(command_line: &str) -> Vec<String> {
+    let mut arguments = vec![];
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut quoted = false;
+    let mut backslashes = 0;
+
+    for c in command_line.chars() {
+        match c {
+            '\\' => {
+                backslashes += 1;
+                in_argument = true;
+                continue;
+            }
+            '"' => {
+                current.extend(std::iter::repeat('\\').take(backslashes / 2));
+                if backslashes % 2 == 1 {
+                    current.push('"');
+                } else {
+                    quoted = !quoted;
+                }
+                in_argument = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                current.extend(std::iter::repeat('\\').take(backslashes));
+                if in_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            c => {
+                current.extend(std::iter::repeat('\\').take(backslashes));
+                current.push(c);
+                in_argument = true;
+            }
+        }
+        backslashes = 0;
+    }
+
+    current.extend(std::iter::repeat('\\').take(backslashes));
+    if in_argument {
+        arguments.push(current);
+    }
+
+    arguments
+}
+
+/// Writes a best-effort `compile_commands.json` reconstructed from each module's
+/// `S_BUILDINFO` record. Modules without a source file in their build info (e.g. those
+/// from assembler or resource compiler output) are omitted
+pub fn print_compile_commands(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let commands: Vec<serde_json::Value> = pdb_info
+        .debug_modules
+        .iter()
+        .filter_map(|module| {
+            let build_info = module.build_info.as_ref()?;
+            let file = build_info.source_file()?;
+
+            let mut arguments = vec![build_info.build_tool().unwrap_or("cl.exe").to_string()];
+            if let Some(command_arguments) = build_info.command_arguments() {
+                arguments.extend(split_command_line(command_arguments));
+            }
+            arguments.push(file.to_string());
+
+            Some(json!({
+                "directory": build_info.current_directory().unwrap_or(""),
+                "file": file,
+                "arguments": arguments,
+                "output": module.object_file_name,
+            }))
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(&mut *output, &commands)?;
+    writeln!(output)
+}
@@ -0,0 +1,100 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use std::io::{self, Write};
+
+/// Returns `(rva, len, name)` for every procedure with a known address, sorted by RVA
+fn function_ranges(pdb_info: &ParsedPdb) -> Vec<(usize, usize, &str)> {
+    let mut ranges: Vec<_> = pdb_info
+        .procedures
+        .iter()
+        .filter(|procedure| procedure.len > 0)
+        .filter_map(|procedure| {
+            procedure.offset.map(|offset| {
+                (
+                    offset - pdb_info.base_address,
+                    procedure.len,
+                    procedure.name.as_str(),
+                )
+            })
+        })
+        .collect();
+    ranges.sort();
+    ranges.dedup_by_key(|(rva, _len, _name)| *rva);
+
+    ranges
+}
+
+/// Writes a tab-separated list of function boundaries: start RVA, end RVA (exclusive), and name
+pub fn print_boundaries(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    for (rva, len, name) in function_ranges(pdb_info) {
+        writeln!(output, "0x{:08X}\t0x{:08X}\t{}", rva, rva + len, name)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a drcov (DynamoRIO coverage) file with one block per function. Loading it in
+/// Lighthouse or Dragondance marks out every function the PDB knows about, which is useful
+/// as the denominator when comparing against real coverage.
+pub fn print_drcov(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    let ranges = function_ranges(pdb_info);
+
+    let image_size = match pe {
+        Some(pe) => pe.size_of_image as usize,
+        None => pdb_info
+            .sections
+            .iter()
+            .map(|section| section.offset + section.size - pdb_info.base_address)
+            .max()
+            .unwrap_or(0),
+    };
+
+    // drcov blocks have a 16-bit size so large functions are split into several blocks
+    let mut blocks: Vec<(u32, u16)> = vec![];
+    for (rva, len, _name) in &ranges {
+        let mut start = *rva;
+        let end = rva + len;
+        while start < end {
+            let size = (end - start).min(u16::MAX as usize);
+            blocks.push((start as u32, size as u16));
+            start += size;
+        }
+    }
+
+    let path = match pe {
+        Some(pe) => pe.path.display().to_string(),
+        None => super::image_name(pdb_info, pe),
+    };
+
+    writeln!(output, "DRCOV VERSION: 2")?;
+    writeln!(output, "DRCOV FLAVOR: drcov")?;
+    writeln!(output, "Module Table: version 2, count 1")?;
+    writeln!(
+        output,
+        "Columns: id, base, end, entry, checksum, timestamp, path"
+    )?;
+    writeln!(
+        output,
+        " 0, 0x{:016x}, 0x{:016x}, 0x{:016x}, 0x{:08x}, 0x{:08x}, {}",
+        pdb_info.base_address,
+        pdb_info.base_address + image_size,
+        0,
+        0,
+        pe.map(|pe| pe.timestamp).unwrap_or(0),
+        path
+    )?;
+    writeln!(output, "BB Table: {} bbs", blocks.len())?;
+
+    for (start, size) in blocks {
+        output.write_all(&start.to_le_bytes())?;
+        output.write_all(&size.to_le_bytes())?;
+        // module id
+        output.write_all(&0u16.to_le_bytes())?;
+    }
+
+    Ok(())
+}
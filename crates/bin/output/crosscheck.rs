@@ -0,0 +1,32 @@
+use crate::dbghelp::CrossCheckReport;
+use std::io::Write;
+
+/// Writes the differences between pdbview's parse of a PDB and dbghelp's
+pub fn print_cross_check(
+    output: &mut impl Write,
+    report: &CrossCheckReport,
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        serde_json::to_writer(output, report)?;
+        return Ok(());
+    }
+
+    for (kind, count) in &report.compared {
+        writeln!(output, "Compared {} {} names", count, kind)?;
+    }
+    writeln!(output, "Discrepancies: {}", report.discrepancies.len())?;
+    for discrepancy in &report.discrepancies {
+        writeln!(
+            output,
+            "{:<10} {:<8} {}\n\tpdbview: {}\n\tdbghelp: {}",
+            discrepancy.kind,
+            discrepancy.field,
+            discrepancy.name,
+            discrepancy.native.as_deref().unwrap_or("-"),
+            discrepancy.dbghelp.as_deref().unwrap_or("-")
+        )?;
+    }
+
+    Ok(())
+}
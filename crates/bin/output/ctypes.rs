@@ -0,0 +1,297 @@
+//! Renders parsed types as Python `ctypes` structure definitions
+
+use crate::output::cdecl::{c_identifier, variant_value};
+use crate::output::layout::{layout, unique_name, Slot, TypeSelection};
+use crate::output::pointer_size;
+use ezpdb::symbol_types::{ParsedPdb, TypeRef};
+use ezpdb::type_info::*;
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Converts `name` into an identifier which isn't a Python keyword
+fn python_identifier(name: &str) -> String {
+    let identifier = c_identifier(name);
+    if KEYWORDS.contains(&identifier.as_str()) {
+        format!("{}_", identifier)
+    } else {
+        identifier
+    }
+}
+
+/// Returns the `ctypes` type of a fixed-size primitive. `wchar_t` is 4 bytes on most
+/// non-Windows platforms, so wide characters are plain 16-bit integers
+fn primitive_name(kind: PrimitiveKind) -> Option<&'static str> {
+    let name = match kind {
+        PrimitiveKind::Char | PrimitiveKind::RChar | PrimitiveKind::I8 => "ctypes.c_int8",
+        PrimitiveKind::UChar | PrimitiveKind::U8 | PrimitiveKind::Bool8 => "ctypes.c_uint8",
+        PrimitiveKind::WChar
+        | PrimitiveKind::RChar16
+        | PrimitiveKind::UShort
+        | PrimitiveKind::U16
+        | PrimitiveKind::Bool16 => "ctypes.c_uint16",
+        PrimitiveKind::Short | PrimitiveKind::I16 => "ctypes.c_int16",
+        PrimitiveKind::RChar32
+        | PrimitiveKind::ULong
+        | PrimitiveKind::U32
+        | PrimitiveKind::Bool32 => "ctypes.c_uint32",
+        PrimitiveKind::Long | PrimitiveKind::I32 | PrimitiveKind::HRESULT => "ctypes.c_int32",
+        PrimitiveKind::UQuad | PrimitiveKind::U64 | PrimitiveKind::Bool64 => "ctypes.c_uint64",
+        PrimitiveKind::Quad | PrimitiveKind::I64 => "ctypes.c_int64",
+        PrimitiveKind::F32 | PrimitiveKind::F32PP => "ctypes.c_float",
+        PrimitiveKind::F64 => "ctypes.c_double",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+struct Generator<'a> {
+    pdb_info: &'a ParsedPdb,
+    selection: TypeSelection,
+    pointer_size: usize,
+}
+
+impl<'a> Generator<'a> {
+    /// Pointers of a 64-bit target are typed pointers. Pointers of a 32-bit target are
+    /// plain addresses, since `ctypes` pointers take the width of the host
+    fn pointer(&self, pointee: Option<String>) -> String {
+        match pointee {
+            _ if self.pointer_size != 8 => "ctypes.c_uint32".to_string(),
+            Some(pointee) => format!("ctypes.POINTER({})", pointee),
+            None => "ctypes.c_void_p".to_string(),
+        }
+    }
+
+    /// Returns the type a pointer to `ty` points to, or `None` for an opaque pointer
+    fn pointee(&self, ty: &Type) -> Option<String> {
+        match ty {
+            Type::Class(_) | Type::Union(_) => self.selection.identifier(ty).map(str::to_string),
+            Type::Modifier(modifier) => self.pointee(&*modifier.underlying_type.as_ref().borrow()),
+            Type::Procedure(_) | Type::MemberFunction(_) => None,
+            Type::Enumeration(_) => Some(self.ctype(ty)),
+            Type::Primitive(primitive) if primitive.indirection.is_none() => {
+                primitive_name(primitive.kind).map(str::to_string)
+            }
+            Type::Primitive(_) | Type::Pointer(_) => Some(self.ctype(ty)),
+            _ => None,
+        }
+    }
+
+    fn ctype(&self, ty: &Type) -> String {
+        match ty {
+            Type::Class(_) | Type::Union(_) => match self.selection.identifier(ty) {
+                Some(identifier) => identifier.to_string(),
+                None => format!("(ctypes.c_uint8 * {})", ty.type_size(self.pdb_info)),
+            },
+            // Enums are stored as their underlying integer
+            Type::Enumeration(e) => self.ctype(&*e.underlying_type.as_ref().borrow()),
+            Type::Primitive(primitive) => match &primitive.indirection {
+                Some(_) => self.pointer(primitive_name(primitive.kind).map(str::to_string)),
+                None => match primitive_name(primitive.kind) {
+                    Some(name) => name.to_string(),
+                    None => format!("(ctypes.c_uint8 * {})", primitive.kind.size()),
+                },
+            },
+            Type::Pointer(pointer) => self.pointer(
+                pointer
+                    .underlying_type
+                    .as_ref()
+                    .and_then(|underlying_type| self.pointee(&*underlying_type.as_ref().borrow())),
+            ),
+            Type::Array(array) => array.dimensions_elements.iter().rev().fold(
+                self.ctype(&*array.element_type.as_ref().borrow()),
+                |accum, dimension| format!("({} * {})", accum, dimension),
+            ),
+            Type::Modifier(modifier) => self.ctype(&*modifier.underlying_type.as_ref().borrow()),
+            Type::Bitfield(bitfield) => self.ctype(&*bitfield.underlying_type.as_ref().borrow()),
+            _ => self.pointer(None),
+        }
+    }
+
+    /// Writes the `_fields_` of a class or union, followed by read-only properties
+    /// extracting each bitfield from its storage unit
+    fn write_fields(
+        &self,
+        output: &mut impl Write,
+        identifier: &str,
+        fields: &[TypeRef],
+        size: usize,
+        is_union: bool,
+    ) -> io::Result<()> {
+        let mut used = HashSet::new();
+        let mut properties = vec![];
+
+        writeln!(output, "{}._pack_ = 1", identifier)?;
+        writeln!(output, "{}._fields_ = [", identifier)?;
+        for slot in layout(fields, size, is_union, self.pdb_info) {
+            match slot {
+                Slot::Padding { offset, len } => writeln!(
+                    output,
+                    "    (\"_pad_0x{:04X}\", ctypes.c_uint8 * {}),",
+                    offset, len
+                )?,
+                Slot::Field { name, offset, ty } => writeln!(
+                    output,
+                    "    (\"{}\", {}),  # 0x{:04X}",
+                    unique_name(python_identifier(&name), &mut used),
+                    self.ctype(&*ty.as_ref().borrow()),
+                    offset
+                )?,
+                Slot::Bitfields {
+                    offset,
+                    storage,
+                    bits,
+                } => {
+                    let storage_name =
+                        unique_name(format!("_bitfield_0x{:04X}", offset), &mut used);
+                    writeln!(
+                        output,
+                        "    (\"{}\", {}),  # 0x{:04X}",
+                        storage_name,
+                        self.ctype(&*storage.as_ref().borrow()),
+                        offset
+                    )?;
+                    for (name, position, len) in bits {
+                        properties.push((
+                            python_identifier(&name),
+                            storage_name.clone(),
+                            position,
+                            len,
+                        ));
+                    }
+                }
+                Slot::Overlap { name, offset } => writeln!(
+                    output,
+                    "    # 0x{:04X} overlaps previous field: {}",
+                    offset, name
+                )?,
+            }
+        }
+        writeln!(output, "]")?;
+
+        for (name, storage_name, position, len) in properties {
+            // Properties can't shadow fields
+            if !used.insert(name.clone()) {
+                continue;
+            }
+            let mask = (1u128 << len) - 1;
+            writeln!(
+                output,
+                "{}.{} = property(lambda self: (self.{} >> {}) & 0x{:X})",
+                identifier, name, storage_name, position, mask
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the class statement for a type. Fields are assigned afterwards, so classes
+    /// may point to each other regardless of order
+    fn write_declaration(
+        &self,
+        output: &mut impl Write,
+        key: &str,
+        identifier: &str,
+    ) -> io::Result<()> {
+        let ty = self.selection.definitions[key].as_ref().borrow();
+        match &*ty {
+            Type::Class(class) => writeln!(
+                output,
+                "\n\nclass {}(ctypes.Structure):\n    \"\"\"`{}`, 0x{:X} bytes\"\"\"",
+                identifier, class.name, class.size
+            ),
+            Type::Union(union) => writeln!(
+                output,
+                "\n\nclass {}(ctypes.Union):\n    \"\"\"`{}`, 0x{:X} bytes\"\"\"",
+                identifier, union.name, union.size
+            ),
+            Type::Enumeration(e) => {
+                writeln!(
+                    output,
+                    "\n\nclass {}:\n    \"\"\"`{}`\"\"\"",
+                    identifier, e.name
+                )?;
+                let mut used = HashSet::new();
+                for variant in &e.variants {
+                    let name = python_identifier(&variant.name);
+                    if used.insert(name.clone()) {
+                        writeln!(output, "    {} = {}", name, variant_value(&variant.value))?;
+                    }
+                }
+
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn write_definition(
+        &self,
+        output: &mut impl Write,
+        key: &str,
+        identifier: &str,
+    ) -> io::Result<()> {
+        let ty = self.selection.definitions[key].as_ref().borrow();
+        let (fields, size, is_union) = match &*ty {
+            Type::Class(class) => (&class.fields, class.size, false),
+            Type::Union(union) => (&union.fields, union.size, true),
+            _ => return Ok(()),
+        };
+
+        writeln!(output)?;
+        self.write_fields(output, identifier, fields, size, is_union)?;
+        writeln!(
+            output,
+            "assert ctypes.sizeof({}) == 0x{:X}",
+            identifier, size
+        )
+    }
+}
+
+/// Writes Python `ctypes` definitions of the classes, unions, and enums whose names match
+/// any of `patterns`, or of every type if there are none. Types embedded by value are
+/// included so the output runs on its own, while pointers to other types become
+/// `c_void_p`. Structures are packed with explicit padding so every field lands at its
+/// recorded offset
+pub fn print_ctypes(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    patterns: &[String],
+) -> io::Result<()> {
+    let generator = Generator {
+        pdb_info,
+        selection: TypeSelection::new(pdb_info, patterns, python_identifier),
+        pointer_size: pointer_size(pdb_info),
+    };
+
+    writeln!(
+        output,
+        "# Generated by pdbview from {} ({})\n\nimport ctypes",
+        pdb_info
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        pdb_info.debug_identifier()
+    )?;
+
+    for (key, identifier) in &generator.selection.identifiers {
+        generator.write_declaration(output, key, identifier)?;
+    }
+
+    // `_fields_` can only refer to types whose own fields are already set
+    writeln!(output)?;
+    for key in generator.selection.dependency_order() {
+        let identifier = &generator.selection.identifiers[key];
+        generator.write_definition(output, key, identifier)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use std::io::{self, Write};
+
+/// Writes a module-definition (.def) file describing the module's exports.
+///
+/// Exports are taken from the PE export table when a PE is provided, using public
+/// symbols to recover names for ordinal-only exports. Otherwise the `S_EXPORT`
+/// records the linker wrote to the PDB are used.
+pub fn print_def(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    writeln!(output, "LIBRARY \"{}\"", super::image_name(pdb_info, pe))?;
+    writeln!(output, "EXPORTS")?;
+
+    match pe {
+        Some(pe) => {
+            let mut exports: Vec<_> = pe.exports.iter().collect();
+            exports.sort_by_key(|export| export.ordinal);
+
+            for export in exports {
+                if let Some(forwarder) = &export.forwarder {
+                    let name = export.name.as_deref().unwrap_or_default();
+                    writeln!(output, "\t{}={} @{}", name, forwarder, export.ordinal)?;
+                    continue;
+                }
+
+                match &export.name {
+                    Some(name) => writeln!(output, "\t{} @{}", name, export.ordinal)?,
+                    None => {
+                        // Ordinal-only export. Name it after the public symbol at the same address
+                        let address = export.rva + pdb_info.base_address;
+                        let public = pdb_info
+                            .public_symbols
+                            .iter()
+                            .find(|symbol| symbol.offset == Some(address));
+
+                        match public {
+                            Some(symbol) => {
+                                writeln!(output, "\t{} @{} NONAME", symbol.name, export.ordinal)?
+                            }
+                            None => writeln!(
+                                output,
+                                "\tOrdinal{} @{} NONAME",
+                                export.ordinal, export.ordinal
+                            )?,
+                        }
+                    }
+                }
+            }
+        }
+        None => {
+            let mut exports: Vec<&Export> = pdb_info.exports.iter().collect();
+            exports.sort_by_key(|export| export.ordinal);
+
+            for export in exports {
+                write!(output, "\t{} @{}", export.name, export.ordinal)?;
+                if export.is_no_name {
+                    write!(output, " NONAME")?;
+                }
+                if export.is_private {
+                    write!(output, " PRIVATE")?;
+                }
+                if export.is_data {
+                    write!(output, " DATA")?;
+                }
+                writeln!(output)?;
+            }
+        }
+    }
+
+    Ok(())
+}
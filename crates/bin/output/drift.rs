@@ -0,0 +1,122 @@
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// A symbol from the first PDB and its address in each PDB it was matched in
+#[derive(Debug, Serialize)]
+struct DriftRow<'a> {
+    kind: &'static str,
+    name: &'a str,
+    /// Address in each PDB, in the order they were provided
+    addresses: Vec<Option<usize>>,
+}
+
+/// Kinds of symbols matched between PDBs
+const KINDS: [&str; 3] = ["procedure", "public", "data"];
+
+/// Returns the `(key, name, address)` of every symbol of `kind`. Procedures are keyed by
+/// their stable ID and everything else by name
+fn symbol_keys<'a>(pdb_info: &'a ParsedPdb, kind: &str) -> Vec<(&'a str, &'a str, Option<usize>)> {
+    match kind {
+        "procedure" => pdb_info
+            .procedures
+            .iter()
+            .map(|procedure| {
+                let key = procedure.stable_id.as_deref().unwrap_or(&procedure.name);
+                (key, procedure.name.as_str(), procedure.offset)
+            })
+            .collect(),
+        "public" => pdb_info
+            .public_symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.name.as_str(), symbol.offset))
+            .collect(),
+        _ => pdb_info
+            .global_data
+            .iter()
+            .map(|data| (data.name.as_str(), data.name.as_str(), data.offset))
+            .collect(),
+    }
+}
+
+/// Maps each key to its symbol's address. Keys which appear more than once are ambiguous
+/// and left out so they can't be matched to the wrong symbol
+fn unique_addresses<'a>(keys: &[(&'a str, &str, Option<usize>)]) -> HashMap<&'a str, usize> {
+    let mut addresses: HashMap<&str, Option<usize>> = HashMap::new();
+    for (key, _name, address) in keys {
+        addresses
+            .entry(*key)
+            .and_modify(|existing| *existing = None)
+            .or_insert(*address);
+    }
+
+    addresses
+        .into_iter()
+        .filter_map(|(key, address)| address.map(|address| (key, address)))
+        .collect()
+}
+
+/// Writes the address of every procedure, public symbol, and global in the first PDB
+/// alongside the address of the same symbol in each later PDB, for porting annotations
+/// and hooks between builds. Procedures are matched by stable ID and other symbols by
+/// name. Symbols whose key isn't unique within a PDB are left unmatched
+pub fn print_drift(output: &mut impl Write, pdbs: &[ParsedPdb], json: bool) -> io::Result<()> {
+    let (first, rest) = match pdbs.split_first() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+
+    let mut rows = vec![];
+    for kind in KINDS.iter() {
+        let keys = symbol_keys(first, kind);
+        let first_addresses = unique_addresses(&keys);
+        let later: Vec<HashMap<&str, usize>> = rest
+            .iter()
+            .map(|pdb| unique_addresses(&symbol_keys(pdb, kind)))
+            .collect();
+
+        for (key, name, _address) in &keys {
+            let address = match first_addresses.get(key) {
+                Some(address) => *address,
+                None => continue,
+            };
+
+            let mut addresses = vec![Some(address)];
+            addresses.extend(later.iter().map(|addresses| addresses.get(key).copied()));
+            rows.push(DriftRow {
+                kind: *kind,
+                name: *name,
+                addresses,
+            });
+        }
+    }
+
+    if json {
+        return Ok(serde_json::to_writer(output, &rows)?);
+    }
+
+    write!(output, "{:<10}", "Kind")?;
+    for pdb in pdbs {
+        let file_name = pdb
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        write!(output, " {:<18}", file_name)?;
+    }
+    writeln!(output, " Name")?;
+
+    for row in &rows {
+        write!(output, "{:<10}", row.kind)?;
+        for address in &row.addresses {
+            match address {
+                Some(address) => write!(output, " 0x{:016X}", address)?,
+                None => write!(output, " {:<18}", "-")?,
+            }
+        }
+        writeln!(output, " {}", row.name)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,370 @@
+//! Prints type layouts and instances in the style of WinDbg's `dt` command
+
+use crate::output::cdecl::variant_value;
+use crate::output::layout::TypeSelection;
+use crate::output::{format_type_name, pointer_size};
+use ezpdb::symbol_types::{ParsedPdb, TypeRef};
+use ezpdb::type_info::*;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Indentation added for each level of nested fields
+const INDENT: usize = 3;
+
+/// Returns WinDbg's name for a primitive type
+fn primitive_name(kind: PrimitiveKind) -> Option<&'static str> {
+    let name = match kind {
+        PrimitiveKind::NoType | PrimitiveKind::Void => "Void",
+        PrimitiveKind::Char | PrimitiveKind::RChar | PrimitiveKind::I8 => "Char",
+        PrimitiveKind::UChar | PrimitiveKind::U8 => "UChar",
+        PrimitiveKind::WChar | PrimitiveKind::RChar16 => "Wchar",
+        PrimitiveKind::Short | PrimitiveKind::I16 => "Int2B",
+        PrimitiveKind::UShort | PrimitiveKind::U16 => "Uint2B",
+        PrimitiveKind::Long | PrimitiveKind::I32 | PrimitiveKind::HRESULT => "Int4B",
+        PrimitiveKind::ULong | PrimitiveKind::U32 | PrimitiveKind::RChar32 => "Uint4B",
+        PrimitiveKind::Quad | PrimitiveKind::I64 => "Int8B",
+        PrimitiveKind::UQuad | PrimitiveKind::U64 => "Uint8B",
+        PrimitiveKind::F32 | PrimitiveKind::F32PP | PrimitiveKind::F64 => "Float",
+        PrimitiveKind::Bool8 | PrimitiveKind::Bool16 | PrimitiveKind::Bool32 => "Bool",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+fn is_signed(kind: PrimitiveKind) -> bool {
+    matches!(
+        kind,
+        PrimitiveKind::Char
+            | PrimitiveKind::RChar
+            | PrimitiveKind::I8
+            | PrimitiveKind::Short
+            | PrimitiveKind::I16
+            | PrimitiveKind::Long
+            | PrimitiveKind::I32
+            | PrimitiveKind::HRESULT
+            | PrimitiveKind::Quad
+            | PrimitiveKind::I64
+            | PrimitiveKind::Octa
+            | PrimitiveKind::I128
+    )
+}
+
+/// Reads a little-endian integer of `size` bytes at `offset`, if the bytes are available
+fn read_unsigned(memory: &[u8], offset: usize, size: usize) -> Option<u128> {
+    if size == 0 || size > 16 {
+        return None;
+    }
+
+    let bytes = memory.get(offset..offset + size)?;
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0u128, |value, byte| (value << 8) | *byte as u128),
+    )
+}
+
+fn sign_extend(value: u128, size: usize) -> i128 {
+    let shift = 128 - size * 8;
+    ((value << shift) as i128) >> shift
+}
+
+/// Returns the name of a type as `dt` displays it
+fn type_description(ty: &Type, pointer_bits: usize) -> String {
+    match ty {
+        Type::Class(class) => class.name.clone(),
+        Type::Union(union) => union.name.clone(),
+        Type::Enumeration(e) => e.name.clone(),
+        Type::Primitive(primitive) => {
+            let name = primitive_name(primitive.kind)
+                .map(str::to_string)
+                .unwrap_or_else(|| format_type_name(ty));
+            match primitive.indirection {
+                Some(_) => format!("Ptr{} {}", pointer_bits, name),
+                None => name,
+            }
+        }
+        Type::Pointer(pointer) => {
+            let pointee = match pointer.underlying_type.as_ref() {
+                Some(underlying_type) => match &*underlying_type.as_ref().borrow() {
+                    Type::Procedure(_) | Type::MemberFunction(_) => "Void".to_string(),
+                    other => type_description(other, pointer_bits),
+                },
+                None => "Void".to_string(),
+            };
+            format!("Ptr{} {}", pointer_bits, pointee)
+        }
+        Type::Array(array) => {
+            let dimensions: Vec<String> = array
+                .dimensions_elements
+                .iter()
+                .map(|dimension| format!("[{}]", dimension))
+                .collect();
+            format!(
+                "{} {}",
+                dimensions.join(" "),
+                type_description(&*array.element_type.as_ref().borrow(), pointer_bits)
+            )
+        }
+        Type::Modifier(modifier) => {
+            type_description(&*modifier.underlying_type.as_ref().borrow(), pointer_bits)
+        }
+        Type::Bitfield(bitfield) => format!(
+            "Pos {}, {} Bit{}",
+            bitfield.position,
+            bitfield.len,
+            if bitfield.len == 1 { "" } else { "s" }
+        ),
+        other => format_type_name(other),
+    }
+}
+
+struct Dt<'a> {
+    pdb_info: &'a ParsedPdb,
+    selection: TypeSelection,
+    pointer_size: usize,
+    /// Bytes of the instance being displayed, starting at its first byte
+    memory: Option<Vec<u8>>,
+}
+
+impl<'a> Dt<'a> {
+    /// Formats the value of a field of type `ty` at `offset` within the instance
+    fn value(&self, ty: &Type, offset: usize, memory: &[u8]) -> Option<String> {
+        let value = match ty {
+            Type::Primitive(primitive) if primitive.indirection.is_none() => {
+                let size = primitive.kind.size();
+                let raw = read_unsigned(memory, offset, size)?;
+                match primitive.kind {
+                    PrimitiveKind::NoType | PrimitiveKind::Void => String::new(),
+                    PrimitiveKind::F32 | PrimitiveKind::F32PP => {
+                        format!("{}", f32::from_bits(raw as u32))
+                    }
+                    PrimitiveKind::F64 => format!("{}", f64::from_bits(raw as u64)),
+                    PrimitiveKind::Char
+                    | PrimitiveKind::RChar
+                    | PrimitiveKind::UChar
+                    | PrimitiveKind::I8
+                    | PrimitiveKind::U8
+                        if (0x20..0x7F).contains(&raw) =>
+                    {
+                        format!("0x{:02x} '{}'", raw, raw as u8 as char)
+                    }
+                    kind if is_signed(kind) => format!("0n{}", sign_extend(raw, size)),
+                    _ => format!("0x{:x}", raw),
+                }
+            }
+            Type::Primitive(_) | Type::Pointer(_) => format!(
+                "0x{:0width$x}",
+                read_unsigned(memory, offset, self.pointer_size)?,
+                width = self.pointer_size * 2
+            ),
+            Type::Enumeration(e) => {
+                let underlying_type = e.underlying_type.as_ref().borrow();
+                let size = underlying_type.type_size(self.pdb_info);
+                let raw = read_unsigned(memory, offset, size)?;
+                let value = match &*underlying_type {
+                    Type::Primitive(primitive) if is_signed(primitive.kind) => {
+                        sign_extend(raw, size)
+                    }
+                    _ => raw as i128,
+                };
+                let name = e
+                    .variants
+                    .iter()
+                    .find(|variant| variant_value(&variant.value) == value)
+                    .map(|variant| variant.name.as_str())
+                    .unwrap_or("???");
+                format!("0n{} ( {} )", value, name)
+            }
+            Type::Bitfield(bitfield) => {
+                let size = bitfield
+                    .underlying_type
+                    .as_ref()
+                    .borrow()
+                    .type_size(self.pdb_info);
+                let raw = read_unsigned(memory, offset, size)?;
+                let bits = (raw >> bitfield.position) & ((1u128 << bitfield.len) - 1);
+                format!("0y{:0width$b}", bits, width = bitfield.len)
+            }
+            Type::Modifier(modifier) => {
+                return self.value(&*modifier.underlying_type.as_ref().borrow(), offset, memory)
+            }
+            other => type_description(other, self.pointer_size * 8),
+        };
+
+        Some(value)
+    }
+
+    /// Returns the definition of the class or union `ty` holds by value, if any
+    fn nested_definition(&self, ty: &Type) -> Option<TypeRef> {
+        match ty {
+            Type::Class(_) | Type::Union(_) => self.selection.definition(ty),
+            Type::Modifier(modifier) => {
+                self.nested_definition(&*modifier.underlying_type.as_ref().borrow())
+            }
+            _ => None,
+        }
+    }
+
+    /// Writes one line per field of a class or union starting at `base` within the
+    /// instance, expanding nested classes and unions `depth` levels deep
+    fn write_fields(
+        &self,
+        output: &mut impl Write,
+        fields: &[TypeRef],
+        base: usize,
+        indent: usize,
+        depth: usize,
+    ) -> io::Result<()> {
+        let mut rows = vec![];
+        for field in fields {
+            match &*field.as_ref().borrow() {
+                Type::Member(member) => rows.push((
+                    member.name.clone(),
+                    member.offset,
+                    Rc::clone(&member.underlying_type),
+                )),
+                Type::BaseClass(base_class) => rows.push((
+                    "__BaseClass".to_string(),
+                    base_class.offset,
+                    Rc::clone(&base_class.base_class),
+                )),
+                _ => {}
+            }
+        }
+        let width = rows
+            .iter()
+            .map(|(name, _, _)| name.len())
+            .max()
+            .unwrap_or(0);
+
+        for (name, offset, ty) in rows {
+            let ty = ty.as_ref().borrow();
+            let offset = base + offset;
+            let description = match self.memory.as_deref() {
+                Some(memory) => self
+                    .value(&*ty, offset, memory)
+                    .unwrap_or_else(|| "??".to_string()),
+                None => type_description(&*ty, self.pointer_size * 8),
+            };
+            writeln!(
+                output,
+                "{:indent$}+0x{:03x} {:width$} : {}",
+                "",
+                offset,
+                name,
+                description,
+                indent = indent,
+                width = width
+            )?;
+
+            if depth == 0 {
+                continue;
+            }
+            if let Some(definition) = self.nested_definition(&*ty) {
+                match &*definition.as_ref().borrow() {
+                    Type::Class(class) => self.write_fields(
+                        output,
+                        &class.fields,
+                        offset,
+                        indent + INDENT,
+                        depth - 1,
+                    )?,
+                    Type::Union(union) => self.write_fields(
+                        output,
+                        &union.fields,
+                        offset,
+                        indent + INDENT,
+                        depth - 1,
+                    )?,
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the layout of the class, union, or enum called `type_name`, like WinDbg's `dt`.
+/// A `module!` prefix on the name is ignored. If `instance` names a file and an offset
+/// within it, the bytes there are displayed as an instance of the type. Nested classes
+/// and unions are expanded `depth` levels deep
+pub fn print_dt(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    type_name: &str,
+    instance: Option<(&Path, u64)>,
+    depth: usize,
+) -> anyhow::Result<()> {
+    let type_name = match type_name.find('!') {
+        Some(idx) if !type_name[..idx].contains(':') => &type_name[idx + 1..],
+        _ => type_name,
+    };
+
+    let definition = pdb_info
+        .find_type_definition(type_name)
+        .ok_or_else(|| anyhow::anyhow!("no class, union, or enum named {:?}", type_name))?;
+    let definition = definition.as_ref().borrow();
+
+    let memory = match instance {
+        Some((path, offset)) => {
+            let size = definition.type_size(pdb_info);
+            let mut file = File::open(path)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut memory = Vec::with_capacity(size);
+            file.take(size as u64).read_to_end(&mut memory)?;
+            if memory.len() < size {
+                log::warn!(
+                    "{} ends {} bytes into the instance, which is 0x{:X} bytes",
+                    path.display(),
+                    memory.len(),
+                    size
+                );
+            }
+
+            Some(memory)
+        }
+        None => None,
+    };
+
+    let dt = Dt {
+        pdb_info,
+        selection: TypeSelection::new(pdb_info, &[type_name.to_string()], str::to_string),
+        pointer_size: pointer_size(pdb_info),
+        memory,
+    };
+
+    writeln!(output, "{}", type_name)?;
+    match &*definition {
+        Type::Class(class) => dt.write_fields(output, &class.fields, 0, INDENT, depth)?,
+        Type::Union(union) => dt.write_fields(output, &union.fields, 0, INDENT, depth)?,
+        Type::Enumeration(e) => {
+            let value = dt
+                .memory
+                .as_deref()
+                .and_then(|memory| dt.value(&*definition, 0, memory));
+            match value {
+                Some(value) => writeln!(output, "{:indent$}{}", "", value, indent = INDENT)?,
+                None => {
+                    for variant in &e.variants {
+                        writeln!(
+                            output,
+                            "{:indent$}{} = 0n{}",
+                            "",
+                            variant.name,
+                            variant_value(&variant.value),
+                            indent = INDENT
+                        )?;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
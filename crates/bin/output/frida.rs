@@ -0,0 +1,114 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write};
+
+/// Collects name -> RVA maps for functions and globals. The first symbol seen for a
+/// name wins so that overloads do not clobber each other unpredictably.
+fn symbol_maps(pdb_info: &ParsedPdb) -> (BTreeMap<&str, usize>, BTreeMap<&str, usize>) {
+    let mut functions = BTreeMap::new();
+    let mut procedure_offsets = HashSet::new();
+
+    for procedure in &pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            procedure_offsets.insert(offset);
+            functions
+                .entry(procedure.name.as_str())
+                .or_insert(offset - pdb_info.base_address);
+        }
+    }
+
+    // Publics cover functions without private symbols (e.g. from libraries without debug info)
+    for symbol in &pdb_info.public_symbols {
+        if let Some(offset) = symbol.offset {
+            if symbol.is_function && !procedure_offsets.contains(&offset) {
+                functions
+                    .entry(symbol.name.as_str())
+                    .or_insert(offset - pdb_info.base_address);
+            }
+        }
+    }
+
+    let mut globals = BTreeMap::new();
+    for data in &pdb_info.global_data {
+        if let Some(offset) = data.offset {
+            globals
+                .entry(data.name.as_str())
+                .or_insert(offset - pdb_info.base_address);
+        }
+    }
+
+    (functions, globals)
+}
+
+/// Writes a JSON document mapping function and global names to RVAs
+pub fn print_frida_json(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    let (functions, globals) = symbol_maps(pdb_info);
+
+    let document = json!({
+        "module": super::image_name(pdb_info, pe),
+        "functions": functions,
+        "globals": globals,
+    });
+
+    write!(output, "{}", serde_json::to_string(&document)?)
+}
+
+/// Writes a TypeScript module for Frida scripts which resolves function and global
+/// names to addresses in the running process
+pub fn print_frida_ts(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    let (functions, globals) = symbol_maps(pdb_info);
+
+    writeln!(
+        output,
+        "// Generated by pdbview from {}",
+        pdb_info.path.display()
+    )?;
+    writeln!(
+        output,
+        "export const moduleName = {};",
+        serde_json::to_string(&super::image_name(pdb_info, pe))?
+    )?;
+    writeln!(
+        output,
+        "export const functions: {{ [name: string]: number }} = {};",
+        serde_json::to_string_pretty(&functions)?
+    )?;
+    writeln!(
+        output,
+        "export const globals: {{ [name: string]: number }} = {};",
+        serde_json::to_string_pretty(&globals)?
+    )?;
+    write!(output, "{}", FRIDA_TS_HELPERS)?;
+
+    Ok(())
+}
+
+const FRIDA_TS_HELPERS: &str = r#"
+function resolve(table: { [name: string]: number }, name: string): NativePointer {
+    const rva = table[name];
+    if (rva === undefined) {
+        throw new Error(`unknown symbol: ${name}`);
+    }
+    return Process.getModuleByName(moduleName).base.add(rva);
+}
+
+/** Returns the address of the named function in the loaded module */
+export function functionAddress(name: string): NativePointer {
+    return resolve(functions, name);
+}
+
+/** Returns the address of the named global in the loaded module */
+export function globalAddress(name: string): NativePointer {
+    return resolve(globals, name);
+}
+"#;
@@ -0,0 +1,200 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+/// Bytes compilers and linkers pad between functions with: `int3`, `nop`, and zeroes
+const PADDING_BYTES: &[u8] = &[0xCC, 0x90, 0x00];
+
+#[derive(Debug, Serialize)]
+struct Gap<'a> {
+    rva: usize,
+    len: usize,
+    section: &'a str,
+    /// Whether the gap extends past the section's file data, i.e. is only filled in
+    /// at runtime
+    uninitialized: bool,
+    /// Public symbols starting within the gap, such as thunks or assembly routines
+    /// without debug info
+    public_symbols: Vec<&'a str>,
+    /// Procedure ending closest before the gap
+    after: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct SectionCoverage<'a> {
+    name: &'a str,
+    size: usize,
+    /// Bytes covered by a procedure
+    covered: usize,
+    /// Bytes in reported gaps
+    uncovered: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct GapReport<'a> {
+    sections: Vec<SectionCoverage<'a>>,
+    gaps: Vec<Gap<'a>>,
+}
+
+/// Narrows `start..end` to exclude leading and trailing alignment padding. Returns
+/// `None` if the range is entirely padding.
+fn trim_padding(pe: &PeInfo, start: usize, end: usize) -> Option<(usize, usize)> {
+    let bytes = match pe.read_rva(start, end - start) {
+        Some(bytes) => bytes,
+        // Not backed by file data, so there's nothing to trim
+        None => return Some((start, end)),
+    };
+
+    let first = bytes
+        .iter()
+        .position(|byte| !PADDING_BYTES.contains(byte))?;
+    let last = bytes
+        .iter()
+        .rposition(|byte| !PADDING_BYTES.contains(byte))?;
+
+    Some((start + first, start + last + 1))
+}
+
+/// Reports the ranges of the image's executable sections which no procedure covers,
+/// ignoring alignment padding and gaps shorter than `min_size` bytes. Such code comes
+/// from hand-written assembly, packers, or modules whose debug info was stripped
+pub fn print_code_gaps(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: &PeInfo,
+    min_size: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    let base_address = pdb_info.base_address;
+
+    // Procedure ranges keyed by start RVA, keeping the longest at each address
+    let mut procedures: BTreeMap<usize, &Procedure> = BTreeMap::new();
+    for procedure in &pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            let existing = procedures.entry(offset - base_address).or_insert(procedure);
+            if procedure.len > existing.len {
+                *existing = procedure;
+            }
+        }
+    }
+    let public_symbols: BTreeMap<usize, &str> = pdb_info
+        .public_symbols
+        .iter()
+        .filter_map(|symbol| {
+            symbol
+                .offset
+                .map(|offset| (offset - base_address, symbol.name.as_str()))
+        })
+        .collect();
+
+    let mut report = GapReport {
+        sections: vec![],
+        gaps: vec![],
+    };
+    for section in pe.sections.iter().filter(|section| section.is_executable()) {
+        let section_start = section.virtual_address;
+        let section_end = section_start + section.virtual_size.max(section.raw_size);
+        let file_end = section_start + section.raw_size;
+
+        let mut coverage = SectionCoverage {
+            name: &section.name,
+            size: section_end - section_start,
+            covered: 0,
+            uncovered: 0,
+        };
+
+        // Walk the procedures in address order, tracking the end of covered code so
+        // overlapping and nested ranges are merged
+        let mut cursor = section_start;
+        let mut previous: Option<&Procedure> = None;
+        let ranges = procedures
+            .range(section_start..section_end)
+            .map(|(start, procedure)| {
+                (
+                    *start,
+                    (*start + procedure.len).min(section_end),
+                    Some(*procedure),
+                )
+            })
+            .chain(std::iter::once((section_end, section_end, None)));
+        for (start, end, procedure) in ranges {
+            if start > cursor {
+                if let Some((gap_start, gap_end)) = trim_padding(pe, cursor, start) {
+                    if gap_end - gap_start >= min_size {
+                        coverage.uncovered += gap_end - gap_start;
+                        report.gaps.push(Gap {
+                            rva: gap_start,
+                            len: gap_end - gap_start,
+                            section: &section.name,
+                            uninitialized: gap_end > file_end,
+                            public_symbols: public_symbols
+                                .range(gap_start..gap_end)
+                                .map(|(_, name)| *name)
+                                .collect(),
+                            after: previous.map(|procedure| procedure.name.as_str()),
+                        });
+                    }
+                }
+            }
+            if end > cursor {
+                coverage.covered += end - start.max(cursor);
+                cursor = end;
+                previous = procedure.or(previous);
+            }
+        }
+
+        report.sections.push(coverage);
+    }
+
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    writeln!(output, "Executable sections:")?;
+    writeln!(
+        output,
+        "\t{:8} {:>10} {:>10} {:>10} {:>8}",
+        "Name", "Size", "Covered", "Uncovered", "Coverage"
+    )?;
+    for section in &report.sections {
+        let percent = match section.size {
+            0 => 100.0,
+            size => section.covered as f64 * 100.0 / size as f64,
+        };
+        writeln!(
+            output,
+            "\t{:8} {:>#10X} {:>#10X} {:>#10X} {:>7.2}%",
+            section.name, section.size, section.covered, section.uncovered, percent
+        )?;
+    }
+
+    writeln!(output, "\nGaps ({}):", report.gaps.len())?;
+    writeln!(
+        output,
+        "\t{:>10} {:>10} {:8} After",
+        "RVA", "Length", "Section"
+    )?;
+    for gap in &report.gaps {
+        writeln!(
+            output,
+            "\t{:>#10X} {:>#10X} {:8} {}{}",
+            gap.rva,
+            gap.len,
+            gap.section,
+            gap.after.unwrap_or("<start of section>"),
+            if gap.uninitialized {
+                " (uninitialized)"
+            } else {
+                ""
+            }
+        )?;
+        for name in &gap.public_symbols {
+            writeln!(output, "\t\tPublic: {}", name)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use super::compat;
+use crate::group::module_groups;
+use ezpdb::symbol_types::*;
+use std::io::{self, Write};
+
+/// Same as [super::print_json], but each entry of `debug_modules` also lists the
+/// procedures, globals, and types attributed to the module
+pub fn print_grouped_json(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    format_version: u32,
+) -> io::Result<()> {
+    let mut value = serde_json::to_value(pdb_info)?;
+    if format_version != FORMAT_VERSION {
+        compat::downgrade(&mut value, format_version);
+    }
+
+    value["debug_modules"] = serde_json::to_value(module_groups(pdb_info))?;
+
+    Ok(serde_json::to_writer(output, &value)?)
+}
+
+/// Lists each module followed by the procedures and globals attributed to it
+pub fn print_grouped_plain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    for group in module_groups(pdb_info) {
+        writeln!(output, "{}", group.module.name)?;
+        writeln!(output, "\tObject file: {}", group.module.object_file_name)?;
+
+        if !group.procedures.is_empty() {
+            writeln!(output, "\tProcedures:")?;
+            for procedure in &group.procedures {
+                writeln!(
+                    output,
+                    "\t\t0x{:08X} 0x{:08X} {}",
+                    procedure.offset.unwrap_or_default(),
+                    procedure.len,
+                    procedure.name
+                )?;
+            }
+        }
+
+        if !group.globals.is_empty() {
+            writeln!(output, "\tGlobals:")?;
+            for global in &group.globals {
+                writeln!(
+                    output,
+                    "\t\t0x{:08X} {}",
+                    global.offset.unwrap_or_default(),
+                    global.name
+                )?;
+            }
+        }
+
+        if !group.types_used.is_empty() {
+            let types: Vec<String> = group
+                .types_used
+                .iter()
+                .map(|index| format!("0x{:X}", index))
+                .collect();
+            writeln!(output, "\tTypes used: {}", types.join(", "))?;
+        }
+    }
+
+    Ok(())
+}
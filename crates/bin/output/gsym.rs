@@ -0,0 +1,287 @@
+//! Writer for LLVM's GSYM symbolication format (see `llvm/DebugInfo/GSYM`)
+
+use ezpdb::symbol_types::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+const GSYM_MAGIC: u32 = 0x4753_594d;
+const GSYM_VERSION: u16 = 1;
+const HEADER_SIZE: usize = 48;
+
+const INFO_TYPE_END_OF_LIST: u32 = 0;
+const INFO_TYPE_LINE_TABLE: u32 = 1;
+
+const LINE_OP_END_SEQUENCE: u8 = 0x00;
+const LINE_OP_SET_FILE: u8 = 0x01;
+const LINE_OP_ADVANCE_PC: u8 = 0x02;
+const LINE_OP_ADVANCE_LINE: u8 = 0x03;
+const LINE_OP_FIRST_SPECIAL: u8 = 0x04;
+/// Largest line delta range special opcodes will be allowed to cover
+const MAX_LINE_RANGE: i64 = 14;
+
+fn write_uleb128(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(buf: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn align(buf: &mut Vec<u8>, alignment: usize) {
+    while buf.len() % alignment != 0 {
+        buf.push(0);
+    }
+}
+
+struct StringTable {
+    data: Vec<u8>,
+    offsets: HashMap<String, u32>,
+}
+
+impl StringTable {
+    fn new() -> Self {
+        // Offset 0 is always the empty string
+        StringTable {
+            data: vec![0],
+            offsets: HashMap::new(),
+        }
+    }
+
+    fn insert(&mut self, s: &str) -> u32 {
+        if s.is_empty() {
+            return 0;
+        }
+
+        if let Some(offset) = self.offsets.get(s) {
+            return *offset;
+        }
+
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(s.as_bytes());
+        self.data.push(0);
+        self.offsets.insert(s.to_string(), offset);
+
+        offset
+    }
+}
+
+struct Row {
+    address: u64,
+    file: u32,
+    line: u32,
+}
+
+/// Encodes a GSYM line table for a function starting at `start`. `rows` must be sorted
+/// by address.
+fn encode_line_table(buf: &mut Vec<u8>, start: u64, rows: &[Row]) {
+    let first_line = rows[0].line;
+
+    let mut min_delta = 0i64;
+    let mut max_delta = 0i64;
+    let mut prev_line = first_line as i64;
+    for row in rows {
+        let delta = row.line as i64 - prev_line;
+        min_delta = min_delta.min(delta);
+        max_delta = max_delta.max(delta);
+        prev_line = row.line as i64;
+    }
+    if max_delta - min_delta > MAX_LINE_RANGE {
+        max_delta = min_delta + MAX_LINE_RANGE;
+    }
+    let line_range = max_delta - min_delta + 1;
+
+    write_sleb128(buf, min_delta);
+    write_sleb128(buf, max_delta);
+    write_uleb128(buf, first_line as u64);
+
+    let mut prev = Row {
+        address: start,
+        file: 1,
+        line: first_line,
+    };
+    for row in rows {
+        let address_delta = row.address - prev.address;
+        let line_delta = row.line as i64 - prev.line as i64;
+
+        if row.file != prev.file {
+            buf.push(LINE_OP_SET_FILE);
+            write_uleb128(buf, row.file as u64);
+        }
+
+        let special = if line_delta >= min_delta && line_delta <= max_delta {
+            Some(
+                (line_delta - min_delta)
+                    + line_range * address_delta as i64
+                    + LINE_OP_FIRST_SPECIAL as i64,
+            )
+        } else {
+            None
+        };
+
+        match special {
+            Some(special) if special <= u8::MAX as i64 => buf.push(special as u8),
+            _ => {
+                if line_delta != 0 {
+                    buf.push(LINE_OP_ADVANCE_LINE);
+                    write_sleb128(buf, line_delta);
+                }
+                buf.push(LINE_OP_ADVANCE_PC);
+                write_uleb128(buf, address_delta);
+            }
+        }
+
+        prev = Row {
+            address: row.address,
+            file: row.file,
+            line: row.line,
+        };
+    }
+
+    buf.push(LINE_OP_END_SEQUENCE);
+}
+
+/// Writes a GSYM file mapping every procedure to its name and source lines
+pub fn print_gsym(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let base_address = pdb_info.base_address as u64;
+
+    let mut procedures: Vec<_> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| procedure.offset.map(|offset| (offset as u64, procedure)))
+        .collect();
+    procedures.sort_by_key(|(offset, _procedure)| *offset);
+    // GSYM requires unique addresses. Identical COMDAT-folded functions share an address
+    procedures.dedup_by_key(|(offset, _procedure)| *offset);
+
+    let mut lines: Vec<&LineInfo> = pdb_info
+        .debug_modules
+        .iter()
+        .flat_map(|module| module.lines.iter())
+        .filter(|line| line.offset.is_some())
+        .collect();
+    lines.sort_by_key(|line| line.offset);
+
+    let mut strings = StringTable::new();
+    // File 0 is reserved as "no file"
+    let mut files: Vec<(u32, u32)> = vec![(0, 0)];
+    let mut file_indices: HashMap<&str, u32> = HashMap::new();
+
+    // Encode each function's info up front. Offsets are patched once the layout is known
+    let mut function_infos = Vec::with_capacity(procedures.len());
+    for (offset, procedure) in &procedures {
+        let end = offset + procedure.len as u64;
+        let first = lines.partition_point(|line| (line.offset.unwrap() as u64) < *offset);
+        let rows: Vec<Row> = lines[first..]
+            .iter()
+            .take_while(|line| (line.offset.unwrap() as u64) < end)
+            .map(|line| {
+                let file = *file_indices.entry(line.file.as_str()).or_insert_with(|| {
+                    let split = line.file.rfind(|c| c == '\\' || c == '/');
+                    let (dir, base) = match split {
+                        Some(idx) => (&line.file[..idx], &line.file[idx + 1..]),
+                        None => ("", line.file.as_str()),
+                    };
+                    files.push((strings.insert(dir), strings.insert(base)));
+                    (files.len() - 1) as u32
+                });
+
+                Row {
+                    address: line.offset.unwrap() as u64,
+                    file,
+                    line: line.line_start,
+                }
+            })
+            .collect();
+
+        let mut info = vec![];
+        info.extend_from_slice(&(procedure.len as u32).to_le_bytes());
+        info.extend_from_slice(&strings.insert(&procedure.name).to_le_bytes());
+        if !rows.is_empty() {
+            let mut line_table = vec![];
+            encode_line_table(&mut line_table, *offset, &rows);
+            info.extend_from_slice(&INFO_TYPE_LINE_TABLE.to_le_bytes());
+            info.extend_from_slice(&(line_table.len() as u32).to_le_bytes());
+            info.extend_from_slice(&line_table);
+        }
+        info.extend_from_slice(&INFO_TYPE_END_OF_LIST.to_le_bytes());
+        info.extend_from_slice(&0u32.to_le_bytes());
+
+        function_infos.push(info);
+    }
+
+    let max_offset = procedures
+        .last()
+        .map(|(offset, _procedure)| offset - base_address)
+        .unwrap_or(0);
+    let address_offset_size: usize = match max_offset {
+        0..=0xff => 1,
+        0x100..=0xffff => 2,
+        0x1_0000..=0xffff_ffff => 4,
+        _ => 8,
+    };
+
+    let mut buf = Vec::with_capacity(HEADER_SIZE);
+    buf.resize(HEADER_SIZE, 0);
+
+    align(&mut buf, address_offset_size);
+    for (offset, _procedure) in &procedures {
+        let relative = (offset - base_address).to_le_bytes();
+        buf.extend_from_slice(&relative[..address_offset_size]);
+    }
+
+    align(&mut buf, 4);
+    let info_offsets_start = buf.len();
+    buf.resize(buf.len() + procedures.len() * 4, 0);
+
+    align(&mut buf, 4);
+    buf.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    for (dir, base) in &files {
+        buf.extend_from_slice(&dir.to_le_bytes());
+        buf.extend_from_slice(&base.to_le_bytes());
+    }
+
+    let strtab_offset = buf.len();
+    buf.extend_from_slice(&strings.data);
+
+    for (i, info) in function_infos.iter().enumerate() {
+        align(&mut buf, 4);
+        let info_offset = (buf.len() as u32).to_le_bytes();
+        let slot = info_offsets_start + i * 4;
+        buf[slot..slot + 4].copy_from_slice(&info_offset);
+        buf.extend_from_slice(info);
+    }
+
+    // Header
+    let guid = pdb_info.guid.as_bytes();
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(&GSYM_MAGIC.to_le_bytes());
+    header.extend_from_slice(&GSYM_VERSION.to_le_bytes());
+    header.push(address_offset_size as u8);
+    header.push(guid.len() as u8);
+    header.extend_from_slice(&base_address.to_le_bytes());
+    header.extend_from_slice(&(procedures.len() as u32).to_le_bytes());
+    header.extend_from_slice(&(strtab_offset as u32).to_le_bytes());
+    header.extend_from_slice(&(strings.data.len() as u32).to_le_bytes());
+    header.extend_from_slice(guid);
+    header.resize(HEADER_SIZE, 0);
+    buf[..HEADER_SIZE].copy_from_slice(&header);
+
+    output.write_all(&buf)
+}
@@ -0,0 +1,236 @@
+use super::cdecl;
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::Type;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; }
+h1, h2 { font-weight: normal; }
+table { border-collapse: collapse; font-family: monospace; }
+th, td { text-align: left; padding: 2px 12px 2px 0; }
+th { border-bottom: 1px solid #888; }
+tr:nth-child(even) { background: #f4f4f4; }
+details { margin-left: 1em; }
+summary { cursor: pointer; font-family: monospace; }
+pre { background: #f4f4f4; padding: 0.5em; }
+#symbol-filter { width: 40em; margin-bottom: 1em; }
+"#;
+
+const SCRIPT: &str = r##"
+document.getElementById("symbol-filter").addEventListener("input", function (e) {
+    var query = e.target.value.toLowerCase();
+    var rows = document.querySelectorAll("#symbols tbody tr");
+    for (var i = 0; i < rows.length; i++) {
+        var name = rows[i].lastElementChild.textContent.toLowerCase();
+        rows[i].style.display = name.indexOf(query) === -1 ? "none" : "";
+    }
+});
+"##;
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn format_address(offset: Option<usize>) -> String {
+    offset
+        .map(|offset| format!("0x{:08X}", offset))
+        .unwrap_or_default()
+}
+
+/// Types grouped by the namespaces in their names
+#[derive(Default)]
+struct Namespace {
+    children: BTreeMap<String, Namespace>,
+    /// Unqualified name and C definition of each type in this namespace
+    types: Vec<(String, String)>,
+}
+
+impl Namespace {
+    fn insert(&mut self, name: &str, definition: String) {
+        let mut components: Vec<&str> = name.split("::").collect();
+        let leaf = components.pop().unwrap_or(name);
+
+        let mut namespace = self;
+        for component in components {
+            namespace = namespace.children.entry(component.to_string()).or_default();
+        }
+
+        namespace.types.push((leaf.to_string(), definition));
+    }
+
+    fn write(&self, output: &mut impl Write) -> io::Result<()> {
+        for (name, child) in &self.children {
+            writeln!(
+                output,
+                "<details><summary>namespace {}</summary>",
+                escape(name)
+            )?;
+            child.write(output)?;
+            writeln!(output, "</details>")?;
+        }
+
+        for (name, definition) in &self.types {
+            writeln!(
+                output,
+                "<details><summary>{}</summary><pre>{}</pre></details>",
+                escape(name),
+                escape(definition)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_symbols(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let mut rows: Vec<(Option<usize>, &str, usize, &str)> = vec![];
+    rows.extend(pdb_info.procedures.iter().map(|procedure| {
+        (
+            procedure.offset,
+            "procedure",
+            procedure.len,
+            procedure.name.as_str(),
+        )
+    }));
+    rows.extend(
+        pdb_info
+            .public_symbols
+            .iter()
+            .map(|symbol| (symbol.offset, "public", 0, symbol.name.as_str())),
+    );
+    rows.extend(
+        pdb_info
+            .global_data
+            .iter()
+            .map(|data| (data.offset, "data", 0, data.name.as_str())),
+    );
+    rows.sort();
+
+    writeln!(output, "<h2>Symbols ({})</h2>", rows.len())?;
+    writeln!(
+        output,
+        r#"<input id="symbol-filter" type="search" placeholder="Filter symbols by name">"#
+    )?;
+    writeln!(
+        output,
+        r#"<table id="symbols"><thead><tr><th>Address</th><th>Kind</th><th>Size</th><th>Name</th></tr></thead><tbody>"#
+    )?;
+    for (offset, kind, len, name) in rows {
+        writeln!(
+            output,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            format_address(offset),
+            kind,
+            if len > 0 {
+                format!("0x{:X}", len)
+            } else {
+                String::new()
+            },
+            escape(name)
+        )?;
+    }
+    writeln!(output, "</tbody></table>")
+}
+
+fn write_modules(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    writeln!(
+        output,
+        "<h2>Modules ({})</h2>",
+        pdb_info.debug_modules.len()
+    )?;
+    for module in &pdb_info.debug_modules {
+        writeln!(
+            output,
+            "<details><summary>{}</summary>",
+            escape(&module.name)
+        )?;
+        writeln!(
+            output,
+            "<p>Object file: {}<br>Line records: {}</p>",
+            escape(&module.object_file_name),
+            module.lines.len()
+        )?;
+        if let Some(files) = module
+            .source_files
+            .as_ref()
+            .filter(|files| !files.is_empty())
+        {
+            writeln!(output, "<ul>")?;
+            for file in files {
+                writeln!(output, "<li>{}</li>", escape(&file.name))?;
+            }
+            writeln!(output, "</ul>")?;
+        }
+        writeln!(output, "</details>")?;
+    }
+
+    Ok(())
+}
+
+/// Writes a self-contained HTML report with a searchable symbol table, a collapsible
+/// type tree, and the module list
+pub fn print_html(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let title = pdb_info
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    writeln!(output, "<!DOCTYPE html>")?;
+    writeln!(output, "<html><head><meta charset=\"utf-8\">")?;
+    writeln!(output, "<title>{}</title>", escape(&title))?;
+    writeln!(output, "<style>{}</style></head><body>", STYLE)?;
+    writeln!(output, "<h1>{}</h1>", escape(&title))?;
+    writeln!(
+        output,
+        "<p>GUID: {}<br>Age: {}<br>Machine type: {}<br>Debug identifier: {}</p>",
+        pdb_info.guid,
+        pdb_info.age,
+        pdb_info
+            .machine_type
+            .as_ref()
+            .map(|ty| format!("{:?}", ty))
+            .unwrap_or_else(|| "Unknown".to_string()),
+        pdb_info.debug_identifier()
+    )?;
+
+    write_symbols(output, pdb_info)?;
+
+    let mut types = Namespace::default();
+    let mut type_count = 0;
+    let mut indices: Vec<_> = pdb_info.types.keys().collect();
+    indices.sort();
+    for index in indices {
+        let ty = pdb_info.types[index].as_ref().borrow();
+        let name = match &*ty {
+            Type::Class(class) => &class.name,
+            Type::Union(union) => &union.name,
+            Type::Enumeration(e) => &e.name,
+            _ => continue,
+        };
+
+        if let Some(definition) = cdecl::c_definition(&*ty, pdb_info) {
+            types.insert(name, definition);
+            type_count += 1;
+        }
+    }
+    writeln!(output, "<h2>Types ({})</h2>", type_count)?;
+    types.write(output)?;
+
+    write_modules(output, pdb_info)?;
+
+    writeln!(output, "<script>{}</script></body></html>", SCRIPT)
+}
@@ -0,0 +1,187 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::io::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    /// Runs before `main` to construct a global
+    Initializer,
+    /// Registered with `atexit` by an initializer to destroy the global on exit
+    Destructor,
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    kind: Kind,
+    /// Name of the global the function constructs or destroys
+    variable: String,
+    function: String,
+    offset: Option<usize>,
+    /// Address of the function's entry in the `.CRT$XCU` initializer table
+    table_slot: Option<usize>,
+}
+
+/// Returns the global a dynamic initializer or atexit destructor is for, from either
+/// its undecorated or decorated name
+fn global_name(name: &str) -> Option<(Kind, String)> {
+    let undecorated = [
+        ("`dynamic initializer for '", Kind::Initializer),
+        ("`dynamic atexit destructor for '", Kind::Destructor),
+    ];
+    for (prefix, kind) in &undecorated {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            return Some((*kind, rest.trim_end_matches('\'').to_string()));
+        }
+    }
+
+    let decorated = [("??__E", Kind::Initializer), ("??__F", Kind::Destructor)];
+    for (prefix, kind) in &decorated {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            let variable = rest.strip_suffix("@@YAXXZ").unwrap_or(rest);
+            return Some((*kind, variable.to_string()));
+        }
+    }
+
+    None
+}
+
+/// Returns the global an initializer table entry such as `?x$initializer$@@3P6AXXZEA`
+/// is for
+fn table_entry_name(name: &str) -> Option<String> {
+    let end = name.find("$initializer$")?;
+    Some(name[..end].trim_start_matches('?').to_string())
+}
+
+/// Lists the dynamic initializers of globals in the order the CRT runs them, followed
+/// by their atexit destructors in the reverse order, which is the order they run on
+/// exit. Initializers are ordered by their entry in the `.CRT$XCU` table, which the
+/// linker sorts and the CRT walks from start to end. With a PE, each entry is resolved
+/// to the function it points to; otherwise entries are matched to initializers by the
+/// global's name. Initializers without a table entry follow in address order
+pub fn print_initializers(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut functions: Vec<(Kind, String, &Procedure)> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| {
+            let (kind, variable) = global_name(&procedure.name)?;
+            Some((kind, variable, procedure))
+        })
+        .collect();
+    functions.sort_by_key(|(_, _, procedure)| procedure.offset);
+
+    let mut table: Vec<(usize, String)> = pdb_info
+        .public_symbols
+        .iter()
+        .map(|symbol| (&symbol.name, symbol.offset))
+        .chain(
+            pdb_info
+                .global_data
+                .iter()
+                .map(|data| (&data.name, data.offset)),
+        )
+        .filter_map(|(name, offset)| Some((offset?, table_entry_name(name)?)))
+        .collect();
+    table.sort();
+    table.dedup_by_key(|(slot, _)| *slot);
+
+    // Resolve each table entry to the initializer it points to
+    let by_offset: HashMap<usize, usize> = functions
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (_, _, procedure))| Some((procedure.offset?, i)))
+        .collect();
+    let by_variable: HashMap<&str, usize> = functions
+        .iter()
+        .enumerate()
+        .filter(|(_, (kind, _, _))| *kind == Kind::Initializer)
+        .map(|(i, (_, variable, _))| (variable.as_str(), i))
+        .collect();
+    let mut slots: HashMap<usize, usize> = HashMap::new();
+    for (slot, variable) in &table {
+        let target = pe
+            .and_then(|pe| {
+                let size = if pe.is_64 { 8 } else { 4 };
+                let bytes = pe.read_rva(*slot, size)?;
+                let address = if pe.is_64 {
+                    u64::from_le_bytes(bytes.try_into().unwrap())
+                } else {
+                    u32::from_le_bytes(bytes.try_into().unwrap()) as u64
+                };
+                let rva = address.checked_sub(pe.image_base)? as usize;
+                by_offset.get(&rva).copied()
+            })
+            .or_else(|| by_variable.get(variable.as_str()).copied());
+        if let Some(target) = target {
+            slots.entry(target).or_insert(*slot);
+        }
+    }
+
+    let mut initializers: Vec<Entry> = vec![];
+    let mut destructors: Vec<Entry> = vec![];
+    for (i, (kind, variable, procedure)) in functions.iter().enumerate() {
+        let entry = Entry {
+            kind: *kind,
+            variable: variable.clone(),
+            function: procedure.name.clone(),
+            offset: procedure.offset,
+            table_slot: slots.get(&i).copied(),
+        };
+        match kind {
+            Kind::Initializer => initializers.push(entry),
+            Kind::Destructor => destructors.push(entry),
+        }
+    }
+    // Stable, so initializers without a slot keep their address order at the end
+    initializers.sort_by_key(|entry| (entry.table_slot.is_none(), entry.table_slot));
+
+    // Destructors are registered as their initializer runs and run in reverse
+    let position: HashMap<&str, usize> = initializers
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (entry.variable.as_str(), i))
+        .collect();
+    destructors.sort_by_key(|entry| {
+        let position = position.get(entry.variable.as_str());
+        (position.is_none(), position.map(|i| Reverse(*i)))
+    });
+
+    let report: Vec<Entry> = initializers.into_iter().chain(destructors).collect();
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    let format_offset = |offset: Option<usize>| {
+        offset.map_or_else(String::new, |offset| format!("0x{:08X}", offset))
+    };
+    writeln!(
+        output,
+        "{:<12} {:<10} {:<10} Function",
+        "Kind", "Offset", "Slot"
+    )?;
+    for entry in &report {
+        writeln!(
+            output,
+            "{:<12} {:<10} {:<10} {}",
+            match entry.kind {
+                Kind::Initializer => "initializer",
+                Kind::Destructor => "destructor",
+            },
+            format_offset(entry.offset),
+            format_offset(entry.table_slot),
+            entry.function
+        )?;
+    }
+
+    Ok(())
+}
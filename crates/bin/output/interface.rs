@@ -0,0 +1,226 @@
+use crate::group::ModuleRanges;
+use crate::output::kernel::undecorated;
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+#[derive(Debug, Serialize)]
+struct Export<'a> {
+    name: Option<&'a str>,
+    ordinal: u16,
+    rva: usize,
+    forwarder: Option<&'a str>,
+    /// Names of the symbols at the export's address
+    symbols: Vec<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct RelocatedName<'a> {
+    name: &'a str,
+    /// Address of the export
+    export_rva: usize,
+    /// Address of the symbol with the export's name
+    symbol_rva: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Import<'a> {
+    name: Option<&'a str>,
+    ordinal: u16,
+    /// RVA of the import address table slot
+    rva: usize,
+    /// Name of the `__imp_` symbol at the slot
+    symbol: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportModule<'a> {
+    dll: &'a str,
+    /// Module whose section contribution holds the import address table slots,
+    /// usually the import library member for the DLL
+    module: Option<&'a str>,
+    imports: Vec<Import<'a>>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct InterfaceReport<'a> {
+    /// Exports with a symbol at their address
+    exports: Vec<Export<'a>>,
+    /// Exports with no symbol at their address, excluding forwarders
+    unsymbolized_exports: Vec<Export<'a>>,
+    forwarded_exports: Vec<Export<'a>>,
+    /// Symbols sharing an export's name but not its address, which happens when the
+    /// export is a thunk or was renamed in a `.def` file
+    relocated_names: Vec<RelocatedName<'a>>,
+    imports: Vec<ImportModule<'a>>,
+}
+
+/// Cross-references the image's exports and imports with the PDB's symbols. Exports are
+/// matched to the symbols at their addresses, and imports are grouped by DLL along with
+/// the module contributing their import address table slots
+pub fn print_interface_report(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: &PeInfo,
+    json: bool,
+) -> anyhow::Result<()> {
+    let base_address = pdb_info.base_address;
+
+    let mut symbols_at: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    let mut symbols_named: HashMap<&str, usize> = HashMap::new();
+    let named_offsets = pdb_info
+        .public_symbols
+        .iter()
+        .map(|symbol| (symbol.name.as_str(), symbol.offset))
+        .chain(
+            pdb_info
+                .procedures
+                .iter()
+                .map(|procedure| (procedure.name.as_str(), procedure.offset)),
+        );
+    for (name, offset) in named_offsets {
+        if let Some(offset) = offset {
+            let rva = offset - base_address;
+            let names = symbols_at.entry(rva).or_default();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+            symbols_named.insert(undecorated(name), rva);
+        }
+    }
+
+    let mut report = InterfaceReport::default();
+    for export in &pe.exports {
+        let entry = Export {
+            name: export.name.as_deref(),
+            ordinal: export.ordinal,
+            rva: export.rva,
+            forwarder: export.forwarder.as_deref(),
+            symbols: symbols_at.get(&export.rva).cloned().unwrap_or_default(),
+        };
+
+        if let Some(name) = entry.name {
+            match symbols_named.get(name) {
+                Some(&symbol_rva) if entry.forwarder.is_none() && symbol_rva != export.rva => {
+                    report.relocated_names.push(RelocatedName {
+                        name,
+                        export_rva: export.rva,
+                        symbol_rva,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        if entry.forwarder.is_some() {
+            report.forwarded_exports.push(entry);
+        } else if entry.symbols.is_empty() {
+            report.unsymbolized_exports.push(entry);
+        } else {
+            report.exports.push(entry);
+        }
+    }
+
+    let module_ranges = ModuleRanges::new(pdb_info);
+    let mut import_modules: BTreeMap<&str, ImportModule> = BTreeMap::new();
+    for import in &pe.imports {
+        let module = import_modules
+            .entry(import.dll.as_str())
+            .or_insert_with(|| ImportModule {
+                dll: &import.dll,
+                module: None,
+                imports: vec![],
+            });
+        if module.module.is_none() {
+            module.module = module_ranges
+                .module_index(base_address + import.rva)
+                .and_then(|index| pdb_info.debug_modules.get(index))
+                .map(|module| module.name.as_str());
+        }
+        module.imports.push(Import {
+            name: import.name.as_deref(),
+            ordinal: import.ordinal,
+            rva: import.rva,
+            symbol: symbols_at
+                .get(&import.rva)
+                .and_then(|names| names.iter().find(|name| name.contains("__imp_")).copied()),
+        });
+    }
+    report.imports = import_modules.into_values().collect();
+
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    for (title, exports) in [
+        ("Exports", &report.exports),
+        ("Unsymbolized exports", &report.unsymbolized_exports),
+        ("Forwarded exports", &report.forwarded_exports),
+    ]
+    .iter()
+    {
+        writeln!(output, "{} ({}):", title, exports.len())?;
+        writeln!(
+            output,
+            "\t{:>7} {:>10} {:30} Symbols",
+            "Ordinal", "RVA", "Name"
+        )?;
+        for export in exports.iter() {
+            let symbols = match export.forwarder {
+                Some(forwarder) => format!("-> {}", forwarder),
+                None => export.symbols.join(", "),
+            };
+            writeln!(
+                output,
+                "\t{:>7} {:>#10X} {:30} {}",
+                export.ordinal,
+                export.rva,
+                export.name.unwrap_or("<ordinal only>"),
+                symbols
+            )?;
+        }
+        writeln!(output)?;
+    }
+
+    writeln!(
+        output,
+        "Symbols named like exports at other addresses ({}):",
+        report.relocated_names.len()
+    )?;
+    for relocated in &report.relocated_names {
+        writeln!(
+            output,
+            "\t{} exported at {:#X}, symbol at {:#X}",
+            relocated.name, relocated.export_rva, relocated.symbol_rva
+        )?;
+    }
+
+    writeln!(output, "\nImports:")?;
+    for module in &report.imports {
+        writeln!(
+            output,
+            "\t{} ({} imports, module {})",
+            module.dll,
+            module.imports.len(),
+            module.module.unwrap_or("<unknown>")
+        )?;
+        for import in &module.imports {
+            let name = match import.name {
+                Some(name) => name.to_string(),
+                None => format!("#{}", import.ordinal),
+            };
+            writeln!(
+                output,
+                "\t\t{:>#10X} {:30} {}",
+                import.rva,
+                name,
+                import.symbol.unwrap_or("")
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,319 @@
+//! Writes Volatility 3 intermediate symbol files (ISF)
+
+use crate::output::layout::{unique_name, TypeSelection};
+use crate::output::pointer_size;
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::*;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+
+const ISF_FORMAT: &str = "6.2.0";
+
+/// Returns the `IMAGE_FILE_MACHINE_*` value Volatility expects for a machine type
+fn machine_type_value(machine_type: Option<&MachineType>) -> u16 {
+    match machine_type {
+        Some(MachineType::X86) => 0x014C,
+        Some(MachineType::Amd64) => 0x8664,
+        Some(MachineType::Arm) => 0x01C0,
+        Some(MachineType::ArmNT) => 0x01C4,
+        Some(MachineType::Arm64) => 0xAA64,
+        Some(MachineType::Ia64) => 0x0200,
+        _ => 0,
+    }
+}
+
+/// Returns the name, ISF kind, and signedness of a primitive type
+fn primitive_base_type(kind: PrimitiveKind) -> (&'static str, &'static str, bool) {
+    match kind {
+        PrimitiveKind::NoType | PrimitiveKind::Void => ("void", "void", true),
+        PrimitiveKind::Char | PrimitiveKind::RChar | PrimitiveKind::I8 => ("char", "char", true),
+        PrimitiveKind::UChar | PrimitiveKind::U8 => ("unsigned char", "char", false),
+        PrimitiveKind::WChar => ("wchar", "char", false),
+        PrimitiveKind::RChar16 => ("char16_t", "char", false),
+        PrimitiveKind::RChar32 => ("char32_t", "char", false),
+        PrimitiveKind::Short | PrimitiveKind::I16 => ("short", "int", true),
+        PrimitiveKind::UShort | PrimitiveKind::U16 => ("unsigned short", "int", false),
+        PrimitiveKind::Long | PrimitiveKind::HRESULT => ("long", "int", true),
+        PrimitiveKind::ULong => ("unsigned long", "int", false),
+        PrimitiveKind::I32 => ("int", "int", true),
+        PrimitiveKind::U32 => ("unsigned int", "int", false),
+        PrimitiveKind::Quad | PrimitiveKind::I64 => ("long long", "int", true),
+        PrimitiveKind::UQuad | PrimitiveKind::U64 => ("unsigned long long", "int", false),
+        PrimitiveKind::Octa | PrimitiveKind::I128 => ("__int128", "int", true),
+        PrimitiveKind::UOcta | PrimitiveKind::U128 => ("unsigned __int128", "int", false),
+        PrimitiveKind::F32 | PrimitiveKind::F32PP => ("float", "float", true),
+        PrimitiveKind::F64 => ("double", "float", true),
+        PrimitiveKind::F80 => ("long double", "float", true),
+        PrimitiveKind::Bool8 => ("bool", "bool", false),
+        PrimitiveKind::Bool16 => ("bool16", "bool", false),
+        PrimitiveKind::Bool32 => ("bool32", "bool", false),
+        PrimitiveKind::Bool64 => ("bool64", "bool", false),
+        // Remaining kinds (half-precision and complex floats) have no ISF equivalent
+        _ => ("unsigned char", "char", false),
+    }
+}
+
+/// Converts an enum variant's value to a JSON number
+fn variant_json(value: &VariantValue) -> Value {
+    match *value {
+        VariantValue::U8(v) => json!(v),
+        VariantValue::U16(v) => json!(v),
+        VariantValue::U32(v) => json!(v),
+        VariantValue::U64(v) => json!(v),
+        VariantValue::I8(v) => json!(v),
+        VariantValue::I16(v) => json!(v),
+        VariantValue::I32(v) => json!(v),
+        VariantValue::I64(v) => json!(v),
+    }
+}
+
+struct Isf<'a> {
+    pdb_info: &'a ParsedPdb,
+    selection: TypeSelection,
+    /// Base types referenced so far, by name
+    base_types: BTreeMap<&'static str, Value>,
+}
+
+impl<'a> Isf<'a> {
+    fn base_type(&mut self, kind: PrimitiveKind) -> Value {
+        let (name, base_kind, signed) = primitive_base_type(kind);
+        let size = match base_kind {
+            "void" => 0,
+            _ => kind.size(),
+        };
+        self.base_types.entry(name).or_insert_with(|| {
+            json!({
+                "kind": base_kind,
+                "size": size,
+                "signed": signed,
+                "endian": "little",
+            })
+        });
+
+        json!({ "kind": "base", "name": name })
+    }
+
+    /// Describes a reference to `ty`. Classes and enums without a definition are
+    /// treated as opaque bytes, or as `void` when pointed to
+    fn describe(&mut self, ty: &Type, pointee: bool) -> Value {
+        match ty {
+            Type::Class(_) | Type::Union(_) | Type::Enumeration(_) => {
+                match self.selection.identifier(ty).map(str::to_string) {
+                    Some(name) => {
+                        let kind = match ty {
+                            Type::Union(_) => "union",
+                            Type::Enumeration(_) => "enum",
+                            _ => "struct",
+                        };
+                        json!({ "kind": kind, "name": name })
+                    }
+                    None if pointee => self.base_type(PrimitiveKind::Void),
+                    None => json!({
+                        "kind": "array",
+                        "count": ty.type_size(self.pdb_info),
+                        "subtype": self.base_type(PrimitiveKind::UChar),
+                    }),
+                }
+            }
+            Type::Primitive(primitive) => {
+                let base = self.base_type(primitive.kind);
+                match primitive.indirection {
+                    Some(_) => json!({ "kind": "pointer", "subtype": base }),
+                    None => base,
+                }
+            }
+            Type::Pointer(pointer) => {
+                let subtype = match pointer.underlying_type.as_ref() {
+                    Some(underlying_type) => {
+                        self.describe(&*underlying_type.as_ref().borrow(), true)
+                    }
+                    None => self.base_type(PrimitiveKind::Void),
+                };
+                json!({ "kind": "pointer", "subtype": subtype })
+            }
+            Type::Array(array) => {
+                let element = self.describe(&*array.element_type.as_ref().borrow(), false);
+                array.dimensions_elements.iter().rev().fold(
+                    element,
+                    |subtype, count| json!({ "kind": "array", "count": count, "subtype": subtype }),
+                )
+            }
+            Type::Modifier(modifier) => {
+                self.describe(&*modifier.underlying_type.as_ref().borrow(), pointee)
+            }
+            Type::Bitfield(bitfield) => json!({
+                "kind": "bitfield",
+                "bit_position": bitfield.position,
+                "bit_length": bitfield.len,
+                "type": self.describe(&*bitfield.underlying_type.as_ref().borrow(), false),
+            }),
+            Type::Procedure(_) | Type::MemberFunction(_) => json!({ "kind": "function" }),
+            _ => self.base_type(PrimitiveKind::Void),
+        }
+    }
+
+    fn fields(&mut self, fields: &[TypeRef]) -> Map<String, Value> {
+        let mut used = HashSet::new();
+        let mut described = Map::new();
+        for field in fields {
+            let (name, offset, ty) = match &*field.as_ref().borrow() {
+                Type::Member(member) => (
+                    member.name.clone(),
+                    member.offset,
+                    std::rc::Rc::clone(&member.underlying_type),
+                ),
+                Type::BaseClass(base) => (
+                    self.selection
+                        .identifier(&*base.base_class.as_ref().borrow())
+                        .unwrap_or("base")
+                        .to_string(),
+                    base.offset,
+                    std::rc::Rc::clone(&base.base_class),
+                ),
+                _ => continue,
+            };
+
+            let ty = self.describe(&*ty.as_ref().borrow(), false);
+            described.insert(
+                unique_name(name, &mut used),
+                json!({ "offset": offset, "type": ty }),
+            );
+        }
+
+        described
+    }
+}
+
+/// Writes a Volatility 3 intermediate symbol file holding every class, union, and enum
+/// along with the RVAs of public symbols, procedures, and global data
+pub fn print_isf(output: &mut impl Write, pdb_info: &ParsedPdb) -> anyhow::Result<()> {
+    let mut isf = Isf {
+        pdb_info,
+        selection: TypeSelection::new(pdb_info, &[], str::to_string),
+        base_types: BTreeMap::new(),
+    };
+
+    let mut user_types = Map::new();
+    let mut enums = Map::new();
+    let definitions: Vec<_> = isf
+        .selection
+        .identifiers
+        .iter()
+        .map(|(key, name)| {
+            (
+                std::rc::Rc::clone(&isf.selection.definitions[key]),
+                name.clone(),
+            )
+        })
+        .collect();
+    for (ty, name) in definitions {
+        match &*ty.as_ref().borrow() {
+            Type::Class(class) => {
+                let fields = isf.fields(&class.fields);
+                user_types.insert(
+                    name,
+                    json!({ "kind": "struct", "size": class.size, "fields": fields }),
+                );
+            }
+            Type::Union(union) => {
+                let fields = isf.fields(&union.fields);
+                user_types.insert(
+                    name,
+                    json!({ "kind": "union", "size": union.size, "fields": fields }),
+                );
+            }
+            Type::Enumeration(e) => {
+                let underlying_type = e.underlying_type.as_ref().borrow();
+                let base = match isf.describe(&*underlying_type, false) {
+                    Value::Object(mut base) => base.remove("name").unwrap_or(Value::Null),
+                    _ => Value::Null,
+                };
+                let constants: Map<String, Value> = e
+                    .variants
+                    .iter()
+                    .map(|variant| (variant.name.clone(), variant_json(&variant.value)))
+                    .collect();
+                enums.insert(
+                    name,
+                    json!({
+                        "size": underlying_type.type_size(pdb_info),
+                        "base": base,
+                        "constants": constants,
+                    }),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    // Volatility requires these regardless of whether they're referenced
+    isf.base_type(PrimitiveKind::Void);
+    isf.base_types.insert(
+        "pointer",
+        json!({
+            "kind": "int",
+            "size": pointer_size(pdb_info),
+            "signed": false,
+            "endian": "little",
+        }),
+    );
+
+    // Later entries win, so data (which carries a type) takes priority over procedures,
+    // which take priority over public symbols
+    let mut symbols = Map::new();
+    for symbol in &pdb_info.public_symbols {
+        if let Some(offset) = symbol.offset {
+            symbols.insert(
+                symbol.name.clone(),
+                json!({ "address": offset - pdb_info.base_address }),
+            );
+        }
+    }
+    for procedure in &pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            symbols.insert(
+                procedure.name.clone(),
+                json!({ "address": offset - pdb_info.base_address }),
+            );
+        }
+    }
+    for data in &pdb_info.global_data {
+        if let Some(offset) = data.offset {
+            let ty = isf.describe(&*data.ty.as_ref().borrow(), false);
+            symbols.insert(
+                data.name.clone(),
+                json!({ "address": offset - pdb_info.base_address, "type": ty }),
+            );
+        }
+    }
+
+    let isf_json = json!({
+        "metadata": {
+            "format": ISF_FORMAT,
+            "producer": {
+                "name": "pdbview",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "windows": {
+                "pdb": {
+                    "GUID": pdb_info.guid.to_simple().to_string().to_uppercase(),
+                    "age": pdb_info.age,
+                    "database": pdb_info
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    "machine_type": machine_type_value(pdb_info.machine_type.as_ref()),
+                }
+            }
+        },
+        "base_types": isf.base_types,
+        "user_types": user_types,
+        "enums": enums,
+        "symbols": symbols,
+    });
+    serde_json::to_writer(output, &isf_json)?;
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::{Derivation, DerivedType, Type};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+#[derive(Debug, Serialize)]
+struct Syscall {
+    /// Name without the `Nt`/`Zw` prefix
+    name: String,
+    /// Address of the `Nt` implementation
+    nt_offset: Option<usize>,
+    /// Address of the `Zw` stub, which enters the kernel through the system call path
+    zw_offset: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct Routine<'a> {
+    name: &'a str,
+    offset: Option<usize>,
+    len: usize,
+}
+
+impl<'a> From<&'a Procedure> for Routine<'a> {
+    fn from(procedure: &'a Procedure) -> Self {
+        Routine {
+            name: &procedure.name,
+            offset: procedure.offset,
+            len: procedure.len,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct KernelReport<'a> {
+    /// Services with both an `Nt` and a `Zw` entry point
+    syscalls: Vec<Syscall>,
+    /// Procedures the compiler flagged as deferred procedure calls
+    dpc_routines: Vec<Routine<'a>>,
+    /// Procedures shaped like `NTSTATUS (PDEVICE_OBJECT, PIRP)`, the signature of IRP
+    /// major function handlers
+    irp_dispatch_candidates: Vec<Routine<'a>>,
+}
+
+/// Strips x86 `__stdcall` decoration, e.g. `_NtClose@4` becomes `NtClose`
+pub(crate) fn undecorated(name: &str) -> &str {
+    let name = name.strip_prefix('_').unwrap_or(name);
+    match name.rfind('@') {
+        Some(idx) if name[idx + 1..].chars().all(|c| c.is_ascii_digit()) => &name[..idx],
+        _ => name,
+    }
+}
+
+/// Returns the name of the class `ty` points to, if it's a pointer to a class
+fn pointee_class_name(ty: &TypeRef) -> Option<String> {
+    let derived = DerivedType::new(ty);
+    if derived.indirection_level() != 1
+        || derived
+            .derivations
+            .iter()
+            .any(|derivation| matches!(derivation, Derivation::Array { .. }))
+    {
+        return None;
+    }
+
+    match &*derived.base?.as_ref().borrow() {
+        Type::Class(class) => Some(class.name.clone()),
+        _ => None,
+    }
+}
+
+/// Returns whether a procedure takes a device object and an IRP
+fn is_irp_dispatch(pdb_info: &ParsedPdb, procedure: &Procedure) -> bool {
+    let ty = match pdb_info.types.get(&procedure.type_index) {
+        Some(ty) => ty.as_ref().borrow(),
+        None => return false,
+    };
+    let arguments = match &*ty {
+        Type::Procedure(procedure) => &procedure.argument_list,
+        _ => return false,
+    };
+
+    let names: Vec<Option<String>> = arguments.iter().map(pointee_class_name).collect();
+    matches!(
+        names.as_slice(),
+        [Some(device), Some(irp)] if device == "_DEVICE_OBJECT" && irp == "_IRP"
+    )
+}
+
+/// Categorizes the procedures of a kernel or driver PDB: system services with `Nt`/`Zw`
+/// pairs, DPC routines, and candidate IRP dispatch routines
+pub fn print_kernel_report(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    json: bool,
+) -> io::Result<()> {
+    let mut report = KernelReport::default();
+
+    // Procedures are preferred over public symbols since they have undecorated names
+    let mut entry_points: BTreeMap<&str, Option<usize>> = BTreeMap::new();
+    for symbol in &pdb_info.public_symbols {
+        entry_points.insert(undecorated(&symbol.name), symbol.offset);
+    }
+    for procedure in &pdb_info.procedures {
+        entry_points.insert(undecorated(&procedure.name), procedure.offset);
+    }
+    for (name, offset) in &entry_points {
+        if let Some(service) = name.strip_prefix("Nt") {
+            if let Some(zw_offset) = entry_points.get(format!("Zw{}", service).as_str()) {
+                report.syscalls.push(Syscall {
+                    name: service.to_string(),
+                    nt_offset: *offset,
+                    zw_offset: *zw_offset,
+                });
+            }
+        }
+    }
+
+    for procedure in &pdb_info.procedures {
+        if procedure.is_dpc {
+            report.dpc_routines.push(procedure.into());
+        }
+        if is_irp_dispatch(pdb_info, procedure) {
+            report.irp_dispatch_candidates.push(procedure.into());
+        }
+    }
+
+    if json {
+        return Ok(serde_json::to_writer(output, &report)?);
+    }
+
+    let format_offset = |offset: Option<usize>| {
+        offset
+            .map(|offset| format!("0x{:016X}", offset))
+            .unwrap_or_else(|| format!("{:18}", "<unknown>"))
+    };
+
+    writeln!(output, "Syscalls ({} Nt/Zw pairs):", report.syscalls.len())?;
+    writeln!(output, "\t{:18} {:18} Name", "Nt", "Zw")?;
+    for syscall in &report.syscalls {
+        writeln!(
+            output,
+            "\t{} {} {}",
+            format_offset(syscall.nt_offset),
+            format_offset(syscall.zw_offset),
+            syscall.name
+        )?;
+    }
+
+    for (title, routines) in [
+        ("DPC routines", &report.dpc_routines),
+        ("IRP dispatch candidates", &report.irp_dispatch_candidates),
+    ]
+    .iter()
+    {
+        writeln!(output, "\n{} ({}):", title, routines.len())?;
+        for routine in routines.iter() {
+            writeln!(
+                output,
+                "\t{} {:>#8X} {}",
+                format_offset(routine.offset),
+                routine.len,
+                routine.name
+            )?;
+        }
+    }
+
+    Ok(())
+}
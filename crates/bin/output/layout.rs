@@ -0,0 +1,300 @@
+//! Helpers for generators of type definitions in other languages: choosing which types
+//! to emit, and placing the fields of a class or union at their recorded offsets in
+//! languages which have no bitfields and no way to position a field explicitly
+
+use crate::filter::wildcard_match;
+use crate::output::cdecl::field_size;
+use ezpdb::symbol_types::{ParsedPdb, TypeRef};
+use ezpdb::type_info::Type;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::rc::Rc;
+
+/// One entry in the body of a class or union
+pub enum Slot {
+    /// Bytes no field occupies
+    Padding { offset: usize, len: usize },
+    /// A data member or base class
+    Field {
+        name: String,
+        offset: usize,
+        ty: TypeRef,
+    },
+    /// Bitfields sharing a single storage unit of type `storage`, as
+    /// `(name, position, len)` in bits
+    Bitfields {
+        offset: usize,
+        storage: TypeRef,
+        bits: Vec<(String, usize, usize)>,
+    },
+    /// A field starting inside the previous one, e.g. in a packed or overlaid
+    /// layout, which can't be represented without reordering
+    Overlap { name: String, offset: usize },
+}
+
+/// Returns `name` made unique among `used` by appending `_2`, `_3`, ... as needed
+pub fn unique_name(name: String, used: &mut HashSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut n = 2;
+    while !used.insert(unique.clone()) {
+        unique = format!("{}_{}", name, n);
+        n += 1;
+    }
+
+    unique
+}
+
+/// Lays out `fields`, inserting explicit padding so every field lands at its recorded
+/// offset and the body fills `size` bytes. Union fields all start at offset 0 so they
+/// are never padded
+pub fn layout(fields: &[TypeRef], size: usize, is_union: bool, pdb_info: &ParsedPdb) -> Vec<Slot> {
+    let mut slots = vec![];
+    let mut cursor = 0usize;
+
+    for (i, field) in fields.iter().enumerate() {
+        let (name, offset, ty) = match &*field.as_ref().borrow() {
+            Type::Member(member) => (
+                member.name.clone(),
+                member.offset,
+                Rc::clone(&member.underlying_type),
+            ),
+            Type::BaseClass(base) => (
+                format!("base_{}", i),
+                base.offset,
+                Rc::clone(&base.base_class),
+            ),
+            _ => continue,
+        };
+
+        let bitfield = match &*ty.as_ref().borrow() {
+            Type::Bitfield(bitfield) => Some((
+                Rc::clone(&bitfield.underlying_type),
+                bitfield.position,
+                bitfield.len,
+            )),
+            _ => None,
+        };
+
+        // Consecutive bitfields at the same offset share their storage unit
+        if let (Some((_, position, len)), false) = (&bitfield, is_union) {
+            if let Some(Slot::Bitfields {
+                offset: last_offset,
+                bits,
+                ..
+            }) = slots.last_mut()
+            {
+                if *last_offset == offset {
+                    bits.push((name, *position, *len));
+                    continue;
+                }
+            }
+        }
+
+        if !is_union {
+            if offset < cursor {
+                slots.push(Slot::Overlap { name, offset });
+                continue;
+            }
+            if offset > cursor {
+                slots.push(Slot::Padding {
+                    offset: cursor,
+                    len: offset - cursor,
+                });
+            }
+            cursor = offset + field_size(&*ty.as_ref().borrow(), pdb_info);
+        }
+
+        slots.push(match bitfield {
+            Some((storage, position, len)) => Slot::Bitfields {
+                offset,
+                storage,
+                bits: vec![(name, position, len)],
+            },
+            None => Slot::Field { name, offset, ty },
+        });
+    }
+
+    if !is_union && size > cursor {
+        slots.push(Slot::Padding {
+            offset: cursor,
+            len: size - cursor,
+        });
+    }
+
+    slots
+}
+
+/// Identifies a class, union, or enum across its forward references, along with whether
+/// `ty` is the definition. Unnamed types all share a name, so the decorated name is
+/// preferred
+fn type_key(ty: &Type) -> Option<(&str, bool)> {
+    ty.definition_key()
+        .map(|(key, forward_reference)| (key, !forward_reference))
+}
+
+fn type_name(ty: &Type) -> &str {
+    match ty {
+        Type::Class(class) => &class.name,
+        Type::Union(union) => &union.name,
+        Type::Enumeration(e) => &e.name,
+        _ => "",
+    }
+}
+
+/// Collects the keys of the types `ty` embeds by value, and so must be defined alongside it
+fn value_dependencies(ty: &Type, dependencies: &mut Vec<String>) {
+    match ty {
+        Type::Class(_) | Type::Union(_) | Type::Enumeration(_) => {
+            dependencies.extend(type_key(ty).map(|(key, _)| key.to_string()))
+        }
+        Type::Array(array) => {
+            value_dependencies(&*array.element_type.as_ref().borrow(), dependencies)
+        }
+        Type::Modifier(modifier) => {
+            value_dependencies(&*modifier.underlying_type.as_ref().borrow(), dependencies)
+        }
+        Type::Bitfield(bitfield) => {
+            value_dependencies(&*bitfield.underlying_type.as_ref().borrow(), dependencies)
+        }
+        _ => {}
+    }
+}
+
+/// Keys of the types a class or union embeds by value in its fields and base classes
+fn embedded_types(ty: &Type) -> Vec<String> {
+    let fields = match ty {
+        Type::Class(class) => &class.fields,
+        Type::Union(union) => &union.fields,
+        _ => return vec![],
+    };
+
+    let mut dependencies = vec![];
+    for field in fields {
+        match &*field.as_ref().borrow() {
+            Type::Member(member) => value_dependencies(
+                &*member.underlying_type.as_ref().borrow(),
+                &mut dependencies,
+            ),
+            Type::BaseClass(base) => {
+                value_dependencies(&*base.base_class.as_ref().borrow(), &mut dependencies)
+            }
+            _ => {}
+        }
+    }
+
+    dependencies
+}
+
+/// The classes, unions, and enums chosen for output, along with every type they embed
+/// by value so the output is complete on its own
+pub struct TypeSelection {
+    /// Definitions of all classes, unions, and enums by key
+    pub definitions: HashMap<String, TypeRef>,
+    /// Identifiers of the selected types by key
+    pub identifiers: BTreeMap<String, String>,
+}
+
+impl TypeSelection {
+    /// Selects the types whose names match any of `patterns`, or every type if there are
+    /// none. `identifier` converts a name into a valid identifier for the target language
+    pub fn new(
+        pdb_info: &ParsedPdb,
+        patterns: &[String],
+        identifier: impl Fn(&str) -> String,
+    ) -> Self {
+        let mut definitions = HashMap::new();
+        for ty in pdb_info.types.values() {
+            let definition = pdb_info.resolve_forward_reference(ty);
+            let key = match type_key(&*definition.as_ref().borrow()) {
+                Some((key, true)) => key.to_string(),
+                _ => continue,
+            };
+            definitions.entry(key).or_insert(definition);
+        }
+
+        let mut queue: Vec<String> = definitions
+            .iter()
+            .filter(|(_, ty)| {
+                let ty = ty.as_ref().borrow();
+                patterns.is_empty()
+                    || patterns
+                        .iter()
+                        .any(|pattern| wildcard_match(pattern, type_name(&*ty)))
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut selected = BTreeSet::new();
+        while let Some(key) = queue.pop() {
+            if !selected.insert(key.clone()) {
+                continue;
+            }
+
+            let dependencies = embedded_types(&*definitions[&key].as_ref().borrow());
+            queue.extend(
+                dependencies
+                    .into_iter()
+                    .filter(|key| definitions.contains_key(key) && !selected.contains(key)),
+            );
+        }
+
+        let mut used = HashSet::new();
+        let mut identifiers = BTreeMap::new();
+        for key in selected {
+            let ty = definitions[&key].as_ref().borrow();
+            // Unnamed types are told apart by their decorated name
+            let base = match type_name(&*ty) {
+                name if name.starts_with('<') => identifier(&key),
+                name => identifier(name),
+            };
+            identifiers.insert(key, unique_name(base, &mut used));
+        }
+
+        TypeSelection {
+            definitions,
+            identifiers,
+        }
+    }
+
+    /// Returns the identifier of `ty` if it's one of the selected types
+    pub fn identifier(&self, ty: &Type) -> Option<&str> {
+        type_key(ty)
+            .and_then(|(key, _)| self.identifiers.get(key))
+            .map(String::as_str)
+    }
+
+    /// Returns the definition of a class, union, or enum, following forward references
+    pub fn definition(&self, ty: &Type) -> Option<TypeRef> {
+        type_key(ty).and_then(|(key, _)| self.definitions.get(key).map(Rc::clone))
+    }
+
+    /// Returns the keys of the selected types ordered so each type follows every type
+    /// it embeds by value
+    pub fn dependency_order(&self) -> Vec<&str> {
+        fn visit<'s>(
+            selection: &'s TypeSelection,
+            key: &'s str,
+            visited: &mut HashSet<&'s str>,
+            order: &mut Vec<&'s str>,
+        ) {
+            if !visited.insert(key) {
+                return;
+            }
+
+            let dependencies = embedded_types(&*selection.definitions[key].as_ref().borrow());
+            for dependency in dependencies {
+                if let Some((dependency, _)) = selection.identifiers.get_key_value(&dependency) {
+                    visit(selection, dependency, visited, order);
+                }
+            }
+            order.push(key);
+        }
+
+        let mut visited = HashSet::new();
+        let mut order = vec![];
+        for key in self.identifiers.keys() {
+            visit(self, key, &mut visited, &mut order);
+        }
+
+        order
+    }
+}
@@ -0,0 +1,103 @@
+use crate::group::ModuleRanges;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// Name used for object files which were linked directly rather than from a library
+const DIRECT_OBJECTS: &str = "<object files>";
+
+#[derive(Debug, Default, Serialize)]
+struct Library {
+    name: String,
+    /// Object files the library contributed
+    objects: Vec<String>,
+    functions: usize,
+    /// Total size of the library's functions
+    function_bytes: usize,
+    /// Total size of the library's section contributions, i.e. its code and data
+    bytes: usize,
+}
+
+/// Returns the static library a module was linked from, if any. Modules from a `.lib`
+/// have the object's path as their name and the library's path as their object file
+fn library_name(module: &DebugModule) -> Option<&str> {
+    let object_file = module.object_file_name.as_str();
+    if object_file != module.name && object_file.to_ascii_lowercase().ends_with(".lib") {
+        Some(object_file)
+    } else {
+        None
+    }
+}
+
+/// Reports which static libraries contributed which object files and how many
+/// functions and bytes each library accounts for, largest first
+pub fn print_library_report(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    json: bool,
+) -> io::Result<()> {
+    let module_libraries: Vec<&str> = pdb_info
+        .debug_modules
+        .iter()
+        .map(|module| library_name(module).unwrap_or(DIRECT_OBJECTS))
+        .collect();
+
+    let mut libraries: BTreeMap<&str, Library> = BTreeMap::new();
+    for (module, library) in pdb_info.debug_modules.iter().zip(&module_libraries) {
+        let entry = libraries.entry(*library).or_insert_with(|| Library {
+            name: library.to_string(),
+            ..Default::default()
+        });
+        entry.objects.push(module.name.clone());
+    }
+
+    for contribution in &pdb_info.section_contributions {
+        if let Some(library) = module_libraries.get(contribution.module_index) {
+            if let Some(entry) = libraries.get_mut(library) {
+                entry.bytes += contribution.size;
+            }
+        }
+    }
+
+    let ranges = ModuleRanges::new(pdb_info);
+    for procedure in &pdb_info.procedures {
+        let library = procedure
+            .offset
+            .and_then(|offset| ranges.module_index(offset))
+            .and_then(|module_index| module_libraries.get(module_index));
+        if let Some(entry) = library.and_then(|library| libraries.get_mut(library)) {
+            entry.functions += 1;
+            entry.function_bytes += procedure.len;
+        }
+    }
+
+    let mut libraries: Vec<Library> = libraries.into_values().collect();
+    libraries.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    if json {
+        return Ok(serde_json::to_writer(output, &libraries)?);
+    }
+
+    writeln!(
+        output,
+        "{:>12} {:>10} {:>14} {:>8} Library",
+        "Bytes", "Functions", "Function bytes", "Objects"
+    )?;
+    for library in &libraries {
+        writeln!(
+            output,
+            "{:>12} {:>10} {:>14} {:>8} {}",
+            library.bytes,
+            library.functions,
+            library.function_bytes,
+            library.objects.len(),
+            library.name
+        )?;
+        for object in &library.objects {
+            writeln!(output, "\t{}", object)?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use ezpdb::symbol_types::*;
+use std::io::{self, Write};
+
+/// Public names which the MSVC CRT uses as the image entry point
+const ENTRY_POINT_NAMES: &[&str] = &[
+    "mainCRTStartup",
+    "wmainCRTStartup",
+    "WinMainCRTStartup",
+    "wWinMainCRTStartup",
+    "_DllMainCRTStartup",
+    "DllMainCRTStartup",
+    "NtProcessStartup",
+    "DriverEntry",
+    "GsDriverEntry",
+];
+
+/// Strips x86 name decoration (`_name`, `_name@12`) from a public symbol name
+fn undecorated_name(name: &str) -> &str {
+    let name = name.strip_prefix('_').unwrap_or(name);
+    match name.rfind('@') {
+        Some(idx) if name[idx + 1..].chars().all(|c| c.is_ascii_digit()) => &name[..idx],
+        _ => name,
+    }
+}
+
+/// Formats an address as a linker-style `section:offset` pair
+fn section_offset(pdb_info: &ParsedPdb, address: usize) -> String {
+    match pdb_info.section_for_address(address) {
+        Some(section) => format!("{:04X}:{:08X}", section.index, address - section.offset),
+        None => format!("{:04X}:{:08X}", 0, address),
+    }
+}
+
+/// Writes a linker-style (`link.exe /MAP`) .map file
+pub fn print_map(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let module_name = pdb_info
+        .path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    writeln!(output, " {}", module_name)?;
+    writeln!(output)?;
+    writeln!(output, " Timestamp is {:08x}", pdb_info.timestamp)?;
+    writeln!(output)?;
+    writeln!(
+        output,
+        " Preferred load address is {:016X}",
+        pdb_info.base_address
+    )?;
+    writeln!(output)?;
+
+    // region: Sections
+    writeln!(
+        output,
+        " {:<13} {:<10} {:<23} Class",
+        "Start", "Length", "Name"
+    )?;
+    for section in &pdb_info.sections {
+        writeln!(
+            output,
+            " {:04X}:{:08X} {:08X}H {:<23} {}",
+            section.index,
+            0,
+            section.size,
+            section.name,
+            if section.is_code() { "CODE" } else { "DATA" }
+        )?;
+    }
+    writeln!(output)?;
+    // endregion
+
+    // region: Publics
+    writeln!(
+        output,
+        "  {:<15} {:<29} {:<22} Lib:Object",
+        "Address", "Publics by Value", "Rva+Base"
+    )?;
+    writeln!(output)?;
+
+    let mut publics: Vec<(usize, &PublicSymbol)> = pdb_info
+        .public_symbols
+        .iter()
+        .filter_map(|symbol| symbol.offset.map(|offset| (offset, symbol)))
+        .collect();
+    publics.sort_by(|(a_offset, a), (b_offset, b)| {
+        a_offset.cmp(b_offset).then_with(|| a.name.cmp(&b.name))
+    });
+
+    for (offset, symbol) in &publics {
+        let object = pdb_info
+            .lookup_address(*offset)
+            .module
+            .map(|module| module.name.as_str())
+            .unwrap_or("<absolute>");
+
+        writeln!(
+            output,
+            " {}       {:<29} {:016X} {} {}",
+            section_offset(pdb_info, *offset),
+            symbol.name,
+            offset,
+            if symbol.is_function { "f" } else { " " },
+            object
+        )?;
+    }
+    writeln!(output)?;
+    // endregion
+
+    let entry_point = publics
+        .iter()
+        .find(|(_offset, symbol)| ENTRY_POINT_NAMES.contains(&undecorated_name(&symbol.name)));
+    if let Some((offset, _symbol)) = entry_point {
+        writeln!(
+            output,
+            " entry point at        {}",
+            section_offset(pdb_info, *offset)
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::Type;
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+/// Number of entries listed in the "largest" sections
+const TOP_COUNT: usize = 10;
+
+/// Escapes text for use inside a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Wraps `text` in a code span, escaping it for use inside a table cell
+fn code(text: &str) -> String {
+    if text.is_empty() {
+        String::new()
+    } else {
+        format!("`{}`", cell(&text.replace('`', "'")))
+    }
+}
+
+fn format_version(version: &CompilerVersion) -> String {
+    match version.qfe {
+        Some(qfe) => format!(
+            "{}.{}.{}.{}",
+            version.major, version.minor, version.build, qfe
+        ),
+        None => format!("{}.{}.{}", version.major, version.minor, version.build),
+    }
+}
+
+fn write_toolchain(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    writeln!(output, "## Toolchain\n")?;
+
+    let compiler_info = match &pdb_info.assembly_info.compiler_info {
+        Some(compiler_info) => compiler_info,
+        None => return writeln!(output, "No compiler information is present.\n"),
+    };
+
+    let CompileFlags {
+        edit_and_continue,
+        link_time_codegen,
+        security_checks,
+        hot_patch,
+        sdl,
+        pgo,
+        managed,
+        ..
+    } = compiler_info.flags;
+    let flags: Vec<&str> = [
+        (security_checks, "/GS"),
+        (sdl, "/sdl"),
+        (link_time_codegen, "LTCG"),
+        (pgo, "PGO"),
+        (hot_patch, "/hotpatch"),
+        (edit_and_continue, "edit and continue"),
+        (managed, "managed"),
+    ]
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, name)| *name)
+    .collect();
+
+    writeln!(output, "| | |")?;
+    writeln!(output, "|---|---|")?;
+    writeln!(output, "| Language | {} |", cell(&compiler_info.language))?;
+    writeln!(
+        output,
+        "| Compiler | {} |",
+        code(&compiler_info.version_string)
+    )?;
+    writeln!(
+        output,
+        "| Frontend version | {} |",
+        format_version(&compiler_info.frontend_version)
+    )?;
+    writeln!(
+        output,
+        "| Backend version | {} |",
+        format_version(&compiler_info.backend_version)
+    )?;
+    writeln!(output, "| CPU type | {} |", cell(&compiler_info.cpu_type))?;
+    writeln!(output, "| Flags | {} |\n", flags.join(", "))
+}
+
+/// Collects observations an analyst would want called out: user names leaked through
+/// build paths, disabled mitigations, and anything the parser had to skip
+fn indicators(pdb_info: &ParsedPdb) -> Vec<String> {
+    let mut indicators = vec![];
+
+    let user_path = Regex::new(r"(?i)[\\/](?:users|home|documents and settings)[\\/]([^\\/]+)")
+        .expect("user path pattern is valid");
+    let mut users = BTreeSet::new();
+    let paths = pdb_info.debug_modules.iter().flat_map(|module| {
+        std::iter::once(module.name.as_str())
+            .chain(std::iter::once(module.object_file_name.as_str()))
+            .chain(
+                module
+                    .source_files
+                    .iter()
+                    .flatten()
+                    .map(|file| file.name.as_str()),
+            )
+    });
+    for path in paths {
+        if let Some(captures) = user_path.captures(path) {
+            users.insert(captures[1].to_string());
+        }
+    }
+    if !users.is_empty() {
+        let users: Vec<String> = users.iter().map(|user| code(user)).collect();
+        indicators.push(format!(
+            "Build paths contain user names: {}",
+            users.join(", ")
+        ));
+    }
+
+    if let Some(compiler_info) = &pdb_info.assembly_info.compiler_info {
+        if !compiler_info.flags.security_checks {
+            indicators.push("Compiled without buffer security checks (/GS)".to_string());
+        }
+        if compiler_info.flags.managed {
+            indicators.push("Managed code or data is present".to_string());
+        }
+    }
+
+    if !pdb_info.assembly_info.instrumentation.is_empty() {
+        let instrumentation: Vec<String> = pdb_info
+            .assembly_info
+            .instrumentation
+            .iter()
+            .map(|instrumentation| format!("{:?}", instrumentation))
+            .collect();
+        indicators.push(format!(
+            "Instrumented build: {}",
+            instrumentation.join(", ")
+        ));
+    }
+
+    if !pdb_info.exports.is_empty() {
+        indicators.push(format!("{} exports", pdb_info.exports.len()));
+    }
+
+    if !pdb_info.warnings.is_empty() {
+        indicators.push(format!(
+            "{} warnings were raised while parsing",
+            pdb_info.warnings.len()
+        ));
+    }
+
+    indicators
+}
+
+/// Writes a concise Markdown summary of the PDB for pasting into issue trackers and wikis
+pub fn print_markdown(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let title = pdb_info
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    // region: Identity
+    writeln!(output, "# {}\n", title)?;
+    writeln!(output, "| | |")?;
+    writeln!(output, "|---|---|")?;
+    writeln!(output, "| GUID | `{}` |", pdb_info.guid)?;
+    writeln!(output, "| Age | {} |", pdb_info.age)?;
+    writeln!(
+        output,
+        "| Debug identifier | `{}` |",
+        pdb_info.debug_identifier()
+    )?;
+    writeln!(output, "| Timestamp | 0x{:08X} |", pdb_info.timestamp)?;
+    writeln!(
+        output,
+        "| Machine type | {} |",
+        pdb_info
+            .machine_type
+            .as_ref()
+            .map(|ty| format!("{:?}", ty))
+            .unwrap_or_else(|| "Unknown".to_string())
+    )?;
+    writeln!(output, "| PDB version | {:?} |", pdb_info.version)?;
+    if let Some(identity) = &pdb_info.identity {
+        for (label, hashes) in std::iter::once(("PDB", &identity.pdb))
+            .chain(identity.pe.as_ref().map(|hashes| ("PE", hashes)))
+        {
+            writeln!(
+                output,
+                "| {} SHA256 | `{}` |",
+                label,
+                crate::signature::hex_digest(&hashes.sha256)
+            )?;
+        }
+    }
+    writeln!(
+        output,
+        "| Counts | {} procedures, {} public symbols, {} globals, {} types, {} modules |\n",
+        pdb_info.procedures.len(),
+        pdb_info.public_symbols.len(),
+        pdb_info.global_data.len(),
+        pdb_info.types.len(),
+        pdb_info.debug_modules.len()
+    )?;
+    // endregion
+
+    write_toolchain(output, pdb_info)?;
+
+    // region: Indicators
+    let indicators = indicators(pdb_info);
+    if !indicators.is_empty() {
+        writeln!(output, "## Notable indicators\n")?;
+        for indicator in indicators {
+            writeln!(output, "- {}", indicator)?;
+        }
+        writeln!(output)?;
+    }
+    // endregion
+
+    // region: Modules
+    let mut module_sizes = vec![0usize; pdb_info.debug_modules.len()];
+    for contribution in &pdb_info.section_contributions {
+        if let Some(size) = module_sizes.get_mut(contribution.module_index) {
+            *size += contribution.size;
+        }
+    }
+
+    writeln!(output, "## Modules\n")?;
+    writeln!(output, "| Module | Object file | Source files | Size |")?;
+    writeln!(output, "|---|---|---:|---:|")?;
+    for (module, size) in pdb_info.debug_modules.iter().zip(&module_sizes) {
+        writeln!(
+            output,
+            "| {} | {} | {} | 0x{:X} |",
+            code(&module.name),
+            code(&module.object_file_name),
+            module.source_files.as_ref().map_or(0, Vec::len),
+            size
+        )?;
+    }
+    writeln!(output)?;
+    // endregion
+
+    // region: Largest functions
+    let mut procedures: Vec<&Procedure> = pdb_info.procedures.iter().collect();
+    procedures.sort_by(|a, b| b.len.cmp(&a.len).then_with(|| a.name.cmp(&b.name)));
+
+    writeln!(output, "## Largest functions\n")?;
+    writeln!(output, "| Address | Size | Name |")?;
+    writeln!(output, "|---|---:|---|")?;
+    for procedure in procedures.iter().take(TOP_COUNT) {
+        writeln!(
+            output,
+            "| {} | 0x{:X} | {} |",
+            procedure
+                .offset
+                .map(|offset| format!("0x{:08X}", offset))
+                .unwrap_or_default(),
+            procedure.len,
+            code(&procedure.name)
+        )?;
+    }
+    writeln!(output)?;
+    // endregion
+
+    // region: Largest types
+    let mut types: Vec<(String, usize)> = pdb_info
+        .types
+        .values()
+        .filter_map(|ty| match &*ty.as_ref().borrow() {
+            Type::Class(class) if !class.properties.forward_reference => {
+                Some((class.name.clone(), class.size))
+            }
+            Type::Union(union) if !union.properties.forward_reference => {
+                Some((union.name.clone(), union.size))
+            }
+            _ => None,
+        })
+        .collect();
+    types.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    writeln!(output, "## Largest types\n")?;
+    writeln!(output, "| Size | Name |")?;
+    writeln!(output, "|---:|---|")?;
+    for (name, size) in types.iter().take(TOP_COUNT) {
+        writeln!(output, "| 0x{:X} | {} |", size, code(name))?;
+    }
+    // endregion
+
+    Ok(())
+}
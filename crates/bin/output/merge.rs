@@ -0,0 +1,149 @@
+use ezpdb::lookup::AddressSpace;
+use ezpdb::symbol_types::*;
+use std::io::{self, BufRead, Write};
+
+fn module_name(pdb_info: &ParsedPdb) -> String {
+    pdb_info
+        .path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Returns `(address, len, name)` for every procedure and public symbol in a module.
+/// Public symbols at the same address as a procedure are omitted
+fn module_symbols(pdb_info: &ParsedPdb) -> Vec<(usize, usize, &str)> {
+    let mut symbols: Vec<_> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| {
+            procedure
+                .offset
+                .map(|offset| (offset, procedure.len, procedure.name.as_str()))
+        })
+        .collect();
+    symbols.sort();
+
+    let publics: Vec<_> = pdb_info
+        .public_symbols
+        .iter()
+        .filter_map(|symbol| {
+            symbol
+                .offset
+                .map(|offset| (offset, 0, symbol.name.as_str()))
+        })
+        .filter(|(offset, _len, _name)| {
+            symbols
+                .binary_search_by_key(offset, |(offset, _len, _name)| *offset)
+                .is_err()
+        })
+        .collect();
+
+    symbols.extend(publics);
+    symbols.sort();
+
+    symbols
+}
+
+/// Writes the symbols of every module in the address space sorted by address
+pub fn print_merged_symbols(
+    output: &mut impl Write,
+    space: &AddressSpace,
+    json: bool,
+) -> io::Result<()> {
+    if json {
+        write!(output, "[")?;
+    }
+
+    let mut first = true;
+    for module in space.modules() {
+        let name = module_name(module);
+        for (address, len, symbol) in module_symbols(module) {
+            if json {
+                if !first {
+                    write!(output, ",")?;
+                }
+                serde_json::to_writer(
+                    &mut *output,
+                    &serde_json::json!({
+                        "address": address,
+                        "len": len,
+                        "module": name,
+                        "name": symbol,
+                    }),
+                )?;
+            } else {
+                writeln!(
+                    output,
+                    "0x{:016X} 0x{:08X} {}!{}",
+                    address, len, name, symbol
+                )?;
+            }
+            first = false;
+        }
+    }
+
+    if json {
+        write!(output, "]")?;
+    }
+
+    Ok(())
+}
+
+/// Symbolizes the address at the start of each line of `input` as `module!symbol+0xdisp`
+pub fn print_symbolized(
+    output: &mut impl Write,
+    space: &AddressSpace,
+    input: impl BufRead,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        let token = line.split_whitespace().next().unwrap_or("");
+        let address = match token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"))
+        {
+            Some(hex) => usize::from_str_radix(hex, 16).ok(),
+            None => token.parse().ok(),
+        };
+
+        let address = match address {
+            Some(address) => address,
+            None => {
+                // Pass through anything that isn't an address untouched
+                writeln!(output, "{}", line)?;
+                continue;
+            }
+        };
+
+        let symbol = space.lookup_address(address).map(|(module, lookup)| {
+            let name = module_name(module);
+            if let Some(procedure) = lookup.procedure {
+                let offset = procedure.offset.unwrap_or(address);
+                match address
+                    .checked_sub(offset)
+                    .filter(|displacement| *displacement < procedure.len)
+                {
+                    Some(displacement) => {
+                        format!("{}!{}+0x{:X}", name, procedure.name, displacement)
+                    }
+                    // The address is inside a separated code block of this procedure
+                    None => format!("{}!{} <separated code>", name, procedure.name),
+                }
+            } else if let Some((public, displacement)) = lookup.nearest_public {
+                format!("{}!{}+0x{:X}", name, public.name, displacement)
+            } else {
+                format!("{}+0x{:X}", name, address - module.base_address)
+            }
+        });
+
+        writeln!(
+            output,
+            "0x{:016X} {}",
+            address,
+            symbol.as_deref().unwrap_or("<unknown>")
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use crate::filter::wildcard_match;
+use crate::output::format_type_name;
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::Type;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::rc::Rc;
+
+#[derive(Debug, Serialize)]
+struct Implementation {
+    offset: usize,
+    /// Size of the procedure in BYTES
+    len: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct MethodEntry {
+    name: String,
+    signature: String,
+    access: &'static str,
+    is_static: bool,
+    is_virtual: bool,
+    is_pure_virtual: bool,
+    /// Whether this method overrides a virtual method of a base class
+    is_override: bool,
+    /// Index of this method's entry in the class's vtable
+    vtable_slot: Option<usize>,
+    /// Procedures implementing this method. Empty for pure virtual and inlined methods
+    implementations: Vec<Implementation>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClassMethods {
+    name: String,
+    methods: Vec<MethodEntry>,
+}
+
+fn access_name(access: u8) -> &'static str {
+    match access {
+        1 => "private",
+        2 => "protected",
+        3 => "public",
+        _ => "",
+    }
+}
+
+/// Returns the signature of a member function type along with the size of its `this`
+/// pointer, which vtable offsets are divided by to get the slot
+fn signature(method_type: &Type, name: &str) -> (String, Option<usize>) {
+    match method_type {
+        Type::MemberFunction(function) => {
+            let arguments: Vec<String> = function
+                .argument_list
+                .iter()
+                .map(|argument| format_type_name(&*argument.as_ref().borrow()))
+                .collect();
+            let pointer_size = function
+                .this_pointer_type
+                .as_ref()
+                .and_then(|this| match &*this.as_ref().borrow() {
+                    Type::Pointer(pointer) => Some(pointer.attributes.size),
+                    _ => None,
+                })
+                .filter(|size| *size != 0);
+
+            (
+                format!(
+                    "{} {}({})",
+                    format_type_name(&*function.return_type.as_ref().borrow()),
+                    name,
+                    arguments.join(", ")
+                ),
+                pointer_size,
+            )
+        }
+        other => (format!("{} {}", format_type_name(other), name), None),
+    }
+}
+
+/// Which classes and methods [print_methods] includes
+#[derive(Debug, Default)]
+pub struct MethodFilter<'a> {
+    /// Wildcard pattern class names must match
+    pub class: Option<&'a str>,
+    pub virtual_only: bool,
+    /// Only include methods with at least one implementing procedure
+    pub implemented_only: bool,
+}
+
+/// Writes the methods of each class, from its `LF_ONEMETHOD` and `LF_METHODLIST`
+/// records, along with the procedures implementing them
+pub fn print_methods(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    filter: &MethodFilter<'_>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let procedures: HashMap<usize, &Procedure> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| Some((procedure.offset?, procedure)))
+        .collect();
+
+    let mut report = vec![];
+    for ty in pdb_info.types.values() {
+        let ty = ty.as_ref().borrow();
+        let class = match &*ty {
+            Type::Class(class) if !class.properties.forward_reference => class,
+            _ => continue,
+        };
+        let class_name = ty.display_name().unwrap_or_default();
+        if filter
+            .class
+            .map_or(false, |pattern| !wildcard_match(pattern, class_name))
+        {
+            continue;
+        }
+
+        // Flatten overloads into (name, type, vtable offset, attributes, implementations)
+        let mut declared = vec![];
+        for field in &class.fields {
+            match &*field.as_ref().borrow() {
+                Type::Method(method) => declared.push((
+                    method.name.clone(),
+                    Rc::clone(&method.method_type),
+                    method.vtable_offset,
+                    method.attributes.clone(),
+                    method.implementations.clone(),
+                )),
+                Type::OverloadedMethod(overloaded) => {
+                    if let Type::MethodList(list) = &*overloaded.method_list.as_ref().borrow() {
+                        declared.extend(list.0.iter().map(|entry| {
+                            (
+                                overloaded.name.clone(),
+                                Rc::clone(&entry.method_type),
+                                entry.vtable_offset,
+                                entry.attributes.clone(),
+                                entry.implementations.clone(),
+                            )
+                        }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        declared.retain(|(_, _, _, attributes, implementations)| {
+            (!filter.virtual_only || attributes.is_virtual || attributes.is_pure_virtual)
+                && (!filter.implemented_only || !implementations.is_empty())
+        });
+        if declared.is_empty() {
+            continue;
+        }
+
+        let methods = declared
+            .into_iter()
+            .map(|(name, method_type, vtable_offset, attributes, offsets)| {
+                let (signature, pointer_size) = signature(&*method_type.as_ref().borrow(), &name);
+                let implementations = offsets
+                    .into_iter()
+                    .map(|offset| Implementation {
+                        offset,
+                        len: procedures.get(&offset).map_or(0, |procedure| procedure.len),
+                    })
+                    .collect();
+
+                MethodEntry {
+                    signature,
+                    access: access_name(attributes.access),
+                    is_static: attributes.is_static,
+                    is_virtual: attributes.is_virtual || attributes.is_pure_virtual,
+                    is_pure_virtual: attributes.is_pure_virtual,
+                    is_override: attributes.is_override(),
+                    vtable_slot: vtable_offset.map(|offset| offset / pointer_size.unwrap_or(8)),
+                    implementations,
+                    name,
+                }
+            })
+            .collect();
+
+        report.push(ClassMethods {
+            name: class_name.to_string(),
+            methods,
+        });
+    }
+
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    for class in &report {
+        writeln!(output, "{}", class.name)?;
+        for method in &class.methods {
+            let mut flags = vec![method.access];
+            if method.is_static {
+                flags.push("static");
+            }
+            if method.is_pure_virtual {
+                flags.push("pure virtual");
+            } else if method.is_virtual {
+                flags.push("virtual");
+            }
+            if method.is_override {
+                flags.push("override");
+            }
+
+            let slot = method
+                .vtable_slot
+                .map(|slot| format!("[{}]", slot))
+                .unwrap_or_default();
+            writeln!(
+                output,
+                "\t{:>5} {:24} {}",
+                slot,
+                flags.join(" "),
+                method.signature
+            )?;
+            for implementation in &method.implementations {
+                writeln!(
+                    output,
+                    "\t\t0x{:08X} (0x{:X} bytes)",
+                    implementation.offset, implementation.len
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use ezpdb::msf::{MsfReport, Severity};
+use std::io::{self, Write};
+
+/// Writes the findings of [ezpdb::msf::check_msf]
+pub fn print_msf_report(output: &mut impl Write, report: &MsfReport, json: bool) -> io::Result<()> {
+    if json {
+        return Ok(serde_json::to_writer(output, report)?);
+    }
+
+    writeln!(output, "Block size:      {}", report.block_size)?;
+    writeln!(output, "Blocks:          {}", report.block_count)?;
+    writeln!(output, "Free page map:   {}", report.free_block_map_block)?;
+    writeln!(output, "Directory bytes: {}", report.directory_bytes)?;
+    writeln!(output, "Streams:         {}", report.stream_count)?;
+
+    if report.issues.is_empty() {
+        return writeln!(output, "\nNo problems found");
+    }
+
+    writeln!(output, "\nProblems:")?;
+    for issue in &report.issues {
+        let severity = match issue.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        writeln!(output, "\t{}: {}", severity, issue.message)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use crate::output::cdecl::c_identifier;
+use crate::output::pointer_size;
+use crate::CliArgumentError;
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::{Class, Type};
+use std::collections::HashSet;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Language an offsets header is generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderLanguage {
+    #[default]
+    Cpp,
+    Rust,
+}
+
+impl FromStr for HeaderLanguage {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_ref() {
+            "cpp" | "c++" => Ok(HeaderLanguage::Cpp),
+            "rust" | "rs" => Ok(HeaderLanguage::Rust),
+            _ => Err(CliArgumentError::InvalidValue("lang", s.to_string())),
+        }
+    }
+}
+
+/// A named constant within a class's section of the header
+struct Constant {
+    /// Member or method name as it appears in the PDB
+    member: String,
+    /// Identifier the constant is emitted as
+    name: String,
+    value: usize,
+}
+
+/// Offsets and vtable indices selected from one class
+struct ClassOffsets {
+    name: String,
+    size: usize,
+    members: Vec<Constant>,
+    vtable: Vec<Constant>,
+}
+
+/// Returns the definition of the class or struct called `name`
+fn find_class(pdb_info: &ParsedPdb, name: &str) -> Option<Class> {
+    pdb_info
+        .types
+        .values()
+        .find_map(|ty| match &*ty.as_ref().borrow() {
+            Type::Class(class) if !class.properties.forward_reference && class.name == name => {
+                Some(class.clone())
+            }
+            _ => None,
+        })
+}
+
+/// Returns `name` made unique among `used` by appending `_2`, `_3`, ... as needed, since
+/// overloaded virtual methods share a name
+fn unique_identifier(name: &str, used: &mut HashSet<String>) -> String {
+    let base = c_identifier(name);
+    let mut identifier = base.clone();
+    let mut n = 2;
+    while !used.insert(identifier.clone()) {
+        identifier = format!("{}_{}", base, n);
+        n += 1;
+    }
+
+    identifier
+}
+
+/// Collects the data member offsets and virtual method indices of `class`. If `only` is
+/// provided, just the members and methods with those names are kept
+fn class_offsets(class: &Class, only: Option<&HashSet<&str>>, pointer_size: usize) -> ClassOffsets {
+    let wanted = |name: &str| only.map_or(true, |only| only.contains(name));
+    let mut used = HashSet::new();
+    let mut members = vec![];
+    let mut vtable = vec![];
+
+    let mut push_method = |name: &str, vtable_offset: Option<usize>, used: &mut HashSet<String>| {
+        if let Some(vtable_offset) = vtable_offset.filter(|_| wanted(name)) {
+            vtable.push(Constant {
+                member: name.to_string(),
+                name: unique_identifier(name, used),
+                value: vtable_offset / pointer_size,
+            });
+        }
+    };
+
+    for field in &class.fields {
+        match &*field.as_ref().borrow() {
+            Type::Member(member) if wanted(&member.name) => members.push(Constant {
+                member: member.name.clone(),
+                name: unique_identifier(&member.name, &mut used),
+                value: member.offset,
+            }),
+            Type::Method(method) => push_method(&method.name, method.vtable_offset, &mut used),
+            Type::OverloadedMethod(overloaded) => {
+                if let Type::MethodList(list) = &*overloaded.method_list.as_ref().borrow() {
+                    for entry in &list.0 {
+                        push_method(&overloaded.name, entry.vtable_offset, &mut used);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    vtable.sort_by_key(|constant| constant.value);
+
+    ClassOffsets {
+        name: class.name.clone(),
+        size: class.size,
+        members,
+        vtable,
+    }
+}
+
+fn write_cpp(output: &mut impl Write, classes: &[ClassOffsets]) -> std::io::Result<()> {
+    writeln!(
+        output,
+        "#pragma once\n\n#include <cstddef>\n\nnamespace offsets {{"
+    )?;
+    for class in classes {
+        writeln!(output, "\n// {}", class.name)?;
+        writeln!(output, "namespace {} {{", c_identifier(&class.name))?;
+        writeln!(
+            output,
+            "\tconstexpr std::size_t _size = 0x{:X};",
+            class.size
+        )?;
+        for member in &class.members {
+            writeln!(
+                output,
+                "\tconstexpr std::ptrdiff_t {} = 0x{:X};",
+                member.name, member.value
+            )?;
+        }
+        if !class.vtable.is_empty() {
+            writeln!(output, "\tnamespace vtable {{")?;
+            for method in &class.vtable {
+                writeln!(
+                    output,
+                    "\t\tconstexpr std::size_t {} = {};",
+                    method.name, method.value
+                )?;
+            }
+            writeln!(output, "\t}}")?;
+        }
+        writeln!(output, "}}")?;
+    }
+    writeln!(output, "\n}} // namespace offsets")
+}
+
+fn write_rust(output: &mut impl Write, classes: &[ClassOffsets]) -> std::io::Result<()> {
+    writeln!(
+        output,
+        "#[allow(non_snake_case, non_upper_case_globals, dead_code)]"
+    )?;
+    writeln!(output, "pub mod offsets {{")?;
+    for class in classes {
+        writeln!(output, "    /// {}", class.name)?;
+        writeln!(output, "    pub mod {} {{", c_identifier(&class.name))?;
+        writeln!(
+            output,
+            "        pub const _size: usize = 0x{:X};",
+            class.size
+        )?;
+        for member in &class.members {
+            writeln!(
+                output,
+                "        pub const {}: usize = 0x{:X};",
+                member.name, member.value
+            )?;
+        }
+        if !class.vtable.is_empty() {
+            writeln!(output, "        pub mod vtable {{")?;
+            for method in &class.vtable {
+                writeln!(
+                    output,
+                    "            pub const {}: usize = {};",
+                    method.name, method.value
+                )?;
+            }
+            writeln!(output, "        }}")?;
+        }
+        writeln!(output, "    }}")?;
+    }
+    writeln!(output, "}}")
+}
+
+/// Writes a header of member offsets and virtual method indices. Each item is either a
+/// class name, selecting all of its members and virtual methods, or `Class::member`,
+/// selecting a single member or method. Items naming the same class are combined
+pub fn print_offsets_header(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    items: &[String],
+    language: HeaderLanguage,
+) -> anyhow::Result<()> {
+    // `None` selects the whole class
+    let mut selections: Vec<(String, Option<HashSet<&str>>)> = vec![];
+    for item in items {
+        let (class_name, member) = if find_class(pdb_info, item).is_some() {
+            (item.as_str(), None)
+        } else {
+            match item.rfind("::") {
+                Some(idx) => (&item[..idx], Some(&item[idx + 2..])),
+                None => anyhow::bail!("no class named {:?}", item),
+            }
+        };
+
+        let position = selections.iter().position(|(name, _)| name == class_name);
+        let selection = match position {
+            Some(position) => &mut selections[position].1,
+            None => {
+                selections.push((class_name.to_string(), Some(HashSet::new())));
+                &mut selections.last_mut().unwrap().1
+            }
+        };
+        match member {
+            Some(member) => {
+                if let Some(members) = selection.as_mut() {
+                    members.insert(member);
+                }
+            }
+            None => *selection = None,
+        }
+    }
+
+    let pointer_size = pointer_size(pdb_info);
+    let mut classes = vec![];
+    for (name, only) in &selections {
+        let class = find_class(pdb_info, name)
+            .ok_or_else(|| anyhow::anyhow!("no class named {:?}", name))?;
+        let offsets = class_offsets(&class, only.as_ref(), pointer_size);
+
+        if let Some(only) = only {
+            let found: HashSet<&str> = offsets
+                .members
+                .iter()
+                .chain(&offsets.vtable)
+                .map(|constant| constant.member.as_str())
+                .collect();
+            if let Some(missing) = only.iter().find(|member| !found.contains(*member)) {
+                anyhow::bail!(
+                    "{} has no member or virtual method named {:?}",
+                    name,
+                    missing
+                );
+            }
+        }
+
+        classes.push(offsets);
+    }
+
+    writeln!(
+        output,
+        "// Generated by pdbview from {} ({})\n",
+        pdb_info
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        pdb_info.debug_identifier()
+    )?;
+    match language {
+        HeaderLanguage::Cpp => write_cpp(output, &classes)?,
+        HeaderLanguage::Rust => write_rust(output, &classes)?,
+    }
+
+    Ok(())
+}
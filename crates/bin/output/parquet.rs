@@ -0,0 +1,132 @@
+use arrow::array::{ArrayRef, StringArray, UInt32Array, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::Type;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::InMemoryWriteableCursor;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Columns of the exported table. Procedures, public symbols, data, and types share a
+/// single table distinguished by `kind` so a corpus of PDBs can be queried as one dataset
+#[derive(Default)]
+struct Rows<'a> {
+    kind: Vec<&'static str>,
+    name: Vec<&'a str>,
+    address: Vec<Option<u64>>,
+    size: Vec<Option<u64>>,
+    type_index: Vec<Option<u32>>,
+}
+
+impl<'a> Rows<'a> {
+    fn push(
+        &mut self,
+        kind: &'static str,
+        name: &'a str,
+        address: Option<usize>,
+        size: Option<usize>,
+        type_index: Option<TypeIndexNumber>,
+    ) {
+        self.kind.push(kind);
+        self.name.push(name);
+        self.address.push(address.map(|address| address as u64));
+        self.size.push(size.map(|size| size as u64));
+        self.type_index.push(type_index);
+    }
+}
+
+/// Writes procedures, public symbols, global data, and named types as a single Parquet
+/// table for dataset-scale analysis with tools such as DuckDB or Spark. Every row carries
+/// the PDB's name and debug identifier so files from many PDBs can be queried together
+pub fn print_parquet(output: &mut impl Write, pdb_info: &ParsedPdb) -> anyhow::Result<()> {
+    let mut indices: Vec<_> = pdb_info.types.keys().copied().collect();
+    indices.sort_unstable();
+    let type_names: Vec<(TypeIndexNumber, String, Option<usize>)> = indices
+        .into_iter()
+        .filter_map(|index| match &*pdb_info.types[&index].as_ref().borrow() {
+            Type::Class(class) if !class.properties.forward_reference => {
+                Some((index, class.name.clone(), Some(class.size)))
+            }
+            Type::Union(union) if !union.properties.forward_reference => {
+                Some((index, union.name.clone(), Some(union.size)))
+            }
+            Type::Enumeration(e) if !e.properties.forward_reference => {
+                Some((index, e.name.clone(), None))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut rows = Rows::default();
+
+    for procedure in &pdb_info.procedures {
+        rows.push(
+            "procedure",
+            &procedure.name,
+            procedure.offset,
+            Some(procedure.len),
+            Some(procedure.type_index),
+        );
+    }
+
+    for symbol in &pdb_info.public_symbols {
+        rows.push("public", &symbol.name, symbol.offset, None, None);
+    }
+
+    for data in &pdb_info.global_data {
+        rows.push("data", &data.name, data.offset, None, None);
+    }
+
+    for (index, name, size) in &type_names {
+        rows.push("type", name, None, *size, Some(*index));
+    }
+
+    let pdb_name = pdb_info
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let debug_identifier = pdb_info.debug_identifier();
+    let row_count = rows.kind.len();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("pdb", DataType::Utf8, false),
+        Field::new("debug_identifier", DataType::Utf8, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("address", DataType::UInt64, true),
+        Field::new("size", DataType::UInt64, true),
+        Field::new("type_index", DataType::UInt32, true),
+    ]));
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![pdb_name.as_str(); row_count])),
+        Arc::new(StringArray::from(vec![
+            debug_identifier.as_str();
+            row_count
+        ])),
+        Arc::new(StringArray::from(rows.kind)),
+        Arc::new(StringArray::from(rows.name)),
+        Arc::new(UInt64Array::from(rows.address)),
+        Arc::new(UInt64Array::from(rows.size)),
+        Arc::new(UInt32Array::from(rows.type_index)),
+    ];
+    let batch = RecordBatch::try_new(Arc::clone(&schema), columns)?;
+
+    // Parquet writes its footer by seeking, so build the file in memory before copying
+    // it to the (possibly compressed or piped) output
+    let cursor = InMemoryWriteableCursor::default();
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(cursor.clone(), schema, Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    output.write_all(&cursor.data())?;
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::pe::PeInfo;
+use crate::signature;
+use ezpdb::symbol_types::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Number of leading bytes which make up the pattern
+const PATTERN_LEN: usize = 32;
+/// Functions shorter than this produce signatures too generic to be useful
+const MIN_FUNCTION_LEN: usize = 8;
+
+/// CRC16 as used by IDA's FLIRT tools
+fn crc16(data: &[u8]) -> u16 {
+    const POLY: u32 = 0x8408;
+
+    if data.is_empty() {
+        return 0;
+    }
+
+    let mut crc: u32 = 0xffff;
+    for byte in data {
+        let mut data = *byte as u32;
+        for _ in 0..8 {
+            if (crc ^ data) & 1 != 0 {
+                crc = (crc >> 1) ^ POLY;
+            } else {
+                crc >>= 1;
+            }
+            data >>= 1;
+        }
+    }
+
+    let crc = !crc;
+    (((crc << 8) | ((crc >> 8) & 0xff)) & 0xffff) as u16
+}
+
+fn hex_byte(byte: Option<u8>) -> String {
+    match byte {
+        Some(byte) => format!("{:02X}", byte),
+        None => "..".to_string(),
+    }
+}
+
+/// Writes an IDA `.pat` pattern file (input to FLIRT's `sigmake`) with one pattern per
+/// procedure, using the PDB's function boundaries and the PE's bytes
+pub fn print_pat(output: &mut impl Write, pdb_info: &ParsedPdb, pe: &PeInfo) -> io::Result<()> {
+    // Prefer decorated public names since that's what FLIRT matches against in libraries
+    let public_names: HashMap<usize, &str> = pdb_info
+        .public_symbols
+        .iter()
+        .filter_map(|symbol| symbol.offset.map(|offset| (offset, symbol.name.as_str())))
+        .collect();
+
+    for procedure in &pdb_info.procedures {
+        let offset = match procedure.offset {
+            Some(offset) if procedure.len >= MIN_FUNCTION_LEN => offset,
+            _ => continue,
+        };
+
+        let rva = offset - pdb_info.base_address;
+        let bytes = match signature::masked_bytes(pe, rva, procedure.len) {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+
+        let mut line = String::new();
+        for i in 0..PATTERN_LEN {
+            line.push_str(&hex_byte(bytes.get(i).copied().flatten()));
+        }
+
+        // The CRC covers the bytes after the pattern up to the first variable byte
+        let crc_bytes: Vec<u8> = bytes
+            .iter()
+            .skip(PATTERN_LEN)
+            .take(0xff)
+            .map_while(|byte| *byte)
+            .collect();
+
+        let name = public_names
+            .get(&offset)
+            .copied()
+            .unwrap_or(procedure.name.as_str());
+
+        line.push_str(&format!(
+            " {:02X} {:04X} {:04X} :0000 {}",
+            crc_bytes.len(),
+            crc16(&crc_bytes),
+            procedure.len,
+            name
+        ));
+
+        // Tail bytes help sigmake resolve collisions between functions with equal patterns
+        let tail_start = PATTERN_LEN + crc_bytes.len();
+        if tail_start < bytes.len() {
+            line.push(' ');
+            for byte in &bytes[tail_start..] {
+                line.push_str(&hex_byte(*byte));
+            }
+        }
+
+        writeln!(output, "{}", line)?;
+    }
+
+    writeln!(output, "---")
+}
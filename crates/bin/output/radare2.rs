@@ -0,0 +1,88 @@
+use super::cdecl;
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::{Type, Typed};
+use std::io::{self, Write};
+
+/// Converts a symbol name into a valid radare2/rizin flag name
+fn flag_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Writes a radare2/rizin script (run with `. script.r2`) which defines flags, functions,
+/// and type definitions. Addresses include the base address provided at parse time.
+pub fn print_radare2(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    writeln!(
+        output,
+        "# Generated by pdbview from {}",
+        pdb_info.path.display()
+    )?;
+    writeln!(output, "fs symbols")?;
+
+    for symbol in &pdb_info.public_symbols {
+        if let Some(offset) = symbol.offset {
+            writeln!(
+                output,
+                "f sym.{} 1 @ 0x{:X}",
+                flag_name(&symbol.name),
+                offset
+            )?;
+        }
+    }
+
+    for data in &pdb_info.global_data {
+        if let Some(offset) = data.offset {
+            let ty = data.ty.as_ref().borrow();
+            let size = match &*ty {
+                Type::Class(_)
+                | Type::Union(_)
+                | Type::Array(_)
+                | Type::Primitive(_)
+                | Type::Pointer(_)
+                | Type::Enumeration(_)
+                | Type::Modifier(_) => ty.type_size(pdb_info).max(1),
+                _ => 1,
+            };
+
+            writeln!(
+                output,
+                "f obj.{} {} @ 0x{:X}",
+                flag_name(&data.name),
+                size,
+                offset
+            )?;
+        }
+    }
+
+    writeln!(output, "fs functions")?;
+    for procedure in &pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            let name = flag_name(&procedure.name);
+            writeln!(output, "f fcn.{} {} @ 0x{:X}", name, procedure.len, offset)?;
+            writeln!(output, "af @ 0x{:X}", offset)?;
+            writeln!(output, "afn {} @ 0x{:X}", name, offset)?;
+        }
+    }
+
+    // Type indices are assigned in dependency order, so defining types by ascending index
+    // ensures embedded types are defined before the types containing them
+    let mut type_indices: Vec<_> = pdb_info.types.keys().collect();
+    type_indices.sort();
+
+    for index in type_indices {
+        let ty = pdb_info.types[index].as_ref().borrow();
+        if let Some(definition) = cdecl::c_definition(&*ty, pdb_info) {
+            let definition = definition.replace('\n', " ").replace('\t', "");
+            writeln!(output, "td \"{}\"", definition.replace('"', "\\\""))?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::rtti::RttiClass;
+use std::io::Write;
+
+/// Writes the classes recovered from the image's RTTI with their vtables and base
+/// classes. With `unnamed_only`, only vtables without a public symbol are included
+pub fn print_rtti(
+    output: &mut impl Write,
+    mut classes: Vec<RttiClass>,
+    unnamed_only: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    if unnamed_only {
+        for class in &mut classes {
+            class.vtables.retain(|vtable| vtable.public_name.is_none());
+        }
+        classes.retain(|class| !class.vtables.is_empty());
+    }
+
+    if json {
+        serde_json::to_writer(output, &classes)?;
+        return Ok(());
+    }
+
+    for class in &classes {
+        match (&class.name, class.type_index) {
+            (Some(name), Some(index)) => writeln!(
+                output,
+                "{} {} (type 0x{:X})",
+                name, class.decorated_name, index
+            )?,
+            _ => writeln!(output, "{} (not in PDB)", class.decorated_name)?,
+        }
+        for vtable in &class.vtables {
+            writeln!(
+                output,
+                "\tvtable 0x{:08X} offset 0x{:X} entries {} {}",
+                vtable.rva,
+                vtable.offset,
+                vtable.entries,
+                vtable.public_name.as_deref().unwrap_or("")
+            )?;
+        }
+        if !class.base_classes.is_empty() {
+            writeln!(output, "\tbases: {}", class.base_classes.join(", "))?;
+        }
+    }
+
+    Ok(())
+}
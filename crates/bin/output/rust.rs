@@ -0,0 +1,296 @@
+//! Renders parsed types as `#[repr(C)]` Rust definitions
+
+use crate::output::cdecl::{c_identifier, variant_value};
+use crate::output::layout::{layout, unique_name, Slot, TypeSelection};
+use crate::output::pointer_size;
+use ezpdb::symbol_types::{ParsedPdb, TypeRef};
+use ezpdb::type_info::*;
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "try",
+    "type", "union", "unsafe", "use", "where", "while", "yield",
+];
+
+/// Converts `name` into an identifier which is neither a keyword nor the `_` pattern
+fn rust_identifier(name: &str) -> String {
+    let identifier = c_identifier(name);
+    if identifier == "_" {
+        "_unnamed".to_string()
+    } else if KEYWORDS.contains(&identifier.as_str()) {
+        format!("{}_", identifier)
+    } else {
+        identifier
+    }
+}
+
+/// Returns the Rust spelling of a primitive type, or `None` if it has no equivalent.
+/// Booleans are treated as integers since memory read from the target may hold values
+/// other than 0 and 1
+fn primitive_name(kind: PrimitiveKind) -> Option<&'static str> {
+    let name = match kind {
+        PrimitiveKind::NoType | PrimitiveKind::Void => "c_void",
+        PrimitiveKind::Char | PrimitiveKind::RChar | PrimitiveKind::I8 => "i8",
+        PrimitiveKind::UChar | PrimitiveKind::U8 | PrimitiveKind::Bool8 => "u8",
+        PrimitiveKind::WChar
+        | PrimitiveKind::RChar16
+        | PrimitiveKind::UShort
+        | PrimitiveKind::U16
+        | PrimitiveKind::Bool16 => "u16",
+        PrimitiveKind::Short | PrimitiveKind::I16 => "i16",
+        PrimitiveKind::RChar32 | PrimitiveKind::ULong | PrimitiveKind::U32 => "u32",
+        PrimitiveKind::Bool32 => "u32",
+        PrimitiveKind::Long | PrimitiveKind::I32 | PrimitiveKind::HRESULT => "i32",
+        PrimitiveKind::UQuad | PrimitiveKind::U64 | PrimitiveKind::Bool64 => "u64",
+        PrimitiveKind::Quad | PrimitiveKind::I64 => "i64",
+        PrimitiveKind::UOcta | PrimitiveKind::U128 => "u128",
+        PrimitiveKind::Octa | PrimitiveKind::I128 => "i128",
+        PrimitiveKind::F32 | PrimitiveKind::F32PP => "f32",
+        PrimitiveKind::F64 => "f64",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+struct Generator<'a> {
+    pdb_info: &'a ParsedPdb,
+    selection: TypeSelection,
+    pointer_size: usize,
+}
+
+impl<'a> Generator<'a> {
+    fn new(pdb_info: &'a ParsedPdb, patterns: &[String]) -> Self {
+        Generator {
+            pdb_info,
+            selection: TypeSelection::new(pdb_info, patterns, rust_identifier),
+            pointer_size: pointer_size(pdb_info),
+        }
+    }
+
+    fn identifier(&self, ty: &Type) -> Option<&str> {
+        self.selection.identifier(ty)
+    }
+
+    /// Pointers are only spelled as raw pointers when they match the host's width for a
+    /// 64-bit target. Pointers of a 32-bit target are plain addresses
+    fn pointer(&self, pointee: &str, constant: bool) -> String {
+        if self.pointer_size != 8 {
+            "u32".to_string()
+        } else if constant {
+            format!("*const {}", pointee)
+        } else {
+            format!("*mut {}", pointee)
+        }
+    }
+
+    /// Returns the type a pointer to `ty` points to. Types which aren't being emitted are
+    /// treated as opaque
+    fn pointee(&self, ty: &Type) -> String {
+        match ty {
+            Type::Class(_) | Type::Union(_) | Type::Enumeration(_) => {
+                self.identifier(ty).unwrap_or("c_void").to_string()
+            }
+            Type::Procedure(_) | Type::MemberFunction(_) => "c_void".to_string(),
+            Type::Modifier(modifier) => self.pointee(&*modifier.underlying_type.as_ref().borrow()),
+            other => self.rust_type(other),
+        }
+    }
+
+    fn rust_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::Class(_) | Type::Union(_) | Type::Enumeration(_) => match self.identifier(ty) {
+                Some(identifier) => identifier.to_string(),
+                None => format!("[u8; {}]", ty.type_size(self.pdb_info)),
+            },
+            Type::Primitive(primitive) => {
+                match (primitive_name(primitive.kind), &primitive.indirection) {
+                    (kind, Some(_)) => self.pointer(kind.unwrap_or("c_void"), false),
+                    (Some(kind), None) => kind.to_string(),
+                    (None, None) => format!("[u8; {}]", primitive.kind.size()),
+                }
+            }
+            Type::Pointer(pointer) => match pointer.underlying_type.as_ref() {
+                Some(underlying_type) => {
+                    let underlying_type = underlying_type.as_ref().borrow();
+                    let constant =
+                        matches!(&*underlying_type, Type::Modifier(modifier) if modifier.constant);
+                    self.pointer(&self.pointee(&*underlying_type), constant)
+                }
+                None => self.pointer("c_void", false),
+            },
+            Type::Array(array) => array.dimensions_elements.iter().rev().fold(
+                self.rust_type(&*array.element_type.as_ref().borrow()),
+                |accum, dimension| format!("[{}; {}]", accum, dimension),
+            ),
+            Type::Modifier(modifier) => {
+                self.rust_type(&*modifier.underlying_type.as_ref().borrow())
+            }
+            Type::Bitfield(bitfield) => {
+                self.rust_type(&*bitfield.underlying_type.as_ref().borrow())
+            }
+            _ => self.pointer("c_void", false),
+        }
+    }
+
+    fn write_fields(
+        &self,
+        output: &mut impl Write,
+        fields: &[TypeRef],
+        size: usize,
+        is_union: bool,
+    ) -> io::Result<()> {
+        let mut used = HashSet::new();
+        for slot in layout(fields, size, is_union, self.pdb_info) {
+            match slot {
+                Slot::Padding { offset, len } => {
+                    writeln!(output, "    pub _pad_0x{:04X}: [u8; {}],", offset, len)?
+                }
+                Slot::Field { name, offset, ty } => writeln!(
+                    output,
+                    "    pub {}: {}, // 0x{:04X}",
+                    unique_name(rust_identifier(&name), &mut used),
+                    self.rust_type(&*ty.as_ref().borrow()),
+                    offset
+                )?,
+                Slot::Bitfields {
+                    offset,
+                    storage,
+                    bits,
+                } => {
+                    let bits: Vec<String> = bits
+                        .iter()
+                        .map(|(name, position, len)| {
+                            format!("`{}` (bits {}..{})", name, position, position + len)
+                        })
+                        .collect();
+                    writeln!(output, "    /// Bitfields: {}", bits.join(", "))?;
+                    writeln!(
+                        output,
+                        "    pub {}: {}, // 0x{:04X}",
+                        unique_name(format!("_bitfield_0x{:04X}", offset), &mut used),
+                        self.rust_type(&*storage.as_ref().borrow()),
+                        offset
+                    )?
+                }
+                Slot::Overlap { name, offset } => writeln!(
+                    output,
+                    "    // 0x{:04X} overlaps previous field: {}",
+                    offset, name
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_definition(
+        &self,
+        output: &mut impl Write,
+        key: &str,
+        identifier: &str,
+    ) -> io::Result<()> {
+        let ty = self.selection.definitions[key].as_ref().borrow();
+        match &*ty {
+            Type::Class(class) => {
+                writeln!(output, "/// `{}`, 0x{:X} bytes", class.name, class.size)?;
+                if class.properties.packed {
+                    writeln!(output, "#[repr(C, packed)]")?;
+                } else {
+                    writeln!(output, "#[repr(C)]")?;
+                }
+                writeln!(output, "#[derive(Clone, Copy)]")?;
+                writeln!(output, "pub struct {} {{", identifier)?;
+                self.write_fields(output, &class.fields, class.size, false)?;
+                writeln!(output, "}}")?;
+                writeln!(
+                    output,
+                    "const _: () = assert!(core::mem::size_of::<{}>() == 0x{:X});\n",
+                    identifier, class.size
+                )
+            }
+            Type::Union(union) => {
+                writeln!(output, "/// `{}`, 0x{:X} bytes", union.name, union.size)?;
+                writeln!(output, "#[repr(C)]\n#[derive(Clone, Copy)]")?;
+                writeln!(output, "pub union {} {{", identifier)?;
+                self.write_fields(output, &union.fields, union.size, true)?;
+                writeln!(output, "}}")?;
+                writeln!(
+                    output,
+                    "const _: () = assert!(core::mem::size_of::<{}>() == 0x{:X});\n",
+                    identifier, union.size
+                )
+            }
+            // Enums become newtypes over their underlying integer, since memory read from
+            // the target may hold values that aren't listed and variants may share values
+            Type::Enumeration(e) => {
+                let underlying_type = self.rust_type(&*e.underlying_type.as_ref().borrow());
+                writeln!(output, "/// `{}`", e.name)?;
+                writeln!(output, "#[repr(transparent)]")?;
+                writeln!(output, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]")?;
+                writeln!(
+                    output,
+                    "pub struct {}(pub {});",
+                    identifier, underlying_type
+                )?;
+                if !e.variants.is_empty() {
+                    writeln!(output, "\nimpl {} {{", identifier)?;
+                    let mut used = HashSet::new();
+                    for variant in &e.variants {
+                        let name = rust_identifier(&variant.name);
+                        if used.insert(name.clone()) {
+                            writeln!(
+                                output,
+                                "    pub const {}: {} = {}({});",
+                                name,
+                                identifier,
+                                identifier,
+                                variant_value(&variant.value)
+                            )?;
+                        }
+                    }
+                    writeln!(output, "}}")?;
+                }
+                writeln!(output)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Writes `#[repr(C)]` Rust definitions of the classes, unions, and enums whose names
+/// match any of `patterns`, or of every type if there are none. Types embedded by value
+/// are included so the output compiles on its own, while pointers to other types become
+/// `*mut c_void`. Explicit padding fields place every field at its recorded offset
+pub fn print_rust(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    patterns: &[String],
+) -> io::Result<()> {
+    let generator = Generator::new(pdb_info, patterns);
+
+    writeln!(
+        output,
+        "// Generated by pdbview from {} ({})\n",
+        pdb_info
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        pdb_info.debug_identifier()
+    )?;
+    writeln!(
+        output,
+        "#![allow(non_camel_case_types, non_snake_case, non_upper_case_globals, dead_code)]\n"
+    )?;
+    writeln!(output, "use core::ffi::c_void;\n")?;
+
+    for (key, identifier) in &generator.selection.identifiers {
+        generator.write_definition(output, key, identifier)?;
+    }
+
+    Ok(())
+}
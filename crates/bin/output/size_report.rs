@@ -0,0 +1,154 @@
+use crate::group::ModuleRanges;
+use crate::namespace::{split_scopes, SizeGroup};
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::Type;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Write};
+
+/// Name used for functions which have no enclosing scope of the requested kind
+const NO_GROUP: &str = "<none>";
+
+#[derive(Debug, Default, Serialize)]
+struct SizeRow {
+    name: String,
+    functions: usize,
+    bytes: usize,
+    /// Share of all functions' bytes
+    percent: f64,
+}
+
+/// Names of every defined class, struct, and union, used to tell class scopes apart from
+/// namespaces since both are spelled the same way in qualified names
+fn class_names(pdb_info: &ParsedPdb) -> HashSet<String> {
+    pdb_info
+        .types
+        .values()
+        .filter_map(|ty| match &*ty.as_ref().borrow() {
+            Type::Class(class) => Some(class.name.clone()),
+            Type::Union(union) => Some(union.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Splits the scope of a function (its name without the last component) into the
+/// enclosing namespace and, for member functions, the class
+fn namespace_and_class<'a>(
+    name: &'a str,
+    classes: &HashSet<String>,
+) -> (Option<&'a str>, Option<&'a str>) {
+    let scopes = split_scopes(name);
+    if scopes.len() < 2 {
+        return (None, None);
+    }
+
+    // Each scope's qualified name ends where the next `::` begins
+    let mut ends = vec![];
+    let mut end = 0;
+    for scope in &scopes[..scopes.len() - 1] {
+        end += scope.len();
+        ends.push(end);
+        end += 2;
+    }
+
+    let owner = &name[..*ends.last().unwrap()];
+    let class = Some(owner).filter(|owner| classes.contains(*owner));
+
+    // Nested classes are part of their enclosing class, not a namespace
+    let namespace = ends
+        .iter()
+        .rev()
+        .map(|end| &name[..*end])
+        .find(|scope| !classes.contains(*scope));
+
+    (namespace, class)
+}
+
+/// Reports the number and total size of functions grouped by namespace, class, or
+/// module, largest first, as a table, JSON, or CSV
+pub fn print_size_report(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    group: SizeGroup,
+    json: bool,
+    csv: bool,
+) -> io::Result<()> {
+    let classes = class_names(pdb_info);
+    let ranges = ModuleRanges::new(pdb_info);
+
+    let mut groups: BTreeMap<&str, SizeRow> = BTreeMap::new();
+    let mut total = 0;
+    for procedure in &pdb_info.procedures {
+        let name = match group {
+            SizeGroup::Namespace => namespace_and_class(&procedure.name, &classes).0,
+            SizeGroup::Class => namespace_and_class(&procedure.name, &classes).1,
+            SizeGroup::Module => procedure
+                .offset
+                .and_then(|offset| ranges.module_index(offset))
+                .and_then(|index| pdb_info.debug_modules.get(index))
+                .map(|module| module.name.as_str()),
+        }
+        .unwrap_or(NO_GROUP);
+
+        let row = groups.entry(name).or_insert_with(|| SizeRow {
+            name: name.to_string(),
+            ..Default::default()
+        });
+        row.functions += 1;
+        row.bytes += procedure.len;
+        total += procedure.len;
+    }
+
+    let mut rows: Vec<SizeRow> = groups.into_values().collect();
+    for row in &mut rows {
+        if total > 0 {
+            row.percent = row.bytes as f64 * 100.0 / total as f64;
+        }
+    }
+    rows.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    if json {
+        return Ok(serde_json::to_writer(output, &rows)?);
+    }
+
+    if csv {
+        writeln!(output, "name,functions,bytes,percent")?;
+        for row in &rows {
+            writeln!(
+                output,
+                "{},{},{},{:.2}",
+                csv_field(&row.name),
+                row.functions,
+                row.bytes,
+                row.percent
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    writeln!(
+        output,
+        "{:>12} {:>7} {:>10} Name",
+        "Bytes", "%", "Functions"
+    )?;
+    for row in &rows {
+        writeln!(
+            output,
+            "{:>12} {:>7.2} {:>10} {}",
+            row.bytes, row.percent, row.functions, row.name
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Quotes a CSV field if it contains a separator, quote, or newline
+fn csv_field(value: &str) -> String {
+    if value.contains(|c| matches!(c, ',' | '"' | '\n')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,117 @@
+use crate::remote::ProbeReport;
+use crate::symsrv::{AvailabilityReport, FetchResult, Variant};
+use std::io::Write;
+
+/// Writes which symbol server tiers hold a file and which of them would serve it
+pub fn print_availability(
+    output: &mut impl Write,
+    report: &AvailabilityReport,
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        serde_json::to_writer(output, report)?;
+        return Ok(());
+    }
+
+    writeln!(output, "{}/{}", report.symbol.name, report.symbol.key)?;
+    for (i, tier) in report.tiers.iter().enumerate() {
+        let status = match (&tier.error, tier.available.is_empty()) {
+            (Some(error), _) => format!("error: {}", error),
+            (None, true) => "not found".to_string(),
+            (None, false) => tier
+                .available
+                .iter()
+                .map(|variant| match variant {
+                    Variant::Uncompressed => "uncompressed",
+                    Variant::Compressed => "compressed",
+                    Variant::FilePtr => "file.ptr",
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        let marker = if report.serving_tier == Some(i) {
+            "*"
+        } else {
+            " "
+        };
+        writeln!(output, "{} [{}] {}: {}", marker, i, tier.location, status)?;
+    }
+    if report.serving_tier.is_none() {
+        writeln!(output, "No tier has this file")?;
+    }
+
+    Ok(())
+}
+
+/// Writes where each requested file was downloaded to, or why it couldn't be
+pub fn print_fetch_results(
+    output: &mut impl Write,
+    results: &[FetchResult],
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        serde_json::to_writer(output, results)?;
+        return Ok(());
+    }
+
+    for result in results {
+        let symbol = format!("{}/{}", result.symbol.name, result.symbol.key);
+        match (&result.path, &result.error) {
+            (Some(path), _) => writeln!(
+                output,
+                "{} -> {} ({} bytes{}{})",
+                symbol,
+                path.display(),
+                result.size,
+                result.source.as_ref().map_or_else(
+                    || ", cached".to_string(),
+                    |source| format!(", from {}", source)
+                ),
+                if result.resumed { ", resumed" } else { "" }
+            )?,
+            (None, error) => writeln!(
+                output,
+                "{} failed: {}",
+                symbol,
+                error.as_deref().unwrap_or_default()
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the identity and public symbols read from a remote PDB, along with how much
+/// of it had to be downloaded
+pub fn print_probe(
+    output: &mut impl Write,
+    report: &ProbeReport,
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        serde_json::to_writer(output, report)?;
+        return Ok(());
+    }
+
+    writeln!(output, "{}", report.url)?;
+    writeln!(output, "Debug identifier: {}", report.debug_identifier)?;
+    writeln!(output, "Timestamp: 0x{:08X}", report.timestamp)?;
+    writeln!(
+        output,
+        "Machine Type: {}",
+        report.machine_type.as_deref().unwrap_or("Unknown")
+    )?;
+    for symbol in &report.public_symbols {
+        match symbol.offset {
+            Some(offset) => writeln!(output, "0x{:08X} {}", offset, symbol.name)?,
+            None => writeln!(output, "{:10} {}", "", symbol.name)?,
+        }
+    }
+    writeln!(
+        output,
+        "Downloaded {} of {} bytes in {} requests",
+        report.downloaded, report.size, report.requests
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use crate::symstore::Transaction;
+use std::io::Write;
+
+/// Writes the files a symbol store transaction added and where they were stored
+pub fn print_store_transaction(
+    output: &mut impl Write,
+    transaction: &Transaction,
+    json: bool,
+) -> anyhow::Result<()> {
+    if json {
+        serde_json::to_writer(output, transaction)?;
+        return Ok(());
+    }
+
+    writeln!(output, "Transaction {}", transaction.id)?;
+    for file in &transaction.files {
+        writeln!(output, "\t{} -> {}", file.source.display(), file.index_path)?;
+    }
+
+    Ok(())
+}
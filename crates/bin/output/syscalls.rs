@@ -0,0 +1,129 @@
+use crate::output::kernel::undecorated;
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::io::Write;
+
+/// Number of bytes at the start of a stub searched for the system call number
+const STUB_LEN: usize = 32;
+
+#[derive(Debug, Serialize)]
+struct Syscall<'a> {
+    name: &'a str,
+    number: u32,
+    rva: usize,
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Extracts the system call number from the start of a `Nt`/`Zw` stub
+fn syscall_number(machine_type: Option<&MachineType>, stub: &[u8]) -> Option<u32> {
+    match machine_type {
+        // ntdll: `mov r10, rcx; mov eax, imm32`. Kernel `Zw` stubs build a trap frame
+        // first and load the number with `push rax; mov eax, imm32`
+        Some(MachineType::Amd64) => {
+            if stub.starts_with(&[0x4C, 0x8B, 0xD1, 0xB8]) {
+                return read_u32(stub, 4);
+            }
+            stub.windows(2)
+                .position(|window| window == [0x50, 0xB8])
+                .and_then(|idx| read_u32(stub, idx + 2))
+        }
+        // Both ntdll and kernel stubs start with `mov eax, imm32`
+        Some(MachineType::X86) => match stub.first() {
+            Some(0xB8) => read_u32(stub, 1),
+            _ => None,
+        },
+        // `svc #imm16` within the first few instructions
+        Some(MachineType::Arm64) => stub
+            .chunks_exact(4)
+            .take(4)
+            .map(|insn| u32::from_le_bytes(insn.try_into().unwrap()))
+            .find(|insn| insn & 0xFFE0_001F == 0xD400_0001)
+            .map(|insn| (insn >> 5) & 0xFFFF),
+        _ => None,
+    }
+}
+
+/// Joins the system call numbers found in the `Nt`/`Zw` stubs of ntdll or the kernel
+/// image with their names. Each service is listed once, under its `Nt` name if that
+/// stub holds the number and otherwise under its `Zw` name
+pub fn print_syscalls(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: &PeInfo,
+    json: bool,
+) -> anyhow::Result<()> {
+    let machine_type = pdb_info.machine_type.as_ref();
+    if !matches!(
+        machine_type,
+        Some(MachineType::Amd64) | Some(MachineType::X86) | Some(MachineType::Arm64)
+    ) {
+        anyhow::bail!(
+            "system call stubs can't be decoded for machine type {:?}",
+            machine_type
+        );
+    }
+
+    let mut stubs: BTreeMap<&str, usize> = BTreeMap::new();
+    for symbol in &pdb_info.public_symbols {
+        if let Some(offset) = symbol.offset {
+            stubs.insert(undecorated(&symbol.name), offset - pdb_info.base_address);
+        }
+    }
+    for procedure in &pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            stubs.insert(undecorated(&procedure.name), offset - pdb_info.base_address);
+        }
+    }
+
+    // Keyed by the service name without its prefix
+    let mut syscalls: BTreeMap<&str, Syscall> = BTreeMap::new();
+    for prefix in ["Zw", "Nt"].iter() {
+        for (name, rva) in &stubs {
+            let service = match name.strip_prefix(*prefix) {
+                Some(service) => service,
+                None => continue,
+            };
+            let number = pe
+                .read_rva(*rva, STUB_LEN)
+                .or_else(|| pe.read_rva(*rva, 8))
+                .and_then(|stub| syscall_number(machine_type, stub));
+            if let Some(number) = number {
+                syscalls.insert(
+                    service,
+                    Syscall {
+                        name: *name,
+                        number,
+                        rva: *rva,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut syscalls: Vec<Syscall> = syscalls.into_values().collect();
+    syscalls.sort_by(|a, b| a.number.cmp(&b.number).then_with(|| a.name.cmp(b.name)));
+
+    if json {
+        serde_json::to_writer(output, &syscalls)?;
+        return Ok(());
+    }
+
+    writeln!(output, "{:>8} {:>10} Name", "Number", "RVA")?;
+    for syscall in &syscalls {
+        writeln!(
+            output,
+            "{:>#8X} {:>#10X} {}",
+            syscall.number, syscall.rva, syscall.name
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use crate::sort::SortKey;
+use ezpdb::symbol_types::*;
+use std::io::{self, Write};
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders rows as a table with aligned columns. The last column is left unpadded so
+/// long names don't widen every row
+struct Table {
+    title: &'static str,
+    headers: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn write(&self, output: &mut impl Write, color: bool) -> io::Result<()> {
+        let mut widths: Vec<usize> = self.headers.iter().map(|header| header.len()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let (bold, cyan, reset) = if color {
+            (BOLD, CYAN, RESET)
+        } else {
+            ("", "", "")
+        };
+
+        writeln!(
+            output,
+            "{}{} ({}){}",
+            bold,
+            self.title,
+            self.rows.len(),
+            reset
+        )?;
+
+        let last = self.headers.len() - 1;
+        let header: Vec<String> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                if i == last {
+                    header.to_string()
+                } else {
+                    format!("{:<width$}", header, width = widths[i])
+                }
+            })
+            .collect();
+        writeln!(output, "{}{}{}", bold, header.join("  "), reset)?;
+
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        writeln!(output, "{}", rule.join("  "))?;
+
+        for row in &self.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == last {
+                        cell.clone()
+                    } else if i == 0 {
+                        // The first column is always the address or index
+                        format!("{}{:<width$}{}", cyan, cell, reset, width = widths[i])
+                    } else {
+                        format!("{:<width$}", cell, width = widths[i])
+                    }
+                })
+                .collect();
+            writeln!(output, "{}", cells.join("  "))?;
+        }
+
+        writeln!(output)
+    }
+}
+
+fn format_address(offset: Option<usize>) -> String {
+    offset
+        .map(|offset| format!("0x{:08X}", offset))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Writes public symbols, procedures, and modules as aligned tables for skimming
+pub fn print_table(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    sort: SortKey,
+    reverse: bool,
+    color: bool,
+) -> io::Result<()> {
+    let mut publics: Vec<&PublicSymbol> = pdb_info.public_symbols.iter().collect();
+    sort.sort(&mut publics, reverse, |symbol| {
+        (symbol.offset, symbol.name.as_str(), 0)
+    });
+    Table {
+        title: "Public symbols",
+        headers: &["Address", "Code", "Function", "Name"],
+        rows: publics
+            .iter()
+            .map(|symbol| {
+                vec![
+                    format_address(symbol.offset),
+                    symbol.is_code.to_string(),
+                    symbol.is_function.to_string(),
+                    symbol.name.clone(),
+                ]
+            })
+            .collect(),
+    }
+    .write(output, color)?;
+
+    let mut procedures: Vec<&Procedure> = pdb_info.procedures.iter().collect();
+    sort.sort(&mut procedures, reverse, |procedure| {
+        (procedure.offset, procedure.name.as_str(), procedure.len)
+    });
+    Table {
+        title: "Procedures",
+        headers: &["Address", "Size", "Global", "Name"],
+        rows: procedures
+            .iter()
+            .map(|procedure| {
+                vec![
+                    format_address(procedure.offset),
+                    format!("0x{:X}", procedure.len),
+                    procedure.is_global.to_string(),
+                    procedure.name.clone(),
+                ]
+            })
+            .collect(),
+    }
+    .write(output, color)?;
+
+    // A module's size is the total size of the section ranges it contributed
+    let mut module_sizes = vec![0usize; pdb_info.debug_modules.len()];
+    for contribution in &pdb_info.section_contributions {
+        if let Some(size) = module_sizes.get_mut(contribution.module_index) {
+            *size += contribution.size;
+        }
+    }
+
+    let mut modules: Vec<(usize, &DebugModule)> =
+        pdb_info.debug_modules.iter().enumerate().collect();
+    sort.sort(&mut modules, reverse, |(i, module)| {
+        // Modules have no address, so order them by index in its place
+        (Some(*i), module.name.as_str(), module_sizes[*i])
+    });
+    Table {
+        title: "Modules",
+        headers: &["Index", "Size", "Lines", "Object file", "Name"],
+        rows: modules
+            .iter()
+            .map(|(i, module)| {
+                vec![
+                    i.to_string(),
+                    format!("0x{:X}", module_sizes[*i]),
+                    module.lines.len().to_string(),
+                    module.object_file_name.clone(),
+                    module.name.clone(),
+                ]
+            })
+            .collect(),
+    }
+    .write(output, color)
+}
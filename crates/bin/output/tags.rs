@@ -0,0 +1,163 @@
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::{ClassKind, Type};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+
+/// A name and the source location it was defined at. Type names are owned since types
+/// are only reachable through a borrow of their `RefCell`
+struct Tag<'a> {
+    name: Cow<'a, str>,
+    file: Cow<'a, str>,
+    line: u32,
+    /// Single-letter ctags kind, e.g. `f` for functions
+    kind: char,
+}
+
+/// Returns the unqualified part of a C++ name, e.g. `bar` for `foo::bar`
+fn short_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Returns `name` and, if it's qualified, its unqualified part
+fn tag_names(name: &str) -> Vec<&str> {
+    let short = short_name(name);
+    if short.len() == name.len() {
+        vec![short]
+    } else {
+        vec![name, short]
+    }
+}
+
+/// Collects tags for every class, union, and enum with a known source line, along with
+/// their members and enumerators. Members have no line of their own so they point at
+/// their type's definition
+fn collect_type_tags(pdb_info: &ParsedPdb, tags: &mut Vec<Tag<'_>>) {
+    let mut push = |name: &str, file: &str, line: u32, kind: char| {
+        for name in tag_names(name) {
+            tags.push(Tag {
+                name: Cow::Owned(name.to_string()),
+                file: Cow::Owned(file.to_string()),
+                line,
+                kind,
+            });
+        }
+    };
+
+    for typ in pdb_info.types.values() {
+        let typ = typ.as_ref().borrow();
+        let source = match typ.source() {
+            Some(source) => source,
+            None => continue,
+        };
+
+        let (name, kind, fields) = match &*typ {
+            Type::Class(class) => {
+                let kind = match class.kind {
+                    ClassKind::Class => 'c',
+                    ClassKind::Struct => 's',
+                    ClassKind::Interface => 'i',
+                };
+                (&class.name, kind, &class.fields)
+            }
+            Type::Union(union) => (&union.name, 'u', &union.fields),
+            Type::Enumeration(e) => {
+                push(&e.name, &source.file, source.line, 'g');
+                for variant in &e.variants {
+                    push(&variant.name, &source.file, source.line, 'e');
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        push(name, &source.file, source.line, kind);
+        for field in fields {
+            if let Type::Member(member) = &*field.as_ref().borrow() {
+                push(&member.name, &source.file, source.line, 'm');
+            }
+        }
+    }
+}
+
+/// Collects a tag for every procedure with line information and every type with a known
+/// source line. Qualified names are tagged both in full and unqualified since editors
+/// look up whatever is under the cursor
+fn collect_tags(pdb_info: &ParsedPdb) -> Vec<Tag<'_>> {
+    // Procedures start at the first line record of their body, so look lines up by
+    // exact address rather than scanning every module's line table for each procedure
+    let mut lines: HashMap<usize, &LineInfo> = HashMap::new();
+    for line in pdb_info
+        .debug_modules
+        .iter()
+        .flat_map(|module| module.lines.iter())
+    {
+        if let Some(offset) = line.offset {
+            lines.entry(offset).or_insert(line);
+        }
+    }
+
+    let mut tags = vec![];
+    for procedure in &pdb_info.procedures {
+        let line = match procedure.offset.and_then(|offset| lines.get(&offset)) {
+            Some(line) => line,
+            None => continue,
+        };
+
+        for name in tag_names(&procedure.name) {
+            tags.push(Tag {
+                name: Cow::Borrowed(name),
+                file: Cow::Borrowed(&line.file),
+                line: line.line_start,
+                kind: 'f',
+            });
+        }
+    }
+    collect_type_tags(pdb_info, &mut tags);
+
+    tags
+}
+
+/// Writes a Vi-style `tags` file mapping names to the source lines they're defined at
+pub fn print_ctags(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let mut tags = collect_tags(pdb_info);
+    // Editors binary search the file, so tags must be sorted bytewise by name
+    tags.sort_by(|a, b| (&a.name, &a.file, a.line).cmp(&(&b.name, &b.file, b.line)));
+    tags.dedup_by(|a, b| (&a.name, &a.file, a.line) == (&b.name, &b.file, b.line));
+
+    writeln!(output, "!_TAG_FILE_FORMAT\t2\t/extended format/")?;
+    writeln!(output, "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/")?;
+    writeln!(output, "!_TAG_PROGRAM_NAME\tpdbview\t//")?;
+    for tag in tags {
+        writeln!(
+            output,
+            "{}\t{}\t{};\"\t{}",
+            tag.name, tag.file, tag.line, tag.kind
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes an Emacs-style `TAGS` file mapping names to the source lines they're defined at
+pub fn print_etags(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let mut files: BTreeMap<String, Vec<Tag<'_>>> = BTreeMap::new();
+    for tag in collect_tags(pdb_info) {
+        files.entry(tag.file.to_string()).or_default().push(tag);
+    }
+
+    for (file, mut tags) in files {
+        tags.sort_by(|a, b| (a.line, &a.name).cmp(&(b.line, &b.name)));
+
+        // Without the source text, the name stands in for the line's contents. Each
+        // section is prefixed with its size in bytes
+        let mut section = String::new();
+        for tag in tags {
+            section.push_str(&format!("{}\x7f{}\x01{},0\n", tag.name, tag.name, tag.line));
+        }
+
+        write!(output, "\x0c\n{},{}\n{}", file, section.len(), section)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::filter::wildcard_match;
+use crate::template::TemplateName;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+#[derive(Debug, Default, Serialize)]
+struct Instantiation {
+    /// Normalized name of the instantiation, e.g. `std::vector<int>`
+    name: String,
+    functions: usize,
+    /// Total size of the functions in BYTES
+    bytes: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Template {
+    name: String,
+    functions: usize,
+    /// Total size of the functions in BYTES
+    bytes: usize,
+    /// Instantiations ordered largest first
+    instantiations: Vec<Instantiation>,
+}
+
+/// Groups procedures by the class or function template they were instantiated from,
+/// e.g. `std::vector<int>::push_back` under `std::vector<int>` and `std::vector`, to show
+/// how much code each template generates. Instantiations are compared by their
+/// normalized names, so default arguments such as allocators don't split them up.
+/// With `matching`, only instantiations whose normalized name matches the wildcard
+/// pattern are counted
+pub fn print_template_report(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    matching: Option<&str>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut templates: BTreeMap<String, BTreeMap<String, Instantiation>> = BTreeMap::new();
+    for procedure in &pdb_info.procedures {
+        let parsed = TemplateName::parse(&procedure.name);
+        if !parsed.is_template {
+            continue;
+        }
+
+        let name = parsed.instantiation().normalized();
+        if matching.map_or(false, |pattern| !wildcard_match(pattern, &name)) {
+            continue;
+        }
+
+        let instantiation = templates
+            .entry(parsed.name.clone())
+            .or_default()
+            .entry(name.clone())
+            .or_insert_with(|| Instantiation {
+                name,
+                ..Default::default()
+            });
+        instantiation.functions += 1;
+        instantiation.bytes += procedure.len;
+    }
+
+    let mut report: Vec<Template> = templates
+        .into_iter()
+        .map(|(name, instantiations)| {
+            let mut instantiations: Vec<Instantiation> = instantiations.into_values().collect();
+            instantiations.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+            Template {
+                name,
+                functions: instantiations.iter().map(|i| i.functions).sum(),
+                bytes: instantiations.iter().map(|i| i.bytes).sum(),
+                instantiations,
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    writeln!(
+        output,
+        "{:>10} {:>9} {:>14} Template",
+        "Bytes", "Functions", "Instantiations"
+    )?;
+    for template in &report {
+        writeln!(
+            output,
+            "{:>10} {:>9} {:>14} {}",
+            template.bytes,
+            template.functions,
+            template.instantiations.len(),
+            template.name
+        )?;
+        for instantiation in &template.instantiations {
+            writeln!(
+                output,
+                "{:>10} {:>9} {:>14} \t{}",
+                instantiation.bytes, instantiation.functions, "", instantiation.name
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+const IMAGE_DEBUG_TYPE_REPRO: u32 = 16;
+
+#[derive(Debug, Serialize)]
+struct Event {
+    /// Seconds since the Unix epoch
+    timestamp: u64,
+    /// `timestamp` as an ISO 8601 UTC time
+    time: String,
+    source: String,
+    /// Set for the header timestamps of deterministic builds, which are hashes of the
+    /// image's contents rather than times
+    is_hash: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Timeline {
+    /// Number of times the PDB was written since its GUID was generated
+    age: u32,
+    events: Vec<Event>,
+}
+
+/// Formats seconds since the Unix epoch as an ISO 8601 UTC time
+fn format_time(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // Converts days since 1970-01-01 into a proleptic Gregorian date
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Returns the name of an `IMAGE_DEBUG_TYPE_*` value
+fn debug_type_name(kind: u32) -> String {
+    let name = match kind {
+        1 => "COFF",
+        2 => "CodeView",
+        3 => "FPO",
+        4 => "Misc",
+        9 => "Borland",
+        12 => "VC feature",
+        13 => "POGO",
+        14 => "ILTCG",
+        16 => "Repro",
+        20 => "Extended DLL characteristics",
+        _ => return format!("type {}", kind),
+    };
+
+    name.to_string()
+}
+
+fn modified_time(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Collects every timestamp reachable through the PDB, and through the image if given,
+/// into a single timeline sorted from oldest to newest. Sources are the PDB signature,
+/// the PE header, debug directory, and export directory, and the modification times of
+/// the PDB, the image, and any object files which still exist on this machine
+pub fn print_timeline(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut events = vec![];
+    let mut add = |timestamp: u64, source: String, is_hash: bool| {
+        // Zero means the field wasn't filled in
+        if timestamp != 0 {
+            events.push(Event {
+                timestamp,
+                time: format_time(timestamp),
+                source,
+                is_hash,
+            });
+        }
+    };
+
+    add(
+        pdb_info.timestamp as u64,
+        "PDB signature".to_string(),
+        false,
+    );
+    if let Some(modified) = modified_time(&pdb_info.path) {
+        add(modified, "PDB file modified".to_string(), false);
+    }
+
+    if let Some(pe) = pe {
+        let is_reproducible = pe
+            .debug_entries
+            .iter()
+            .any(|entry| entry.kind == IMAGE_DEBUG_TYPE_REPRO);
+
+        add(
+            pe.timestamp as u64,
+            "PE header TimeDateStamp".to_string(),
+            is_reproducible,
+        );
+        for entry in &pe.debug_entries {
+            add(
+                entry.timestamp as u64,
+                format!("{} debug directory entry", debug_type_name(entry.kind)),
+                is_reproducible,
+            );
+        }
+        if let Some(timestamp) = pe.export_timestamp {
+            add(
+                timestamp as u64,
+                "Export directory".to_string(),
+                is_reproducible,
+            );
+        }
+        if let Some(modified) = modified_time(&pe.path) {
+            add(modified, "PE file modified".to_string(), false);
+        }
+    }
+
+    for module in &pdb_info.debug_modules {
+        if module.object_file_name.is_empty() {
+            continue;
+        }
+        if let Some(modified) = modified_time(Path::new(&module.object_file_name)) {
+            add(
+                modified,
+                format!("Object file modified: {}", module.object_file_name),
+                false,
+            );
+        }
+    }
+
+    events.sort_by_key(|event| event.timestamp);
+    let timeline = Timeline {
+        age: pdb_info.age,
+        events,
+    };
+
+    if json {
+        serde_json::to_writer(output, &timeline)?;
+        return Ok(());
+    }
+
+    writeln!(output, "PDB age: {}", timeline.age)?;
+    writeln!(output, "Timeline:")?;
+    for event in &timeline.events {
+        writeln!(
+            output,
+            "\t{} 0x{:08X} {}{}",
+            event.time,
+            event.timestamp,
+            event.source,
+            if event.is_hash {
+                " (deterministic build hash)"
+            } else {
+                ""
+            }
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use crate::namespace::NamespaceNode;
+use ezpdb::symbol_types::*;
+use std::io::{self, Write};
+
+fn print_node(
+    output: &mut impl Write,
+    name: &str,
+    node: &NamespaceNode,
+    depth: usize,
+) -> io::Result<()> {
+    write!(
+        output,
+        "{:indent$}{} ({} bytes",
+        "",
+        name,
+        node.bytes,
+        indent = depth * 2
+    )?;
+    if node.children.is_empty() && node.functions == 1 {
+        writeln!(output, ")")?;
+    } else {
+        writeln!(output, ", {} functions)", node.functions)?;
+    }
+
+    for (child_name, child) in node.sorted_children() {
+        print_node(output, child_name, child, depth + 1)?;
+    }
+
+    Ok(())
+}
+
+/// Writes procedures as a tree of the namespaces and classes they're declared in, with
+/// the total size of each scope's functions
+pub fn print_namespace_tree(output: &mut impl Write, pdb_info: &ParsedPdb) -> io::Result<()> {
+    let root = NamespaceNode::from_procedures(pdb_info);
+
+    writeln!(
+        output,
+        "{} bytes in {} functions",
+        root.bytes, root.functions
+    )?;
+    for (name, node) in root.sorted_children() {
+        print_node(output, name, node, 0)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use ezpdb::stats::TypeStreamStats;
+use std::io::{self, Write};
+
+/// Writes the record counts and sizes of the TPI and IPI streams, broken down by leaf kind
+pub fn print_type_stats(
+    output: &mut impl Write,
+    stats: &[TypeStreamStats],
+    json: bool,
+) -> io::Result<()> {
+    if json {
+        return Ok(serde_json::to_writer(output, stats)?);
+    }
+
+    for stream in stats {
+        writeln!(output, "{}:", stream.stream.to_ascii_uppercase())?;
+        writeln!(output, "\tVersion: {}", stream.version)?;
+        match stream.highest_index {
+            Some(highest) => writeln!(
+                output,
+                "\tType indices: 0x{:X} - 0x{:X}",
+                stream.first_index, highest
+            )?,
+            None => writeln!(output, "\tType indices: none")?,
+        }
+        writeln!(output, "\tRecords: {}", stream.record_count)?;
+        writeln!(output, "\tRecord bytes: {}", stream.record_bytes)?;
+        match stream.hash_stream {
+            Some(hash_stream) => writeln!(output, "\tHash stream: {}", hash_stream)?,
+            None => writeln!(output, "\tHash stream: none")?,
+        }
+        writeln!(output, "\tHash value bytes: {}", stream.hash_value_bytes)?;
+        writeln!(
+            output,
+            "\tIndex offset bytes: {}",
+            stream.index_offset_bytes
+        )?;
+        writeln!(
+            output,
+            "\tHash adjustments: {}",
+            if stream.hash_adjustment_bytes > 0 {
+                format!("{} bytes", stream.hash_adjustment_bytes)
+            } else {
+                "none".to_string()
+            }
+        )?;
+
+        writeln!(
+            output,
+            "\t{:<22} {:>10} {:>12} {:>6}",
+            "Kind", "Records", "Bytes", "%"
+        )?;
+        for kind in &stream.kinds {
+            let name = kind
+                .name
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("0x{:04X}", kind.kind));
+            let percent = if stream.record_bytes > 0 {
+                kind.bytes as f64 * 100.0 / stream.record_bytes as f64
+            } else {
+                0.0
+            };
+            writeln!(
+                output,
+                "\t{:<22} {:>10} {:>12} {:>6.2}",
+                name, kind.count, kind.bytes, percent
+            )?;
+        }
+        writeln!(output)?;
+    }
+
+    Ok(())
+}
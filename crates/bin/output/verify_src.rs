@@ -0,0 +1,154 @@
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use sha2::Digest;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    /// The local file hashes to the checksum recorded in the PDB
+    Match,
+    /// The local file differs from the one the binary was built from
+    Mismatch,
+    /// No file under the source root corresponds to the PDB's path
+    Missing,
+    /// The PDB records no checksum for the file
+    NoChecksum,
+}
+
+#[derive(Debug, Serialize)]
+struct SourceCheck {
+    /// Path of the source file as recorded in the PDB
+    path: String,
+    local_path: Option<PathBuf>,
+    algorithm: Option<&'static str>,
+    expected: Option<String>,
+    actual: Option<String>,
+    status: Status,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Returns the file under `src_root` corresponding to `path`, a path from the build
+/// machine. The longest trailing part of `path` which exists under `src_root` wins, so
+/// `C:\build\proj\src\main.cpp` is found as `src/main.cpp` or `proj/src/main.cpp`
+fn find_local(src_root: &Path, path: &str) -> Option<PathBuf> {
+    let components: Vec<&str> = path
+        .split(|c: char| c == '\\' || c == '/')
+        .filter(|component| !component.is_empty() && !component.ends_with(':'))
+        .collect();
+
+    (0..components.len())
+        .map(|start| {
+            components[start..]
+                .iter()
+                .fold(src_root.to_path_buf(), |local, component| {
+                    local.join(component)
+                })
+        })
+        .find(|local| local.is_file())
+}
+
+/// Returns the name of the algorithm `checksum` was computed with and the digest of
+/// `data` with that algorithm
+fn digest(checksum: &Checksum, data: &[u8]) -> Option<(&'static str, Vec<u8>, Vec<u8>)> {
+    match checksum {
+        Checksum::None => None,
+        Checksum::Md5(expected) => Some(("md5", expected.clone(), md5::Md5::digest(data).to_vec())),
+        Checksum::Sha1(expected) => {
+            Some(("sha1", expected.clone(), sha1::Sha1::digest(data).to_vec()))
+        }
+        Checksum::Sha256(expected) => Some((
+            "sha256",
+            expected.clone(),
+            sha2::Sha256::digest(data).to_vec(),
+        )),
+    }
+}
+
+/// Hashes the local copy of each source file the PDB records a checksum for and
+/// compares it to that checksum, reporting which sources match the ones the binary was
+/// built from. Local copies are found under `src_root` by the trailing components of
+/// the recorded path
+pub fn print_source_verification(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    src_root: &Path,
+    json: bool,
+) -> anyhow::Result<()> {
+    // Headers are listed by every module including them
+    let mut files: BTreeMap<&str, &Checksum> = BTreeMap::new();
+    for file in pdb_info
+        .debug_modules
+        .iter()
+        .flat_map(|module| module.source_files.iter().flatten())
+    {
+        files.entry(file.name.as_str()).or_insert(&file.checksum);
+    }
+
+    let mut report = vec![];
+    for (path, checksum) in files {
+        let local_path = find_local(src_root, path);
+        let mut check = SourceCheck {
+            path: path.to_string(),
+            local_path: local_path.clone(),
+            algorithm: None,
+            expected: None,
+            actual: None,
+            status: Status::Missing,
+        };
+
+        if let Checksum::None = checksum {
+            check.status = Status::NoChecksum;
+        } else if let Some(local_path) = local_path {
+            let data = std::fs::read(&local_path)?;
+            if let Some((algorithm, expected, actual)) = digest(checksum, &data) {
+                check.algorithm = Some(algorithm);
+                check.status = if expected == actual {
+                    Status::Match
+                } else {
+                    Status::Mismatch
+                };
+                check.expected = Some(to_hex(&expected));
+                check.actual = Some(to_hex(&actual));
+            }
+        }
+
+        report.push(check);
+    }
+
+    if json {
+        serde_json::to_writer(output, &report)?;
+        return Ok(());
+    }
+
+    let count = |status: Status| report.iter().filter(|check| check.status == status).count();
+    writeln!(output, "Matching:    {}", count(Status::Match))?;
+    writeln!(output, "Mismatched:  {}", count(Status::Mismatch))?;
+    writeln!(output, "Missing:     {}", count(Status::Missing))?;
+    writeln!(output, "No checksum: {}", count(Status::NoChecksum))?;
+    for check in &report {
+        let status = match check.status {
+            Status::Match => "match",
+            Status::Mismatch => "MISMATCH",
+            Status::Missing => "missing",
+            Status::NoChecksum => "no checksum",
+        };
+        writeln!(output, "{:<11} {}", status, check.path)?;
+        if check.status == Status::Mismatch {
+            writeln!(
+                output,
+                "\t{} expected {} got {}",
+                check.algorithm.unwrap_or_default(),
+                check.expected.as_deref().unwrap_or_default(),
+                check.actual.as_deref().unwrap_or_default()
+            )?;
+        }
+    }
+
+    Ok(())
+}
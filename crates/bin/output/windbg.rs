@@ -0,0 +1,180 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use std::io::{self, Write};
+
+/// Returns the name WinDbg uses for the module, i.e. the image name without its extension
+fn module_name(pdb_info: &ParsedPdb, pe: Option<&PeInfo>) -> String {
+    let image_name = super::image_name(pdb_info, pe);
+    match image_name.rfind('.') {
+        Some(idx) => image_name[..idx].to_string(),
+        None => image_name,
+    }
+}
+
+/// Converts a symbol name into something usable as a WinDbg alias name
+fn alias_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    format!("pv_{}", sanitized)
+}
+
+/// Escapes `text` for use in a `.printf` format string
+fn printf_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+}
+
+/// Escapes `command` for use as the quoted command string of a breakpoint
+fn command_escape(command: &str) -> String {
+    command.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes a WinDbg command script (run with `$$>a< script.txt`) which defines an alias
+/// for each procedure's address and sets a logging breakpoint on it. No symbols are
+/// created, so `ln` and stack traces don't use these names; the script from
+/// [print_windbg_js] resolves addresses with `!pvln`
+pub fn print_windbg_script(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    let module = module_name(pdb_info, pe);
+
+    writeln!(
+        output,
+        "$$ Generated by pdbview from {}",
+        pdb_info.path.display()
+    )?;
+    writeln!(output, "$$ Run with: $$>a< <this file>")?;
+
+    for procedure in &pdb_info.procedures {
+        let offset = match procedure.offset {
+            Some(offset) => offset - pdb_info.base_address,
+            None => continue,
+        };
+
+        writeln!(output)?;
+        writeln!(
+            output,
+            "$$ {} (0x{:X} bytes)",
+            procedure.name, procedure.len
+        )?;
+        writeln!(
+            output,
+            "aS ${{/v:{}}} {}+0x{:X}",
+            alias_name(&procedure.name),
+            module,
+            offset
+        )?;
+        let command = format!(".printf \"{}\\n\"; gc", printf_escape(&procedure.name));
+        writeln!(
+            output,
+            "bu {}+0x{:X} \"{}\"",
+            module,
+            offset,
+            command_escape(&command)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes a WinDbg JavaScript extension which looks up and sets breakpoints on the
+/// procedures through the `!pvln`, `!pvaddr`, and `!pvbp` functions.
+pub fn print_windbg_js(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    let module = module_name(pdb_info, pe);
+
+    let symbols: Vec<_> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| {
+            procedure.offset.map(|offset| {
+                serde_json::json!({
+                    "name": procedure.name,
+                    "rva": offset - pdb_info.base_address,
+                    "size": procedure.len,
+                })
+            })
+        })
+        .collect();
+
+    writeln!(
+        output,
+        "// Generated by pdbview from {}",
+        pdb_info.path.display()
+    )?;
+    writeln!(output, "// Load with: .scriptload <this file>")?;
+    writeln!(output, "\"use strict\";")?;
+    writeln!(output)?;
+    writeln!(
+        output,
+        "const moduleName = {};",
+        serde_json::to_string(&module.to_lowercase())?
+    )?;
+    writeln!(
+        output,
+        "const symbols = {};",
+        serde_json::to_string(&symbols)?
+    )?;
+    write!(output, "{}", WINDBG_JS_HELPERS)?;
+
+    Ok(())
+}
+
+const WINDBG_JS_HELPERS: &str = r#"
+function moduleBase() {
+    const module = host.currentProcess.Modules.First(m => {
+        const name = m.Name.toLowerCase();
+        const fileName = name.substring(name.lastIndexOf("\\") + 1);
+        return fileName.substring(0, fileName.lastIndexOf(".")) == moduleName;
+    });
+    return module.BaseAddress;
+}
+
+function findSymbol(name) {
+    const symbol = symbols.find(s => s.name == name);
+    if (symbol === undefined) {
+        throw new Error("unknown symbol: " + name);
+    }
+    return symbol;
+}
+
+// Returns the address of the named procedure
+function symbolAddress(name) {
+    return moduleBase().add(findSymbol(name).rva);
+}
+
+// Resolves an address to `name+displacement`
+function lookupAddress(address) {
+    const rva = host.parseInt64(address.toString()).subtract(moduleBase()).asNumber();
+    const symbol = symbols.find(s => rva >= s.rva && rva < s.rva + s.size);
+    if (symbol === undefined) {
+        return "<unknown>";
+    }
+    return symbol.name + "+0x" + (rva - symbol.rva).toString(16);
+}
+
+// Sets a breakpoint on the named procedure
+function breakOnSymbol(name) {
+    const address = symbolAddress(name);
+    host.namespace.Debugger.Utility.Control.ExecuteCommand("bp " + address.toString(16));
+    return address;
+}
+
+function initializeScript() {
+    return [
+        new host.apiVersionSupport(1, 2),
+        new host.functionAlias(lookupAddress, "pvln"),
+        new host.functionAlias(symbolAddress, "pvaddr"),
+        new host.functionAlias(breakOnSymbol, "pvbp"),
+    ];
+}
+"#;
@@ -0,0 +1,93 @@
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::*;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// Writes an x64dbg database (the JSON contents of a `.dd32`/`.dd64` file) containing
+/// labels, source line comments, and function boundaries.
+pub fn print_x64dbg(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    pe: Option<&PeInfo>,
+) -> io::Result<()> {
+    // x64dbg matches module names case-insensitively but stores them lowercase
+    let module = super::image_name(pdb_info, pe).to_lowercase();
+    let rva = |offset: usize| format!("0x{:X}", offset - pdb_info.base_address);
+
+    // Procedures have undecorated names so they take priority over publics at the same address
+    let mut labels = BTreeMap::new();
+    for symbol in &pdb_info.public_symbols {
+        if let Some(offset) = symbol.offset {
+            labels.insert(offset, symbol.name.as_str());
+        }
+    }
+    for procedure in &pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            labels.insert(offset, procedure.name.as_str());
+        }
+    }
+
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|(offset, name)| {
+            json!({
+                "module": module,
+                "address": rva(*offset),
+                "manual": false,
+                "text": name,
+            })
+        })
+        .collect();
+
+    let mut comments = BTreeMap::new();
+    for line in pdb_info
+        .debug_modules
+        .iter()
+        .flat_map(|module| module.lines.iter())
+    {
+        if let Some(offset) = line.offset {
+            comments
+                .entry(offset)
+                .or_insert_with(|| format!("{}:{}", line.file, line.line_start));
+        }
+    }
+
+    let comments: Vec<_> = comments
+        .iter()
+        .map(|(offset, text)| {
+            json!({
+                "module": module,
+                "address": rva(*offset),
+                "manual": false,
+                "text": text,
+            })
+        })
+        .collect();
+
+    let functions: Vec<_> = pdb_info
+        .procedures
+        .iter()
+        .filter(|procedure| procedure.len > 0)
+        .filter_map(|procedure| {
+            procedure.offset.map(|offset| {
+                json!({
+                    "module": module,
+                    "start": rva(offset),
+                    // x64dbg function ranges are inclusive
+                    "end": rva(offset + procedure.len - 1),
+                    "manual": false,
+                    "icount": 0,
+                })
+            })
+        })
+        .collect();
+
+    let database = json!({
+        "labels": labels,
+        "comments": comments,
+        "functions": functions,
+    });
+
+    write!(output, "{}", serde_json::to_string(&database)?)
+}
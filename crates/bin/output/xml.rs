@@ -0,0 +1,85 @@
+use super::compat;
+use ezpdb::symbol_types::*;
+use serde_json::Value;
+use std::io::{self, Write};
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace aren't allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {
+                escaped.push_str(&format!("\\u{{{:x}}}", c as u32))
+            }
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Whether `name` can be used as an element name as-is. Map keys such as type indices
+/// can't, so they're written as `<entry key="...">` instead
+fn is_element_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+fn write_element(
+    output: &mut impl Write,
+    name: &str,
+    value: &Value,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    let (open, close) = if is_element_name(name) {
+        (name.to_string(), name)
+    } else {
+        (format!("entry key=\"{}\"", escape(name)), "entry")
+    };
+
+    match value {
+        Value::Null => writeln!(output, "{}<{}/>", indent, open),
+        Value::Bool(b) => writeln!(output, "{}<{}>{}</{}>", indent, open, b, close),
+        Value::Number(n) => writeln!(output, "{}<{}>{}</{}>", indent, open, n, close),
+        Value::String(s) => writeln!(output, "{}<{}>{}</{}>", indent, open, escape(s), close),
+        Value::Array(items) if items.is_empty() => writeln!(output, "{}<{}/>", indent, open),
+        Value::Object(fields) if fields.is_empty() => writeln!(output, "{}<{}/>", indent, open),
+        Value::Array(items) => {
+            writeln!(output, "{}<{}>", indent, open)?;
+            for item in items {
+                write_element(output, "item", item, depth + 1)?;
+            }
+            writeln!(output, "{}</{}>", indent, close)
+        }
+        Value::Object(fields) => {
+            writeln!(output, "{}<{}>", indent, open)?;
+            for (key, field) in fields {
+                write_element(output, key, field, depth + 1)?;
+            }
+            writeln!(output, "{}</{}>", indent, close)
+        }
+    }
+}
+
+/// Writes the same document as the JSON output as XML. Objects become elements named
+/// after their fields and array entries become `<item>` elements
+pub fn print_xml(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    format_version: u32,
+) -> io::Result<()> {
+    let mut value = serde_json::to_value(pdb_info)?;
+    if format_version != FORMAT_VERSION {
+        compat::downgrade(&mut value, format_version);
+    }
+
+    writeln!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    write_element(output, "pdb", &value, 0)
+}
@@ -0,0 +1,565 @@
+use goblin::pe::export::{Export, ExportAddressTableEntry, Reexport};
+use goblin::pe::PE;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryInto;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const IMAGE_REL_BASED_HIGHLOW: u16 = 3;
+const IMAGE_REL_BASED_DIR64: u16 = 10;
+
+const IMAGE_DEBUG_DIRECTORY_SIZE: usize = 28;
+const IMAGE_DEBUG_TYPE_CODEVIEW: u32 = 2;
+/// Signature of a CodeView PDB 7.0 debug record
+const RSDS_SIGNATURE: &[u8] = b"RSDS";
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+const IMAGE_GUARD_CF_INSTRUMENTED: u32 = 0x100;
+const IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT: u32 = 0x400;
+const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK: u32 = 0xF000_0000;
+const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT: u32 = 28;
+
+/// Information extracted from the PE image the PDB was generated for
+#[derive(Debug)]
+pub struct PeInfo {
+    pub path: PathBuf,
+    pub is_64: bool,
+    /// `IMAGE_FILE_MACHINE_*` value of the COFF header
+    pub machine: u16,
+    pub image_base: u64,
+    pub timestamp: u32,
+    pub entry_point: usize,
+    pub size_of_image: u32,
+    /// `IMAGE_DLLCHARACTERISTICS_*` flags of the optional header
+    pub dll_characteristics: u16,
+    pub sections: Vec<PeSection>,
+    pub exports: Vec<PeExport>,
+    pub imports: Vec<PeImport>,
+    /// `TimeDateStamp` of the export directory, if the image has one
+    pub export_timestamp: Option<u32>,
+    pub debug_entries: Vec<PeDebugEntry>,
+    /// The PDB the image was linked with, from its CodeView debug record
+    pub codeview: Option<PeCodeView>,
+    /// Base relocations as a map of RVA to the number of bytes patched
+    pub relocations: BTreeMap<usize, usize>,
+    /// RVAs of the valid indirect call targets from the load config's Control Flow
+    /// Guard function table, or `None` if the image wasn't built with `/guard:cf`
+    pub cfg_targets: Option<BTreeSet<usize>>,
+    /// Number of handlers in a 32-bit image's SafeSEH table, or `None` if the load
+    /// config has no table
+    pub safe_seh_handlers: Option<usize>,
+    bytes: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct PeSection {
+    pub name: String,
+    pub virtual_address: usize,
+    pub virtual_size: usize,
+    pub raw_offset: usize,
+    pub raw_size: usize,
+    pub characteristics: u32,
+}
+
+#[derive(Debug)]
+pub struct PeExport {
+    pub name: Option<String>,
+    pub ordinal: u16,
+    pub rva: usize,
+    /// `module.function` this export is forwarded to
+    pub forwarder: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct PeDebugEntry {
+    /// `IMAGE_DEBUG_TYPE_*` value, e.g. 2 for CodeView
+    pub kind: u32,
+    pub timestamp: u32,
+    /// RVA of the entry's data, or 0 if it isn't mapped
+    pub data_rva: usize,
+    pub data_size: usize,
+}
+
+/// An entry of the Rich header: a tool the linker was given objects from and how many
+#[derive(Debug)]
+pub struct RichEntry {
+    /// Identifies the tool and toolset, e.g. the C++ compiler of Visual Studio 2019
+    pub product: u16,
+    /// Build number of the tool
+    pub build: u16,
+    pub count: u32,
+}
+
+#[derive(Debug)]
+pub struct PeCodeView {
+    /// Path of the PDB as passed to the linker
+    pub pdb_path: String,
+    pub guid: [u8; 16],
+    pub age: u32,
+}
+
+impl PeCodeView {
+    /// Returns the file name of the PDB, e.g. `foo.pdb`
+    pub fn pdb_name(&self) -> &str {
+        self.pdb_path
+            .rsplit(|c| c == '\\' || c == '/')
+            .next()
+            .unwrap_or(&self.pdb_path)
+    }
+
+    /// Returns the identifier symbol servers index the PDB under: the GUID as uppercase
+    /// hex without dashes followed by the age in hex
+    pub fn debug_identifier(&self) -> String {
+        let guid = &self.guid;
+        let data1 = u32::from_le_bytes(guid[0..4].try_into().unwrap());
+        let data2 = u16::from_le_bytes(guid[4..6].try_into().unwrap());
+        let data3 = u16::from_le_bytes(guid[6..8].try_into().unwrap());
+        let data4: String = guid[8..]
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect();
+
+        format!(
+            "{:08X}{:04X}{:04X}{}{:X}",
+            data1, data2, data3, data4, self.age
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct PeImport {
+    /// DLL the function is imported from
+    pub dll: String,
+    /// Name of the function, or `None` if it's imported by ordinal
+    pub name: Option<String>,
+    pub ordinal: u16,
+    /// RVA of the import address table slot the loader writes the address into
+    pub rva: usize,
+}
+
+/// Returns whether the file at `path` starts with the `MZ` signature of a PE image
+pub fn is_pe(path: &Path) -> std::io::Result<bool> {
+    let mut magic = [0u8; 2];
+    match std::fs::File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == b"MZ"),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+impl PeSection {
+    /// Returns whether the section holds code or is mapped executable
+    pub fn is_executable(&self) -> bool {
+        self.characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0
+    }
+}
+
+impl PeInfo {
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<PeInfo> {
+        let bytes = std::fs::read(path.as_ref())?;
+        let pe = PE::parse(&bytes)?;
+
+        let sections = pe
+            .sections
+            .iter()
+            .map(|section| PeSection {
+                name: String::from_utf8_lossy(&section.name)
+                    .trim_end_matches('\0')
+                    .to_string(),
+                virtual_address: section.virtual_address as usize,
+                virtual_size: section.virtual_size as usize,
+                raw_offset: section.pointer_to_raw_data as usize,
+                raw_size: section.size_of_raw_data as usize,
+                characteristics: section.characteristics,
+            })
+            .collect();
+
+        // The export address table holds every export, indexed by ordinal minus the
+        // ordinal base. goblin only lists named exports, in name pointer table order,
+        // and the ordinal table gives the address table index of each name
+        let (ordinal_base, addresses, ordinals) = match pe.export_data.as_ref() {
+            Some(data) => (
+                data.export_directory_table.ordinal_base,
+                &data.export_address_table[..],
+                &data.export_ordinal_table[..],
+            ),
+            None => (1, &[][..], &[][..]),
+        };
+        let mut names: BTreeMap<usize, Vec<&Export>> = BTreeMap::new();
+        for (export, index) in pe.exports.iter().zip(ordinals) {
+            names.entry(*index as usize).or_default().push(export);
+        }
+        // Forwarders of exports without a name, which goblin doesn't resolve. Read once
+        // the sections are available
+        let mut unnamed_forwarders = vec![];
+        let mut exports = vec![];
+        for (index, entry) in addresses.iter().enumerate() {
+            let (rva, is_forwarder) = match entry {
+                ExportAddressTableEntry::ExportRVA(rva) => (*rva as usize, false),
+                ExportAddressTableEntry::ForwarderRVA(rva) => (*rva as usize, true),
+            };
+            // Ordinals between exports are unused and left zero
+            if rva == 0 {
+                continue;
+            }
+
+            let ordinal = (index as u32 + ordinal_base) as u16;
+            match names.get(&index) {
+                Some(named) => exports.extend(named.iter().map(|export| PeExport {
+                    name: export.name.map(str::to_string),
+                    ordinal,
+                    rva: export.rva,
+                    forwarder: export.reexport.as_ref().map(|reexport| match reexport {
+                        Reexport::DLLName { export, lib } => format!("{}.{}", lib, export),
+                        Reexport::DLLOrdinal { ordinal, lib } => format!("{}.#{}", lib, ordinal),
+                    }),
+                })),
+                None => {
+                    if is_forwarder {
+                        unnamed_forwarders.push((exports.len(), rva));
+                    }
+                    exports.push(PeExport {
+                        name: None,
+                        ordinal,
+                        rva,
+                        forwarder: None,
+                    });
+                }
+            }
+        }
+
+        let imports = pe
+            .imports
+            .iter()
+            .map(|import| PeImport {
+                dll: import.dll.to_string(),
+                // goblin names imports by ordinal `ORDINAL <n>`
+                name: if import.name.starts_with("ORDINAL ") {
+                    None
+                } else {
+                    Some(import.name.to_string())
+                },
+                ordinal: import.ordinal,
+                // `offset` is the RVA of the IAT slot, while `rva` is that of the hint/name entry
+                rva: import.offset,
+            })
+            .collect();
+
+        let optional_header = pe.header.optional_header;
+        let relocation_directory =
+            optional_header.and_then(|header| *header.data_directories.get_base_relocation_table());
+        let debug_directory =
+            optional_header.and_then(|header| *header.data_directories.get_debug_table());
+        let load_config_directory =
+            optional_header.and_then(|header| *header.data_directories.get_load_config_table());
+
+        let mut info = PeInfo {
+            path: path.as_ref().to_owned(),
+            is_64: pe.is_64,
+            machine: pe.header.coff_header.machine,
+            image_base: pe.image_base as u64,
+            timestamp: pe.header.coff_header.time_date_stamp,
+            entry_point: optional_header
+                .map(|header| header.standard_fields.address_of_entry_point as usize)
+                .unwrap_or(0),
+            size_of_image: optional_header
+                .map(|header| header.windows_fields.size_of_image)
+                .unwrap_or(0),
+            dll_characteristics: optional_header
+                .map(|header| header.windows_fields.dll_characteristics)
+                .unwrap_or(0),
+            sections,
+            exports,
+            imports,
+            export_timestamp: pe
+                .export_data
+                .as_ref()
+                .map(|data| data.export_directory_table.time_date_stamp),
+            debug_entries: vec![],
+            codeview: None,
+            relocations: BTreeMap::new(),
+            cfg_targets: None,
+            safe_seh_handlers: None,
+            bytes,
+        };
+
+        for (index, rva) in unnamed_forwarders {
+            info.exports[index].forwarder = info.read_c_string(rva);
+        }
+
+        if let Some(directory) = relocation_directory {
+            info.relocations =
+                info.parse_relocations(directory.virtual_address as usize, directory.size as usize);
+        }
+
+        if let Some(directory) = debug_directory {
+            info.debug_entries = info
+                .parse_debug_entries(directory.virtual_address as usize, directory.size as usize);
+            info.codeview = info.parse_codeview();
+        }
+
+        if let Some(directory) = load_config_directory {
+            info.cfg_targets =
+                info.parse_cfg_targets(directory.virtual_address as usize, directory.size as usize);
+            info.safe_seh_handlers = info.parse_safe_seh_handlers(
+                directory.virtual_address as usize,
+                directory.size as usize,
+            );
+        }
+
+        Ok(info)
+    }
+
+    fn parse_relocations(&self, rva: usize, size: usize) -> BTreeMap<usize, usize> {
+        let mut relocations = BTreeMap::new();
+        let data = match self.read_rva(rva, size) {
+            Some(data) => data,
+            None => return relocations,
+        };
+
+        let mut block = data;
+        while block.len() >= 8 {
+            let page = u32::from_le_bytes(block[0..4].try_into().unwrap()) as usize;
+            let block_size = u32::from_le_bytes(block[4..8].try_into().unwrap()) as usize;
+            if block_size < 8 || block_size > block.len() {
+                break;
+            }
+
+            for entry in block[8..block_size].chunks_exact(2) {
+                let entry = u16::from_le_bytes([entry[0], entry[1]]);
+                let offset = (entry & 0xfff) as usize;
+                match entry >> 12 {
+                    IMAGE_REL_BASED_HIGHLOW => {
+                        relocations.insert(page + offset, 4);
+                    }
+                    IMAGE_REL_BASED_DIR64 => {
+                        relocations.insert(page + offset, 8);
+                    }
+                    _ => {}
+                }
+            }
+
+            block = &block[block_size..];
+        }
+
+        relocations
+    }
+
+    fn parse_debug_entries(&self, rva: usize, size: usize) -> Vec<PeDebugEntry> {
+        let data = match self.read_rva(rva, size) {
+            Some(data) => data,
+            None => return vec![],
+        };
+
+        data.chunks_exact(IMAGE_DEBUG_DIRECTORY_SIZE)
+            .map(|entry| PeDebugEntry {
+                kind: u32::from_le_bytes(entry[12..16].try_into().unwrap()),
+                timestamp: u32::from_le_bytes(entry[4..8].try_into().unwrap()),
+                data_size: u32::from_le_bytes(entry[16..20].try_into().unwrap()) as usize,
+                data_rva: u32::from_le_bytes(entry[20..24].try_into().unwrap()) as usize,
+            })
+            .collect()
+    }
+
+    /// Reads the first CodeView debug entry in the `RSDS` format
+    fn parse_codeview(&self) -> Option<PeCodeView> {
+        self.debug_entries
+            .iter()
+            .filter(|entry| entry.kind == IMAGE_DEBUG_TYPE_CODEVIEW && entry.data_rva != 0)
+            .find_map(|entry| {
+                let data = self.read_rva(entry.data_rva, entry.data_size)?;
+                if data.len() < 24 || !data.starts_with(RSDS_SIGNATURE) {
+                    return None;
+                }
+                let path = &data[24..];
+                let end = path
+                    .iter()
+                    .position(|byte| *byte == 0)
+                    .unwrap_or(path.len());
+
+                Some(PeCodeView {
+                    pdb_path: String::from_utf8_lossy(&path[..end]).into_owned(),
+                    guid: data[4..20].try_into().unwrap(),
+                    age: u32::from_le_bytes(data[20..24].try_into().unwrap()),
+                })
+            })
+    }
+
+    /// Reads the SEHandlerCount of a 32-bit load config directory at `rva`. 64-bit images
+    /// use table-based exception handling and have no SafeSEH table
+    fn parse_safe_seh_handlers(&self, rva: usize, size: usize) -> Option<usize> {
+        // Offsets of SEHandlerTable and SEHandlerCount
+        const TABLE_OFFSET: usize = 0x40;
+        const COUNT_OFFSET: usize = 0x44;
+
+        if self.is_64 {
+            return None;
+        }
+
+        let header = self.read_rva(rva, 4)?;
+        let struct_size = u32::from_le_bytes(header.try_into().unwrap()) as usize;
+        let size = if struct_size == 0 { size } else { struct_size };
+        if size < COUNT_OFFSET + 4 {
+            return None;
+        }
+
+        let load_config = self.read_rva(rva, COUNT_OFFSET + 4)?;
+        let table = u32::from_le_bytes(
+            load_config[TABLE_OFFSET..TABLE_OFFSET + 4]
+                .try_into()
+                .unwrap(),
+        );
+        let count = u32::from_le_bytes(
+            load_config[COUNT_OFFSET..COUNT_OFFSET + 4]
+                .try_into()
+                .unwrap(),
+        );
+        if table == 0 {
+            return None;
+        }
+
+        Some(count as usize)
+    }
+
+    /// Reads the Control Flow Guard function table referenced by the load config
+    /// directory at `rva`. Each entry is an RVA followed by a number of metadata bytes
+    /// given by the stride bits of `GuardFlags`.
+    fn parse_cfg_targets(&self, rva: usize, size: usize) -> Option<BTreeSet<usize>> {
+        // Offsets of GuardCFFunctionTable, GuardCFFunctionCount, and GuardFlags
+        let (table_offset, count_offset, flags_offset) = if self.is_64 {
+            (0x80, 0x88, 0x90)
+        } else {
+            (0x50, 0x54, 0x58)
+        };
+
+        // The directory's own Size field takes precedence over the data directory's
+        let header = self.read_rva(rva, 4)?;
+        let struct_size = u32::from_le_bytes(header.try_into().unwrap()) as usize;
+        let size = if struct_size == 0 { size } else { struct_size };
+        if size < flags_offset + 4 {
+            return None;
+        }
+        let load_config = self.read_rva(rva, flags_offset + 4)?;
+
+        let read_pointer = |offset: usize| -> u64 {
+            if self.is_64 {
+                u64::from_le_bytes(load_config[offset..offset + 8].try_into().unwrap())
+            } else {
+                u32::from_le_bytes(load_config[offset..offset + 4].try_into().unwrap()) as u64
+            }
+        };
+        let table = read_pointer(table_offset);
+        let count = read_pointer(count_offset) as usize;
+        let flags = u32::from_le_bytes(
+            load_config[flags_offset..flags_offset + 4]
+                .try_into()
+                .unwrap(),
+        );
+
+        if flags & IMAGE_GUARD_CF_INSTRUMENTED == 0 {
+            return None;
+        }
+        if flags & IMAGE_GUARD_CF_FUNCTION_TABLE_PRESENT == 0 || table == 0 || count == 0 {
+            return Some(BTreeSet::new());
+        }
+
+        let stride = 4
+            + ((flags & IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK)
+                >> IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT) as usize;
+        let table_rva = table.checked_sub(self.image_base)? as usize;
+        let entries = self.read_rva(table_rva, count.checked_mul(stride)?)?;
+
+        Some(
+            entries
+                .chunks_exact(stride)
+                .map(|entry| u32::from_le_bytes(entry[0..4].try_into().unwrap()) as usize)
+                .collect(),
+        )
+    }
+
+    /// Returns the file name of the image, e.g. `ntdll.dll`
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Decodes the Rich header the MSVC linker places between the DOS stub and the PE
+    /// header. Returns no entries if the image doesn't have one
+    pub fn rich_header(&self) -> Vec<RichEntry> {
+        // "DanS" and "Rich"
+        const START_MARKER: u32 = 0x536E_6144;
+        const END_MARKER: &[u8] = b"Rich";
+
+        let dword = |offset: usize| -> Option<u32> {
+            let bytes = self.bytes.get(offset..offset + 4)?;
+            Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        let pe_offset = dword(0x3C).map_or(0, |offset| offset as usize);
+        let stub = &self.bytes[..pe_offset.min(self.bytes.len())];
+
+        let end = match (0..stub.len().saturating_sub(8))
+            .step_by(4)
+            .find(|offset| stub[*offset..].starts_with(END_MARKER))
+        {
+            Some(end) => end,
+            None => return vec![],
+        };
+        let key = dword(end + 4).unwrap_or_default();
+        let start = match (0..end)
+            .step_by(4)
+            .find(|offset| dword(*offset).map(|value| value ^ key) == Some(START_MARKER))
+        {
+            Some(start) => start,
+            None => return vec![],
+        };
+
+        // The start marker is followed by three zeroed dwords, then pairs of the
+        // product and build followed by the count
+        (start + 16..end)
+            .step_by(8)
+            .filter_map(|offset| {
+                let id = dword(offset)? ^ key;
+                let count = dword(offset + 4)? ^ key;
+                Some(RichEntry {
+                    product: (id >> 16) as u16,
+                    build: id as u16,
+                    count,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the NUL-terminated string at `rva`, which must end within its section
+    fn read_c_string(&self, rva: usize) -> Option<String> {
+        let section = self.sections.iter().find(|section| {
+            rva >= section.virtual_address && rva - section.virtual_address < section.raw_size
+        })?;
+
+        let available = section.raw_size - (rva - section.virtual_address);
+        let bytes = self.read_rva(rva, available)?;
+        let len = bytes.iter().position(|byte| *byte == 0)?;
+
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    /// Reads `len` bytes of the mapped image starting at `rva`. Returns `None` if the
+    /// range is not backed by file data.
+    pub fn read_rva(&self, rva: usize, len: usize) -> Option<&[u8]> {
+        let section = self.sections.iter().find(|section| {
+            rva >= section.virtual_address
+                && rva - section.virtual_address < section.virtual_size.max(section.raw_size)
+        })?;
+
+        let section_offset = rva - section.virtual_address;
+        if section_offset + len > section.raw_size {
+            return None;
+        }
+
+        let start = section.raw_offset + section_offset;
+        self.bytes.get(start..start + len)
+    }
+}
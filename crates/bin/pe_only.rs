@@ -0,0 +1,172 @@
+//! Building a [ParsedPdb] from a PE image alone, for when its PDB isn't available. Only
+//! what the image itself records is filled in: exports, sections, the PDB's identity
+//! from the debug directory, and a toolchain guess from the Rich header
+
+use crate::pe::{PeInfo, RichEntry};
+use ezpdb::symbol_types::*;
+use std::path::Path;
+
+/// Rich header product IDs of each Visual Studio toolset, with the compiler's major and
+/// minor version. Visual Studio 2015 and later share product IDs, so their minor
+/// version is found from the build number instead
+const TOOLSETS: &[(u16, u16, u16, u16)] = &[
+    (0x0083, 0x0097, 15, 0),
+    (0x0098, 0x00AA, 16, 0),
+    (0x00AB, 0x00BC, 17, 0),
+    (0x00BD, 0x00CE, 18, 0),
+    (0x00CF, 0x01FF, 19, 0),
+];
+
+/// First build number of each MSVC 19.x compiler release
+const COMPILER_19_BUILDS: &[(u16, u16)] = &[
+    (23026, 0),
+    (25017, 10),
+    (25506, 11),
+    (25830, 12),
+    (26128, 13),
+    (26428, 14),
+    (26726, 15),
+    (27023, 16),
+    (27508, 20),
+    (27702, 21),
+    (27905, 22),
+    (28105, 23),
+    (28314, 24),
+    (28610, 25),
+    (28805, 26),
+    (29110, 27),
+    (29333, 28),
+    (30133, 29),
+    (30705, 30),
+    (31104, 31),
+    (31329, 32),
+    (31629, 33),
+    (31933, 34),
+    (32215, 35),
+    (32532, 36),
+    (32822, 37),
+    (33130, 38),
+    (33519, 39),
+    (33808, 40),
+];
+
+/// Returns the compiler version of the newest toolset named by the Rich header
+fn guess_compiler(entries: &[RichEntry]) -> Option<CompilerVersion> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let (_, _, major, minor) = TOOLSETS
+                .iter()
+                .find(|(first, last, _, _)| (*first..=*last).contains(&entry.product))?;
+            let minor = if *major == 19 {
+                COMPILER_19_BUILDS
+                    .iter()
+                    .take_while(|(build, _)| *build <= entry.build)
+                    .last()
+                    .map_or(0, |(_, minor)| *minor)
+            } else {
+                *minor
+            };
+
+            Some(CompilerVersion {
+                major: *major,
+                minor,
+                build: entry.build,
+                qfe: None,
+            })
+        })
+        .max_by_key(|version| (version.major, version.minor, version.build))
+}
+
+fn machine_type(machine: u16) -> MachineType {
+    (&pdb::MachineType::from(machine)).into()
+}
+
+/// Returns what can be recovered from the image at `path` in the same form as a parsed
+/// PDB, with a warning noting that the PDB was unavailable
+pub fn parse_pe(path: &Path) -> anyhow::Result<ParsedPdb> {
+    let pe = PeInfo::open(path)?;
+    let mut pdb_info = ParsedPdb::new(path.to_path_buf());
+    pdb_info.machine_type = Some(machine_type(pe.machine));
+
+    if let Some(codeview) = pe.codeview.as_ref() {
+        let guid = &codeview.guid;
+        pdb_info.guid = uuid::Uuid::from_fields(
+            u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+            u16::from_le_bytes([guid[4], guid[5]]),
+            u16::from_le_bytes([guid[6], guid[7]]),
+            &guid[8..],
+        )?;
+        pdb_info.age = codeview.age;
+    }
+
+    pdb_info.sections = pe
+        .sections
+        .iter()
+        .enumerate()
+        .map(|(i, section)| Section {
+            name: section.name.clone(),
+            index: i + 1,
+            offset: section.virtual_address,
+            size: section.virtual_size,
+            characteristics: section.characteristics,
+        })
+        .collect();
+
+    for export in &pe.exports {
+        let is_code = pe.sections.iter().any(|section| {
+            section.is_executable()
+                && export.rva >= section.virtual_address
+                && export.rva - section.virtual_address < section.virtual_size.max(section.raw_size)
+        });
+        let name = export
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("#{}", export.ordinal));
+
+        pdb_info.exports.push(Export {
+            name: name.clone(),
+            ordinal: export.ordinal,
+            is_constant: false,
+            is_data: !is_code,
+            is_private: false,
+            is_no_name: export.name.is_none(),
+            is_explicit_ordinal: false,
+            is_forwarder: export.forwarder.is_some(),
+        });
+        // Forwarded exports have no code in this image
+        if export.forwarder.is_none() {
+            pdb_info.public_symbols.push(PublicSymbol {
+                name,
+                is_code,
+                is_function: is_code,
+                is_managed: false,
+                is_msil: false,
+                offset: Some(export.rva),
+                location: Location::default(),
+            });
+        }
+    }
+
+    if let Some(version) = guess_compiler(&pe.rich_header()) {
+        pdb_info.assembly_info.compiler_info = Some(CompilerInfo {
+            language: "Unknown".to_string(),
+            flags: CompileFlags::default(),
+            cpu_type: String::new(),
+            version_string: format!(
+                "MSVC {}.{}.{} (guessed from the Rich header)",
+                version.major, version.minor, version.build
+            ),
+            frontend_version: version.clone(),
+            backend_version: version,
+        });
+    }
+
+    pdb_info.warnings.push(format!(
+        "no PDB was available; only exports, sections, and the PDB identity from the debug \
+         directory were recovered from {}",
+        path.display()
+    ));
+
+    Ok(pdb_info)
+}
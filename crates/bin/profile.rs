@@ -0,0 +1,179 @@
+use ezpdb::progress::{Phase, ProgressEvent};
+#[cfg(feature = "alloc-stats")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+/// Number of slowest modules included in the report
+const SLOWEST_MODULES: usize = 10;
+
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+static LIVE: AtomicU64 = AtomicU64::new(0);
+
+/// Wraps the system allocator to count the total number of bytes allocated and the
+/// number currently in use. Installed as the global allocator by the `alloc-stats`
+/// feature; without it the counts stay zero
+#[cfg(feature = "alloc-stats")]
+pub struct CountingAllocator;
+
+#[cfg(feature = "alloc-stats")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        LIVE.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            let grown = (new_size - layout.size()) as u64;
+            ALLOCATED.fetch_add(grown, Ordering::Relaxed);
+            LIVE.fetch_add(grown, Ordering::Relaxed);
+        } else {
+            LIVE.fetch_sub((layout.size() - new_size) as u64, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+fn allocated() -> u64 {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+/// Number of heap bytes currently allocated
+pub fn live_bytes() -> u64 {
+    LIVE.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, serde::Serialize)]
+struct PhaseProfile {
+    phase: String,
+    elapsed_us: u64,
+    allocated_bytes: u64,
+    bytes_read: u64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ModuleProfile {
+    name: String,
+    line_program_us: u64,
+    symbols_us: u64,
+    allocated_bytes: u64,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Profile {
+    phases: Vec<PhaseProfile>,
+    slowest_modules: Vec<ModuleProfile>,
+}
+
+fn phase_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Headers => "headers (DBI/PDB info)",
+        Phase::Ids => "IPI walk",
+        Phase::Types => "TPI walk",
+        Phase::GlobalSymbols => "global symbols",
+        Phase::Modules => "module symbols and lines",
+        Phase::SectionContributions => "section contributions",
+    }
+}
+
+/// Parses the PDB at `path` and reports time and allocations spent in each phase,
+/// including serializing the result to JSON
+pub fn print_profile(output: &mut impl Write, path: &Path, json: bool) -> anyhow::Result<()> {
+    let mut phases = vec![];
+    let mut modules = vec![];
+    let mut phase_allocated = 0;
+    let mut module_allocated = 0;
+    if !cfg!(feature = "alloc-stats") {
+        log::warn!("allocations are only counted when built with the alloc-stats feature");
+    }
+
+    let parsed_pdb = ezpdb::parse_pdb_with_progress(path, &mut |event| match event {
+        ProgressEvent::PhaseStarted { .. } => phase_allocated = allocated(),
+        ProgressEvent::PhaseFinished {
+            phase,
+            elapsed_us,
+            bytes_read,
+        } => phases.push(PhaseProfile {
+            phase: phase_name(*phase).to_string(),
+            elapsed_us: *elapsed_us,
+            allocated_bytes: allocated() - phase_allocated,
+            bytes_read: *bytes_read,
+        }),
+        ProgressEvent::ModuleStarted { .. } => module_allocated = allocated(),
+        ProgressEvent::ModuleFinished { name, timings, .. } => modules.push(ModuleProfile {
+            name: name.clone(),
+            line_program_us: timings.line_program_us,
+            symbols_us: timings.symbols_us,
+            allocated_bytes: allocated() - module_allocated,
+        }),
+    })?;
+
+    let bytes_read = phases.last().map(|phase| phase.bytes_read).unwrap_or(0);
+    let serialize_allocated = allocated();
+    let serialize_start = Instant::now();
+    serde_json::to_writer(io::sink(), &parsed_pdb)?;
+    phases.push(PhaseProfile {
+        phase: "JSON serialization".to_string(),
+        elapsed_us: serialize_start.elapsed().as_micros() as u64,
+        allocated_bytes: allocated() - serialize_allocated,
+        bytes_read,
+    });
+
+    modules.sort_by_key(|module| std::cmp::Reverse(module.line_program_us + module.symbols_us));
+    modules.truncate(SLOWEST_MODULES);
+
+    let profile = Profile {
+        phases,
+        slowest_modules: modules,
+    };
+
+    if json {
+        serde_json::to_writer(output, &profile)?;
+        return Ok(());
+    }
+
+    writeln!(output, "Phases:")?;
+    writeln!(
+        output,
+        "\t{:<28} {:>12} {:>16} {:>16}",
+        "Phase", "Time (ms)", "Allocated (KiB)", "Read (KiB)"
+    )?;
+    for phase in &profile.phases {
+        writeln!(
+            output,
+            "\t{:<28} {:>12.3} {:>16} {:>16}",
+            phase.phase,
+            phase.elapsed_us as f64 / 1000.0,
+            phase.allocated_bytes / 1024,
+            phase.bytes_read / 1024
+        )?;
+    }
+
+    writeln!(output, "Slowest modules:")?;
+    writeln!(
+        output,
+        "\t{:>12} {:>12} {:>16} Name",
+        "Lines (ms)", "Symbols (ms)", "Allocated (KiB)"
+    )?;
+    for module in &profile.slowest_modules {
+        writeln!(
+            output,
+            "\t{:>12.3} {:>12.3} {:>16} {}",
+            module.line_program_us as f64 / 1000.0,
+            module.symbols_us as f64 / 1000.0,
+            module.allocated_bytes / 1024,
+            module.name
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::CliArgumentError;
+use ezpdb::progress::{Phase, ProgressEvent};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::str::FromStr;
+
+/// How progress is reported on stderr while PDBs are parsed
+#[derive(Debug, Clone, Copy)]
+pub enum ProgressKind {
+    /// An interactive progress bar
+    Bar,
+    /// One JSON object per event
+    Json,
+}
+
+impl FromStr for ProgressKind {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_ref() {
+            "bar" => Ok(ProgressKind::Bar),
+            "json" => Ok(ProgressKind::Json),
+            _ => Err(CliArgumentError::InvalidValue("progress", s.to_string())),
+        }
+    }
+}
+
+fn phase_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Headers => "reading headers",
+        Phase::Ids => "reading IDs",
+        Phase::Types => "reading types",
+        Phase::GlobalSymbols => "reading global symbols",
+        Phase::Modules => "reading modules",
+        Phase::SectionContributions => "reading section contributions",
+    }
+}
+
+/// Returns a callback for [ezpdb::parse_pdb_with_progress] which reports progress in
+/// the requested style
+pub fn reporter(kind: ProgressKind) -> Box<dyn FnMut(&ProgressEvent)> {
+    match kind {
+        ProgressKind::Json => Box::new(|event| {
+            if let Ok(event) = serde_json::to_string(event) {
+                eprintln!("{}", event);
+            }
+        }),
+        ProgressKind::Bar => {
+            let bar = ProgressBar::new_spinner();
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner} [{elapsed_precise}] {msg} [{bar:40}] {pos}/{len}"),
+            );
+
+            Box::new(move |event| match event {
+                ProgressEvent::PhaseStarted { phase } => {
+                    bar.set_message(phase_name(*phase));
+                    bar.tick();
+                }
+                ProgressEvent::ModuleStarted { index, total, .. } => {
+                    bar.set_length(*total as u64);
+                    bar.set_position(*index as u64 + 1);
+                }
+                ProgressEvent::PhaseFinished {
+                    phase: Phase::SectionContributions,
+                    ..
+                } => bar.finish_and_clear(),
+                ProgressEvent::PhaseFinished { .. } | ProgressEvent::ModuleFinished { .. } => {}
+            })
+        }
+    }
+}
@@ -0,0 +1,662 @@
+//! A small filter language for selecting a slice of the parsed PDB, e.g.
+//! `procedures[len > 0x1000 && name =~ /Crypt/i]`
+
+use ezpdb::options::SymbolKind;
+use ezpdb::symbol_types::*;
+use regex::Regex;
+use std::collections::HashSet;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum QueryError {
+    #[error("unexpected `{0}` at position {1}")]
+    UnexpectedCharacter(char, usize),
+    #[error("unexpected end of query")]
+    UnexpectedEnd,
+    #[error("expected {0} at position {1}")]
+    Expected(&'static str, usize),
+    #[error("unknown collection `{0}`. Valid collections are procedures, publics, data, modules, and exports")]
+    UnknownCollection(String),
+    #[error("`{0}` is not a field of {1}")]
+    UnknownField(String, &'static str),
+    #[error("`{0}` is a {1} and can't be compared with {2}")]
+    TypeMismatch(String, &'static str, &'static str),
+    #[error("`{0}` can't be used with {1}")]
+    InvalidOperator(&'static str, &'static str),
+    #[error("invalid regular expression: {0}")]
+    InvalidRegex(#[from] regex::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Collection {
+    Procedures,
+    PublicSymbols,
+    GlobalData,
+    DebugModules,
+    Exports,
+}
+
+impl Collection {
+    fn name(self) -> &'static str {
+        match self {
+            Collection::Procedures => "procedures",
+            Collection::PublicSymbols => "publics",
+            Collection::GlobalData => "data",
+            Collection::DebugModules => "modules",
+            Collection::Exports => "exports",
+        }
+    }
+
+    fn fields(self) -> &'static [(&'static str, FieldType)] {
+        use FieldType::*;
+
+        match self {
+            Collection::Procedures => &[
+                ("name", Str),
+                ("signature", Str),
+                ("offset", Number),
+                ("location", Str),
+                ("len", Number),
+                ("type_index", Number),
+                ("is_global", Bool),
+                ("is_dpc", Bool),
+                ("prologue_end", Number),
+                ("epilogue_start", Number),
+                ("locals", Number),
+            ],
+            Collection::PublicSymbols => &[
+                ("name", Str),
+                ("offset", Number),
+                ("location", Str),
+                ("is_code", Bool),
+                ("is_function", Bool),
+                ("is_managed", Bool),
+                ("is_msil", Bool),
+            ],
+            Collection::GlobalData => &[
+                ("name", Str),
+                ("offset", Number),
+                ("location", Str),
+                ("is_global", Bool),
+                ("is_managed", Bool),
+            ],
+            Collection::DebugModules => &[
+                ("name", Str),
+                ("object_file_name", Str),
+                ("source_files", Number),
+                ("lines", Number),
+            ],
+            Collection::Exports => &[
+                ("name", Str),
+                ("ordinal", Number),
+                ("is_constant", Bool),
+                ("is_data", Bool),
+                ("is_private", Bool),
+                ("is_no_name", Bool),
+                ("is_forwarder", Bool),
+            ],
+        }
+    }
+
+    /// Kinds of symbols the collection is made of, which are the only ones that need to
+    /// be parsed
+    fn symbol_kinds(self) -> &'static [SymbolKind] {
+        match self {
+            Collection::Procedures => &[SymbolKind::Function, SymbolKind::Local],
+            Collection::PublicSymbols => &[SymbolKind::Public],
+            Collection::GlobalData => &[SymbolKind::Data],
+            // Modules are always parsed, but none of their symbols are needed
+            Collection::DebugModules => &[],
+            Collection::Exports => &[SymbolKind::Export],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldType {
+    Number,
+    Str,
+    Bool,
+}
+
+impl FieldType {
+    fn name(self) -> &'static str {
+        match self {
+            FieldType::Number => "number",
+            FieldType::Str => "string",
+            FieldType::Bool => "boolean",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Literal {
+    Number(u64),
+    Str(String),
+    Bool(bool),
+    Regex(Regex),
+}
+
+impl Literal {
+    fn name(&self) -> &'static str {
+        match self {
+            Literal::Number(_) => "a number",
+            Literal::Str(_) => "a string",
+            Literal::Bool(_) => "a boolean",
+            Literal::Regex(_) => "a regex",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Matches,
+    NotMatches,
+}
+
+impl Operator {
+    fn token(self) -> &'static str {
+        match self {
+            Operator::Eq => "==",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Matches => "=~",
+            Operator::NotMatches => "!~",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    /// A bare field, which is true when its value is true or non-zero
+    Field(String),
+    Compare(String, Operator, Literal),
+}
+
+/// A field's value for a single item
+enum Value<'a> {
+    Number(u64),
+    Str(&'a str),
+    Bool(bool),
+    Missing,
+}
+
+#[derive(Debug)]
+pub struct Query {
+    collection: Collection,
+    filter: Option<Expr>,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.input[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &'static str) -> Result<(), QueryError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(QueryError::Expected(token, self.pos))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, QueryError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_') {
+                break;
+            }
+            self.pos += 1;
+        }
+
+        if start == self.pos {
+            return match self.peek() {
+                Some(c) => Err(QueryError::UnexpectedCharacter(c, self.pos)),
+                None => Err(QueryError::UnexpectedEnd),
+            };
+        }
+
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    /// Reads characters up to an unescaped `terminator`, consuming the terminator
+    fn delimited(&mut self, terminator: char) -> Result<String, QueryError> {
+        let mut value = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+            match c {
+                _ if escaped => {
+                    // Regexes need to keep their escapes, only the terminator is unescaped
+                    if c != terminator && terminator == '/' {
+                        value.push('\\');
+                    }
+                    value.push(c);
+                    escaped = false;
+                }
+                '\\' => escaped = true,
+                _ if c == terminator => return Ok(value),
+                _ => value.push(c),
+            }
+        }
+
+        Err(QueryError::UnexpectedEnd)
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.and()?;
+        while self.eat("||") {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut lhs = self.unary()?;
+        while self.eat("&&") {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.unary()?));
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        if self.eat("(") {
+            let expr = self.or()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+
+        let field = self.identifier()?;
+        // Longer operators first so `<=` isn't read as `<`
+        let operators = [
+            ("==", Operator::Eq),
+            ("!=", Operator::Ne),
+            ("<=", Operator::Le),
+            (">=", Operator::Ge),
+            ("=~", Operator::Matches),
+            ("!~", Operator::NotMatches),
+            ("<", Operator::Lt),
+            (">", Operator::Gt),
+        ];
+        let operator = operators
+            .iter()
+            .find(|(token, _operator)| self.eat(token))
+            .map(|(_token, operator)| *operator);
+
+        match operator {
+            Some(operator) => Ok(Expr::Compare(field, operator, self.literal()?)),
+            None => Ok(Expr::Field(field)),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Literal, QueryError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                Ok(Literal::Str(self.delimited('"')?))
+            }
+            Some('/') => {
+                self.pos += 1;
+                let pattern = self.delimited('/')?;
+                let pattern = if self.eat("i") {
+                    format!("(?i){}", pattern)
+                } else {
+                    pattern
+                };
+
+                Ok(Literal::Regex(Regex::new(&pattern)?))
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.pos;
+                let token = self.identifier()?;
+                let number = match token
+                    .strip_prefix("0x")
+                    .or_else(|| token.strip_prefix("0X"))
+                {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => token.parse(),
+                };
+
+                number
+                    .map(Literal::Number)
+                    .map_err(|_| QueryError::Expected("a number", start))
+            }
+            Some(_) => match self.identifier()?.as_str() {
+                "true" => Ok(Literal::Bool(true)),
+                "false" => Ok(Literal::Bool(false)),
+                _ => Err(QueryError::Expected("a literal", self.pos)),
+            },
+            None => Err(QueryError::UnexpectedEnd),
+        }
+    }
+}
+
+/// Checks that every field exists in `collection` and is only compared with literals
+/// and operators of its type, so a query can't silently select nothing
+fn validate(expr: &Expr, collection: Collection) -> Result<(), QueryError> {
+    let field_type = |field: &str| {
+        collection
+            .fields()
+            .iter()
+            .find(|(name, _field_type)| *name == field)
+            .map(|(_name, field_type)| *field_type)
+            .ok_or_else(|| QueryError::UnknownField(field.to_string(), collection.name()))
+    };
+
+    match expr {
+        Expr::And(lhs, rhs) | Expr::Or(lhs, rhs) => {
+            validate(lhs, collection)?;
+            validate(rhs, collection)
+        }
+        Expr::Not(expr) => validate(expr, collection),
+        Expr::Field(field) => field_type(field).map(|_| ()),
+        Expr::Compare(field, operator, literal) => {
+            let field_type = field_type(field)?;
+            let matches_type = matches!(
+                (field_type, literal),
+                (FieldType::Number, Literal::Number(_))
+                    | (FieldType::Str, Literal::Str(_))
+                    | (FieldType::Str, Literal::Regex(_))
+                    | (FieldType::Bool, Literal::Bool(_))
+            );
+            if !matches_type {
+                return Err(QueryError::TypeMismatch(
+                    field.clone(),
+                    field_type.name(),
+                    literal.name(),
+                ));
+            }
+
+            let is_match = matches!(operator, Operator::Matches | Operator::NotMatches);
+            let is_ordering = matches!(
+                operator,
+                Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge
+            );
+            match literal {
+                Literal::Regex(_) if !is_match => {
+                    Err(QueryError::InvalidOperator(operator.token(), "a regex"))
+                }
+                Literal::Regex(_) => Ok(()),
+                _ if is_match => Err(QueryError::InvalidOperator(
+                    operator.token(),
+                    literal.name(),
+                )),
+                Literal::Bool(_) if is_ordering => Err(QueryError::InvalidOperator(
+                    operator.token(),
+                    literal.name(),
+                )),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser { input, pos: 0 };
+
+        let collection = match parser.identifier()?.as_str() {
+            "procedures" => Collection::Procedures,
+            "publics" | "public_symbols" => Collection::PublicSymbols,
+            "data" | "global_data" => Collection::GlobalData,
+            "modules" | "debug_modules" => Collection::DebugModules,
+            "exports" => Collection::Exports,
+            other => return Err(QueryError::UnknownCollection(other.to_string())),
+        };
+
+        let filter = if parser.eat("[") {
+            let expr = parser.or()?;
+            parser.expect("]")?;
+            validate(&expr, collection)?;
+            Some(expr)
+        } else {
+            None
+        };
+
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(QueryError::UnexpectedCharacter(c, parser.pos));
+        }
+
+        Ok(Query { collection, filter })
+    }
+
+    /// Kinds of symbols the query can select. Others don't need to be parsed, since
+    /// [Query::apply] empties their collections
+    pub fn symbol_kinds(&self) -> HashSet<SymbolKind> {
+        self.collection.symbol_kinds().iter().copied().collect()
+    }
+
+    /// Returns whether `item` passes the query's filter
+    fn selects<T: Fields>(&self, item: &T) -> bool {
+        match &self.filter {
+            Some(filter) => evaluate(filter, item),
+            None => true,
+        }
+    }
+
+    /// Removes every item the query does not select. Collections other than the
+    /// queried one are emptied so that only the requested slice is output.
+    pub fn apply(&self, pdb_info: &mut ParsedPdb) {
+        let collection = self.collection;
+
+        if collection == Collection::Procedures {
+            pdb_info
+                .procedures
+                .retain(|procedure| self.selects(procedure));
+        } else {
+            pdb_info.procedures.clear();
+        }
+
+        if collection == Collection::PublicSymbols {
+            pdb_info
+                .public_symbols
+                .retain(|symbol| self.selects(symbol));
+        } else {
+            pdb_info.public_symbols.clear();
+        }
+
+        if collection == Collection::GlobalData {
+            pdb_info.global_data.retain(|data| self.selects(data));
+        } else {
+            pdb_info.global_data.clear();
+        }
+
+        if collection == Collection::DebugModules {
+            pdb_info.debug_modules.retain(|module| self.selects(module));
+        } else {
+            pdb_info.debug_modules.clear();
+        }
+
+        if collection == Collection::Exports {
+            pdb_info.exports.retain(|export| self.selects(export));
+        } else {
+            pdb_info.exports.clear();
+        }
+    }
+}
+
+impl std::str::FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+/// Items which can be filtered by a query
+trait Fields {
+    fn field(&self, name: &str) -> Value<'_>;
+}
+
+fn offset_value<'a>(offset: Option<usize>) -> Value<'a> {
+    offset
+        .map(|offset| Value::Number(offset as u64))
+        .unwrap_or(Value::Missing)
+}
+
+impl Fields for Procedure {
+    fn field(&self, name: &str) -> Value<'_> {
+        match name {
+            "name" => Value::Str(&self.name),
+            "signature" => self
+                .signature
+                .as_deref()
+                .map(Value::Str)
+                .unwrap_or(Value::Missing),
+            "offset" => offset_value(self.offset),
+            "location" => Value::Str(self.location.name()),
+            "len" => Value::Number(self.len as u64),
+            "type_index" => Value::Number(self.type_index as u64),
+            "is_global" => Value::Bool(self.is_global),
+            "is_dpc" => Value::Bool(self.is_dpc),
+            "prologue_end" => Value::Number(self.prologue_end as u64),
+            "epilogue_start" => Value::Number(self.epilogue_start as u64),
+            "locals" => Value::Number(self.locals.len() as u64),
+            _ => Value::Missing,
+        }
+    }
+}
+
+impl Fields for PublicSymbol {
+    fn field(&self, name: &str) -> Value<'_> {
+        match name {
+            "name" => Value::Str(&self.name),
+            "offset" => offset_value(self.offset),
+            "location" => Value::Str(self.location.name()),
+            "is_code" => Value::Bool(self.is_code),
+            "is_function" => Value::Bool(self.is_function),
+            "is_managed" => Value::Bool(self.is_managed),
+            "is_msil" => Value::Bool(self.is_msil),
+            _ => Value::Missing,
+        }
+    }
+}
+
+impl Fields for Data {
+    fn field(&self, name: &str) -> Value<'_> {
+        match name {
+            "name" => Value::Str(&self.name),
+            "offset" => offset_value(self.offset),
+            "location" => Value::Str(self.location.name()),
+            "is_global" => Value::Bool(self.is_global),
+            "is_managed" => Value::Bool(self.is_managed),
+            _ => Value::Missing,
+        }
+    }
+}
+
+impl Fields for DebugModule {
+    fn field(&self, name: &str) -> Value<'_> {
+        match name {
+            "name" => Value::Str(&self.name),
+            "object_file_name" => Value::Str(&self.object_file_name),
+            "source_files" => Value::Number(
+                self.source_files
+                    .as_ref()
+                    .map(|files| files.len())
+                    .unwrap_or(0) as u64,
+            ),
+            "lines" => Value::Number(self.lines.len() as u64),
+            _ => Value::Missing,
+        }
+    }
+}
+
+impl Fields for Export {
+    fn field(&self, name: &str) -> Value<'_> {
+        match name {
+            "name" => Value::Str(&self.name),
+            "ordinal" => Value::Number(self.ordinal as u64),
+            "is_constant" => Value::Bool(self.is_constant),
+            "is_data" => Value::Bool(self.is_data),
+            "is_private" => Value::Bool(self.is_private),
+            "is_no_name" => Value::Bool(self.is_no_name),
+            "is_forwarder" => Value::Bool(self.is_forwarder),
+            _ => Value::Missing,
+        }
+    }
+}
+
+fn compare<T: PartialOrd + ?Sized>(lhs: &T, operator: Operator, rhs: &T) -> bool {
+    match operator {
+        Operator::Eq => lhs == rhs,
+        Operator::Ne => lhs != rhs,
+        Operator::Lt => lhs < rhs,
+        Operator::Le => lhs <= rhs,
+        Operator::Gt => lhs > rhs,
+        Operator::Ge => lhs >= rhs,
+        Operator::Matches | Operator::NotMatches => false,
+    }
+}
+
+fn evaluate<T: Fields>(expr: &Expr, item: &T) -> bool {
+    match expr {
+        Expr::And(lhs, rhs) => evaluate(lhs, item) && evaluate(rhs, item),
+        Expr::Or(lhs, rhs) => evaluate(lhs, item) || evaluate(rhs, item),
+        Expr::Not(expr) => !evaluate(expr, item),
+        Expr::Field(field) => match item.field(field) {
+            Value::Bool(b) => b,
+            Value::Number(n) => n != 0,
+            Value::Str(s) => !s.is_empty(),
+            Value::Missing => false,
+        },
+        Expr::Compare(field, operator, literal) => match (item.field(field), literal) {
+            (Value::Number(lhs), Literal::Number(rhs)) => compare(&lhs, *operator, rhs),
+            (Value::Str(lhs), Literal::Str(rhs)) => compare(lhs, *operator, rhs.as_str()),
+            (Value::Bool(lhs), Literal::Bool(rhs)) => compare(&lhs, *operator, rhs),
+            (Value::Str(lhs), Literal::Regex(regex)) => match operator {
+                Operator::Matches => regex.is_match(lhs),
+                Operator::NotMatches => !regex.is_match(lhs),
+                _ => false,
+            },
+            // Types are checked when parsing, so only missing values get here. They
+            // never match
+            _ => false,
+        },
+    }
+}
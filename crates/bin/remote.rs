@@ -0,0 +1,191 @@
+//! Reading files over HTTP with range requests, so targeted queries against PDBs on a
+//! symbol server only download the pages they touch
+
+use crate::filter::wildcard_match;
+use crate::http::HttpClient;
+use ezpdb::probe::PdbProbe;
+use ezpdb::symbol_types::PublicSymbol;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+/// Size of the blocks requested and cached. Large enough that the scattered MSF pages
+/// of a stream usually share a request
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Requests made and bytes received by a [RangeReader]. Shared so they can still be
+/// read once the reader has been handed to the PDB parser
+#[derive(Debug, Default)]
+pub struct TransferStats {
+    pub requests: Cell<usize>,
+    pub bytes: Cell<u64>,
+}
+
+/// A seekable reader over a remote file which fetches and caches whole blocks
+pub struct RangeReader<'a> {
+    client: &'a HttpClient,
+    url: String,
+    len: u64,
+    position: u64,
+    blocks: HashMap<u64, Vec<u8>>,
+    stats: Rc<TransferStats>,
+}
+
+impl std::fmt::Debug for RangeReader<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RangeReader")
+            .field("url", &self.url)
+            .field("len", &self.len)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+fn to_io_error(error: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error.to_string())
+}
+
+impl<'a> RangeReader<'a> {
+    /// Opens `url`, failing if the server doesn't report the file's size or doesn't
+    /// support range requests
+    pub fn open(client: &'a HttpClient, url: &str) -> anyhow::Result<RangeReader<'a>> {
+        let response = client.head(url).call()?;
+        let len = response
+            .header("Content-Length")
+            .and_then(|len| len.parse::<u64>().ok())
+            .ok_or_else(|| anyhow::anyhow!("{} did not report its size", url))?;
+        if response.header("Accept-Ranges") == Some("none") {
+            anyhow::bail!("{} does not support range requests", url);
+        }
+
+        Ok(RangeReader {
+            client,
+            url: url.to_string(),
+            len,
+            position: 0,
+            blocks: HashMap::new(),
+            stats: Rc::new(TransferStats::default()),
+        })
+    }
+
+    /// Size of the remote file in BYTES
+    pub fn size(&self) -> u64 {
+        self.len
+    }
+
+    pub fn stats(&self) -> Rc<TransferStats> {
+        Rc::clone(&self.stats)
+    }
+
+    fn block(&mut self, index: u64) -> io::Result<&[u8]> {
+        if !self.blocks.contains_key(&index) {
+            let start = index * BLOCK_SIZE;
+            let end = (start + BLOCK_SIZE).min(self.len) - 1;
+            log::debug!("GET {} bytes={}-{}", self.url, start, end);
+            let response = self
+                .client
+                .get(&self.url)
+                .set("Range", &format!("bytes={}-{}", start, end))
+                .call()
+                .map_err(to_io_error)?;
+            if response.status() != 206 {
+                return Err(to_io_error(format!(
+                    "{} ignored the range request",
+                    self.url
+                )));
+            }
+
+            let mut data = Vec::with_capacity((end - start + 1) as usize);
+            response.into_reader().read_to_end(&mut data)?;
+            self.stats.requests.set(self.stats.requests.get() + 1);
+            self.stats
+                .bytes
+                .set(self.stats.bytes.get() + data.len() as u64);
+            self.blocks.insert(index, data);
+        }
+
+        Ok(&self.blocks[&index])
+    }
+}
+
+impl Read for RangeReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let offset = (self.position % BLOCK_SIZE) as usize;
+        let block = self.block(self.position / BLOCK_SIZE)?;
+        let available = block.len().saturating_sub(offset);
+        if available == 0 {
+            return Err(to_io_error("server returned a short block"));
+        }
+        let read = available.min(buf.len());
+        buf[..read].copy_from_slice(&block[offset..offset + read]);
+        self.position += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl Seek for RangeReader<'_> {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match position {
+            SeekFrom::Start(offset) => (offset, 0),
+            SeekFrom::End(offset) => (self.len, offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+
+        self.position = (base as i64)
+            .checked_add(offset)
+            .filter(|position| *position >= 0)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "seek to a negative position")
+            })? as u64;
+
+        Ok(self.position)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProbeReport {
+    pub url: String,
+    pub debug_identifier: String,
+    pub timestamp: u32,
+    pub machine_type: Option<String>,
+    /// Public symbols matching the requested pattern
+    pub public_symbols: Vec<PublicSymbol>,
+    /// Size of the PDB in BYTES
+    pub size: u64,
+    pub requests: usize,
+    /// BYTES downloaded to answer the query
+    pub downloaded: u64,
+}
+
+/// Reads the identity of the PDB behind `reader` and, with `pattern`, the public
+/// symbols whose name matches that wildcard pattern
+pub fn probe(reader: RangeReader<'_>, pattern: Option<&str>) -> anyhow::Result<ProbeReport> {
+    let url = reader.url.clone();
+    let size = reader.size();
+    let stats = reader.stats();
+
+    let mut probe = PdbProbe::open(reader)?;
+    let header = probe.header()?;
+    let public_symbols = match pattern {
+        Some(pattern) => probe.public_symbols(|name| wildcard_match(pattern, name))?,
+        None => vec![],
+    };
+
+    Ok(ProbeReport {
+        url,
+        debug_identifier: header.debug_identifier(),
+        timestamp: header.timestamp,
+        machine_type: header.machine_type.map(|ty| format!("{:?}", ty)),
+        public_symbols,
+        size,
+        requests: stats.requests.get(),
+        downloaded: stats.bytes.get(),
+    })
+}
@@ -0,0 +1,152 @@
+use crate::filter::wildcard_match;
+use crate::index::{NameIndex, NameRef};
+use crate::output;
+use crate::output::cdecl;
+use ezpdb::symbol_types::*;
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "\
+commands:
+  type <pattern>     print the definition of matching classes, unions, and enums
+  sym <pattern>      list procedures, public symbols, and data with matching names
+  addr <address>     resolve an address to its procedure, module, and nearest public
+  module <pattern>   print matching modules and their source files
+  help               print this message
+  quit               exit
+
+patterns accept `*` and `?` wildcards";
+
+fn print_types(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    index: &NameIndex,
+    pattern: &str,
+) -> io::Result<()> {
+    for name_ref in index.matches(pattern) {
+        let type_index = match name_ref {
+            NameRef::Type(type_index) => type_index,
+            _ => continue,
+        };
+
+        let ty = pdb_info.types[&type_index].as_ref().borrow();
+        if let Some(definition) = cdecl::c_definition(&*ty, pdb_info) {
+            writeln!(output, "// type index 0x{:X}\n{}\n", type_index, definition)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_symbols(
+    output: &mut impl Write,
+    pdb_info: &ParsedPdb,
+    index: &NameIndex,
+    pattern: &str,
+) -> io::Result<()> {
+    let format_offset = |offset: Option<usize>| {
+        offset
+            .map(|offset| format!("0x{:016X}", offset))
+            .unwrap_or_else(|| format!("{:18}", "<unknown>"))
+    };
+
+    for name_ref in index.matches(pattern) {
+        match name_ref {
+            NameRef::Procedure(i) => {
+                let procedure = &pdb_info.procedures[i];
+                writeln!(
+                    output,
+                    "{} proc   {} (0x{:X} bytes)",
+                    format_offset(procedure.offset),
+                    procedure.name,
+                    procedure.len
+                )?;
+            }
+            NameRef::Public(i) => {
+                let symbol = &pdb_info.public_symbols[i];
+                writeln!(
+                    output,
+                    "{} public {}",
+                    format_offset(symbol.offset),
+                    symbol.name
+                )?;
+            }
+            NameRef::Data(i) => {
+                let data = &pdb_info.global_data[i];
+                writeln!(
+                    output,
+                    "{} data   {} {}",
+                    format_offset(data.offset),
+                    output::format_type_name(&*data.ty.as_ref().borrow()),
+                    data.name
+                )?;
+            }
+            NameRef::Type(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn print_modules(output: &mut impl Write, pdb_info: &ParsedPdb, pattern: &str) -> io::Result<()> {
+    for module in &pdb_info.debug_modules {
+        if !wildcard_match(pattern, &module.name) {
+            continue;
+        }
+
+        writeln!(output, "{}", module.name)?;
+        writeln!(output, "\tObject file: {}", module.object_file_name)?;
+        if let Some(files) = &module.source_files {
+            for file in files {
+                writeln!(output, "\t{}", file.name)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads commands from stdin and answers them from the already-parsed PDB until
+/// stdin is closed or the user quits
+pub fn run(output: &mut impl Write, pdb_info: &ParsedPdb) -> anyhow::Result<()> {
+    let stdin = io::stdin();
+    // Patterns are matched against an index rather than every name in turn since
+    // large PDBs have millions of symbols
+    let index = NameIndex::new(pdb_info)?;
+
+    writeln!(output, "{}\n", HELP)?;
+
+    let mut lines = stdin.lock().lines();
+    loop {
+        write!(output, "pdbview> ")?;
+        output.flush()?;
+
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+
+        let line = line.trim();
+        let (command, argument) = match line.find(char::is_whitespace) {
+            Some(idx) => (&line[..idx], line[idx..].trim()),
+            None => (line, ""),
+        };
+
+        match command {
+            "" => {}
+            "type" => print_types(output, pdb_info, &index, argument)?,
+            "sym" => print_symbols(output, pdb_info, &index, argument)?,
+            "module" => print_modules(output, pdb_info, argument)?,
+            "addr" => match crate::parse_number(argument) {
+                Ok(address) => {
+                    output::print_lookup_plain(output, &pdb_info.lookup_address(address))?
+                }
+                Err(e) => writeln!(output, "{}", e)?,
+            },
+            "help" => writeln!(output, "{}", HELP)?,
+            "quit" | "exit" => break,
+            other => writeln!(output, "unknown command {:?}. Type `help` for usage", other)?,
+        }
+    }
+
+    Ok(())
+}
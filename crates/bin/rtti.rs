@@ -0,0 +1,312 @@
+//! Recovery of MSVC run-time type information from the PE image, to find the vtables of
+//! classes whose vtables aren't named by public symbols
+
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::{ParsedPdb, TypeIndexNumber};
+use ezpdb::type_info::Type;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+
+/// Longest decorated name read from a `TypeDescriptor`
+const MAX_NAME_LEN: usize = 4096;
+/// Most base classes read from a `ClassHierarchyDescriptor`
+const MAX_BASE_CLASSES: usize = 1024;
+
+#[derive(Debug, Serialize)]
+pub struct RecoveredVtable {
+    pub rva: usize,
+    /// Offset of the subobject this vtable belongs to within the complete object
+    pub offset: u32,
+    /// RVA of the `CompleteObjectLocator` preceding the vtable
+    pub locator: usize,
+    /// Number of leading entries pointing into executable sections
+    pub entries: usize,
+    /// Name of the public symbol at the vtable's address, if there is one
+    pub public_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RttiClass {
+    /// Decorated name from the `TypeDescriptor`, e.g. `.?AVFoo@@`
+    pub decorated_name: String,
+    /// Name of the PDB class with the same decorated name
+    pub name: Option<String>,
+    /// Key of that class in [ParsedPdb::types]
+    pub type_index: Option<TypeIndexNumber>,
+    pub type_descriptor: usize,
+    pub vtables: Vec<RecoveredVtable>,
+    /// Names of the base classes from the `ClassHierarchyDescriptor`
+    pub base_classes: Vec<String>,
+}
+
+struct Image<'a> {
+    pe: &'a PeInfo,
+    /// Non-executable sections with their RVA and file data
+    data: Vec<(usize, &'a [u8])>,
+}
+
+impl<'a> Image<'a> {
+    fn new(pe: &'a PeInfo) -> Image<'a> {
+        let data = pe
+            .sections
+            .iter()
+            .filter(|section| !section.is_executable())
+            .filter_map(|section| {
+                let len = match section.virtual_size {
+                    0 => section.raw_size,
+                    size => size.min(section.raw_size),
+                };
+                Some((
+                    section.virtual_address,
+                    pe.read_rva(section.virtual_address, len)?,
+                ))
+            })
+            .collect();
+
+        Image { pe, data }
+    }
+
+    fn pointer_size(&self) -> usize {
+        if self.pe.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn u32_at(&self, rva: usize) -> Option<u32> {
+        let bytes = self.pe.read_rva(rva, 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Reads a reference between RTTI structures, which is an RVA in 64-bit images and a
+    /// virtual address in 32-bit ones
+    fn reference_at(&self, rva: usize) -> Option<usize> {
+        let value = self.u32_at(rva)?;
+        if self.pe.is_64 {
+            Some(value as usize)
+        } else {
+            (value as u64)
+                .checked_sub(self.pe.image_base)
+                .map(|rva| rva as usize)
+        }
+    }
+
+    fn pointer(&self, bytes: &[u8]) -> u64 {
+        if self.pe.is_64 {
+            u64::from_le_bytes(bytes.try_into().unwrap())
+        } else {
+            u32::from_le_bytes(bytes.try_into().unwrap()) as u64
+        }
+    }
+
+    fn is_code(&self, va: u64) -> bool {
+        let rva = match va.checked_sub(self.pe.image_base) {
+            Some(rva) => rva as usize,
+            None => return false,
+        };
+        self.pe.sections.iter().any(|section| {
+            section.is_executable()
+                && rva >= section.virtual_address
+                && rva - section.virtual_address < section.virtual_size.max(section.raw_size)
+        })
+    }
+
+    /// Finds `TypeDescriptor`s by their decorated class and struct names
+    fn type_descriptors(&self) -> HashMap<usize, String> {
+        let header = 2 * self.pointer_size();
+        let mut descriptors = HashMap::new();
+        for (base, bytes) in &self.data {
+            let aligned = (header..bytes.len().saturating_sub(4)).step_by(self.pointer_size());
+            for i in aligned {
+                if !(bytes[i..].starts_with(b".?AV") || bytes[i..].starts_with(b".?AU")) {
+                    continue;
+                }
+
+                let name = &bytes[i..bytes.len().min(i + MAX_NAME_LEN)];
+                let end = match name.iter().position(|byte| *byte == 0) {
+                    Some(end) => end,
+                    None => continue,
+                };
+                if let Ok(name) = std::str::from_utf8(&name[..end]) {
+                    descriptors.insert(base + i - header, name.to_string());
+                }
+            }
+        }
+
+        descriptors
+    }
+
+    /// Finds `CompleteObjectLocator`s referencing a known `TypeDescriptor`, as a map of
+    /// their RVA to the subobject offset, `TypeDescriptor`, and `ClassHierarchyDescriptor`
+    fn locators(&self, descriptors: &HashMap<usize, String>) -> BTreeMap<usize, Locator> {
+        let signature = if self.pe.is_64 { 1 } else { 0 };
+        let mut locators = BTreeMap::new();
+        for (base, bytes) in &self.data {
+            for i in (0..bytes.len().saturating_sub(24)).step_by(4) {
+                if bytes[i..i + 4] != u32::to_le_bytes(signature) {
+                    continue;
+                }
+                let rva = base + i;
+                let type_descriptor = match self.reference_at(rva + 12) {
+                    Some(td) if descriptors.contains_key(&td) => td,
+                    _ => continue,
+                };
+                // 64-bit locators point at themselves
+                if self.pe.is_64 && self.u32_at(rva + 20) != Some(rva as u32) {
+                    continue;
+                }
+                let hierarchy = match self.reference_at(rva + 16) {
+                    Some(chd) if self.u32_at(chd) == Some(0) => chd,
+                    _ => continue,
+                };
+
+                locators.insert(
+                    rva,
+                    Locator {
+                        offset: self.u32_at(rva + 4).unwrap_or_default(),
+                        type_descriptor,
+                        hierarchy,
+                    },
+                );
+            }
+        }
+
+        locators
+    }
+
+    /// Finds the vtables following a pointer to a locator, returning the RVA of each
+    /// along with the locator's RVA and the number of entries
+    fn vtables(&self, locators: &BTreeMap<usize, Locator>) -> Vec<(usize, usize, usize)> {
+        let pointer_size = self.pointer_size();
+        let by_address: HashMap<u64, usize> = locators
+            .keys()
+            .map(|rva| (self.pe.image_base + *rva as u64, *rva))
+            .collect();
+
+        let mut vtables = vec![];
+        for (base, bytes) in &self.data {
+            let slots: Vec<&[u8]> = bytes.chunks_exact(pointer_size).collect();
+            for (i, slot) in slots.iter().enumerate() {
+                let locator = match by_address.get(&self.pointer(slot)) {
+                    Some(locator) => *locator,
+                    None => continue,
+                };
+                let entries = slots[i + 1..]
+                    .iter()
+                    .take_while(|entry| self.is_code(self.pointer(entry)))
+                    .count();
+                if entries > 0 {
+                    vtables.push((base + (i + 1) * pointer_size, locator, entries));
+                }
+            }
+        }
+
+        vtables
+    }
+
+    /// Returns the `TypeDescriptor`s of the classes in a hierarchy, excluding the class
+    /// itself, which comes first
+    fn base_classes(&self, hierarchy: usize) -> Vec<usize> {
+        let count = self.u32_at(hierarchy + 8).unwrap_or_default() as usize;
+        let array = match self.reference_at(hierarchy + 12) {
+            Some(array) => array,
+            None => return vec![],
+        };
+
+        (1..count.min(MAX_BASE_CLASSES))
+            .filter_map(|i| self.reference_at(array + i * 4))
+            .filter_map(|descriptor| self.reference_at(descriptor))
+            .collect()
+    }
+}
+
+struct Locator {
+    offset: u32,
+    type_descriptor: usize,
+    hierarchy: usize,
+}
+
+/// Locates MSVC RTTI in the image's data sections and recovers the vtables of each
+/// polymorphic class along with its base classes. `TypeDescriptor`s are found by their
+/// decorated names, `CompleteObjectLocator`s by referencing one, and vtables by the
+/// pointer to their locator stored right before them. Classes are correlated with the
+/// PDB's by their decorated name, which PDBs store as the class's unique name
+pub fn recover_rtti(pdb_info: &ParsedPdb, pe: &PeInfo) -> Vec<RttiClass> {
+    let image = Image::new(pe);
+    let descriptors = image.type_descriptors();
+    let locators = image.locators(&descriptors);
+    log::debug!(
+        "found {} type descriptors and {} complete object locators",
+        descriptors.len(),
+        locators.len()
+    );
+
+    let mut pdb_classes = HashMap::new();
+    for (index, ty) in &pdb_info.types {
+        if let Type::Class(class) = &*ty.as_ref().borrow() {
+            if let (false, Some(unique_name)) =
+                (class.properties.forward_reference, &class.unique_name)
+            {
+                pdb_classes
+                    .entry(unique_name.clone())
+                    .or_insert((*index, class.name.clone()));
+            }
+        }
+    }
+    let vtable_names: HashMap<usize, &str> = pdb_info
+        .public_symbols
+        .iter()
+        .filter(|symbol| symbol.name.starts_with("??_7"))
+        .filter_map(|symbol| Some((symbol.offset?, symbol.name.as_str())))
+        .collect();
+    let class_name = |descriptor: usize| {
+        let decorated = descriptors.get(&descriptor)?;
+        Some(
+            pdb_classes
+                .get(decorated)
+                .map_or_else(|| decorated.clone(), |(_, name)| name.clone()),
+        )
+    };
+
+    let mut classes: BTreeMap<usize, RttiClass> = BTreeMap::new();
+    for (rva, locator, entries) in image.vtables(&locators) {
+        let locator_info = &locators[&locator];
+        let class = classes
+            .entry(locator_info.type_descriptor)
+            .or_insert_with(|| {
+                let decorated_name = descriptors[&locator_info.type_descriptor].clone();
+                let pdb_class = pdb_classes.get(&decorated_name);
+                RttiClass {
+                    name: pdb_class.map(|(_, name)| name.clone()),
+                    type_index: pdb_class.map(|(index, _)| *index),
+                    type_descriptor: locator_info.type_descriptor,
+                    vtables: vec![],
+                    base_classes: image
+                        .base_classes(locator_info.hierarchy)
+                        .into_iter()
+                        .filter_map(&class_name)
+                        .collect(),
+                    decorated_name,
+                }
+            });
+
+        class.vtables.push(RecoveredVtable {
+            rva,
+            offset: locator_info.offset,
+            locator,
+            entries,
+            public_name: vtable_names.get(&rva).map(|name| name.to_string()),
+        });
+    }
+
+    let mut classes: Vec<RttiClass> = classes.into_values().collect();
+    for class in &mut classes {
+        class
+            .vtables
+            .sort_by_key(|vtable| (vtable.offset, vtable.rva));
+    }
+
+    classes
+}
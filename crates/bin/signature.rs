@@ -0,0 +1,183 @@
+use crate::group::ModuleRanges;
+use crate::pe::PeInfo;
+use ezpdb::symbol_types::FunctionHashes;
+use ezpdb::ParsedPdb;
+use iced_x86::{Decoder, DecoderOptions, Instruction, OpKind};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// Returns the bytes of the function at `rva` with position-dependent bytes masked out
+/// as `None`. Bytes patched by base relocations are masked, as are the displacements of
+/// relative branches and RIP-relative memory operands since these change whenever the
+/// surrounding code moves. Instructions are decoded to find these so that bytes which
+/// merely look like a branch opcode are kept.
+pub fn masked_bytes(pe: &PeInfo, rva: usize, len: usize) -> Option<Vec<Option<u8>>> {
+    let bytes = pe.read_rva(rva, len)?;
+    let mut masked: Vec<Option<u8>> = bytes.iter().copied().map(Some).collect();
+
+    for (start, size) in pe.relocations.range(rva.saturating_sub(7)..rva + len) {
+        for byte_rva in *start..start + size {
+            if byte_rva >= rva && byte_rva < rva + len {
+                masked[byte_rva - rva] = None;
+            }
+        }
+    }
+
+    let bitness = if pe.is_64 { 64 } else { 32 };
+    let mut decoder = Decoder::with_ip(bitness, bytes, rva as u64, DecoderOptions::NONE);
+    let mut instruction = Instruction::default();
+    let mut mask = |start: usize, size: usize| {
+        for byte in masked.iter_mut().skip(start).take(size) {
+            *byte = None;
+        }
+    };
+
+    while decoder.can_decode() {
+        decoder.decode_out(&mut instruction);
+        if instruction.is_invalid() {
+            continue;
+        }
+
+        let start = instruction.ip() as usize - rva;
+        let offsets = decoder.get_constant_offsets(&instruction);
+
+        if instruction.is_ip_rel_memory_operand() && offsets.has_displacement() {
+            mask(
+                start + offsets.displacement_offset(),
+                offsets.displacement_size(),
+            );
+        }
+
+        // iced reports the displacement of a relative branch as its immediate
+        let is_relative_branch = (0..instruction.op_count()).any(|operand| {
+            matches!(
+                instruction.op_kind(operand),
+                OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64
+            )
+        });
+        if is_relative_branch && offsets.has_immediate() {
+            mask(start + offsets.immediate_offset(), offsets.immediate_size());
+        }
+    }
+
+    Some(masked)
+}
+
+pub fn hex_digest(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Computes [FunctionHashes] for every procedure whose bytes are present in the PE
+pub fn hash_functions(pdb_info: &mut ParsedPdb, pe: &PeInfo) {
+    let base_address = pdb_info.base_address;
+
+    for procedure in &mut pdb_info.procedures {
+        let rva = match procedure.offset {
+            Some(offset) if procedure.len > 0 => offset - base_address,
+            _ => continue,
+        };
+
+        let masked = match masked_bytes(pe, rva, procedure.len) {
+            Some(masked) => masked,
+            None => continue,
+        };
+
+        let raw: Vec<u8> = pe
+            .read_rva(rva, procedure.len)
+            .expect("bytes were already read for masking")
+            .to_vec();
+        let normalized: Vec<u8> = masked.iter().map(|byte| byte.unwrap_or(0)).collect();
+
+        procedure.hashes = Some(FunctionHashes {
+            sha256: hex_digest(&Sha256::digest(&raw)),
+            masked_sha256: hex_digest(&Sha256::digest(&normalized)),
+        });
+    }
+}
+
+/// Sets [ezpdb::symbol_types::Procedure::cfg_target] on every procedure with an address
+/// if the image has a Control Flow Guard function table
+pub fn mark_cfg_targets(pdb_info: &mut ParsedPdb, pe: &PeInfo) {
+    let cfg_targets = match &pe.cfg_targets {
+        Some(cfg_targets) => cfg_targets,
+        None => return,
+    };
+    let base_address = pdb_info.base_address;
+
+    for procedure in &mut pdb_info.procedures {
+        if let Some(offset) = procedure.offset {
+            procedure.cfg_target = Some(cfg_targets.contains(&(offset - base_address)));
+        }
+    }
+}
+
+/// Assigns every procedure without one a [ezpdb::symbol_types::Procedure::stable_id]
+/// derived from its module's file name, its mangled name (from the public symbol at the
+/// same address, if any), and its signature. Module paths are reduced to the file name
+/// since build directories differ between machines. IDs which would collide get a
+/// `.N` suffix in address order
+pub fn assign_stable_ids(pdb_info: &mut ParsedPdb) {
+    let public_names: HashMap<usize, &str> = pdb_info
+        .public_symbols
+        .iter()
+        .filter_map(|symbol| symbol.offset.map(|offset| (offset, symbol.name.as_str())))
+        .collect();
+    let ranges = ModuleRanges::new(pdb_info);
+
+    // Assign in address order so collisions are suffixed the same way on every run
+    let mut order: Vec<usize> = (0..pdb_info.procedures.len()).collect();
+    order.sort_by_key(|&index| {
+        let procedure = &pdb_info.procedures[index];
+        (procedure.offset, &procedure.name)
+    });
+
+    let mut seen: HashSet<String> = pdb_info
+        .procedures
+        .iter()
+        .filter_map(|procedure| procedure.stable_id.clone())
+        .collect();
+    let mut ids = vec![None; pdb_info.procedures.len()];
+    for index in order {
+        let procedure = &pdb_info.procedures[index];
+        if procedure.stable_id.is_some() {
+            continue;
+        }
+
+        let module = procedure
+            .offset
+            .and_then(|offset| ranges.module_index(offset))
+            .and_then(|index| pdb_info.debug_modules.get(index))
+            .and_then(|module| module.name.rsplit(|c: char| c == '\\' || c == '/').next())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let name = procedure
+            .offset
+            .and_then(|offset| public_names.get(&offset).copied())
+            .unwrap_or(procedure.name.as_str());
+
+        let mut hasher = Sha256::new();
+        for part in &[
+            module.as_str(),
+            name,
+            procedure.signature.as_deref().unwrap_or(""),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update(&[0u8]);
+        }
+        let base = hex_digest(&hasher.finalize()[..16]);
+
+        let mut id = base.clone();
+        let mut suffix = 1;
+        while !seen.insert(id.clone()) {
+            id = format!("{}.{}", base, suffix);
+            suffix += 1;
+        }
+        ids[index] = Some(id);
+    }
+
+    for (procedure, id) in pdb_info.procedures.iter_mut().zip(ids) {
+        if id.is_some() {
+            procedure.stable_id = id;
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::CliArgumentError;
+use ezpdb::symbol_types::*;
+use ezpdb::type_info::Typed;
+use std::str::FromStr;
+
+/// Orderings which can be requested for output collections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// By address, then name
+    #[default]
+    Address,
+    /// By name, then address
+    Name,
+    /// Largest first, then by address
+    Size,
+}
+
+impl FromStr for SortKey {
+    type Err = CliArgumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_ref() {
+            "address" | "rva" => Ok(SortKey::Address),
+            "name" => Ok(SortKey::Name),
+            "size" => Ok(SortKey::Size),
+            _ => Err(CliArgumentError::InvalidValue("sort", s.to_string())),
+        }
+    }
+}
+
+impl SortKey {
+    /// Sorts `items` according to this key. Items without an address sort last when
+    /// ordering by address
+    pub fn sort<T>(
+        self,
+        items: &mut [T],
+        reverse: bool,
+        key: impl Fn(&T) -> (Option<usize>, &str, usize),
+    ) {
+        items.sort_by(|a, b| {
+            let (a_address, a_name, a_size) = key(a);
+            let (b_address, b_name, b_size) = key(b);
+            // `None` sorts before `Some`, so compare the flipped presence first
+            let a_address = (a_address.is_none(), a_address);
+            let b_address = (b_address.is_none(), b_address);
+
+            let ordering = match self {
+                SortKey::Address => a_address.cmp(&b_address).then_with(|| a_name.cmp(b_name)),
+                SortKey::Name => a_name.cmp(b_name).then_with(|| a_address.cmp(&b_address)),
+                SortKey::Size => b_size
+                    .cmp(&a_size)
+                    .then_with(|| a_address.cmp(&b_address))
+                    .then_with(|| a_name.cmp(b_name)),
+            };
+
+            if reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
+
+/// Orders every symbol collection in `pdb_info` by `key` so output doesn't depend on
+/// the layout of the PDB's streams. Modules keep their original order since
+/// [SectionContribution::module_index] refers to them by position.
+pub fn sort_pdb(pdb_info: &mut ParsedPdb, key: SortKey, reverse: bool) {
+    key.sort(&mut pdb_info.public_symbols, reverse, |symbol| {
+        (symbol.offset, &symbol.name, 0)
+    });
+    key.sort(&mut pdb_info.procedures, reverse, |procedure| {
+        (procedure.offset, &procedure.name, procedure.len)
+    });
+    key.sort(&mut pdb_info.thunks, reverse, |thunk| {
+        (thunk.offset, &thunk.name, thunk.len)
+    });
+    key.sort(&mut pdb_info.separated_code, reverse, |block| {
+        (block.offset, "", block.len)
+    });
+    key.sort(
+        &mut pdb_info.section_contributions,
+        reverse,
+        |contribution| (contribution.offset, "", contribution.size),
+    );
+    key.sort(&mut pdb_info.exports, reverse, |export| {
+        (Some(export.ordinal as usize), &export.name, 0)
+    });
+
+    // Type sizes need the rest of the PDB, so sort a detached copy of the globals
+    let mut global_data = std::mem::take(&mut pdb_info.global_data);
+    key.sort(&mut global_data, reverse, |data| {
+        let size = data.ty.as_ref().borrow().type_size(pdb_info);
+        (data.offset, &data.name, size)
+    });
+    pdb_info.global_data = global_data;
+
+    for module in &mut pdb_info.debug_modules {
+        key.sort(&mut module.lines, reverse, |line| {
+            (line.offset, &line.file, line.len.unwrap_or_default())
+        });
+        if let Some(files) = module.source_files.as_mut() {
+            files.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
+}
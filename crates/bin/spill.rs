@@ -0,0 +1,215 @@
+//! Bounded-memory parsing for `--max-memory`. Once the heap grows past the limit, the
+//! procedures and lines of every module parsed afterwards are written to a temporary
+//! file, then streamed back into the output one module at a time.
+
+use crate::output::compat;
+use crate::profile::live_bytes;
+use ezpdb::symbol_types::{with_type_indices, LineInfo, Procedure, FORMAT_VERSION};
+use ezpdb::{ParsedModule, ParsedPdb};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufReader, BufWriter, Write};
+use tempfile::NamedTempFile;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SpilledModule {
+    index: usize,
+    procedures: Vec<Procedure>,
+    lines: Vec<LineInfo>,
+}
+
+/// Records moved out of a [ParsedPdb] while it was parsed
+pub struct Spill {
+    limit: u64,
+    base_address: Option<usize>,
+    /// Created with a random name and only readable by the current user. Removed when
+    /// the spill is dropped
+    file: Option<BufWriter<NamedTempFile>>,
+}
+
+impl Spill {
+    /// Creates a spill which starts writing once more than `limit` bytes are allocated.
+    /// Spilled addresses are rebased to `base_address` if provided, since the PDB they
+    /// came from is rebased after parsing
+    pub fn new(limit: u64, base_address: Option<usize>) -> Spill {
+        Spill {
+            limit,
+            base_address,
+            file: None,
+        }
+    }
+
+    /// Sink for [ezpdb::parse_pdb_with_sink]. Moves the procedures and lines of `module`
+    /// to disk if the limit has been exceeded or an earlier module was already moved,
+    /// which keeps spilled records in parse order. `filter` is applied to a [ParsedPdb]
+    /// holding just the module's procedures before they are written
+    pub fn module_parsed(
+        &mut self,
+        pdb_info: &mut ParsedPdb,
+        module: ParsedModule,
+        filter: impl FnOnce(&mut ParsedPdb),
+    ) -> io::Result<()> {
+        if self.file.is_none() {
+            if live_bytes() <= self.limit {
+                return Ok(());
+            }
+
+            let file = tempfile::Builder::new()
+                .prefix("pdbview-")
+                .suffix(".spill")
+                .tempfile()?;
+            log::info!(
+                "memory use exceeded {} bytes; writing remaining modules to {}",
+                self.limit,
+                file.path().display()
+            );
+            self.file = Some(BufWriter::new(file));
+        }
+
+        let mut chunk = ParsedPdb::new(pdb_info.path.clone());
+        chunk.procedures = pdb_info.procedures.drain(module.procedures).collect();
+        let mut lines = std::mem::take(&mut pdb_info.debug_modules[module.index].lines);
+        if let Some(base_address) = self.base_address {
+            chunk.rebase(base_address);
+            for line in &mut lines {
+                if let Some(offset) = line.offset.as_mut() {
+                    *offset += base_address;
+                }
+            }
+        }
+        filter(&mut chunk);
+
+        let record = SpilledModule {
+            index: module.index,
+            procedures: chunk.procedures,
+            lines,
+        };
+        let file = self.file.as_mut().expect("spill file was just created");
+        serde_json::to_writer(&mut *file, &record)?;
+        writeln!(file)?;
+
+        Ok(())
+    }
+
+    /// Reads back the spilled modules in the order they were written
+    fn records(&mut self) -> io::Result<impl Iterator<Item = serde_json::Result<SpilledModule>>> {
+        let records = match self.file.as_mut() {
+            Some(file) => {
+                file.flush()?;
+                Some(
+                    serde_json::Deserializer::from_reader(BufReader::new(file.get_ref().reopen()?))
+                        .into_iter(),
+                )
+            }
+            None => None,
+        };
+
+        Ok(records.into_iter().flatten())
+    }
+
+    /// Writes `pdb_info` as JSON with the spilled records merged back in. Spilled
+    /// procedures follow those kept in memory, so they are in parse order rather than
+    /// the order requested by `--sort`
+    pub fn print_json(
+        mut self,
+        output: &mut impl Write,
+        mut pdb_info: ParsedPdb,
+        format_version: u32,
+    ) -> anyhow::Result<()> {
+        // The largest collections are written item by item rather than converted to a
+        // `Value` along with the rest
+        let types = std::mem::take(&mut pdb_info.types);
+        let procedures = std::mem::take(&mut pdb_info.procedures);
+        let mut modules = std::mem::take(&mut pdb_info.debug_modules);
+
+        // Written separately from `types`, so type references are only written as
+        // indices into it when the version has them
+        let by_index = format_version >= 4;
+        let mut rest = if by_index {
+            with_type_indices(&types, || serde_json::to_value(&pdb_info))?
+        } else {
+            serde_json::to_value(&pdb_info)?
+        };
+        compat::downgrade(&mut rest, format_version);
+
+        write!(output, "{{")?;
+        if let serde_json::Value::Object(fields) = &rest {
+            for (name, value) in fields {
+                if ["types", "procedures", "debug_modules"].contains(&name.as_str()) {
+                    continue;
+                }
+                write!(output, "{}:", serde_json::to_string(name)?)?;
+                serde_json::to_writer(&mut *output, value)?;
+                write!(output, ",")?;
+            }
+        }
+
+        write!(output, "\"types\":")?;
+        if by_index {
+            with_type_indices(&types, || serde_json::to_writer(&mut *output, &types))?;
+        } else {
+            serde_json::to_writer(&mut *output, &types)?;
+        }
+        drop(types);
+
+        write!(output, ",\"procedures\":[")?;
+        let mut first = true;
+        for procedure in &procedures {
+            write_item(output, "procedures", procedure, format_version, &mut first)?;
+        }
+        drop(procedures);
+        for record in self.records()? {
+            for procedure in &record?.procedures {
+                write_item(output, "procedures", procedure, format_version, &mut first)?;
+            }
+        }
+
+        write!(output, "],\"debug_modules\":[")?;
+        let mut first = true;
+        let mut records = self.records()?.peekable();
+        for (index, module) in modules.iter_mut().enumerate() {
+            let spilled = match records.peek() {
+                Some(Ok(record)) => record.index == index,
+                // Surface the error below
+                Some(Err(_)) => true,
+                None => false,
+            };
+            if spilled {
+                module.lines = records.next().expect("record was just peeked")?.lines;
+            }
+            write_item(
+                output,
+                "debug_modules",
+                &*module,
+                format_version,
+                &mut first,
+            )?;
+            module.lines = vec![];
+        }
+        write!(output, "]}}")?;
+
+        Ok(())
+    }
+}
+
+/// Writes an element of the top-level array `collection`, downgraded to `format_version`
+fn write_item<T: Serialize>(
+    output: &mut impl Write,
+    collection: &str,
+    item: &T,
+    format_version: u32,
+    first: &mut bool,
+) -> anyhow::Result<()> {
+    if !std::mem::take(first) {
+        write!(output, ",")?;
+    }
+
+    if format_version == FORMAT_VERSION {
+        serde_json::to_writer(output, item)?;
+    } else {
+        let mut value = serde_json::json!({ collection: [item] });
+        compat::downgrade(&mut value, format_version);
+        serde_json::to_writer(output, &value[collection][0])?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,533 @@
+//! Querying symbol servers for the files they hold, using the same index paths as
+//! [crate::symstore]
+
+use crate::http::HttpClient;
+use crate::pe::PeInfo;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Which form of a file a symbol server holds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// The file itself, e.g. `foo.pdb/<id>/foo.pdb`
+    Uncompressed,
+    /// A CAB archive with the last character of the name replaced by `_`, e.g. `foo.pd_`
+    Compressed,
+    /// A `file.ptr` redirecting to where the file is actually stored
+    FilePtr,
+}
+
+const VARIANTS: [Variant; 3] = [Variant::Uncompressed, Variant::Compressed, Variant::FilePtr];
+
+/// A file symbol servers index by name and key, such as a PDB by its GUID and age
+#[derive(Debug, Serialize)]
+pub struct SymbolId {
+    pub name: String,
+    pub key: String,
+}
+
+impl SymbolId {
+    /// Parses `target`, which is either a PE image, whose PDB is looked up, a PDB, or
+    /// an index path such as `foo.pdb/<GUID><age>`
+    pub fn parse(target: &str) -> anyhow::Result<SymbolId> {
+        let symbol = SymbolId::parse_unchecked(target)?;
+        check_path_component(&symbol.name)?;
+        check_path_component(&symbol.key)?;
+
+        Ok(symbol)
+    }
+
+    fn parse_unchecked(target: &str) -> anyhow::Result<SymbolId> {
+        let path = Path::new(target);
+        if path.is_file() {
+            if crate::pe::is_pe(path)? {
+                let pe = PeInfo::open(path)?;
+                let codeview = pe.codeview.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!("{} has no CodeView debug record", path.display())
+                })?;
+
+                return Ok(SymbolId {
+                    name: codeview.pdb_name().to_string(),
+                    key: codeview.debug_identifier(),
+                });
+            }
+
+            return Ok(SymbolId {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                key: ezpdb::streams::debug_identifier(path)?,
+            });
+        }
+
+        match target.trim_matches('/').split_once('/') {
+            Some((name, key)) if !name.is_empty() && !key.is_empty() && !key.contains('/') => {
+                Ok(SymbolId {
+                    name: name.to_string(),
+                    key: key.to_uppercase(),
+                })
+            }
+            _ => anyhow::bail!(
+                "{} is not a file or an index path such as foo.pdb/<GUID><age>",
+                target
+            ),
+        }
+    }
+
+    /// Returns the path of `variant` relative to a symbol server's root
+    fn index_path(&self, variant: Variant) -> String {
+        let file_name = match variant {
+            Variant::Uncompressed => self.name.clone(),
+            Variant::Compressed => {
+                let mut name = self.name.clone();
+                name.pop();
+                name.push('_');
+                name
+            }
+            Variant::FilePtr => "file.ptr".to_string(),
+        };
+
+        format!("{}/{}/{}", self.name, self.key, file_name)
+    }
+}
+
+/// Fails unless `component` is a plain file name. Names come from CodeView records and
+/// the command line, and are joined onto the destination store and server paths, so
+/// they must not be able to climb out of them
+fn check_path_component(component: &str) -> anyhow::Result<()> {
+    if component.is_empty()
+        || component == "."
+        || component == ".."
+        || component.contains(&['/', '\\', ':', '\0'][..])
+    {
+        anyhow::bail!("{:?} is not a plain file name", component);
+    }
+
+    Ok(())
+}
+
+/// Splits symbol server entries into their tiers, accepting both plain paths and URLs
+/// and `_NT_SYMBOL_PATH`-style `srv*cache*https://server` entries
+pub fn tiers(servers: &[String]) -> Vec<String> {
+    servers
+        .iter()
+        .flat_map(|server| server.split('*'))
+        .map(str::trim)
+        .filter(|tier| {
+            !tier.is_empty()
+                && !tier.eq_ignore_ascii_case("srv")
+                && !tier.eq_ignore_ascii_case("symsrv")
+                && !tier.to_ascii_lowercase().ends_with(".dll")
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct TierStatus {
+    /// URL or directory of the tier
+    pub location: String,
+    /// Variants the tier holds
+    pub available: Vec<Variant>,
+    /// Why the tier couldn't be queried, if it couldn't
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AvailabilityReport {
+    pub symbol: SymbolId,
+    pub tiers: Vec<TierStatus>,
+    /// Index of the first tier holding any variant, which is the one a debugger would
+    /// download from
+    pub serving_tier: Option<usize>,
+}
+
+pub fn is_url(location: &str) -> bool {
+    let lowercase = location.to_ascii_lowercase();
+    lowercase.starts_with("http://") || lowercase.starts_with("https://")
+}
+
+/// Sends a `HEAD` request for `url`, returning whether the server has the file
+fn head(client: &HttpClient, url: &str) -> anyhow::Result<bool> {
+    match client.head(url).call() {
+        Ok(_) => Ok(true),
+        Err(ureq::Error::Status(404, _)) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn check_tier(client: &HttpClient, location: &str, symbol: &SymbolId) -> TierStatus {
+    let mut status = TierStatus {
+        location: location.to_string(),
+        available: vec![],
+        error: None,
+    };
+
+    for variant in VARIANTS.iter().copied() {
+        let index_path = symbol.index_path(variant);
+        let found = if is_url(location) {
+            let url = format!("{}/{}", location.trim_end_matches('/'), index_path);
+            log::debug!("HEAD {}", url);
+            match head(client, &url) {
+                Ok(found) => found,
+                Err(e) => {
+                    status.error = Some(e.to_string());
+                    break;
+                }
+            }
+        } else {
+            let path: PathBuf = std::iter::once(location)
+                .chain(index_path.split('/'))
+                .collect();
+            path.is_file()
+        };
+
+        if found {
+            status.available.push(variant);
+        }
+    }
+
+    status
+}
+
+/// Checks each tier for the variants of `symbol` it holds, without downloading them
+pub fn check_availability(
+    client: &HttpClient,
+    symbol: SymbolId,
+    tiers: &[String],
+) -> AvailabilityReport {
+    let tiers: Vec<TierStatus> = tiers
+        .iter()
+        .map(|location| check_tier(client, location, &symbol))
+        .collect();
+    let serving_tier = tiers.iter().position(|tier| !tier.available.is_empty());
+
+    AvailabilityReport {
+        symbol,
+        tiers,
+        serving_tier,
+    }
+}
+
+/// Returns the URL of `symbol` on the first HTTP tier which has it uncompressed
+pub fn locate(client: &HttpClient, symbol: &SymbolId, tiers: &[String]) -> Option<String> {
+    tiers
+        .iter()
+        .filter(|location| is_url(location))
+        .map(|location| {
+            format!(
+                "{}/{}",
+                location.trim_end_matches('/'),
+                symbol.index_path(Variant::Uncompressed)
+            )
+        })
+        .find(|url| head(client, url).unwrap_or(false))
+}
+
+/// Longest wait between download attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How [fetch] downloads files
+#[derive(Debug)]
+pub struct FetchOptions {
+    /// Store downloaded files are written to, in the same layout as the servers
+    pub destination: PathBuf,
+    /// Attempts after the first for transient failures such as timeouts and 5xx
+    /// responses, waiting twice as long before each
+    pub retries: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FetchResult {
+    pub symbol: SymbolId,
+    /// Where the file was written
+    pub path: Option<PathBuf>,
+    /// Tier the file came from, or `None` if it was already in the destination
+    pub source: Option<String>,
+    /// Size of the file in BYTES
+    pub size: u64,
+    /// Whether a partial download left by an earlier run was continued
+    pub resumed: bool,
+    pub error: Option<String>,
+}
+
+/// Error from a single download attempt, and whether trying again may succeed
+struct AttemptError {
+    error: anyhow::Error,
+    transient: bool,
+}
+
+impl<E: Into<anyhow::Error>> From<E> for AttemptError {
+    fn from(error: E) -> Self {
+        AttemptError {
+            error: error.into(),
+            transient: false,
+        }
+    }
+}
+
+/// Converts a failed request into an error, treating connection problems, rate
+/// limiting, and server errors as transient
+fn request_error(error: ureq::Error) -> AttemptError {
+    let transient = match &error {
+        ureq::Error::Status(status, _) => *status == 429 || *status >= 500,
+        ureq::Error::Transport(_) => true,
+    };
+
+    AttemptError {
+        error: error.into(),
+        transient,
+    }
+}
+
+/// Returns whether the file at `path` is the one `symbol` identifies: a PDB with the
+/// same GUID and age or an image with the same timestamp and size
+fn verify(path: &Path, symbol: &SymbolId) -> anyhow::Result<()> {
+    let key = crate::symstore::index_key(path)?;
+    if !key.eq_ignore_ascii_case(&symbol.key) {
+        anyhow::bail!(
+            "downloaded file is {}/{}, not {}/{}",
+            symbol.name,
+            key,
+            symbol.name,
+            symbol.key
+        );
+    }
+
+    Ok(())
+}
+
+/// Downloads `url` to `partial`, continuing from the end of `partial` if it exists.
+/// Returns `None` if the server doesn't have the file, otherwise whether an earlier
+/// download was resumed
+fn download(client: &HttpClient, url: &str, partial: &Path) -> Result<Option<bool>, AttemptError> {
+    let existing = std::fs::metadata(partial).map_or(0, |metadata| metadata.len());
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.set("Range", &format!("bytes={}-", existing));
+    }
+
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(None),
+        // The partial file is already complete or is longer than the file, so start over
+        Err(ureq::Error::Status(416, _)) => {
+            std::fs::remove_file(partial)?;
+            return download(client, url, partial);
+        }
+        Err(e) => return Err(request_error(e)),
+    };
+
+    // Servers which ignore `Range` send the whole file
+    let resumed = response.status() == 206;
+    let expected = response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok());
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(partial)?;
+
+    let written =
+        std::io::copy(&mut response.into_reader(), &mut file).map_err(|e| AttemptError {
+            error: e.into(),
+            transient: true,
+        })?;
+    if let Some(expected) = expected {
+        if written != expected {
+            return Err(AttemptError {
+                error: anyhow::anyhow!("received {} of {} bytes", written, expected),
+                transient: true,
+            });
+        }
+    }
+
+    Ok(Some(resumed))
+}
+
+/// Returns the file a `file.ptr` redirects to, e.g. `PATH:\\share\foo.pdb`
+fn pointer_target(contents: &str) -> anyhow::Result<PathBuf> {
+    match contents.trim().strip_prefix("PATH:") {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => anyhow::bail!("file.ptr does not point to a file: {}", contents.trim()),
+    }
+}
+
+/// Copies `symbol` from a single tier to `partial`. Returns `None` if the tier doesn't
+/// have it, otherwise whether an earlier download was resumed
+fn fetch_from_tier(
+    client: &HttpClient,
+    location: &str,
+    symbol: &SymbolId,
+    partial: &Path,
+) -> Result<Option<bool>, AttemptError> {
+    let uncompressed = symbol.index_path(Variant::Uncompressed);
+    let pointer = symbol.index_path(Variant::FilePtr);
+    let compressed = symbol.index_path(Variant::Compressed);
+
+    let pointer_contents = if is_url(location) {
+        let base = location.trim_end_matches('/');
+        if let Some(resumed) = download(client, &format!("{}/{}", base, uncompressed), partial)? {
+            return Ok(Some(resumed));
+        }
+
+        match client.get(&format!("{}/{}", base, pointer)).call() {
+            Ok(response) => Some(response.into_string()?),
+            Err(ureq::Error::Status(404, _)) => {
+                if head(client, &format!("{}/{}", base, compressed)).unwrap_or(false) {
+                    return Err(anyhow::anyhow!(
+                        "{} only has a compressed copy, which is not supported",
+                        location
+                    )
+                    .into());
+                }
+                None
+            }
+            Err(e) => return Err(request_error(e)),
+        }
+    } else {
+        let local = |index_path: &str| -> PathBuf {
+            std::iter::once(location)
+                .chain(index_path.split('/'))
+                .collect()
+        };
+        if local(&uncompressed).is_file() {
+            std::fs::copy(local(&uncompressed), partial)?;
+            return Ok(Some(false));
+        }
+        if local(&compressed).is_file() {
+            return Err(anyhow::anyhow!(
+                "{} only has a compressed copy, which is not supported",
+                location
+            )
+            .into());
+        }
+        std::fs::read_to_string(local(&pointer)).ok()
+    };
+
+    // A file.ptr names a path on the machine reading it, which is only meaningful when
+    // it came from a file share. One served over HTTP could point anywhere on this host
+    if pointer_contents.is_some() && is_url(location) {
+        return Err(anyhow::anyhow!(
+            "{} redirects {} through a file.ptr, which is only followed on file shares",
+            location,
+            symbol.name
+        )
+        .into());
+    }
+
+    match pointer_contents {
+        Some(contents) => {
+            std::fs::copy(pointer_target(&contents)?, partial)?;
+            Ok(Some(false))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Downloads `symbol` from the first tier which has it into the destination store.
+/// Downloads are written next to their destination with a `.partial` suffix, which a
+/// later run continues from, and are only moved into place once their identity is
+/// verified. Files already in the destination are verified and kept
+pub fn fetch(
+    client: &HttpClient,
+    symbol: SymbolId,
+    tiers: &[String],
+    options: &FetchOptions,
+) -> FetchResult {
+    let mut result = FetchResult {
+        symbol,
+        path: None,
+        source: None,
+        size: 0,
+        resumed: false,
+        error: None,
+    };
+
+    match fetch_into(client, &mut result, tiers, options) {
+        Ok(path) => {
+            result.size = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+            result.path = Some(path);
+        }
+        Err(e) => result.error = Some(format!("{:#}", e)),
+    }
+
+    result
+}
+
+fn fetch_into(
+    client: &HttpClient,
+    result: &mut FetchResult,
+    tiers: &[String],
+    options: &FetchOptions,
+) -> anyhow::Result<PathBuf> {
+    let symbol = &result.symbol;
+    let destination: PathBuf = std::iter::once(options.destination.as_path())
+        .chain(
+            symbol
+                .index_path(Variant::Uncompressed)
+                .split('/')
+                .map(Path::new),
+        )
+        .collect();
+    if destination.is_file() && verify(&destination, symbol).is_ok() {
+        return Ok(destination);
+    }
+    std::fs::create_dir_all(destination.parent().unwrap())?;
+    let mut partial = destination.clone().into_os_string();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let mut errors = vec![];
+    for location in tiers {
+        let mut backoff = Duration::from_secs(1);
+        let mut attempt = 0;
+        let fetched = loop {
+            match fetch_from_tier(client, location, symbol, &partial) {
+                Ok(fetched) => break fetched,
+                Err(e) if e.transient && attempt < options.retries => {
+                    log::warn!(
+                        "fetching {}/{} from {} failed, retrying in {:?}: {:#}",
+                        symbol.name,
+                        symbol.key,
+                        location,
+                        backoff,
+                        e.error
+                    );
+                    std::thread::sleep(backoff);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    attempt += 1;
+                }
+                Err(e) => {
+                    errors.push(format!("{}: {:#}", location, e.error));
+                    break None;
+                }
+            }
+        };
+
+        let resumed = match fetched {
+            Some(resumed) => resumed,
+            None => continue,
+        };
+        if let Err(e) = verify(&partial, symbol) {
+            std::fs::remove_file(&partial)?;
+            errors.push(format!("{}: {:#}", location, e));
+            continue;
+        }
+
+        std::fs::rename(&partial, &destination)?;
+        result.source = Some(location.clone());
+        result.resumed = resumed;
+        return Ok(destination);
+    }
+
+    if errors.is_empty() {
+        anyhow::bail!("no symbol server has this file");
+    }
+    anyhow::bail!(errors.join("; "))
+}
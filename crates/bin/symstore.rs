@@ -0,0 +1,167 @@
+//! Publishing files to a symbol store in the directory layout written by `symstore.exe`
+//! and read by symbol servers:
+//!
+//! ```text
+//! store/
+//!     pingme.txt
+//!     000Admin/
+//!         server.txt, history.txt, lastid.txt, and a file per transaction
+//!     foo.pdb/
+//!         <GUID><age>/foo.pdb
+//!     foo.exe/
+//!         <timestamp><size of image>/foo.exe
+//! ```
+//!
+//! Stores containing an `index2.txt` use the two-tier layout, which adds a directory
+//! named after the first two characters of each file name above its index directory
+
+use crate::pe::PeInfo;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const ADMIN_DIR: &str = "000Admin";
+
+/// Details recorded in the store's transaction log, shown by `symstore.exe query`
+#[derive(Debug, Default)]
+pub struct TransactionInfo {
+    pub product: String,
+    pub version: String,
+    pub comment: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StoredFile {
+    pub source: PathBuf,
+    /// Path of the copy relative to the store, e.g. `foo.pdb/<GUID><age>/foo.pdb`
+    pub index_path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Transaction {
+    /// Ten digit transaction ID, e.g. `0000000001`
+    pub id: String,
+    pub files: Vec<StoredFile>,
+}
+
+/// Returns the directory name a file is indexed under: `<GUID><age>` for PDBs and
+/// `<timestamp><size of image>` for PE images
+pub fn index_key(path: &Path) -> anyhow::Result<String> {
+    if crate::pe::is_pe(path)? {
+        let pe = PeInfo::open(path)?;
+        return Ok(format!("{:08X}{:x}", pe.timestamp, pe.size_of_image));
+    }
+
+    Ok(ezpdb::streams::debug_identifier(path)?)
+}
+
+/// Formats seconds since the Unix epoch as the UTC `MM/DD/YYYY,HH:MM:SS` used in the
+/// transaction log
+fn format_timestamp(secs: u64) -> String {
+    // Howard Hinnant's days-to-civil conversion
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let time = secs % 86400;
+    format!(
+        "{:02}/{:02}/{:04},{:02}:{:02}:{:02}",
+        month,
+        day,
+        year,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// Appends `line` to the file at `path`, creating it if needed
+fn append_line(path: &Path, line: &str) -> anyhow::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+
+    Ok(())
+}
+
+/// Copies `files` into the symbol store at `store` as a single transaction, creating
+/// the store if it doesn't exist. Files already in the store are replaced
+pub fn add_files(
+    store: &Path,
+    files: &[PathBuf],
+    info: &TransactionInfo,
+) -> anyhow::Result<Transaction> {
+    let admin = store.join(ADMIN_DIR);
+    std::fs::create_dir_all(&admin)?;
+    let pingme = store.join("pingme.txt");
+    if !pingme.exists() {
+        std::fs::write(&pingme, "")?;
+    }
+    let two_tier = store.join("index2.txt").exists();
+
+    let last_id = match std::fs::read_to_string(admin.join("lastid.txt")) {
+        Ok(contents) => contents.trim().parse::<u32>()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e.into()),
+    };
+    let id = format!("{:010}", last_id + 1);
+
+    let mut stored = vec![];
+    let mut entries = vec![];
+    for file in files {
+        let name = file
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("{} is not a file", file.display()))?
+            .to_string_lossy()
+            .into_owned();
+        let key = index_key(file)?;
+
+        let mut components = vec![name.clone(), key.clone(), name.clone()];
+        if two_tier {
+            components.insert(0, name.chars().take(2).collect());
+        }
+        let destination: PathBuf = std::iter::once(store.to_path_buf())
+            .chain(components.iter().map(PathBuf::from))
+            .collect();
+        std::fs::create_dir_all(destination.parent().unwrap())?;
+        std::fs::copy(file, &destination)?;
+        log::debug!("stored {} as {}\\{}", file.display(), name, key);
+
+        let source = std::fs::canonicalize(file).unwrap_or_else(|_| file.clone());
+        entries.push(format!("\"{}\\{}\",\"{}\"", name, key, source.display()));
+        stored.push(StoredFile {
+            source: file.clone(),
+            index_path: components.join("/"),
+        });
+    }
+
+    let mut transaction = std::fs::File::create(admin.join(&id))?;
+    for entry in &entries {
+        writeln!(transaction, "{}", entry)?;
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let record = format!(
+        "{},add,file,{},\"{}\",\"{}\",\"{}\",",
+        id,
+        format_timestamp(now),
+        info.product,
+        info.version,
+        info.comment
+    );
+    append_line(&admin.join("server.txt"), &record)?;
+    append_line(&admin.join("history.txt"), &record)?;
+    std::fs::write(admin.join("lastid.txt"), format!("{}\n", id))?;
+
+    Ok(Transaction { id, files: stored })
+}
@@ -0,0 +1,190 @@
+use serde::Serialize;
+
+/// Trailing arguments which are the template's default and only add noise, e.g. the
+/// allocator of `std::vector<int,std::allocator<int> >`
+const DEFAULT_ARGUMENTS: &[&str] = &[
+    "std::allocator",
+    "std::char_traits",
+    "std::default_delete",
+    "std::equal_to",
+    "std::hash",
+    "std::less",
+];
+
+/// Keywords MSVC puts in front of class types in template arguments
+const TYPE_KEYWORDS: &[&str] = &["class ", "struct ", "union ", "enum "];
+
+/// A C++ name split at its first template argument list, e.g.
+/// `std::map<int,Foo *>::find` into `std::map`, the arguments `int` and `Foo *`, and
+/// the suffix `::find`. Arguments are split the same way in turn
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateName {
+    /// Name preceding the argument list
+    pub name: String,
+    /// Arguments of the template, empty if the name isn't a template instantiation
+    pub arguments: Vec<TemplateName>,
+    /// Whether the name has an argument list, which may be empty
+    pub is_template: bool,
+    /// Remainder of the name after the argument list
+    pub suffix: String,
+}
+
+/// Returns the index of the `>` closing the `<` at `open`
+fn closing_bracket(name: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, byte) in name.bytes().enumerate().skip(open) {
+        match byte {
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Returns the index of the `<` starting the first template argument list, skipping the
+/// angle brackets of `operator<`, `operator<<`, and friends, and of compiler-generated
+/// scopes such as `<lambda_1>`
+fn opening_bracket(name: &str) -> Option<usize> {
+    let mut search_from = 0;
+    loop {
+        let open = search_from + name[search_from..].find('<')?;
+        let preceding = name[..open].trim_end();
+        if preceding.ends_with("operator") {
+            // `operator<<<T>` is `operator<<` followed by an argument list
+            let run = name[open..]
+                .bytes()
+                .take_while(|byte| *byte == b'<')
+                .count();
+            search_from = open + run.min(2);
+        } else if preceding.is_empty() || preceding.ends_with("::") {
+            search_from = closing_bracket(name, open)? + 1;
+        } else {
+            return Some(open);
+        }
+    }
+}
+
+/// Splits a template argument list at its top-level commas
+fn split_arguments(arguments: &str) -> Vec<&str> {
+    let mut split = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, byte) in arguments.bytes().enumerate() {
+        match byte {
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => {
+                split.push(arguments[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(arguments[start..].trim());
+
+    split
+}
+
+impl TemplateName {
+    /// Parses `name`. Names without a well-formed argument list are kept whole in
+    /// [TemplateName::name]
+    pub fn parse(name: &str) -> TemplateName {
+        let name = name.trim();
+        let plain = || TemplateName {
+            name: name.to_string(),
+            arguments: vec![],
+            is_template: false,
+            suffix: String::new(),
+        };
+
+        let open = match opening_bracket(name) {
+            Some(open) => open,
+            None => return plain(),
+        };
+        let close = match closing_bracket(name, open) {
+            Some(close) => close,
+            None => return plain(),
+        };
+
+        let inner = name[open + 1..close].trim();
+        let arguments = if inner.is_empty() {
+            vec![]
+        } else {
+            split_arguments(inner)
+                .into_iter()
+                .map(TemplateName::parse)
+                .collect()
+        };
+
+        TemplateName {
+            name: name[..open].trim_end().to_string(),
+            arguments,
+            is_template: true,
+            suffix: name[close + 1..].to_string(),
+        }
+    }
+
+    /// Returns the template's name with the argument list but without the suffix, e.g.
+    /// `std::map<int,Foo *>` for `std::map<int,Foo *>::find`
+    pub fn instantiation(&self) -> TemplateName {
+        TemplateName {
+            suffix: String::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a shorter, canonical spelling of this name: defaulted trailing
+    /// arguments such as allocators are dropped, `class`/`struct` keywords are
+    /// removed, `std::basic_string<char>` becomes `std::string`, and arguments are
+    /// separated by `, ` with pointer and reference markers attached to their type
+    pub fn normalized(&self) -> String {
+        let mut name = self.name.as_str();
+        for keyword in TYPE_KEYWORDS {
+            if let Some(stripped) = name.strip_prefix(keyword) {
+                name = stripped;
+            }
+        }
+
+        let mut arguments: Vec<String> = self
+            .arguments
+            .iter()
+            .map(TemplateName::normalized)
+            .collect();
+        while arguments.len() > 1 {
+            let last = arguments.last().expect("arguments is not empty");
+            if !DEFAULT_ARGUMENTS
+                .iter()
+                .any(|default| last.starts_with(&format!("{}<", default)))
+            {
+                break;
+            }
+            arguments.pop();
+        }
+
+        let mut normalized = if !self.is_template {
+            name.to_string()
+        } else {
+            match (name, arguments.as_slice()) {
+                ("std::basic_string", [argument]) if argument == "char" => {
+                    "std::string".to_string()
+                }
+                ("std::basic_string", [argument]) if argument == "wchar_t" => {
+                    "std::wstring".to_string()
+                }
+                _ => format!("{}<{}>", name, arguments.join(", ")),
+            }
+        };
+
+        normalized.push_str(self.suffix.trim_end());
+
+        // Attach pointer and reference markers, e.g. `Foo *` becomes `Foo*`
+        normalized.replace(" *", "*").replace(" &", "&")
+    }
+}
@@ -0,0 +1,84 @@
+//! Watching a directory for PDBs and PE images dropped into it, e.g. by a build
+//! pipeline or a sandbox, so each can be parsed as soon as it is complete
+
+use crate::http::HttpClient;
+use notify::{DebouncedEvent, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Summary of a parsed file, as POSTed to the webhook
+#[derive(Debug, Serialize)]
+pub struct WatchEvent {
+    pub path: PathBuf,
+    /// `<name>/<debug identifier>` the file is indexed under on symbol servers
+    pub symbol_id: Option<String>,
+    /// File the output was written to, when writing to an output directory
+    pub output: Option<PathBuf>,
+    /// Why the file couldn't be parsed, if it couldn't
+    pub error: Option<String>,
+}
+
+/// PDBs are recognized by their extension and images by their `MZ` header, so the
+/// outputs written next to them aren't picked up again
+fn is_candidate(path: &Path) -> bool {
+    let is_pdb = path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("pdb"));
+
+    is_pdb || crate::pe::is_pe(path).unwrap_or(false)
+}
+
+/// Calls `handle` with each PDB or PE image created, written, or moved under `dir` once
+/// it has gone unmodified for `settle`. Files are handled again only if their size or
+/// modification time changes. Runs until the watch fails
+pub fn watch(dir: &Path, settle: Duration, mut handle: impl FnMut(&Path)) -> anyhow::Result<()> {
+    let (sender, events) = std::sync::mpsc::channel();
+    let mut watcher = notify::watcher(sender, settle)?;
+    watcher.watch(dir, RecursiveMode::Recursive)?;
+    log::info!("watching {}", dir.display());
+
+    let mut handled: HashMap<PathBuf, (u64, Option<SystemTime>)> = HashMap::new();
+    for event in events {
+        let path = match event {
+            DebouncedEvent::Create(path)
+            | DebouncedEvent::Write(path)
+            | DebouncedEvent::Rename(_, path) => path,
+            DebouncedEvent::Error(e, path) => {
+                log::warn!("error watching {:?}: {}", path, e);
+                continue;
+            }
+            _ => continue,
+        };
+
+        let metadata = match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+        if !is_candidate(&path) {
+            continue;
+        }
+
+        let state = (metadata.len(), metadata.modified().ok());
+        if handled.get(&path) == Some(&state) {
+            continue;
+        }
+        handled.insert(path.clone(), state);
+
+        log::info!("parsing {}", path.display());
+        handle(&path);
+    }
+
+    anyhow::bail!("stopped receiving events for {}", dir.display())
+}
+
+/// POSTs `event` to `url` as JSON
+pub fn post(client: &HttpClient, url: &str, event: &WatchEvent) -> anyhow::Result<()> {
+    client
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&serde_json::to_string(event)?)?;
+
+    Ok(())
+}
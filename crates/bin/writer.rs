@@ -0,0 +1,77 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Destination for output. Compression is chosen from the output file's extension
+pub enum OutputWriter {
+    Stdout(io::StdoutLock<'static>),
+    File(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl OutputWriter {
+    /// Creates a writer for `path`, or stdout if no path is provided. Paths ending in
+    /// `.gz` are gzip compressed and paths ending in `.zst` are zstd compressed
+    pub fn create(path: Option<&Path>) -> io::Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None => return Ok(OutputWriter::Stdout(io::stdout().lock())),
+        };
+
+        let file = BufWriter::new(File::create(path)?);
+        let writer = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("gz") => OutputWriter::Gzip(GzEncoder::new(file, Compression::default())),
+            Some("zst") => OutputWriter::Zstd(zstd::Encoder::new(file, 0)?),
+            _ => OutputWriter::File(file),
+        };
+
+        Ok(writer)
+    }
+
+    /// Flushes all output and writes any trailing compression frames
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            OutputWriter::Stdout(mut stdout) => stdout.flush(),
+            OutputWriter::File(mut file) => file.flush(),
+            OutputWriter::Gzip(encoder) => encoder.finish()?.flush(),
+            OutputWriter::Zstd(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+/// Opens `path` for reading, decompressing it if it was compressed by
+/// [OutputWriter::create] according to its extension
+pub fn open_decompressed(path: &Path) -> io::Result<Box<dyn Read>> {
+    let file = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("gz") => Box::new(BufReader::new(GzDecoder::new(file))),
+        Some("zst") => Box::new(BufReader::new(zstd::Decoder::with_buffer(file)?)),
+        _ => Box::new(file),
+    };
+
+    Ok(reader)
+}
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputWriter::Stdout(stdout) => stdout.write(buf),
+            OutputWriter::File(file) => file.write(buf),
+            OutputWriter::Gzip(encoder) => encoder.write(buf),
+            OutputWriter::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputWriter::Stdout(stdout) => stdout.flush(),
+            OutputWriter::File(file) => file.flush(),
+            OutputWriter::Gzip(encoder) => encoder.flush(),
+            OutputWriter::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
@@ -22,6 +22,12 @@ pub enum Error {
     #[error("IO error occurred: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("ID 0x{0:X} is not an {1} record")]
+    UnexpectedId(u32, &'static str),
+
+    #[error("the PDB does not contain stream `{0}`")]
+    MissingStream(String),
+
     #[error("could not resolve type index {0}")]
     UnresolvedType(TypeIndexNumber),
 }
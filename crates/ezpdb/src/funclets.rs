@@ -0,0 +1,148 @@
+//! Attaching exception handling and unwind funclets to the procedures they were split
+//! out of
+
+use crate::symbol_types::*;
+use std::collections::HashMap;
+
+/// How a funclet's name refers to its parent procedure
+enum ParentName<'a> {
+    /// Undecorated name, e.g. `Foo` from `` `Foo'::`1'::catch$0 ``
+    Undecorated(&'a str),
+    /// Decorated name, e.g. `?Foo@@YAXXZ` from `?catch$0@?0??Foo@@YAXXZ@4HA`
+    Decorated(String),
+}
+
+fn funclet_kind(name: &str) -> Option<FuncletKind> {
+    let kinds = [
+        ("catch$", FuncletKind::Catch),
+        ("fin$", FuncletKind::Finally),
+        ("finally$", FuncletKind::Finally),
+        ("filt$", FuncletKind::Filter),
+        ("except$", FuncletKind::Filter),
+        ("dtor$", FuncletKind::Unwind),
+        ("unwind$", FuncletKind::Unwind),
+    ];
+
+    kinds
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, kind)| *kind)
+}
+
+/// Returns the kind of funclet `name` belongs to and the name of its parent, or `None`
+/// if `name` isn't a funclet's
+fn parse_funclet_name(name: &str) -> Option<(FuncletKind, ParentName<'_>)> {
+    // `Foo'::`1'::catch$0
+    if let Some(scoped) = name.strip_prefix('`') {
+        let end = scoped.find("'::`")?;
+        let kind = funclet_kind(name.rsplit("::").next()?)?;
+        return Some((kind, ParentName::Undecorated(&scoped[..end])));
+    }
+
+    // __catch$?Foo@@YAXXZ$0
+    if let Some(rest) = name.strip_prefix("__") {
+        let kind = funclet_kind(rest)?;
+        let start = rest.find('$')? + 1;
+        let end = rest.rfind('$').filter(|end| *end > start)?;
+        return Some((kind, ParentName::Decorated(rest[start..end].to_string())));
+    }
+
+    // ?catch$0@?0??Foo@@YAXXZ@4HA
+    let rest = name.strip_prefix('?')?;
+    let kind = funclet_kind(rest)?;
+    let start = rest.find("??")? + 2;
+    let parent = rest[start..].strip_suffix("@4HA")?;
+    Some((kind, ParentName::Decorated(format!("?{}", parent))))
+}
+
+impl ParsedPdb {
+    /// Moves procedures which are exception handling or unwind funclets, such as
+    /// `` `Foo'::`1'::catch$0 `` and `?dtor$2@?0??Foo@@YAXXZ@4HA`, out of
+    /// [ParsedPdb::procedures] and into the [Procedure::funclets] of their parent.
+    /// Parents named by their decorated name are found through the public symbol of
+    /// that name. Parents named by their undecorated name may be overloaded, so the
+    /// closest one preceding the funclet is picked, since funclets are emitted after
+    /// their parent. Returns the number of funclets moved
+    pub fn nest_funclets(&mut self) -> usize {
+        let mut parents = HashMap::new();
+        {
+            let public_offsets: HashMap<&str, usize> = self
+                .public_symbols
+                .iter()
+                .filter_map(|symbol| Some((symbol.name.as_str(), symbol.offset?)))
+                .collect();
+            let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+            let mut by_offset = HashMap::new();
+            let funclets: Vec<Option<(FuncletKind, ParentName<'_>)>> = self
+                .procedures
+                .iter()
+                .map(|procedure| parse_funclet_name(&procedure.name))
+                .collect();
+            for (i, procedure) in self.procedures.iter().enumerate() {
+                if funclets[i].is_some() {
+                    continue;
+                }
+                by_name.entry(procedure.name.as_str()).or_default().push(i);
+                if let Some(offset) = procedure.offset {
+                    by_offset.insert(offset, i);
+                }
+            }
+
+            for (i, funclet) in funclets.into_iter().enumerate() {
+                let (kind, parent_name) = match funclet {
+                    Some(funclet) => funclet,
+                    None => continue,
+                };
+                let offset = self.procedures[i].offset;
+                let parent = match parent_name {
+                    ParentName::Decorated(name) => public_offsets
+                        .get(name.as_str())
+                        .and_then(|offset| by_offset.get(offset))
+                        .copied(),
+                    ParentName::Undecorated(name) => by_name.get(name).and_then(|candidates| {
+                        candidates
+                            .iter()
+                            .filter(|candidate| self.procedures[**candidate].offset <= offset)
+                            .max_by_key(|candidate| self.procedures[**candidate].offset)
+                            .or_else(|| candidates.first())
+                            .copied()
+                    }),
+                };
+
+                if let Some(parent) = parent {
+                    parents.insert(i, (parent, kind));
+                }
+            }
+        }
+
+        if parents.is_empty() {
+            return 0;
+        }
+
+        let mut funclets: HashMap<usize, Vec<Funclet>> = HashMap::new();
+        let mut kept = Vec::with_capacity(self.procedures.len() - parents.len());
+        for (i, procedure) in std::mem::take(&mut self.procedures).into_iter().enumerate() {
+            match parents.get(&i) {
+                Some((parent, kind)) => funclets.entry(*parent).or_default().push(Funclet {
+                    name: procedure.name,
+                    kind: *kind,
+                    offset: procedure.offset,
+                    len: procedure.len,
+                }),
+                None => kept.push((i, procedure)),
+            }
+        }
+
+        self.procedures = kept
+            .into_iter()
+            .map(|(i, mut procedure)| {
+                if let Some(funclets) = funclets.remove(&i) {
+                    procedure.funclets.extend(funclets);
+                }
+                procedure
+            })
+            .collect();
+
+        parents.len()
+    }
+}
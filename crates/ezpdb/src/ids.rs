@@ -0,0 +1,89 @@
+//! Resolves records in the ID (IPI) stream to the strings and names they describe
+
+use crate::error::Error;
+use crate::symbol_types::ParsedPdb;
+use crate::type_info::Type;
+use pdb::{IdData, IdFinder, IdIndex};
+
+/// Substring lists only nest one level deep in practice. This bounds the recursion on
+/// corrupt PDBs whose string IDs refer to each other
+const MAX_SUBSTRING_DEPTH: usize = 4;
+
+/// Returns the string an `LF_STRING_ID` holds. Strings too long for a single record
+/// (e.g. long compiler command lines) are split, with the leading parts listed in an
+/// `LF_SUBSTR_LIST`
+pub fn string_id(finder: &IdFinder<'_>, index: IdIndex) -> Result<String, Error> {
+    string_id_at_depth(finder, index, 0)
+}
+
+fn string_id_at_depth(
+    finder: &IdFinder<'_>,
+    index: IdIndex,
+    depth: usize,
+) -> Result<String, Error> {
+    let string = match finder.find(index)?.parse()? {
+        IdData::String(string) => string,
+        _ => return Err(Error::UnexpectedId(index.0, "LF_STRING_ID")),
+    };
+
+    let mut value = String::new();
+    if let Some(substrings) = string.substrings {
+        if depth >= MAX_SUBSTRING_DEPTH {
+            return Err(Error::UnexpectedId(
+                substrings.0,
+                "a shallow LF_SUBSTR_LIST",
+            ));
+        }
+
+        match finder.find(substrings)?.parse()? {
+            IdData::StringList(list) => {
+                for substring in list.substrings {
+                    value.push_str(&string_id_at_depth(
+                        finder,
+                        IdIndex(substring.0),
+                        depth + 1,
+                    )?);
+                }
+            }
+            _ => return Err(Error::UnexpectedId(substrings.0, "LF_SUBSTR_LIST")),
+        }
+    }
+    value.push_str(&string.name.to_string());
+
+    Ok(value)
+}
+
+/// Returns the qualified name of the function an `LF_FUNC_ID` or `LF_MFUNC_ID` refers
+/// to, e.g. `ns::Class::method`. Member functions are qualified by their class, which
+/// must already be in `pdb.types`
+pub fn function_id_name(
+    finder: &IdFinder<'_>,
+    index: IdIndex,
+    pdb: &ParsedPdb,
+) -> Result<String, Error> {
+    match finder.find(index)?.parse()? {
+        IdData::Function(function) => {
+            let name = function.name.to_string();
+            match function.scope {
+                Some(scope) => Ok(format!("{}::{}", string_id(finder, scope)?, name)),
+                None => Ok(name.into_owned()),
+            }
+        }
+        IdData::MemberFunction(function) => {
+            let name = function.name.to_string();
+            let parent = pdb.types.get(&function.parent.0).and_then(|parent| {
+                match &*parent.as_ref().borrow() {
+                    Type::Class(class) => Some(class.name.clone()),
+                    Type::Union(union) => Some(union.name.clone()),
+                    _ => None,
+                }
+            });
+
+            match parent {
+                Some(parent) => Ok(format!("{}::{}", parent, name)),
+                None => Ok(name.into_owned()),
+            }
+        }
+        _ => Err(Error::UnexpectedId(index.0, "LF_FUNC_ID or LF_MFUNC_ID")),
+    }
+}
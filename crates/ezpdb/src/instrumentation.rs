@@ -0,0 +1,96 @@
+//! Detection of sanitizer, coverage, and profiling instrumentation
+
+use crate::symbol_types::{Instrumentation, ParsedPdb};
+
+/// Name fragments of runtime symbols, and the instrumentation each indicates. Matched
+/// anywhere in the name so x86 decoration doesn't matter
+const SYMBOL_FRAGMENTS: &[(&str, Instrumentation)] = &[
+    ("__asan_", Instrumentation::AddressSanitizer),
+    ("__ubsan_", Instrumentation::UndefinedBehaviorSanitizer),
+    ("__sancov_", Instrumentation::SanitizerCoverage),
+    ("__sanitizer_cov_", Instrumentation::SanitizerCoverage),
+    ("LLVMFuzzerTestOneInput", Instrumentation::LibFuzzer),
+    ("__llvm_profile_", Instrumentation::ProfileInstrumentation),
+    ("__profc_", Instrumentation::ProfileInstrumentation),
+    ("PogoAutoSweep", Instrumentation::ProfileInstrumentation),
+];
+
+/// Values of `-fsanitize=`, which takes a comma-separated list
+const SANITIZE_VALUES: &[(&str, Instrumentation)] = &[
+    ("address", Instrumentation::AddressSanitizer),
+    ("undefined", Instrumentation::UndefinedBehaviorSanitizer),
+    ("fuzzer", Instrumentation::LibFuzzer),
+];
+
+/// Compiler flags, without their `/` or `-` prefix, and the instrumentation each enables
+const FLAG_PREFIXES: &[(&str, Instrumentation)] = &[
+    ("fsanitize-coverage", Instrumentation::SanitizerCoverage),
+    (
+        "fprofile-instr-generate",
+        Instrumentation::ProfileInstrumentation,
+    ),
+    ("fprofile-generate", Instrumentation::ProfileInstrumentation),
+    ("GENPROFILE", Instrumentation::ProfileInstrumentation),
+];
+
+/// Detects instrumentation from the public symbols, procedures, compile flags, and build
+/// command lines of every module. Returns each kind found once, in a stable order
+pub fn detect_instrumentation(pdb_info: &ParsedPdb) -> Vec<Instrumentation> {
+    let mut found = vec![];
+    let mut add = |instrumentation: Instrumentation| {
+        if !found.contains(&instrumentation) {
+            found.push(instrumentation);
+        }
+    };
+
+    let names = pdb_info
+        .public_symbols
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .chain(
+            pdb_info
+                .procedures
+                .iter()
+                .map(|procedure| procedure.name.as_str()),
+        );
+    for name in names {
+        for (fragment, instrumentation) in SYMBOL_FRAGMENTS {
+            if name.contains(fragment) {
+                add(*instrumentation);
+            }
+        }
+    }
+
+    for module in &pdb_info.debug_modules {
+        if let Some(compiler_info) = &module.compiler_info {
+            if compiler_info.flags.pgo {
+                add(Instrumentation::ProfileGuidedOptimization);
+            }
+        }
+
+        let arguments = module
+            .build_info
+            .as_ref()
+            .and_then(|build_info| build_info.command_arguments());
+        for argument in arguments.into_iter().flat_map(str::split_whitespace) {
+            let argument = argument.trim_start_matches(|c| c == '/' || c == '-');
+            if let Some(values) = argument.strip_prefix("fsanitize=") {
+                for value in values.split(',') {
+                    for (name, instrumentation) in SANITIZE_VALUES {
+                        if value == *name {
+                            add(*instrumentation);
+                        }
+                    }
+                }
+            }
+            for (prefix, instrumentation) in FLAG_PREFIXES {
+                if argument.starts_with(prefix) {
+                    add(*instrumentation);
+                }
+            }
+        }
+    }
+
+    found.sort();
+    found
+}
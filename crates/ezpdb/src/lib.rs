@@ -1,27 +1,87 @@
 use crate::error::Error;
+use crate::options::{ParseOptions, SymbolKind};
+use crate::progress::{CountingReader, ModuleTimings, Phase, ProgressEvent, ProgressTracker};
 use crate::symbol_types::*;
 use log::{debug, warn};
 use pdb::{
-    AddressMap, AnnotationReferenceSymbol, FallibleIterator, IdIndex, ItemFinder, Symbol,
-    SymbolData, TypeData, TypeIndex, PDB,
+    AddressMap, AnnotationReferenceSymbol, FallibleIterator, IdData, IdIndex, ItemFinder,
+    StreamIndex, StringTable, Symbol, SymbolData, TypeData, TypeIndex,
+    UserDefinedTypeSourceFileRef, UserDefinedTypeSourceId, PDB,
 };
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::fs::File;
 use std::path::Path;
 use std::rc::Rc;
+use std::time::Instant;
 
 pub mod error;
+pub mod funclets;
+pub mod ids;
+pub mod instrumentation;
+pub mod lookup;
+pub mod msf;
+pub mod options;
+pub mod probe;
+pub mod progress;
+pub mod stats;
+pub mod streams;
 pub mod symbol_types;
 pub mod type_info;
 
+pub use crate::options::ParseOptions;
 pub use crate::symbol_types::ParsedPdb;
 
-pub fn parse_pdb<P: AsRef<Path>>(
+/// Parses the PDB at `path`. All offsets in the result are RVAs; use
+/// [ParsedPdb::rebase] to convert them to virtual addresses
+pub fn parse_pdb<P: AsRef<Path>>(path: P) -> Result<ParsedPdb, crate::error::Error> {
+    parse_pdb_with_progress(path, &mut |_event| {})
+}
+
+/// Same as [parse_pdb], but invokes `progress` as parsing moves through each [Phase]
+/// and as each module is parsed
+pub fn parse_pdb_with_progress<P: AsRef<Path>>(
+    path: P,
+    progress: &mut dyn FnMut(&ProgressEvent),
+) -> Result<ParsedPdb, crate::error::Error> {
+    parse_pdb_with_options(path, &ParseOptions::default(), progress)
+}
+
+/// Same as [parse_pdb_with_progress], but only parses what `options` asks for
+pub fn parse_pdb_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ParseOptions,
+    progress: &mut dyn FnMut(&ProgressEvent),
+) -> Result<ParsedPdb, crate::error::Error> {
+    parse_pdb_with_sink(path, options, progress, &mut |_pdb, _module| Ok(()))
+}
+
+/// The records of a module which was just parsed, passed to the sink given to
+/// [parse_pdb_with_sink]
+#[derive(Debug, Clone)]
+pub struct ParsedModule {
+    /// Index of the module in [ParsedPdb::debug_modules]
+    pub index: usize,
+    /// Indices of the module's procedures in [ParsedPdb::procedures]
+    pub procedures: std::ops::Range<usize>,
+}
+
+/// Same as [parse_pdb_with_options], but invokes `sink` with the partially parsed PDB
+/// after each module's symbols and lines are parsed. The sink may move the module's
+/// procedures and lines out of the PDB, e.g. to write them to disk, so they aren't all
+/// held in memory at once. Modules are parsed before section contributions and before
+/// lazily resolved types are completed.
+pub fn parse_pdb_with_sink<P: AsRef<Path>>(
     path: P,
-    base_address: Option<usize>,
+    options: &ParseOptions,
+    progress: &mut dyn FnMut(&ProgressEvent),
+    sink: &mut dyn FnMut(&mut ParsedPdb, ParsedModule) -> Result<(), Error>,
 ) -> Result<ParsedPdb, crate::error::Error> {
-    let file = File::open(path.as_ref())?;
+    let (file, bytes_read) = CountingReader::new(File::open(path.as_ref())?);
+    let mut progress = ProgressTracker::new(progress, bytes_read);
+
+    progress.start(Phase::Headers);
     debug!("opening PDB");
     let mut pdb = PDB::open(file)?;
 
@@ -41,23 +101,57 @@ pub fn parse_pdb<P: AsRef<Path>>(
     output_pdb.guid = pdbi.guid;
     output_pdb.timestamp = pdbi.signature;
     output_pdb.version = (&pdbi.version).into();
+    if let Some(info_stream) = pdb.raw_stream(StreamIndex(1))? {
+        output_pdb.is_fastlink = streams::pdb_features(info_stream.as_slice())
+            .map_or(false, |features| {
+                features.contains(&streams::FASTLINK_FEATURE)
+            });
+    }
+
+    if options.string_table {
+        match pdb.named_stream(b"/names") {
+            Ok(names) => {
+                output_pdb.string_table = streams::names_stream_strings(names.as_slice())
+                    .unwrap_or_else(|| {
+                        warn!("the /names stream is malformed");
+                        vec![]
+                    })
+            }
+            Err(e) => warn!("failed to read the /names stream: {}", e),
+        }
+    }
+
+    debug!("grabbing section headers");
+    if let Some(sections) = pdb.sections()? {
+        output_pdb.sections = sections
+            .iter()
+            .enumerate()
+            .map(|(i, header)| (i + 1, header).into())
+            .collect();
+    }
 
     debug!("getting address map");
     let address_map = pdb.address_map().ok();
     debug!("grabbing string table");
     let string_table = pdb.string_table().ok();
 
+    progress.start(Phase::Ids);
     debug!("fetching ID information");
     // Some symbols such as build information rely on IDs being known. Iterate these to
     // build the database
     let id_information = pdb.id_information();
+    let mut udt_sources = vec![];
     let id_finder = match &id_information {
         Ok(id_information) => {
             debug!("ID information header was valid");
             let mut id_finder = id_information.finder();
             let mut iter = id_information.iter();
-            while let Some(_id) = iter.next()? {
+            while let Some(id) = iter.next()? {
                 id_finder.update(&iter);
+                // Types are parsed after IDs, so hold onto these until they can be applied
+                if let Ok(IdData::UserDefinedTypeSource(source)) = id.parse() {
+                    udt_sources.push(source);
+                }
             }
 
             Some(id_finder)
@@ -68,6 +162,7 @@ pub fn parse_pdb<P: AsRef<Path>>(
         }
     };
 
+    progress.start(Phase::Types);
     debug!("grabbing type information");
     // Parse type information first. Some symbol info (such as function signatures) depends
     // upon type information, but not vice versa
@@ -75,13 +170,30 @@ pub fn parse_pdb<P: AsRef<Path>>(
     let mut type_finder = type_information.finder();
     let mut iter = type_information.iter();
     let mut discovered_types = vec![];
+    // Only used for lazy resolution: definitions by name, for resolving forward references
+    let mut definitions = HashMap::new();
     while let Some(typ) = iter.next()? {
         type_finder.update(&iter);
-        discovered_types.push(typ.index());
+        if !options.lazy_types {
+            discovered_types.push(typ.index());
+            continue;
+        }
+
+        if let Some((name, key)) = definition_key(&typ) {
+            if options.includes_type_name(&name) {
+                discovered_types.push(typ.index());
+            }
+            definitions.insert(key, typ.index());
+        }
     }
 
     for typ in discovered_types.iter() {
-        let _typ = match handle_type(*typ, &mut output_pdb, &type_finder) {
+        let result = handle_type(*typ, &mut output_pdb, &type_finder);
+        if options.raw {
+            record_raw_type(&mut output_pdb, &type_finder, *typ, result.as_ref().err());
+        }
+
+        let _typ = match result {
             Ok(typ) => typ,
             Err(Error::PdbCrateError(e @ pdb::Error::UnimplementedTypeKind(_))) => {
                 warn!("Could not parse type: {}", e);
@@ -98,11 +210,14 @@ pub fn parse_pdb<P: AsRef<Path>>(
         };
     }
 
-    // Iterate through all of the parsed types once just to update any necessary info
-    for typ in output_pdb.types.values() {
-        use crate::type_info::Typed;
-
-        typ.as_ref().borrow_mut().on_complete(&output_pdb);
+    // Lazily resolved types are completed once the symbols referencing them are parsed
+    if !options.lazy_types {
+        complete_types(
+            &mut output_pdb,
+            &udt_sources,
+            id_finder.as_ref(),
+            string_table.as_ref(),
+        );
     }
 
     // Iterate through all of the parsed types once just to update any necessary info
@@ -110,57 +225,629 @@ pub fn parse_pdb<P: AsRef<Path>>(
     //     println!("{:#?}", typ.as_ref().borrow());
     // }
 
+    progress.start(Phase::GlobalSymbols);
     debug!("grabbing public symbols");
     // Parse public symbols
     let symbol_table = pdb.global_symbols()?;
     let mut symbols = symbol_table.iter();
     while let Some(symbol) = symbols.next()? {
-        if let Err(e) = handle_symbol(
+        let result = handle_symbol(
             symbol,
             &mut output_pdb,
             address_map.as_ref(),
             &type_finder,
             id_finder.as_ref(),
-            base_address,
-        ) {
+            options,
+        );
+        if options.raw {
+            record_raw_symbol(&mut output_pdb, "globals", symbol, result.as_ref().err());
+        }
+        if let Err(e) = result {
             warn!("Error handling symbol {:?}: {}", symbol, e);
         }
     }
 
+    progress.start(Phase::Modules);
     debug!("grabbing debug modules");
     // Parse private symbols
     let debug_info = pdb.debug_information()?;
-    let mut modules = debug_info.modules()?;
-    while let Some(module) = modules.next()? {
+    let module_count = debug_info.modules()?.count()?;
+    let mut modules = debug_info.modules()?.enumerate();
+    while let Some((module_index, module)) = modules.next()? {
+        progress.module_started(module_index, module_count, &module.module_name());
+
+        // Skipped modules are still listed since section contributions refer to modules
+        // by position, but their module info stream is never read
+        if !options.includes_module(&module.module_name(), &module.object_file_name()) {
+            output_pdb
+                .debug_modules
+                .push((&module, None, string_table.as_ref()).into());
+            progress.module_finished(module_index, &module.module_name(), Default::default());
+            continue;
+        }
+
         let module_info = pdb.module_info(&module)?;
         output_pdb
             .debug_modules
             .push((&module, module_info.as_ref(), string_table.as_ref()).into());
         if module_info.is_none() {
             warn!("Could not get module info for debug module: {:?}", module);
+            progress.module_finished(module_index, &module.module_name(), Default::default());
             continue;
         }
 
-        debug!("grabbing symbols for module: {}", module.module_name());
         let module_info = module_info.unwrap();
+        let procedures_start = output_pdb.procedures.len();
+
+        let mut timings = ModuleTimings::default();
+        let lines_start = Instant::now();
+        if let (Some(string_table), Ok(line_program)) =
+            (string_table.as_ref(), module_info.line_program())
+        {
+            debug!("grabbing line info for module: {}", module.module_name());
+            let mut lines = vec![];
+            let mut line_iter = line_program.lines();
+            loop {
+                let line = match line_iter.next() {
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    // The iterator can't resume past a malformed record, so keep the lines
+                    // read so far and move on to the module's symbols
+                    Err(e) => {
+                        warn!(
+                            "Malformed line record in module {}, skipping the rest of its \
+                            line program: {}",
+                            module.module_name(),
+                            e
+                        );
+                        break;
+                    }
+                };
+                match (&line, &line_program, string_table, address_map.as_ref()).try_into() {
+                    Ok(line) => lines.push(line),
+                    Err(e) => warn!("Error handling line info {:?}: {}", line, e),
+                }
+            }
+
+            output_pdb
+                .debug_modules
+                .last_mut()
+                .expect("debug module was just pushed")
+                .lines = lines;
+        }
+        timings.line_program_us = lines_start.elapsed().as_micros() as u64;
+
+        let symbols_start = Instant::now();
+        debug!("grabbing symbols for module: {}", module.module_name());
         let mut symbol_iter = module_info.symbols()?;
         while let Some(symbol) = symbol_iter.next()? {
-            if let Err(e) = handle_symbol(
+            let result = handle_symbol(
                 symbol,
                 &mut output_pdb,
                 address_map.as_ref(),
                 &type_finder,
                 id_finder.as_ref(),
-                base_address,
-            ) {
+                options,
+            );
+            if options.raw {
+                record_raw_symbol(
+                    &mut output_pdb,
+                    &module.module_name(),
+                    symbol,
+                    result.as_ref().err(),
+                );
+            }
+            if let Err(e) = result {
                 warn!("Error handling symbol {:?}: {}", symbol, e);
             }
         }
+        timings.symbols_us = symbols_start.elapsed().as_micros() as u64;
+
+        let procedures_end = output_pdb.procedures.len();
+        sink(
+            &mut output_pdb,
+            ParsedModule {
+                index: module_index,
+                procedures: procedures_start..procedures_end,
+            },
+        )?;
+
+        progress.module_finished(module_index, &module.module_name(), timings);
+    }
+
+    progress.start(Phase::SectionContributions);
+    debug!("grabbing section contributions");
+    let mut contributions = debug_info.section_contributions()?;
+    while let Some(contribution) = contributions.next()? {
+        output_pdb
+            .section_contributions
+            .push((&contribution, address_map.as_ref()).into());
+    }
+    progress.finish();
+
+    if options.lazy_types {
+        resolve_definitions(&mut output_pdb, &definitions, &type_finder);
+        complete_types(
+            &mut output_pdb,
+            &udt_sources,
+            id_finder.as_ref(),
+            string_table.as_ref(),
+        );
+    }
+
+    link_methods(&mut output_pdb);
+
+    output_pdb.assembly_info.instrumentation = instrumentation::detect_instrumentation(&output_pdb);
+
+    if output_pdb.is_fastlink {
+        report_fastlink(&output_pdb);
     }
 
     Ok(output_pdb)
 }
 
+/// Updates information which depends on other types, then attaches the source location
+/// of each user-defined type
+fn complete_types(
+    output_pdb: &mut ParsedPdb,
+    udt_sources: &[UserDefinedTypeSourceId],
+    id_finder: Option<&ItemFinder<'_, IdIndex>>,
+    string_table: Option<&StringTable<'_>>,
+) {
+    link_forward_references(output_pdb);
+    name_nested_types(output_pdb);
+
+    // Iterate through all of the parsed types once just to update any necessary info
+    for typ in output_pdb.types.values() {
+        use crate::type_info::Typed;
+
+        typ.as_ref().borrow_mut().on_complete(output_pdb);
+    }
+
+    apply_udt_sources(output_pdb, udt_sources, id_finder, string_table);
+}
+
+/// Points each forward reference to a class, union, or enum at the key of its definition
+/// in [ParsedPdb::types], so that neither consumers nor size calculations have to
+/// search for it
+fn link_forward_references(output_pdb: &ParsedPdb) {
+    use std::mem::discriminant;
+
+    // Keyed by kind as well since a class and an enum may share a name
+    let mut definitions = HashMap::new();
+    for (index, typ) in &output_pdb.types {
+        let typ = typ.as_ref().borrow();
+        if let Some((key, false)) = typ.definition_key() {
+            definitions
+                .entry((discriminant(&*typ), key.to_string()))
+                .or_insert(*index);
+        }
+    }
+
+    for typ in output_pdb.types.values() {
+        let mut typ = typ.as_ref().borrow_mut();
+        let definition = match typ.definition_key() {
+            Some((key, true)) => definitions
+                .get(&(discriminant(&*typ), key.to_string()))
+                .copied(),
+            _ => None,
+        };
+        if let Some(definition) = definition {
+            typ.set_definition(definition);
+        }
+    }
+}
+
+/// Links nested types and the types of anonymous members to the class or union they're
+/// declared in, and gives anonymous types a name built from their parent's name and the
+/// key of their definition, e.g. `Outer::<unnamed_0x1234>`. Forward references get the
+/// same parent and name as their definition
+fn name_nested_types(output_pdb: &ParsedPdb) {
+    use crate::type_info::Type;
+
+    let keys: HashMap<*const RefCell<Type>, TypeIndexNumber> = output_pdb
+        .types
+        .iter()
+        .map(|(index, typ)| (Rc::as_ptr(typ), *index))
+        .collect();
+    let definition_of = |index: TypeIndexNumber| {
+        output_pdb.types[&index]
+            .as_ref()
+            .borrow()
+            .definition()
+            .unwrap_or(index)
+    };
+
+    let mut parents = HashMap::new();
+    for (index, typ) in &output_pdb.types {
+        let typ = typ.as_ref().borrow();
+        let fields = match &*typ {
+            Type::Class(class) if !class.properties.forward_reference => &class.fields,
+            Type::Union(union) if !union.properties.forward_reference => &union.fields,
+            _ => continue,
+        };
+
+        for field in fields {
+            let (child, is_member) = match &*field.as_ref().borrow() {
+                Type::Nested(nested) => (Rc::as_ptr(&nested.nested_type), false),
+                Type::Member(member) => (Rc::as_ptr(&member.underlying_type), true),
+                _ => continue,
+            };
+            let child = match keys.get(&child) {
+                Some(child) => definition_of(*child),
+                None => continue,
+            };
+            // Named member types are declared elsewhere
+            if child == *index
+                || (is_member && !output_pdb.types[&child].as_ref().borrow().is_anonymous())
+            {
+                continue;
+            }
+
+            parents.entry(child).or_insert(*index);
+        }
+    }
+
+    let mut names = HashMap::new();
+    for index in output_pdb.types.keys() {
+        let definition = definition_of(*index);
+        if output_pdb.types[&definition]
+            .as_ref()
+            .borrow()
+            .is_anonymous()
+        {
+            names.insert(
+                *index,
+                synthesized_name(output_pdb, &parents, definition, 0),
+            );
+        }
+    }
+
+    for (index, typ) in &output_pdb.types {
+        let mut typ = typ.as_ref().borrow_mut();
+        if let Some(parent) = parents.get(&typ.definition().unwrap_or(*index)) {
+            typ.set_parent(*parent);
+        }
+        if let Some(name) = names.remove(index) {
+            typ.set_synthesized_name(name);
+        }
+    }
+}
+
+/// Returns `definition`'s name qualified by the names of its anonymous parents
+fn synthesized_name(
+    output_pdb: &ParsedPdb,
+    parents: &HashMap<TypeIndexNumber, TypeIndexNumber>,
+    definition: TypeIndexNumber,
+    depth: usize,
+) -> String {
+    // Guards against cycles in malformed type information
+    const MAX_DEPTH: usize = 32;
+
+    let typ = output_pdb.types[&definition].as_ref().borrow();
+    if !typ.is_anonymous() {
+        return typ.display_name().unwrap_or_default().to_string();
+    }
+
+    match parents.get(&definition) {
+        Some(parent) if depth < MAX_DEPTH => format!(
+            "{}::<unnamed_{:#x}>",
+            synthesized_name(output_pdb, parents, *parent, depth + 1),
+            definition
+        ),
+        _ => format!("<unnamed_{:#x}>", definition),
+    }
+}
+
+/// Links each procedure implementing a method to the class declaring it, and each
+/// method to the offsets of its implementations. Procedures are matched by their
+/// qualified name and type, so overloads are told apart
+fn link_methods(output_pdb: &mut ParsedPdb) {
+    use crate::type_info::Type;
+
+    let keys: HashMap<*const RefCell<Type>, TypeIndexNumber> = output_pdb
+        .types
+        .iter()
+        .map(|(index, typ)| (Rc::as_ptr(typ), *index))
+        .collect();
+    let method_key = |class: &str, name: &str, method_type: &TypeRef| {
+        keys.get(&Rc::as_ptr(method_type))
+            .map(|key| (format!("{}::{}", class, name), *key))
+    };
+
+    let mut declarations = HashMap::new();
+    for (index, typ) in &output_pdb.types {
+        let typ = typ.as_ref().borrow();
+        let class = match &*typ {
+            Type::Class(class) if !class.properties.forward_reference => class,
+            _ => continue,
+        };
+
+        for field in &class.fields {
+            match &*field.as_ref().borrow() {
+                Type::Method(method) => {
+                    if let Some(key) = method_key(&class.name, &method.name, &method.method_type) {
+                        declarations.insert(key, *index);
+                    }
+                }
+                Type::OverloadedMethod(overloaded) => {
+                    if let Type::MethodList(list) = &*overloaded.method_list.as_ref().borrow() {
+                        for entry in &list.0 {
+                            if let Some(key) =
+                                method_key(&class.name, &overloaded.name, &entry.method_type)
+                            {
+                                declarations.insert(key, *index);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if declarations.is_empty() {
+        return;
+    }
+
+    let mut implementations: HashMap<(String, TypeIndexNumber), Vec<usize>> = HashMap::new();
+    for procedure in &mut output_pdb.procedures {
+        let key = (procedure.name.clone(), procedure.type_index);
+        if let Some(class) = declarations.get(&key) {
+            procedure.declaring_class = Some(*class);
+            if let Some(offset) = procedure.offset {
+                implementations.entry(key).or_default().push(offset);
+            }
+        }
+    }
+
+    for typ in output_pdb.types.values() {
+        let typ = typ.as_ref().borrow();
+        let class = match &*typ {
+            Type::Class(class) if !class.properties.forward_reference => class,
+            _ => continue,
+        };
+
+        for field in &class.fields {
+            match &mut *field.as_ref().borrow_mut() {
+                Type::Method(method) => {
+                    if let Some(key) = method_key(&class.name, &method.name, &method.method_type) {
+                        method.implementations =
+                            implementations.get(&key).cloned().unwrap_or_default();
+                    }
+                }
+                Type::OverloadedMethod(overloaded) => {
+                    let mut list = overloaded.method_list.as_ref().borrow_mut();
+                    if let Type::MethodList(list) = &mut *list {
+                        for entry in &mut list.0 {
+                            if let Some(key) =
+                                method_key(&class.name, &overloaded.name, &entry.method_type)
+                            {
+                                entry.implementations =
+                                    implementations.get(&key).cloned().unwrap_or_default();
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Returns the name of a class, union, or enum definition along with the key forward
+/// references to it are resolved by: its unique name, or its name if it has none
+fn definition_key(typ: &pdb::Type<'_>) -> Option<(String, String)> {
+    let (name, unique_name) = match typ.parse().ok()? {
+        TypeData::Class(class) if !class.properties.forward_reference() => {
+            (class.name, class.unique_name)
+        }
+        TypeData::Union(union) if !union.properties.forward_reference() => {
+            (union.name, union.unique_name)
+        }
+        TypeData::Enumeration(e) if !e.properties.forward_reference() => (e.name, e.unique_name),
+        _ => return None,
+    };
+
+    let name = name.to_string().into_owned();
+    let key = unique_name
+        .map(|unique_name| unique_name.to_string().into_owned())
+        .unwrap_or_else(|| name.clone());
+    Some((name, key))
+}
+
+/// Resolves the definitions of forward references among the lazily resolved types, and
+/// the definitions those depend on in turn
+fn resolve_definitions(
+    output_pdb: &mut ParsedPdb,
+    definitions: &HashMap<String, TypeIndex>,
+    type_finder: &ItemFinder<'_, TypeIndex>,
+) {
+    use crate::type_info::Type;
+
+    // Definitions which failed to convert, so they aren't retried
+    let mut failed = HashSet::new();
+    loop {
+        let pending: HashSet<TypeIndex> = output_pdb
+            .types
+            .values()
+            .filter_map(|typ| {
+                let typ = typ.as_ref().borrow();
+                let (properties, name, unique_name) = match &*typ {
+                    Type::Class(class) => (&class.properties, &class.name, &class.unique_name),
+                    Type::Union(union) => (&union.properties, &union.name, &union.unique_name),
+                    Type::Enumeration(e) => (&e.properties, &e.name, &e.unique_name),
+                    _ => return None,
+                };
+                if !properties.forward_reference {
+                    return None;
+                }
+
+                definitions
+                    .get(unique_name.as_ref().unwrap_or(name))
+                    .copied()
+            })
+            .filter(|idx| !output_pdb.types.contains_key(&idx.0) && !failed.contains(idx))
+            .collect();
+        if pending.is_empty() {
+            break;
+        }
+
+        for idx in pending {
+            if let Err(e) = handle_type(idx, output_pdb, type_finder) {
+                warn!("Could not resolve type {:#X}: {}", idx.0, e);
+                failed.insert(idx);
+            }
+        }
+    }
+}
+
+/// Explains why a `/DEBUG:FASTLINK` PDB is missing information and which of the object
+/// files holding that information can't be found
+fn report_fastlink(output_pdb: &ParsedPdb) {
+    warn!(
+        "{} was linked with /DEBUG:FASTLINK. Types and most private symbols are stored in \
+        the object files it was linked from rather than the PDB. Relink with /DEBUG:FULL or \
+        convert it with mspdbcmf.exe for complete output",
+        output_pdb.path.display()
+    );
+
+    // Linker-generated modules such as `* Linker *` have no object file
+    let objects: Vec<&str> = output_pdb
+        .debug_modules
+        .iter()
+        .map(|module| module.name.as_str())
+        .filter(|name| !name.starts_with('*'))
+        .collect();
+    let missing: Vec<&str> = objects
+        .iter()
+        .copied()
+        .filter(|object| !Path::new(object).exists())
+        .collect();
+
+    if !missing.is_empty() {
+        warn!(
+            "{} of {} object files referenced by the PDB are not present: {}",
+            missing.len(),
+            objects.len(),
+            missing.join(", ")
+        );
+    }
+}
+
+/// Attaches the file and line each `LF_UDT_SRC_LINE`/`LF_UDT_MOD_SRC_LINE` record
+/// points to to the class, union, or enum it describes
+fn apply_udt_sources(
+    output_pdb: &mut ParsedPdb,
+    sources: &[UserDefinedTypeSourceId],
+    id_finder: Option<&ItemFinder<'_, IdIndex>>,
+    string_table: Option<&StringTable<'_>>,
+) {
+    use crate::type_info::TypeSource;
+
+    for source in sources {
+        let file = match source.source_file {
+            UserDefinedTypeSourceFileRef::Local(idx) => id_finder
+                .and_then(|id_finder| id_finder.find(idx).ok())
+                .and_then(|item| item.parse().ok())
+                .and_then(|data| match data {
+                    IdData::String(s) => Some(s.name.to_string().into_owned()),
+                    _ => None,
+                }),
+            UserDefinedTypeSourceFileRef::Remote(_, string_ref) => string_table
+                .and_then(|string_table| string_ref.to_string_lossy(string_table).ok())
+                .map(|file| file.into_owned()),
+        };
+
+        let (file, typ) = match (file, output_pdb.types.get(&source.udt.0)) {
+            (Some(file), Some(typ)) => (file, typ),
+            _ => {
+                debug!("could not resolve UDT source {:?}", source);
+                continue;
+            }
+        };
+
+        typ.as_ref().borrow_mut().set_source(TypeSource {
+            file,
+            line: source.line,
+        });
+    }
+}
+
+/// Keeps the bytes of `symbol` if it failed to decode or has a name that could be
+/// filtered for
+fn record_raw_symbol(
+    output_pdb: &mut ParsedPdb,
+    stream: &str,
+    symbol: Symbol,
+    error: Option<&Error>,
+) {
+    let name = symbol
+        .parse()
+        .ok()
+        .and_then(|data| data.name())
+        .map(|name| name.to_string().into_owned());
+    if error.is_none() && name.is_none() {
+        return;
+    }
+
+    output_pdb.raw_records.push(RawRecord::new(
+        stream,
+        symbol.raw_kind(),
+        symbol.index().0,
+        symbol.raw_bytes(),
+        name,
+        error.map(ToString::to_string),
+    ));
+}
+
+/// Keeps the bytes of the type at `idx` if it failed to decode or has a name that could
+/// be filtered for
+fn record_raw_type(
+    output_pdb: &mut ParsedPdb,
+    type_finder: &ItemFinder<'_, TypeIndex>,
+    idx: TypeIndex,
+    error: Option<&Error>,
+) {
+    let item = match type_finder.find(idx) {
+        Ok(item) => item,
+        Err(_) => return,
+    };
+    let name = item
+        .parse()
+        .ok()
+        .and_then(|data| data.name())
+        .map(|name| name.to_string().into_owned());
+    if error.is_none() && name.is_none() {
+        return;
+    }
+
+    output_pdb.raw_records.push(RawRecord::new(
+        "tpi",
+        item.raw_kind(),
+        idx.0,
+        item.raw_bytes(),
+        name,
+        error.map(ToString::to_string),
+    ));
+}
+
+/// Converts the type at `idx` when types are resolved lazily. Failures only leave the
+/// type out, so they aren't fatal to the symbol referencing it
+fn resolve_lazily(
+    idx: TypeIndex,
+    output_pdb: &mut ParsedPdb,
+    type_finder: &ItemFinder<'_, TypeIndex>,
+    options: &ParseOptions,
+) {
+    if !options.lazy_types {
+        return;
+    }
+
+    if let Err(e) = handle_type(idx, output_pdb, type_finder) {
+        warn!("Could not resolve type {:#X}: {}", idx.0, e);
+    }
+}
+
 /// Converts a [pdb::SymbolData] object to a parsed symbol representation that
 /// we can serialize and adds it to the appropriate fields on the output [ParsedPdb].
 /// Errors returned from this function should not be considered fatal.
@@ -170,34 +857,109 @@ fn handle_symbol(
     address_map: Option<&AddressMap>,
     type_finder: &ItemFinder<'_, TypeIndex>,
     id_finder: Option<&ItemFinder<'_, IdIndex>>,
-    base_address: Option<usize>,
+    options: &ParseOptions,
 ) -> Result<(), Error> {
-    let base_address = base_address.unwrap_or(0);
     let sym = sym.parse()?;
 
+    let kind = match &sym {
+        SymbolData::Public(_) => Some(SymbolKind::Public),
+        SymbolData::Procedure(_) => Some(SymbolKind::Function),
+        SymbolData::RegisterRelative(_) | SymbolData::Local(_) => Some(SymbolKind::Local),
+        SymbolData::Thunk(_) => Some(SymbolKind::Thunk),
+        SymbolData::Data(_) | SymbolData::Constant(_) => Some(SymbolKind::Data),
+        SymbolData::Export(_) => Some(SymbolKind::Export),
+        _ => None,
+    };
+    if kind.map_or(false, |kind| !options.includes(kind)) {
+        return Ok(());
+    }
+
     match sym {
         SymbolData::Public(data) => {
             debug!("public symbol: {:?}", data);
 
-            let converted_symbol: crate::symbol_types::PublicSymbol =
-                (data, base_address, address_map).into();
+            let converted_symbol: crate::symbol_types::PublicSymbol = (data, address_map).into();
             output_pdb.public_symbols.push(converted_symbol);
         }
         SymbolData::Procedure(data) => {
             debug!("procedure: {:?}", data);
+            resolve_lazily(data.type_index, output_pdb, type_finder, options);
 
             let converted_symbol: crate::symbol_types::Procedure =
-                (data, base_address, address_map, type_finder).into();
+                (data, address_map, type_finder).into();
             output_pdb.procedures.push(converted_symbol);
         }
+        SymbolData::RegisterRelative(data) => {
+            debug!("register relative: {:?}", data);
+            resolve_lazily(data.type_index, output_pdb, type_finder, options);
+
+            // Locals always follow the procedure which declares them
+            let parameters = output_pdb
+                .procedures
+                .last()
+                .and_then(|procedure| parameter_count(output_pdb, procedure.type_index));
+            if let Some(procedure) = output_pdb.procedures.last_mut() {
+                let mut local: crate::symbol_types::LocalVariable = data.into();
+                // Register-relative records only say where a variable is stored. The
+                // compiler emits parameters first, so the leading records up to the
+                // function type's parameter count are the parameters
+                local.is_parameter = procedure.locals.iter().all(|local| local.is_parameter)
+                    && parameters.map_or(false, |count| procedure.locals.len() < count);
+                procedure.locals.push(local);
+            }
+        }
+        SymbolData::Local(data) => {
+            debug!("local: {:?}", data);
+            resolve_lazily(data.type_index, output_pdb, type_finder, options);
+
+            if let Some(procedure) = output_pdb.procedures.last_mut() {
+                procedure.locals.push(data.into());
+            }
+        }
+        SymbolData::Thunk(data) => {
+            debug!("thunk: {:?}", data);
+
+            let converted_symbol: crate::symbol_types::Thunk = (data, address_map).into();
+            output_pdb.thunks.push(converted_symbol);
+        }
+        SymbolData::SeparatedCode(data) => {
+            debug!("separated code: {:?}", data);
+
+            let converted_symbol: crate::symbol_types::SeparatedCode = (data, address_map).into();
+            output_pdb.separated_code.push(converted_symbol);
+        }
+        SymbolData::Export(data) => {
+            debug!("export: {:?}", data);
+            output_pdb.exports.push(data.into());
+        }
+        SymbolData::InlineSite(data) => {
+            debug!("inline site: {:?}", data);
+
+            // Inline sites are nested in the procedure they were inlined into
+            let id_finder = id_finder.ok_or(Error::MissingDependency("IdFinder"))?;
+            let name = crate::ids::function_id_name(id_finder, data.inlinee, output_pdb)?;
+            if let Some(procedure) = output_pdb.procedures.last_mut() {
+                if !procedure.inlinees.contains(&name) {
+                    procedure.inlinees.push(name);
+                }
+            }
+        }
         SymbolData::BuildInfo(data) => {
             debug!("build info: {:?}", data);
             let converted_symbol: crate::symbol_types::BuildInfo = (&data, id_finder).try_into()?;
+            // Build info is only present in module streams, so it belongs to the module
+            // currently being parsed
+            if let Some(module) = output_pdb.debug_modules.last_mut() {
+                module.build_info = Some(converted_symbol.clone());
+            }
             output_pdb.assembly_info.build_info = Some(converted_symbol);
         }
         SymbolData::CompileFlags(data) => {
             debug!("compile flags: {:?}", data);
             let sym: crate::symbol_types::CompilerInfo = data.into();
+            if let Some(module) = output_pdb.debug_modules.last_mut() {
+                module.compiler_info = Some(sym.clone());
+            }
             output_pdb.assembly_info.compiler_info = Some(sym);
         }
         SymbolData::AnnotationReference(annotation) => {
@@ -207,12 +969,20 @@ fn handle_symbol(
             // output_pdb.annotation_references.push()
         }
         SymbolData::Data(data) => {
+            resolve_lazily(data.type_index, output_pdb, type_finder, options);
             let sym: crate::symbol_types::Data =
-                (data, base_address, address_map, &output_pdb.types).try_into()?;
+                (data, address_map, &output_pdb.types).try_into()?;
             if sym.is_global {
                 output_pdb.global_data.push(sym);
             }
         }
+        SymbolData::Constant(data) => {
+            debug!("constant: {:?}", data);
+            resolve_lazily(data.type_index, output_pdb, type_finder, options);
+
+            let sym: crate::symbol_types::Data = (data, &output_pdb.types).try_into()?;
+            output_pdb.global_data.push(sym);
+        }
         other => {
             warn!("Unhandled SymbolData: {:?}", other);
         }
@@ -221,6 +991,23 @@ fn handle_symbol(
     Ok(())
 }
 
+/// Number of parameters of the procedure or member function type `type_index`,
+/// including `this`, if the type was converted
+fn parameter_count(output_pdb: &ParsedPdb, type_index: TypeIndexNumber) -> Option<usize> {
+    use crate::type_info::Type;
+
+    let ty = output_pdb.types.get(&type_index)?;
+    let count = match &*ty.as_ref().borrow() {
+        Type::Procedure(procedure) => procedure.argument_list.len(),
+        Type::MemberFunction(function) => {
+            function.argument_list.len() + usize::from(function.this_pointer_type.is_some())
+        }
+        _ => return None,
+    };
+
+    Some(count)
+}
+
 /// Converts a [pdb::SymbolData] object to a parsed symbol representation that
 /// we can serialize and adds it to the appropriate fields on the output [ParsedPdb].
 /// Errors returned from this function should not be considered fatal.
@@ -0,0 +1,198 @@
+use crate::symbol_types::*;
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Everything known about a single address, similar to WinDbg's `ln` command
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AddressLookup<'a> {
+    pub address: usize,
+    /// Procedure whose body contains the address, either directly or through
+    /// one of its separated code blocks or funclets
+    pub procedure: Option<&'a Procedure>,
+    /// Module which contributed the section range containing the address
+    pub module: Option<&'a DebugModule>,
+    /// Nearest public symbol at or before the address, and the displacement from it
+    pub nearest_public: Option<(&'a PublicSymbol, usize)>,
+}
+
+fn contains(offset: Option<usize>, len: usize, address: usize) -> bool {
+    match offset {
+        Some(offset) => address >= offset && address - offset < len,
+        None => false,
+    }
+}
+
+impl ParsedPdb {
+    /// Returns the section containing `address`, if any
+    pub fn section_for_address(&self, address: usize) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.contains(address))
+    }
+
+    /// Returns the source line information for the code at `address`, if any
+    pub fn line_for_address(&self, address: usize) -> Option<&LineInfo> {
+        self.debug_modules
+            .iter()
+            .flat_map(|module| module.lines.iter())
+            .find(|line| match line.len {
+                Some(len) => contains(line.offset, len, address),
+                None => line.offset == Some(address),
+            })
+    }
+
+    /// Resolves `address` to its owning procedure, module, and nearest preceding
+    /// public symbol. `address` must be in the same address space as the parsed
+    /// offsets (i.e. include the base address if one was provided at parse time).
+    pub fn lookup_address(&self, address: usize) -> AddressLookup<'_> {
+        let procedure = self
+            .procedures
+            .iter()
+            .find(|procedure| {
+                contains(procedure.offset, procedure.len, address)
+                    || procedure
+                        .funclets
+                        .iter()
+                        .any(|funclet| contains(funclet.offset, funclet.len, address))
+            })
+            .or_else(|| {
+                // The address may live in a block the compiler split out of its parent
+                let block = self
+                    .separated_code
+                    .iter()
+                    .find(|block| contains(block.offset, block.len, address))?;
+
+                block.parent_offset.and_then(|parent_offset| {
+                    self.procedures
+                        .iter()
+                        .find(|procedure| procedure.offset == Some(parent_offset))
+                })
+            });
+
+        let module = self
+            .section_contributions
+            .iter()
+            .find(|contribution| contains(contribution.offset, contribution.size, address))
+            .and_then(|contribution| self.debug_modules.get(contribution.module_index));
+
+        let nearest_public = self
+            .public_symbols
+            .iter()
+            .filter_map(|symbol| match symbol.offset {
+                Some(offset) if offset <= address => Some((symbol, address - offset)),
+                _ => None,
+            })
+            .min_by_key(|(_symbol, displacement)| *displacement);
+
+        AddressLookup {
+            address,
+            procedure,
+            module,
+            nearest_public,
+        }
+    }
+}
+
+/// Several PDBs parsed at the base addresses their images are loaded at, forming a
+/// single process address space
+#[derive(Debug, Default)]
+pub struct AddressSpace {
+    modules: Vec<ParsedPdb>,
+}
+
+impl ParsedPdb {
+    /// Returns the size of the image in memory as described by its section headers
+    pub fn image_size(&self) -> usize {
+        self.sections
+            .iter()
+            .map(|section| section.offset + section.size - self.base_address)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Moves every offset so that the image is loaded at `base_address`. Offsets are
+    /// RVAs after parsing, so rebasing to 0 converts previously rebased offsets back
+    /// to RVAs
+    pub fn rebase(&mut self, base_address: usize) {
+        let old_base = self.base_address;
+        let rebase = |offset: &mut Option<usize>| {
+            if let Some(offset) = offset.as_mut() {
+                *offset = *offset - old_base + base_address;
+            }
+        };
+
+        for symbol in &mut self.public_symbols {
+            rebase(&mut symbol.offset);
+        }
+        for procedure in &mut self.procedures {
+            rebase(&mut procedure.offset);
+            for funclet in &mut procedure.funclets {
+                rebase(&mut funclet.offset);
+            }
+        }
+        for data in &mut self.global_data {
+            rebase(&mut data.offset);
+        }
+        for thunk in &mut self.thunks {
+            rebase(&mut thunk.offset);
+        }
+        for block in &mut self.separated_code {
+            rebase(&mut block.offset);
+            rebase(&mut block.parent_offset);
+        }
+        for contribution in &mut self.section_contributions {
+            rebase(&mut contribution.offset);
+        }
+        for line in self
+            .debug_modules
+            .iter_mut()
+            .flat_map(|module| module.lines.iter_mut())
+        {
+            rebase(&mut line.offset);
+        }
+        for section in &mut self.sections {
+            section.offset = section.offset - old_base + base_address;
+        }
+
+        self.base_address = base_address;
+    }
+}
+
+impl AddressSpace {
+    /// Creates an address space from PDBs which were parsed with their image's base address
+    pub fn new(mut modules: Vec<ParsedPdb>) -> Self {
+        modules.sort_by_key(|module| module.base_address);
+
+        AddressSpace { modules }
+    }
+
+    /// Returns every module in the address space, ordered by base address
+    pub fn modules(&self) -> &[ParsedPdb] {
+        &self.modules
+    }
+
+    /// Returns the module whose image contains `address`
+    pub fn module_for_address(&self, address: usize) -> Option<&ParsedPdb> {
+        let idx = self
+            .modules
+            .partition_point(|module| module.base_address <= address);
+        let module = self.modules[..idx].last()?;
+
+        if address - module.base_address < module.image_size() {
+            Some(module)
+        } else {
+            None
+        }
+    }
+
+    /// Resolves `address` to the module containing it and the symbols within that module
+    pub fn lookup_address(&self, address: usize) -> Option<(&ParsedPdb, AddressLookup<'_>)> {
+        let module = self.module_for_address(address)?;
+
+        Some((module, module.lookup_address(address)))
+    }
+}
@@ -0,0 +1,359 @@
+use crate::error::Error;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::path::Path;
+
+const MSF_MAGIC: &[u8; 32] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+const JG_MAGIC: &[u8] = b"Microsoft C/C++ program database 2.00\r\n\x1aJG\0\0";
+const SUPERBLOCK_LEN: usize = 56;
+const VALID_BLOCK_SIZES: [u32; 4] = [512, 1024, 2048, 4096];
+/// Size recorded in the stream directory for streams which have been deleted
+const NIL_STREAM_SIZE: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Severity {
+    /// Unusual but harmless, such as blocks which were leaked by an incremental link
+    Warning,
+    /// The file cannot be read correctly
+    Error,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct MsfIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Results of validating the MSF container a PDB is stored in
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct MsfReport {
+    pub block_size: u32,
+    pub block_count: u32,
+    /// Which of the two free page maps is active
+    pub free_block_map_block: u32,
+    pub directory_bytes: u32,
+    pub stream_count: u32,
+    pub issues: Vec<MsfIssue>,
+}
+
+impl MsfReport {
+    /// Returns whether any issue prevents the file from being read correctly
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error)
+    }
+
+    fn warning(&mut self, message: String) {
+        self.issues.push(MsfIssue {
+            severity: Severity::Warning,
+            message,
+        });
+    }
+
+    fn error(&mut self, message: String) {
+        self.issues.push(MsfIssue {
+            severity: Severity::Error,
+            message,
+        });
+    }
+}
+
+/// Validates the MSF superblock, stream directory, per-stream block lists, and free
+/// page map of the file at `path`
+pub fn check_msf<P: AsRef<Path>>(path: P) -> Result<MsfReport, Error> {
+    let data = std::fs::read(path.as_ref())?;
+
+    Ok(check_msf_bytes(&data))
+}
+
+/// Same as [check_msf], but for a file which has already been read
+pub fn check_msf_bytes(data: &[u8]) -> MsfReport {
+    let mut checker = Checker {
+        data,
+        report: MsfReport::default(),
+        block_map_address: 0,
+        owners: HashMap::new(),
+    };
+    checker.check();
+
+    checker.report
+}
+
+/// Number of `block_size` blocks needed to hold `len` bytes. Avoids overflowing on
+/// the sizes found in corrupt files
+fn blocks_needed(len: u32, block_size: u32) -> u32 {
+    len / block_size + (len % block_size != 0) as u32
+}
+
+struct Checker<'a> {
+    data: &'a [u8],
+    report: MsfReport,
+    /// Block holding the list of the stream directory's blocks
+    block_map_address: u32,
+    /// Which structure each referenced block belongs to
+    owners: HashMap<u32, String>,
+}
+
+impl<'a> Checker<'a> {
+    fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+        bytes
+            .get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn block(&self, index: u32) -> Option<&'a [u8]> {
+        let block_size = self.report.block_size as usize;
+        let start = index as usize * block_size;
+        self.data.get(start..start + block_size)
+    }
+
+    /// Every interval of `block_size` blocks starts with the superblock (or a block of
+    /// stream data) followed by the two free page maps
+    fn is_free_page_map_block(&self, index: u32) -> bool {
+        matches!(index % self.report.block_size, 1 | 2)
+    }
+
+    /// Records that `owner` uses block `index`, reporting blocks which can't belong to it
+    fn claim(&mut self, index: u32, owner: &str) {
+        if index >= self.report.block_count {
+            self.report.error(format!(
+                "{} references block {} beyond the last block ({})",
+                owner,
+                index,
+                self.report.block_count.saturating_sub(1)
+            ));
+        } else if index == 0 {
+            self.report.error(format!(
+                "{} uses block 0, which holds the superblock",
+                owner
+            ));
+        } else if self.is_free_page_map_block(index) {
+            self.report.error(format!(
+                "{} uses block {}, which is reserved for the free page map",
+                owner, index
+            ));
+        } else if let Some(previous) = self.owners.get(&index) {
+            self.report.error(format!(
+                "block {} is used by both {} and {}",
+                index, previous, owner
+            ));
+        } else {
+            self.owners.insert(index, owner.to_string());
+        }
+    }
+
+    fn check(&mut self) {
+        if self.data.starts_with(JG_MAGIC) {
+            self.report.error(
+                "file uses the MSF 2.00 (JG) container, which predates the supported format"
+                    .to_string(),
+            );
+            return;
+        }
+        if self.data.len() < SUPERBLOCK_LEN || &self.data[..MSF_MAGIC.len()] != MSF_MAGIC {
+            self.report
+                .error("superblock magic is missing or corrupt".to_string());
+            return;
+        }
+
+        if !self.check_superblock() {
+            return;
+        }
+
+        let directory = match self.read_directory() {
+            Some(directory) => directory,
+            None => return,
+        };
+        self.check_streams(&directory);
+
+        if matches!(self.report.free_block_map_block, 1 | 2) {
+            self.check_free_page_map();
+        }
+    }
+
+    /// Returns whether the superblock is intact enough to continue
+    fn check_superblock(&mut self) -> bool {
+        let data = self.data;
+        let field = |index: usize| Self::read_u32(data, MSF_MAGIC.len() + index * 4).unwrap();
+        self.report.block_size = field(0);
+        self.report.free_block_map_block = field(1);
+        self.report.block_count = field(2);
+        self.report.directory_bytes = field(3);
+        // field 4 is unused
+        self.block_map_address = field(5);
+
+        if !VALID_BLOCK_SIZES.contains(&self.report.block_size) {
+            self.report.error(format!(
+                "block size {} is not one of {:?}",
+                self.report.block_size, VALID_BLOCK_SIZES
+            ));
+            return false;
+        }
+
+        if !matches!(self.report.free_block_map_block, 1 | 2) {
+            self.report.error(format!(
+                "free page map block is {}, but must be 1 or 2",
+                self.report.free_block_map_block
+            ));
+        }
+
+        let expected_len = self.report.block_count as u64 * self.report.block_size as u64;
+        let actual_len = self.data.len() as u64;
+        if actual_len < expected_len {
+            self.report.error(format!(
+                "file is truncated: the superblock describes {} blocks ({} bytes) but the file is {} bytes",
+                self.report.block_count, expected_len, actual_len
+            ));
+        } else if actual_len > expected_len {
+            self.report.warning(format!(
+                "{} bytes follow the last block",
+                actual_len - expected_len
+            ));
+        }
+
+        if self.report.directory_bytes == 0 {
+            self.report.error("stream directory is empty".to_string());
+            return false;
+        }
+
+        true
+    }
+
+    /// Reassembles the stream directory from the blocks listed in the block map
+    fn read_directory(&mut self) -> Option<Vec<u8>> {
+        let block_size = self.report.block_size;
+        let block_map_address = self.block_map_address;
+        let directory_blocks = blocks_needed(self.report.directory_bytes, block_size);
+
+        self.claim(block_map_address, "the block map");
+        let block_map = match self.block(block_map_address) {
+            Some(block_map) => block_map,
+            None => {
+                self.report
+                    .error(format!("block map block {} is missing", block_map_address));
+                return None;
+            }
+        };
+
+        if directory_blocks as usize * 4 > block_map.len() {
+            self.report.error(format!(
+                "stream directory needs {} blocks, more than the block map can list",
+                directory_blocks
+            ));
+            return None;
+        }
+
+        let mut directory = Vec::with_capacity(self.report.directory_bytes as usize);
+        for i in 0..directory_blocks as usize {
+            let index = Self::read_u32(block_map, i * 4).unwrap();
+            self.claim(index, "the stream directory");
+            match self.block(index) {
+                Some(block) => directory.extend_from_slice(block),
+                None => {
+                    self.report
+                        .error(format!("stream directory block {} is missing", index));
+                    return None;
+                }
+            }
+        }
+        directory.truncate(self.report.directory_bytes as usize);
+
+        Some(directory)
+    }
+
+    fn check_streams(&mut self, directory: &[u8]) {
+        let block_size = self.report.block_size;
+        let stream_count = Self::read_u32(directory, 0).unwrap_or_default();
+        self.report.stream_count = stream_count;
+
+        let sizes_end = 4 + stream_count as usize * 4;
+        if sizes_end > directory.len() {
+            self.report.error(format!(
+                "stream directory lists {} streams but is too short to hold their sizes",
+                stream_count
+            ));
+            return;
+        }
+
+        let mut position = sizes_end;
+        for stream in 0..stream_count as usize {
+            let size = Self::read_u32(directory, 4 + stream * 4).unwrap();
+            if size == NIL_STREAM_SIZE {
+                continue;
+            }
+
+            let blocks = blocks_needed(size, block_size) as usize;
+            if position + blocks * 4 > directory.len() {
+                self.report.error(format!(
+                    "stream directory ends before the block list of stream {}",
+                    stream
+                ));
+                return;
+            }
+
+            let owner = format!("stream {}", stream);
+            for i in 0..blocks {
+                let index = Self::read_u32(directory, position + i * 4).unwrap();
+                self.claim(index, &owner);
+            }
+            position += blocks * 4;
+        }
+
+        if position < directory.len() {
+            self.report.warning(format!(
+                "{} unused bytes follow the stream directory's block lists",
+                directory.len() - position
+            ));
+        }
+    }
+
+    fn check_free_page_map(&mut self) {
+        let block_size = self.report.block_size;
+        let block_count = self.report.block_count;
+        let bits_per_block = block_size * 8;
+        let map_blocks = blocks_needed(block_count, bits_per_block);
+
+        let mut free_page_map = Vec::with_capacity(map_blocks as usize * block_size as usize);
+        for i in 0..map_blocks {
+            let index = self.report.free_block_map_block + i * block_size;
+            match self.block(index) {
+                Some(block) => free_page_map.extend_from_slice(block),
+                None => {
+                    self.report
+                        .error(format!("free page map block {} is missing", index));
+                    return;
+                }
+            }
+        }
+
+        // A set bit marks a free block
+        let is_free = |index: u32| free_page_map[index as usize / 8] & (1 << (index % 8)) != 0;
+
+        let mut leaked = 0;
+        for index in 0..block_count {
+            let is_reserved = index == 0 || self.is_free_page_map_block(index);
+            match self.owners.get(&index) {
+                Some(owner) if is_free(index) => self.report.error(format!(
+                    "block {} is used by {} but marked free",
+                    index, owner
+                )),
+                None if !is_reserved && !is_free(index) => leaked += 1,
+                _ => {}
+            }
+        }
+
+        if leaked > 0 {
+            self.report.warning(format!(
+                "{} blocks are marked in use but belong to no stream",
+                leaked
+            ));
+        }
+    }
+}
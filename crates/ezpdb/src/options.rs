@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+
+/// Categories of symbols which can be included or excluded at parse time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// Procedures (`S_GPROC32`/`S_LPROC32`)
+    Function,
+    /// Global and static data
+    Data,
+    /// Public symbols
+    Public,
+    /// Thunks such as incremental linking jumps and adjustor thunks
+    Thunk,
+    /// Procedure parameters and local variables
+    Local,
+    /// Exports declared by the linker
+    Export,
+}
+
+impl SymbolKind {
+    pub const ALL: [SymbolKind; 6] = [
+        SymbolKind::Function,
+        SymbolKind::Data,
+        SymbolKind::Public,
+        SymbolKind::Thunk,
+        SymbolKind::Local,
+        SymbolKind::Export,
+    ];
+
+    /// Name of this kind as accepted by [SymbolKind::from_name]
+    pub fn name(self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Data => "data",
+            SymbolKind::Public => "public",
+            SymbolKind::Thunk => "thunk",
+            SymbolKind::Local => "local",
+            SymbolKind::Export => "export",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SymbolKind> {
+        SymbolKind::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Matches `text` against a WinDbg-style wildcard pattern where `*` matches any
+/// sequence of characters and `?` matches a single character
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen and the text position it was matched at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    p = star + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Returns the last component of a Windows or Unix path
+fn file_name(path: &str) -> &str {
+    path.rsplit(|c: char| c == '\\' || c == '/')
+        .next()
+        .unwrap_or(path)
+}
+
+/// Options controlling what [crate::parse_pdb_with_options] parses
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Kinds of symbols to parse. Every kind is parsed if `None`
+    pub kinds: Option<HashSet<SymbolKind>>,
+    /// Keep the undecoded bytes of every named or undecodable record in
+    /// [crate::ParsedPdb::raw_records]
+    pub raw: bool,
+    /// Keep the strings of the `/names` stream in [crate::ParsedPdb::string_table]. It
+    /// often holds paths and identifiers which no parsed record refers to
+    pub string_table: bool,
+    /// Wildcard patterns selecting the modules whose symbols and line information are
+    /// parsed. Patterns are matched case-insensitively against a module's name, its
+    /// object file name, and the file names of both. Every module is parsed if empty
+    pub modules: Vec<String>,
+    /// Only convert the types referenced by parsed symbols and those named by
+    /// `type_names`, along with the types they depend on, instead of the whole type
+    /// stream
+    pub lazy_types: bool,
+    /// Wildcard patterns of class, union, and enum names to convert when `lazy_types`
+    /// is set
+    pub type_names: Vec<String>,
+}
+
+impl ParseOptions {
+    /// Returns whether symbols of `kind` should be parsed
+    pub fn includes(&self, kind: SymbolKind) -> bool {
+        self.kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.contains(&kind))
+    }
+
+    /// Returns whether the class, union, or enum `name` was asked for by `type_names`
+    pub fn includes_type_name(&self, name: &str) -> bool {
+        self.type_names
+            .iter()
+            .any(|pattern| wildcard_match(pattern, name))
+    }
+
+    /// Returns whether the symbols and lines of the module with the given names should
+    /// be parsed
+    pub fn includes_module(&self, module_name: &str, object_file_name: &str) -> bool {
+        if self.modules.is_empty() {
+            return true;
+        }
+
+        let names = [
+            module_name,
+            object_file_name,
+            file_name(module_name),
+            file_name(object_file_name),
+        ];
+        self.modules.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            names
+                .iter()
+                .any(|name| wildcard_match(&pattern, &name.to_lowercase()))
+        })
+    }
+}
@@ -0,0 +1,81 @@
+//! Targeted queries against a PDB which only read the streams they need, for PDBs on
+//! slow storage such as a symbol server read through HTTP range requests
+
+use crate::error::Error;
+use crate::symbol_types::{MachineType, PublicSymbol, Version};
+use pdb::{FallibleIterator, SymbolData, PDB};
+
+/// Identity of a PDB, from the PDB information and DBI stream headers
+#[derive(Debug)]
+pub struct PdbHeader {
+    pub guid: uuid::Uuid,
+    pub age: u32,
+    pub timestamp: u32,
+    pub machine_type: Option<MachineType>,
+    pub version: Version,
+}
+
+impl PdbHeader {
+    /// Returns the identifier symbol servers index the PDB under. See
+    /// [crate::symbol_types::ParsedPdb::debug_identifier]
+    pub fn debug_identifier(&self) -> String {
+        format!(
+            "{}{:X}",
+            self.guid.to_simple().to_string().to_uppercase(),
+            self.age
+        )
+    }
+}
+
+/// A PDB opened for targeted queries. Only the MSF stream directory is read when opening
+pub struct PdbProbe<'s, S> {
+    pdb: PDB<'s, S>,
+}
+
+impl<'s, S: pdb::Source<'s> + 's> PdbProbe<'s, S> {
+    pub fn open(source: S) -> Result<Self, Error> {
+        Ok(PdbProbe {
+            pdb: PDB::open(source)?,
+        })
+    }
+
+    /// Reads the PDB's identity, which only touches the PDB information stream and the
+    /// DBI stream header
+    pub fn header(&mut self) -> Result<PdbHeader, Error> {
+        let pdbi = self.pdb.pdb_information()?;
+        let dbi = self.pdb.debug_information()?;
+
+        Ok(PdbHeader {
+            guid: pdbi.guid,
+            age: dbi.age().unwrap_or(pdbi.age),
+            timestamp: pdbi.signature,
+            machine_type: dbi
+                .machine_type()
+                .ok()
+                .map(|machine_type| (&machine_type).into()),
+            version: (&pdbi.version).into(),
+        })
+    }
+
+    /// Returns the public symbols whose name satisfies `matches`. Reads the symbol
+    /// records stream and the section headers, but not types or module streams
+    pub fn public_symbols(
+        &mut self,
+        matches: impl Fn(&str) -> bool,
+    ) -> Result<Vec<PublicSymbol>, Error> {
+        let address_map = self.pdb.address_map().ok();
+        let symbol_table = self.pdb.global_symbols()?;
+
+        let mut symbols = vec![];
+        let mut iter = symbol_table.iter();
+        while let Some(symbol) = iter.next()? {
+            if let Ok(SymbolData::Public(data)) = symbol.parse() {
+                if matches(&data.name.to_string()) {
+                    symbols.push((data, address_map.as_ref()).into());
+                }
+            }
+        }
+
+        Ok(symbols)
+    }
+}
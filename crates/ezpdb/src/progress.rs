@@ -0,0 +1,156 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::cell::Cell;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Stages of parsing a PDB, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Phase {
+    /// DBI and PDB information streams and section headers
+    Headers,
+    /// Walking the IPI stream
+    Ids,
+    /// Walking the TPI stream and converting types
+    Types,
+    /// The global symbol stream
+    GlobalSymbols,
+    /// Every module's symbols and line programs
+    Modules,
+    SectionContributions,
+}
+
+/// Notifications emitted while a PDB is parsed
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "event", rename_all = "snake_case"))]
+pub enum ProgressEvent {
+    PhaseStarted {
+        phase: Phase,
+    },
+    PhaseFinished {
+        phase: Phase,
+        elapsed_us: u64,
+        /// Total bytes read from the PDB so far
+        bytes_read: u64,
+    },
+    /// A module's symbols and line program are about to be parsed
+    ModuleStarted {
+        /// Zero-based index of the module
+        index: usize,
+        total: usize,
+        name: String,
+        bytes_read: u64,
+    },
+    /// A module's symbols and line program have been parsed
+    ModuleFinished {
+        index: usize,
+        name: String,
+        timings: ModuleTimings,
+        bytes_read: u64,
+    },
+}
+
+/// Time spent parsing each part of a module, in microseconds
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ModuleTimings {
+    pub line_program_us: u64,
+    pub symbols_us: u64,
+}
+
+/// Wraps a reader to count the number of bytes read through it
+#[derive(Debug)]
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    bytes_read: Rc<Cell<u64>>,
+}
+
+impl<R> CountingReader<R> {
+    /// Returns the reader and a handle to its byte count
+    pub(crate) fn new(inner: R) -> (Self, Rc<Cell<u64>>) {
+        let bytes_read = Rc::new(Cell::new(0));
+
+        (
+            CountingReader {
+                inner,
+                bytes_read: Rc::clone(&bytes_read),
+            },
+            bytes_read,
+        )
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + read as u64);
+
+        Ok(read)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Tracks the current phase and emits [ProgressEvent]s to a callback
+pub(crate) struct ProgressTracker<'a> {
+    callback: &'a mut dyn FnMut(&ProgressEvent),
+    bytes_read: Rc<Cell<u64>>,
+    phase: Option<(Phase, Instant)>,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(
+        callback: &'a mut dyn FnMut(&ProgressEvent),
+        bytes_read: Rc<Cell<u64>>,
+    ) -> Self {
+        ProgressTracker {
+            callback,
+            bytes_read,
+            phase: None,
+        }
+    }
+
+    /// Finishes the current phase, if any, and starts `phase`
+    pub(crate) fn start(&mut self, phase: Phase) {
+        self.finish();
+        self.phase = Some((phase, Instant::now()));
+        (self.callback)(&ProgressEvent::PhaseStarted { phase });
+    }
+
+    /// Finishes the current phase, if any
+    pub(crate) fn finish(&mut self) {
+        if let Some((phase, start)) = self.phase.take() {
+            (self.callback)(&ProgressEvent::PhaseFinished {
+                phase,
+                elapsed_us: start.elapsed().as_micros() as u64,
+                bytes_read: self.bytes_read.get(),
+            });
+        }
+    }
+
+    pub(crate) fn module_started(&mut self, index: usize, total: usize, name: &str) {
+        (self.callback)(&ProgressEvent::ModuleStarted {
+            index,
+            total,
+            name: name.to_string(),
+            bytes_read: self.bytes_read.get(),
+        });
+    }
+
+    pub(crate) fn module_finished(&mut self, index: usize, name: &str, timings: ModuleTimings) {
+        (self.callback)(&ProgressEvent::ModuleFinished {
+            index,
+            name: name.to_string(),
+            timings,
+            bytes_read: self.bytes_read.get(),
+        });
+    }
+}
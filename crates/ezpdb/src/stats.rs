@@ -0,0 +1,159 @@
+use crate::error::Error;
+use pdb::{StreamIndex, PDB};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fs::File;
+use std::path::Path;
+
+/// Size of the header shared by the TPI and IPI streams
+const TYPE_STREAM_HEADER_LEN: usize = 56;
+/// Hash stream index used when a type stream has no hash stream
+const NO_STREAM: u16 = 0xFFFF;
+
+/// Number and total size of the records of a single leaf kind
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct LeafKindStats {
+    pub kind: u16,
+    /// `LF_*` name of the leaf kind, if known
+    pub name: Option<&'static str>,
+    pub count: usize,
+    /// Size of the records in BYTES, including their length prefix
+    pub bytes: usize,
+}
+
+/// Header fields and per-leaf-kind totals for a TPI or IPI stream
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TypeStreamStats {
+    /// `tpi` or `ipi`
+    pub stream: &'static str,
+    pub version: u32,
+    pub first_index: u32,
+    /// Highest type index in the stream, if it has any records
+    pub highest_index: Option<u32>,
+    pub record_count: usize,
+    /// Size of all records in BYTES
+    pub record_bytes: usize,
+    /// MSF stream holding the hash values, index offsets, and hash adjustments
+    pub hash_stream: Option<u16>,
+    pub hash_value_bytes: u32,
+    pub index_offset_bytes: u32,
+    pub hash_adjustment_bytes: u32,
+    /// Leaf kinds ordered by the number of bytes their records occupy, largest first
+    pub kinds: Vec<LeafKindStats>,
+}
+
+/// Returns the `LF_*` name of a type or ID record kind
+pub fn leaf_name(kind: u16) -> Option<&'static str> {
+    let name = match kind {
+        0x000A => "LF_VTSHAPE",
+        0x0014 => "LF_ENDPRECOMP",
+        0x1001 => "LF_MODIFIER",
+        0x1002 => "LF_POINTER",
+        0x1008 => "LF_PROCEDURE",
+        0x1009 => "LF_MFUNCTION",
+        0x100A => "LF_COBOL0",
+        0x100D => "LF_VFTPATH",
+        0x1201 => "LF_ARGLIST",
+        0x1203 => "LF_FIELDLIST",
+        0x1205 => "LF_BITFIELD",
+        0x1206 => "LF_METHODLIST",
+        0x1503 => "LF_ARRAY",
+        0x1504 => "LF_CLASS",
+        0x1505 => "LF_STRUCTURE",
+        0x1506 => "LF_UNION",
+        0x1507 => "LF_ENUM",
+        0x1509 => "LF_PRECOMP",
+        0x1515 => "LF_TYPESERVER2",
+        0x1519 => "LF_INTERFACE",
+        0x151D => "LF_VFTABLE",
+        0x1601 => "LF_FUNC_ID",
+        0x1602 => "LF_MFUNC_ID",
+        0x1603 => "LF_BUILDINFO",
+        0x1604 => "LF_SUBSTR_LIST",
+        0x1605 => "LF_STRING_ID",
+        0x1606 => "LF_UDT_SRC_LINE",
+        0x1607 => "LF_UDT_MOD_SRC_LINE",
+        0x1608 => "LF_CLASS2",
+        0x1609 => "LF_STRUCTURE2",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Collects statistics for the TPI and IPI streams of the PDB at `path`. Streams which
+/// are absent or too short to hold a header are left out
+pub fn type_stream_stats<P: AsRef<Path>>(path: P) -> Result<Vec<TypeStreamStats>, Error> {
+    let mut pdb = PDB::open(File::open(path.as_ref())?)?;
+
+    let mut stats = vec![];
+    for (name, index) in [("tpi", 2), ("ipi", 4)].iter() {
+        if let Some(stream) = pdb.raw_stream(StreamIndex(*index))? {
+            stats.extend(parse_type_stream(name, stream.as_slice()));
+        }
+    }
+
+    Ok(stats)
+}
+
+fn parse_type_stream(stream: &'static str, data: &[u8]) -> Option<TypeStreamStats> {
+    let u32_at = |offset: usize| {
+        data.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    if data.len() < TYPE_STREAM_HEADER_LEN {
+        return None;
+    }
+
+    let version = u32_at(0)?;
+    let header_len = u32_at(4)? as usize;
+    let first_index = u32_at(8)?;
+    let end_index = u32_at(12)?;
+    let hash_stream = u16::from_le_bytes(data[20..22].try_into().unwrap());
+
+    let mut kinds: BTreeMap<u16, LeafKindStats> = BTreeMap::new();
+    let mut record_count = 0;
+    let mut record_bytes = 0;
+    let mut offset = header_len;
+    // Each record is a 16-bit length (which excludes itself) followed by a 16-bit kind
+    while offset + 4 <= data.len() {
+        let len = u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap()) as usize + 2;
+        let kind = u16::from_le_bytes(data[offset + 2..offset + 4].try_into().unwrap());
+
+        let entry = kinds.entry(kind).or_insert_with(|| LeafKindStats {
+            kind,
+            name: leaf_name(kind),
+            count: 0,
+            bytes: 0,
+        });
+        entry.count += 1;
+        entry.bytes += len;
+
+        record_count += 1;
+        record_bytes += len;
+        offset += len;
+    }
+
+    let mut kinds: Vec<LeafKindStats> = kinds.into_values().collect();
+    kinds.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.kind.cmp(&b.kind)));
+
+    Some(TypeStreamStats {
+        stream,
+        version,
+        first_index,
+        highest_index: end_index
+            .checked_sub(1)
+            .filter(|index| *index >= first_index),
+        record_count,
+        record_bytes,
+        hash_stream: Some(hash_stream).filter(|index| *index != NO_STREAM),
+        hash_value_bytes: u32_at(36)?,
+        index_offset_bytes: u32_at(44)?,
+        hash_adjustment_bytes: u32_at(52)?,
+        kinds,
+    })
+}
@@ -0,0 +1,139 @@
+use crate::error::Error;
+use crate::symbol_types::StringTableEntry;
+use pdb::{StreamIndex, PDB};
+use std::fs::File;
+use std::path::Path;
+
+/// Feature signature (`MINI`) the linker writes to the PDB information stream of
+/// `/DEBUG:FASTLINK` PDBs
+pub(crate) const FASTLINK_FEATURE: u32 = 0x494E_494D;
+
+/// Streams at fixed indices which have no entry in the PDB's named stream table
+const WELL_KNOWN_STREAMS: [(&str, u16); 4] = [("pdb", 1), ("tpi", 2), ("dbi", 3), ("ipi", 4)];
+
+/// Returns the contents of an MSF stream verbatim. `stream` is either a stream index
+/// (decimal or `0x`-prefixed hexadecimal), one of `pdb`, `tpi`, `dbi`, or `ipi`, or a
+/// name from the PDB's named stream table such as `/names` or `/LinkInfo`
+pub fn read_stream<P: AsRef<Path>>(path: P, stream: &str) -> Result<Vec<u8>, Error> {
+    let mut pdb = PDB::open(File::open(path.as_ref())?)?;
+
+    let index = match stream.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => stream.parse::<u16>().ok(),
+    }
+    .or_else(|| {
+        WELL_KNOWN_STREAMS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(stream))
+            .map(|(_, index)| *index)
+    });
+
+    let data = match index {
+        Some(index) => pdb
+            .raw_stream(StreamIndex(index))?
+            .ok_or_else(|| Error::MissingStream(stream.to_string()))?,
+        None => match pdb.named_stream(stream.as_bytes()) {
+            Ok(data) => data,
+            Err(pdb::Error::StreamNameNotFound) => {
+                return Err(Error::MissingStream(stream.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        },
+    };
+
+    Ok(data.as_slice().to_vec())
+}
+
+/// Returns the identifier symbol servers index the PDB under, the same as
+/// [crate::symbol_types::ParsedPdb::debug_identifier], reading only the PDB information
+/// and DBI stream headers
+pub fn debug_identifier<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let mut probe = crate::probe::PdbProbe::open(File::open(path.as_ref())?)?;
+
+    Ok(probe.header()?.debug_identifier())
+}
+
+/// Returns the feature signatures which follow the named stream table in the PDB
+/// information stream, or `None` if the stream is malformed
+pub(crate) fn pdb_features(data: &[u8]) -> Option<Vec<u32>> {
+    let mut reader = Reader { data, offset: 0 };
+
+    // version, signature, age, and GUID
+    reader.skip(28)?;
+    let string_buffer_len = reader.u32()? as usize;
+    reader.skip(string_buffer_len)?;
+
+    // The named stream table's hash table: size, capacity, then the present and
+    // deleted bit vectors followed by a key/value pair per present entry
+    let entries = reader.u32()? as usize;
+    reader.skip(4)?;
+    for _ in 0..2 {
+        let words = reader.u32()? as usize;
+        reader.skip(words.checked_mul(4)?)?;
+    }
+    reader.skip(entries.checked_mul(8)?)?;
+
+    let mut features = vec![];
+    while let Some(feature) = reader.u32() {
+        features.push(feature);
+    }
+
+    Some(features)
+}
+
+/// Signature at the start of the `/names` stream
+const NAMES_STREAM_SIGNATURE: u32 = 0xEFFE_EFFE;
+
+/// Returns the strings of a `/names` stream along with their offsets, which is how
+/// other records refer to them, or `None` if the stream is malformed. The empty string
+/// at offset 0 is left out
+pub(crate) fn names_stream_strings(data: &[u8]) -> Option<Vec<StringTableEntry>> {
+    let mut reader = Reader { data, offset: 0 };
+
+    if reader.u32()? != NAMES_STREAM_SIGNATURE {
+        return None;
+    }
+    // hash version
+    reader.skip(4)?;
+    let buffer_len = reader.u32()? as usize;
+    let start = reader.offset;
+    reader.skip(buffer_len)?;
+
+    let mut strings = vec![];
+    let mut offset = 0;
+    for string in data[start..start + buffer_len].split(|byte| *byte == 0) {
+        if !string.is_empty() {
+            strings.push(StringTableEntry {
+                offset: offset as u32,
+                value: String::from_utf8_lossy(string).into_owned(),
+            });
+        }
+        offset += string.len() + 1;
+    }
+
+    Some(strings)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn skip(&mut self, len: usize) -> Option<()> {
+        let end = self.offset.checked_add(len)?;
+        if end > self.data.len() {
+            return None;
+        }
+
+        self.offset = end;
+        Some(())
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.data.get(self.offset..self.offset + 4)?;
+        self.offset += 4;
+
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
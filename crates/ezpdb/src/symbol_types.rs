@@ -1,56 +1,188 @@
 use crate::type_info::Type;
-use log::warn;
 use pdb::{FallibleIterator, TypeIndex};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::convert::{From, TryFrom};
 use std::path::PathBuf;
 use std::rc::Rc;
 
 pub type TypeRef = Rc<RefCell<Type>>;
 pub type TypeIndexNumber = u32;
+
+/// Version of the serialized [ParsedPdb] layout. Adding fields does not change the
+/// version, but removing, renaming, or changing the meaning of a field does.
+///
+/// Version history:
+/// 1. The original layout, which had no `format_version` field
+/// 2. Adds `format_version`, `base_address`, `exports`, `sections`,
+///    `section_contributions`, `separated_code`, module `lines`, and procedure
+///    `locals` and `hashes`. `warnings`, `type_aliases`, and `string_table` were later
+///    added without changing the layout
+/// 3. Source file checksums are objects with an `algorithm` and a lowercase hex
+///    `digest` instead of an externally tagged array of bytes. `identity` and symbol
+///    `location`s were later added without changing the layout
+/// 4. Types referenced from other types or from data which have an entry in `types`
+///    are written as that entry's index instead of repeating their definition
+pub const FORMAT_VERSION: u32 = 4;
+
 /// Represents a PDB that has been fully parsed
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+// The derived implementations are wrapped by those below `type_ref`, which write type
+// references as indices into `types`
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(remote = "Self")
+)]
+#[cfg_attr(feature = "schema", derive(JsonSchema), schemars(rename = "ParsedPdb"))]
 pub struct ParsedPdb {
+    /// Layout version of this structure when serialized. See [FORMAT_VERSION]
+    #[cfg_attr(feature = "serde", serde(default = "legacy_format_version"))]
+    pub format_version: u32,
     pub path: PathBuf,
+    /// Base address added to every offset in this PDB. See [ParsedPdb::rebase]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub base_address: usize,
     pub assembly_info: AssemblyInfo,
     pub public_symbols: Vec<PublicSymbol>,
-    pub types: HashMap<TypeIndexNumber, TypeRef>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub exports: Vec<Export>,
+    pub types: BTreeMap<TypeIndexNumber, TypeRef>,
+    /// Indices of types removed from `types` as duplicates, mapped to the index of the
+    /// type kept in their place. Only populated by consumers which collapse duplicates
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub type_aliases: BTreeMap<TypeIndexNumber, TypeIndexNumber>,
     pub procedures: Vec<Procedure>,
     pub global_data: Vec<Data>,
     pub debug_modules: Vec<DebugModule>,
-    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sections: Vec<Section>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub section_contributions: Vec<SectionContribution>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub separated_code: Vec<SeparatedCode>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub thunks: Vec<Thunk>,
+    /// Linked with `/DEBUG:FASTLINK`, so types and most private symbols are stored in the
+    /// object files rather than the PDB
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_fastlink: bool,
+    /// Undecoded records, only collected when parsing with [crate::ParseOptions::raw]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub raw_records: Vec<RawRecord>,
+    /// Contents of the `/names` string table, only collected when parsing with
+    /// [crate::ParseOptions::string_table]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub string_table: Vec<StringTableEntry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "schema", schemars(skip))]
     pub(crate) forward_references: Vec<Rc<Type>>,
     pub version: Version,
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_uuid"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_uuid",
+            deserialize_with = "deserialize_uuid"
+        )
+    )]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub guid: uuid::Uuid,
     pub age: u32,
     pub timestamp: u32,
     pub machine_type: Option<MachineType>,
+    /// Hashes of the files this structure was produced from. Populated by consumers
+    /// which have the files at hand
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub identity: Option<Identity>,
+    /// Problems encountered while parsing which may have left this structure incomplete.
+    /// Populated by consumers which capture log output
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<String>,
 }
 
 impl ParsedPdb {
     /// Constructs a new [ParsedPdb] with the corresponding path
     pub fn new(path: PathBuf) -> Self {
         ParsedPdb {
+            format_version: FORMAT_VERSION,
             path,
+            base_address: 0,
             assembly_info: AssemblyInfo::default(),
             public_symbols: vec![],
+            exports: vec![],
             types: Default::default(),
+            type_aliases: Default::default(),
             procedures: vec![],
             global_data: vec![],
             debug_modules: vec![],
+            sections: vec![],
+            section_contributions: vec![],
+            separated_code: vec![],
+            thunks: vec![],
+            is_fastlink: false,
+            raw_records: vec![],
+            string_table: vec![],
             forward_references: vec![],
             version: Version::Other(0),
             guid: uuid::Uuid::nil(),
             age: 0,
             timestamp: 0,
             machine_type: None,
+            identity: None,
+            warnings: vec![],
         }
     }
+
+    /// Returns the definition of `ty` if it's a forward reference to a class, union, or
+    /// enum defined elsewhere in the PDB, or `ty` itself otherwise
+    pub fn resolve_forward_reference(&self, ty: &TypeRef) -> TypeRef {
+        ty.as_ref()
+            .borrow()
+            .definition()
+            .and_then(|definition| self.types.get(&definition))
+            .map(Rc::clone)
+            .unwrap_or_else(|| Rc::clone(ty))
+    }
+
+    /// Returns the definition of the class, union, or enum named `name`, following
+    /// forward references to it. Returns `None` if there is no such type or only forward
+    /// references to it were found
+    pub fn find_type_definition(&self, name: &str) -> Option<TypeRef> {
+        self.types
+            .values()
+            .filter(|ty| ty.as_ref().borrow().display_name() == Some(name))
+            .map(|ty| self.resolve_forward_reference(ty))
+            .find(|ty| matches!(ty.as_ref().borrow().definition_key(), Some((_, false))))
+    }
+
+    /// Returns the identifier symbol servers and crash reporters use to match this PDB:
+    /// the GUID as uppercase hex without dashes followed by the age in hex
+    pub fn debug_identifier(&self) -> String {
+        format!(
+            "{}{:X}",
+            self.guid.to_simple().to_string().to_uppercase(),
+            self.age
+        )
+    }
 }
 
 #[cfg(feature = "serde")]
@@ -58,8 +190,245 @@ fn serialize_uuid<S: serde::Serializer>(uuid: &uuid::Uuid, s: S) -> Result<S::Ok
     s.serialize_str(uuid.to_string().as_ref())
 }
 
+#[cfg(feature = "serde")]
+fn deserialize_uuid<'de, D: serde::Deserializer<'de>>(d: D) -> Result<uuid::Uuid, D::Error> {
+    let s = String::deserialize(d)?;
+    uuid::Uuid::parse_str(&s).map_err(serde::de::Error::custom)
+}
+
+/// Serializes binary blobs as lowercase hex strings. Every byte buffer in the output
+/// should use this rather than serde's default array of integers
+#[cfg(feature = "serde")]
+pub(crate) mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        s.serialize_str(&hex)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(d)?;
+        if s.len() % 2 != 0 || !s.is_ascii() {
+            return Err(serde::de::Error::custom("invalid hex string"));
+        }
+
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+/// A serialized [TypeRef]: the index of a type in [ParsedPdb::types], or its definition
+/// if it has no index or is serialized outside of a [ParsedPdb]
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[serde(untagged)]
+pub(crate) enum TypeReference {
+    Index(TypeIndexNumber),
+    Definition(Type),
+}
+
+/// Serializes references to types in [ParsedPdb::types] as their index so that a type
+/// shared by many others is written once and is shared again when read back. References
+/// are only written as indices while a [ParsedPdb] is serialized, or within
+/// [with_type_indices]
+#[cfg(feature = "serde")]
+pub(crate) mod type_ref {
+    use super::{TypeIndexNumber, TypeRef, TypeReference};
+    use crate::type_info::{Primitive, PrimitiveKind, Type};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::cell::RefCell;
+    use std::collections::{BTreeMap, HashMap};
+    use std::rc::Rc;
+
+    thread_local! {
+        /// Index of each type in the tables being serialized, keyed by address
+        static INDICES: RefCell<Option<HashMap<*const RefCell<Type>, TypeIndexNumber>>> =
+            RefCell::new(None);
+        /// Types referenced by index in the [super::ParsedPdb] being deserialized. These
+        /// are placeholders until [link] moves the definitions from the table into them
+        static LINKED: RefCell<Option<BTreeMap<TypeIndexNumber, TypeRef>>> =
+            RefCell::new(None);
+    }
+
+    /// Calls `f` with references to the types in `types` serialized as their index, in
+    /// addition to those of any table already being serialized
+    pub fn with_indices<T>(types: &BTreeMap<TypeIndexNumber, TypeRef>, f: impl FnOnce() -> T) -> T {
+        let previous = INDICES.with(|indices| indices.borrow().clone());
+        let mut indices = previous.clone().unwrap_or_default();
+        indices.extend(types.iter().map(|(index, ty)| (Rc::as_ptr(ty), *index)));
+
+        INDICES.with(|current| *current.borrow_mut() = Some(indices));
+        let result = f();
+        INDICES.with(|current| *current.borrow_mut() = previous);
+
+        result
+    }
+
+    /// Calls `f`, returning the placeholders of the types it deserialized references to
+    pub fn linking<T>(f: impl FnOnce() -> T) -> (T, BTreeMap<TypeIndexNumber, TypeRef>) {
+        let previous = LINKED.with(|linked| linked.replace(Some(BTreeMap::new())));
+        let result = f();
+        let linked = LINKED.with(|linked| linked.replace(previous));
+
+        (result, linked.unwrap_or_default())
+    }
+
+    /// Moves each definition in `types` which was referenced by index into its
+    /// placeholder, which then takes its place in the table
+    pub fn link(
+        types: &mut BTreeMap<TypeIndexNumber, TypeRef>,
+        linked: BTreeMap<TypeIndexNumber, TypeRef>,
+    ) -> Result<(), String> {
+        for (index, placeholder) in linked {
+            let entry = types
+                .get_mut(&index)
+                .ok_or_else(|| format!("reference to type {} which is not in `types`", index))?;
+            let definition = std::mem::replace(entry, Rc::clone(&placeholder));
+            let definition = Rc::try_unwrap(definition)
+                .map(RefCell::into_inner)
+                .unwrap_or_else(|shared| shared.borrow().clone());
+            placeholder.replace(definition);
+        }
+
+        Ok(())
+    }
+
+    pub fn serialize<S: Serializer>(ty: &TypeRef, s: S) -> Result<S::Ok, S::Error> {
+        let index = INDICES.with(|indices| {
+            indices
+                .borrow()
+                .as_ref()
+                .and_then(|indices| indices.get(&Rc::as_ptr(ty)).copied())
+        });
+
+        match index {
+            Some(index) => s.serialize_u32(index),
+            None => ty.borrow().serialize(s),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<TypeRef, D::Error> {
+        let index = match TypeReference::deserialize(d)? {
+            TypeReference::Index(index) => index,
+            TypeReference::Definition(ty) => return Ok(Rc::new(RefCell::new(ty))),
+        };
+
+        LINKED.with(|linked| match linked.borrow_mut().as_mut() {
+            Some(linked) => Ok(Rc::clone(linked.entry(index).or_insert_with(|| {
+                Rc::new(RefCell::new(Type::Primitive(Primitive {
+                    kind: PrimitiveKind::NoType,
+                    indirection: None,
+                })))
+            }))),
+            None => Err(D::Error::custom(format!(
+                "reference to type {} outside of a PDB",
+                index
+            ))),
+        })
+    }
+
+    pub mod option {
+        use super::TypeRef;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(ty: &Option<TypeRef>, s: S) -> Result<S::Ok, S::Error> {
+            match ty {
+                Some(ty) => super::serialize(ty, s),
+                None => s.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<TypeRef>, D::Error> {
+            #[derive(Deserialize)]
+            struct Reference(#[serde(with = "super")] TypeRef);
+
+            Ok(Option::<Reference>::deserialize(d)?.map(|reference| reference.0))
+        }
+    }
+
+    pub mod vec {
+        use super::TypeRef;
+        use serde::ser::SerializeSeq;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(types: &[TypeRef], s: S) -> Result<S::Ok, S::Error> {
+            struct Reference<'a>(&'a TypeRef);
+
+            impl serde::Serialize for Reference<'_> {
+                fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                    super::serialize(self.0, s)
+                }
+            }
+
+            let mut seq = s.serialize_seq(Some(types.len()))?;
+            for ty in types {
+                seq.serialize_element(&Reference(ty))?;
+            }
+            seq.end()
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<TypeRef>, D::Error> {
+            #[derive(Deserialize)]
+            struct Reference(#[serde(with = "super")] TypeRef);
+
+            Ok(Vec::<Reference>::deserialize(d)?
+                .into_iter()
+                .map(|reference| reference.0)
+                .collect())
+        }
+    }
+}
+
+/// Serializes type references made by the values `f` serializes as indices into `types`,
+/// as [ParsedPdb] does with its own. For writing parts of a [ParsedPdb] separately
+#[cfg(feature = "serde")]
+pub fn with_type_indices<T>(
+    types: &BTreeMap<TypeIndexNumber, TypeRef>,
+    f: impl FnOnce() -> T,
+) -> T {
+    type_ref::with_indices(types, f)
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ParsedPdb {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        type_ref::with_indices(&self.types, || ParsedPdb::serialize(self, s))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for ParsedPdb {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let (pdb, linked) = type_ref::linking(|| ParsedPdb::deserialize(d));
+        let mut pdb = pdb?;
+        if pdb.format_version > FORMAT_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "format version {} is newer than the latest supported, {}",
+                pdb.format_version, FORMAT_VERSION
+            )));
+        }
+        type_ref::link(&mut pdb.types, linked).map_err(serde::de::Error::custom)?;
+        // Whatever layout was read, the model now holds the current one
+        pdb.format_version = FORMAT_VERSION;
+
+        Ok(pdb)
+    }
+}
+
+/// Output from before `format_version` was introduced is version 1
+#[cfg(feature = "serde")]
+fn legacy_format_version() -> u32 {
+    1
+}
+
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum MachineType {
     /// The contents of this field are assumed to be applicable to any machine type.
     Unknown,
@@ -150,7 +519,8 @@ impl From<&pdb::MachineType> for MachineType {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum Version {
     V41,
     V50,
@@ -175,18 +545,95 @@ impl From<&pdb::HeaderVersion> for Version {
 }
 
 #[derive(Debug, Default)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct AssemblyInfo {
     pub build_info: Option<BuildInfo>,
     pub compiler_info: Option<CompilerInfo>,
+    /// Sanitizers, coverage, and profiling instrumentation the build was compiled with
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub instrumentation: Vec<Instrumentation>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+/// Instrumentation detected from characteristic runtime symbols and compiler flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum Instrumentation {
+    AddressSanitizer,
+    UndefinedBehaviorSanitizer,
+    /// `-fsanitize-coverage` or `/fsanitize-coverage` edge and comparison callbacks
+    SanitizerCoverage,
+    /// libFuzzer's `LLVMFuzzerTestOneInput` entry point
+    LibFuzzer,
+    /// Counters written by a profile-generating build, e.g. `/GENPROFILE` or
+    /// `-fprofile-instr-generate`
+    ProfileInstrumentation,
+    /// Optimized using a profile collected from an instrumented build
+    ProfileGuidedOptimization,
+}
+
+impl Instrumentation {
+    /// Returns whether this is a sanitizer runtime rather than coverage or profiling
+    pub fn is_sanitizer(self) -> bool {
+        matches!(
+            self,
+            Instrumentation::AddressSanitizer | Instrumentation::UndefinedBehaviorSanitizer
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct BuildInfo {
     arguments: Vec<String>,
 }
 
+impl BuildInfo {
+    /// All arguments of the `LF_BUILDINFO` record, in record order
+    pub fn arguments(&self) -> &[String] {
+        &self.arguments
+    }
+
+    /// Mutable access to the arguments, e.g. for rewriting the paths they contain
+    pub fn arguments_mut(&mut self) -> &mut [String] {
+        &mut self.arguments
+    }
+
+    fn argument(&self, index: usize) -> Option<&str> {
+        self.arguments
+            .get(index)
+            .map(String::as_str)
+            .filter(|argument| !argument.is_empty())
+    }
+
+    /// Working directory of the build tool
+    pub fn current_directory(&self) -> Option<&str> {
+        self.argument(0)
+    }
+
+    /// Path to the build tool, e.g. `cl.exe`
+    pub fn build_tool(&self) -> Option<&str> {
+        self.argument(1)
+    }
+
+    /// Source file passed to the build tool
+    pub fn source_file(&self) -> Option<&str> {
+        self.argument(2)
+    }
+
+    /// PDB the build tool wrote to
+    pub fn pdb_file(&self) -> Option<&str> {
+        self.argument(3)
+    }
+
+    /// Command line arguments passed to the build tool
+    pub fn command_arguments(&self) -> Option<&str> {
+        self.argument(4)
+    }
+}
+
 impl TryFrom<(&pdb::BuildInfoSymbol, Option<&pdb::IdFinder<'_>>)> for BuildInfo {
     type Error = crate::error::Error;
 
@@ -200,38 +647,27 @@ impl TryFrom<(&pdb::BuildInfoSymbol, Option<&pdb::IdFinder<'_>>)> for BuildInfo
 
         let finder = finder.unwrap();
 
-        let build_info = finder
-            .find(symbol.id)?
-            .parse()
-            .expect("failed to parse build info");
-        match build_info {
+        match finder.find(symbol.id)?.parse()? {
             pdb::IdData::BuildInfo(build_info_id) => {
-                let argument_ids: Vec<_> = build_info_id
+                let arguments = build_info_id
                     .arguments
                     .iter()
-                    .map(|id| finder.find(*id).expect("failed to parse ID"))
-                    .collect();
-
-                // TODO: Move this out into its own function for ID parsing
-                let arguments: Vec<String> = argument_ids
-                    .iter()
-                    .map(|id| match id.parse().expect("failed to parse ID") {
-                        pdb::IdData::String(s) => s.name.to_string().into_owned(),
-                        other => panic!("unexpected ID type : {:?}", other),
-                    })
-                    .collect();
+                    .map(|id| crate::ids::string_id(finder, *id))
+                    .collect::<Result<Vec<_>, _>>()?;
 
-                return Ok(BuildInfo { arguments });
+                Ok(BuildInfo { arguments })
             }
-            _ => unreachable!(),
-        };
-
-        Err(crate::error::Error::Unsupported("BuildInfo"))
+            _ => Err(crate::error::Error::UnexpectedId(
+                symbol.id.0,
+                "LF_BUILDINFO",
+            )),
+        }
     }
 }
 
-#[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct CompilerInfo {
     // TODO: cpu_type, flags, language
     pub language: String,
@@ -264,8 +700,9 @@ impl From<pdb::CompileFlagsSymbol<'_>> for CompilerInfo {
     }
 }
 
-#[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct CompileFlags {
     /// Compiled for edit and continue.
     pub edit_and_continue: bool,
@@ -328,8 +765,9 @@ impl From<pdb::CompileFlags> for CompileFlags {
     }
 }
 
-#[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct CompilerVersion {
     pub major: u16,
     pub minor: u16,
@@ -355,21 +793,81 @@ impl From<pdb::CompilerVersion> for CompilerVersion {
     }
 }
 
+/// Digests of a file, for telling apart samples which share a name
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FileHashes {
+    pub path: PathBuf,
+    /// Size of the file in BYTES
+    pub size: u64,
+    #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub md5: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub sha1: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub sha256: Vec<u8>,
+}
+
+/// The files a [ParsedPdb] was produced from
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Identity {
+    pub pdb: FileHashes,
+    /// The image the PDB belongs to, if one was provided
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub pe: Option<FileHashes>,
+}
+
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct DebugModule {
-    name: String,
-    object_file_name: String,
-    source_files: Option<Vec<FileInfo>>,
+    pub name: String,
+    pub object_file_name: String,
+    pub source_files: Option<Vec<FileInfo>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lines: Vec<LineInfo>,
+    /// The module's `S_BUILDINFO` record describing how it was compiled
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub build_info: Option<BuildInfo>,
+    /// The module's `S_COMPILE3` record describing the compiler and its flags
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compiler_info: Option<CompilerInfo>,
 }
 
-#[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
-enum Checksum {
+/// Digest of a source file, serialized as its `algorithm` and a lowercase hex `digest`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "algorithm", content = "digest", rename_all = "snake_case")
+)]
+pub enum Checksum {
     None,
-    Md5(Vec<u8>),
-    Sha1(Vec<u8>),
-    Sha256(Vec<u8>),
+    Md5(
+        #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        Vec<u8>,
+    ),
+    Sha1(
+        #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        Vec<u8>,
+    ),
+    Sha256(
+        #[cfg_attr(feature = "serde", serde(with = "hex_bytes"))]
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        Vec<u8>,
+    ),
 }
 
 impl From<pdb::FileChecksum<'_>> for Checksum {
@@ -384,10 +882,41 @@ impl From<pdb::FileChecksum<'_>> for Checksum {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct FileInfo {
-    name: String,
-    checksum: Checksum,
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_checksum"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Checksum"))]
+    pub checksum: Checksum,
+}
+
+/// Reads a [Checksum] in the current layout, or in the externally tagged layout of
+/// format versions 1 and 2, e.g. `{"Md5": [10, 27, ..]}`
+#[cfg(feature = "serde")]
+fn deserialize_checksum<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Checksum, D::Error> {
+    #[derive(Deserialize)]
+    enum Legacy {
+        None,
+        Md5(Vec<u8>),
+        Sha1(Vec<u8>),
+        Sha256(Vec<u8>),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Layout {
+        Current(Checksum),
+        Legacy(Legacy),
+    }
+
+    Ok(match Layout::deserialize(d)? {
+        Layout::Current(checksum) => checksum,
+        Layout::Legacy(Legacy::None) => Checksum::None,
+        Layout::Legacy(Legacy::Md5(digest)) => Checksum::Md5(digest),
+        Layout::Legacy(Legacy::Sha1(digest)) => Checksum::Sha1(digest),
+        Layout::Legacy(Legacy::Sha256(digest)) => Checksum::Sha256(digest),
+    })
 }
 
 impl
@@ -416,7 +945,7 @@ impl
                                     .name
                                     .to_string_lossy(string_table)
                                     .expect("failed to convert string")
-                                    .to_string();
+                                    .into_owned();
 
                                 Ok(FileInfo {
                                     name: file_name,
@@ -431,15 +960,78 @@ impl
             .flatten();
 
         DebugModule {
-            name: module.module_name().to_string(),
-            object_file_name: module.object_file_name().to_string(),
+            name: module.module_name().into_owned(),
+            object_file_name: module.object_file_name().into_owned(),
             source_files,
+            lines: vec![],
+            build_info: None,
+            compiler_info: None,
+        }
+    }
+}
+
+/// Where a symbol lives in the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum Location {
+    /// In one of the image's sections, at the symbol's `offset`. Output from before
+    /// locations were recorded only has offsets for these, so this is the default
+    #[default]
+    Image,
+    /// Not in any section (section index 0). The symbol is an absolute value set by the
+    /// linker, such as `__guard_flags`, rather than an address
+    Absolute { value: u32 },
+    /// A named compile-time value from an `S_CONSTANT` record, which has no storage.
+    /// Unsigned values above `i64::MAX` are stored as their two's complement
+    Constant { value: i64 },
+    /// The symbol's section no longer exists in the image, typically because the linker
+    /// removed or folded it (`/OPT:REF`, `/OPT:ICF`)
+    Removed,
+    /// The PDB has no address map to translate the symbol's address with
+    Unknown,
+}
+
+impl Location {
+    /// Short name of this location, e.g. `absolute`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Location::Image => "image",
+            Location::Absolute { .. } => "absolute",
+            Location::Constant { .. } => "constant",
+            Location::Removed => "removed",
+            Location::Unknown => "unknown",
         }
     }
 }
 
+/// Translates a symbol's section offset to an RVA and classifies where it lives
+fn locate(
+    offset: pdb::PdbInternalSectionOffset,
+    address_map: Option<&pdb::AddressMap<'_>>,
+) -> (Option<usize>, Location) {
+    if offset.section == 0 {
+        return (
+            None,
+            Location::Absolute {
+                value: offset.offset,
+            },
+        );
+    }
+
+    match address_map {
+        Some(address_map) => match offset.to_rva(address_map) {
+            Some(rva) => (Some(u32::from(rva) as usize), Location::Image),
+            None => (None, Location::Removed),
+        },
+        None => (None, Location::Unknown),
+    }
+}
+
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct PublicSymbol {
     pub name: String,
     pub is_code: bool,
@@ -447,11 +1039,14 @@ pub struct PublicSymbol {
     pub is_managed: bool,
     pub is_msil: bool,
     pub offset: Option<usize>,
+    /// Why `offset` is absent, if it is
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub location: Location,
 }
 
-impl From<(pdb::PublicSymbol<'_>, usize, Option<&pdb::AddressMap<'_>>)> for PublicSymbol {
-    fn from(data: (pdb::PublicSymbol<'_>, usize, Option<&pdb::AddressMap<'_>>)) -> Self {
-        let (sym, base_address, address_map) = data;
+impl From<(pdb::PublicSymbol<'_>, Option<&pdb::AddressMap<'_>>)> for PublicSymbol {
+    fn from(data: (pdb::PublicSymbol<'_>, Option<&pdb::AddressMap<'_>>)) -> Self {
+        let (sym, address_map) = data;
 
         let pdb::PublicSymbol {
             code,
@@ -462,32 +1057,23 @@ impl From<(pdb::PublicSymbol<'_>, usize, Option<&pdb::AddressMap<'_>>)> for Publ
             name,
         } = sym;
 
-        if offset.section == 0 {
-            warn!(
-                "symbol type has an invalid section index and RVA will be invalid: {:?}",
-                sym
-            )
-        }
-
-        let offset = address_map.and_then(|address_map| {
-            offset
-                .to_rva(address_map)
-                .map(|rva| u32::from(rva) as usize + base_address)
-        });
+        let (offset, location) = locate(offset, address_map);
 
         PublicSymbol {
-            name: name.to_string().to_string(),
+            name: name.to_string().into_owned(),
             is_code: code,
             is_function: function,
             is_managed: managed,
             is_msil: msil,
             offset,
+            location,
         }
     }
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Data {
     pub name: String,
 
@@ -495,17 +1081,21 @@ pub struct Data {
 
     pub is_managed: bool,
 
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub ty: TypeRef,
 
     pub offset: Option<usize>,
+    /// Why `offset` is absent, if it is
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub location: Location,
 }
 
 impl
     TryFrom<(
         pdb::DataSymbol<'_>,
-        usize,
         Option<&pdb::AddressMap<'_>>,
-        &HashMap<TypeIndexNumber, TypeRef>,
+        &BTreeMap<TypeIndexNumber, TypeRef>,
     )> for Data
 {
     type Error = crate::error::Error;
@@ -513,12 +1103,11 @@ impl
     fn try_from(
         data: (
             pdb::DataSymbol<'_>,
-            usize,
             Option<&pdb::AddressMap<'_>>,
-            &HashMap<TypeIndexNumber, TypeRef>,
+            &BTreeMap<TypeIndexNumber, TypeRef>,
         ),
     ) -> Result<Self, Self::Error> {
-        let (sym, base_address, address_map, parsed_types) = data;
+        let (sym, address_map, parsed_types) = data;
 
         let pdb::DataSymbol {
             global,
@@ -528,11 +1117,7 @@ impl
             name,
         } = sym;
 
-        let offset = address_map.and_then(|address_map| {
-            offset
-                .to_rva(address_map)
-                .map(|rva| u32::from(rva) as usize + base_address)
-        });
+        let (offset, location) = locate(offset, address_map);
 
         let ty = Rc::clone(
             parsed_types
@@ -541,19 +1126,64 @@ impl
         );
 
         let data = Data {
-            name: name.to_string().to_string(),
+            name: name.to_string().into_owned(),
             is_global: global,
             is_managed: managed,
             ty,
             offset,
+            location,
         };
 
         Ok(data)
     }
 }
 
+impl TryFrom<(pdb::ConstantSymbol<'_>, &BTreeMap<TypeIndexNumber, TypeRef>)> for Data {
+    type Error = crate::error::Error;
+
+    fn try_from(
+        data: (pdb::ConstantSymbol<'_>, &BTreeMap<TypeIndexNumber, TypeRef>),
+    ) -> Result<Self, Self::Error> {
+        let (sym, parsed_types) = data;
+
+        let pdb::ConstantSymbol {
+            managed,
+            type_index,
+            value,
+            name,
+        } = sym;
+
+        let value = match value {
+            pdb::Variant::U8(val) => val as i64,
+            pdb::Variant::U16(val) => val as i64,
+            pdb::Variant::U32(val) => val as i64,
+            pdb::Variant::U64(val) => val as i64,
+            pdb::Variant::I8(val) => val as i64,
+            pdb::Variant::I16(val) => val as i64,
+            pdb::Variant::I32(val) => val as i64,
+            pdb::Variant::I64(val) => val,
+        };
+
+        let ty = Rc::clone(
+            parsed_types
+                .get(&type_index.0)
+                .ok_or(Self::Error::UnresolvedType(type_index.0))?,
+        );
+
+        Ok(Data {
+            name: name.to_string().into_owned(),
+            is_global: true,
+            is_managed: managed,
+            ty,
+            offset: None,
+            location: Location::Constant { value },
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Procedure {
     pub name: String,
 
@@ -561,6 +1191,9 @@ pub struct Procedure {
     pub type_index: TypeIndexNumber,
 
     pub offset: Option<usize>,
+    /// Why `offset` is absent, if it is
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub location: Location,
     pub len: usize,
 
     pub is_global: bool,
@@ -568,12 +1201,61 @@ pub struct Procedure {
     /// length of this procedure in BYTES
     pub prologue_end: usize,
     pub epilogue_start: usize,
+    /// Parameters and local variables declared in this procedure
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub locals: Vec<LocalVariable>,
+    /// Names of the functions inlined into this procedure
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inlinees: Vec<String>,
+    /// Identifier which stays the same across builds as long as the function's module,
+    /// mangled name, and signature do. Assigned by consumers, see `pdbview`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub stable_id: Option<String>,
+    /// Hashes of this procedure's bytes. Only available when the PE image was provided
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub hashes: Option<FunctionHashes>,
+    /// Whether this procedure is in the image's Control Flow Guard function table. Only
+    /// available when the PE image was provided and was built with `/guard:cf`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub cfg_target: Option<bool>,
+    /// For procedures implementing a method, the key in [ParsedPdb::types] of the class
+    /// declaring it
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub declaring_class: Option<TypeIndexNumber>,
+    /// Exception handling and unwind funclets split out of this procedure. Only filled
+    /// in by [ParsedPdb::nest_funclets]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub funclets: Vec<Funclet>,
+}
+
+/// Hashes of a function's bytes used for correlating functions across binaries
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FunctionHashes {
+    /// SHA256 of the function's raw bytes
+    pub sha256: String,
+    /// SHA256 of the function's bytes with position-dependent bytes (relocations,
+    /// relative branch displacements) zeroed out. Stable when only the function's
+    /// address or its callees' addresses change.
+    pub masked_sha256: String,
 }
 
 impl
     From<(
         pdb::ProcedureSymbol<'_>,
-        usize,
         Option<&pdb::AddressMap<'_>>,
         &pdb::ItemFinder<'_, pdb::TypeIndex>,
     )> for Procedure
@@ -581,12 +1263,11 @@ impl
     fn from(
         data: (
             pdb::ProcedureSymbol<'_>,
-            usize,
             Option<&pdb::AddressMap<'_>>,
             &pdb::ItemFinder<'_, pdb::TypeIndex>,
         ),
     ) -> Self {
-        let (sym, base_address, address_map, type_finder) = data;
+        let (sym, address_map, type_finder) = data;
 
         let pdb::ProcedureSymbol {
             global,
@@ -603,18 +1284,7 @@ impl
             name,
         } = sym;
 
-        if offset.section == 0 {
-            warn!(
-                "symbol type has an invalid section index and RVA will be invalid: {:?}",
-                sym
-            )
-        }
-
-        let offset = address_map.and_then(|address_map| {
-            offset
-                .to_rva(address_map)
-                .map(|rva| u32::from(rva) as usize + base_address)
-        });
+        let (offset, location) = locate(offset, address_map);
 
         let signature = type_finder.find(type_index).ok().map(|type_info| {
             format!(
@@ -624,15 +1294,502 @@ impl
         });
 
         Procedure {
-            name: name.to_string().to_string(),
+            name: name.to_string().into_owned(),
             signature,
             type_index: type_index.0,
             offset,
+            location,
             len: len as usize,
             is_global: global,
             is_dpc: dpc,
             prologue_end: dbg_start_offset as usize,
             epilogue_start: dbg_end_offset as usize,
+            locals: vec![],
+            inlinees: vec![],
+            stable_id: None,
+            hashes: None,
+            cfg_target: None,
+            declaring_class: None,
+            funclets: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SeparatedCode {
+    pub offset: Option<usize>,
+    /// length of this block in BYTES
+    pub len: usize,
+    /// offset of the procedure this block was split from
+    pub parent_offset: Option<usize>,
+}
+
+impl From<(pdb::SeparatedCodeSymbol, Option<&pdb::AddressMap<'_>>)> for SeparatedCode {
+    fn from(data: (pdb::SeparatedCodeSymbol, Option<&pdb::AddressMap<'_>>)) -> Self {
+        let (sym, address_map) = data;
+
+        let pdb::SeparatedCodeSymbol {
+            len,
+            offset,
+            parent_offset,
+            ..
+        } = sym;
+
+        let to_address = |offset: pdb::PdbInternalSectionOffset| {
+            address_map.and_then(|address_map| {
+                offset
+                    .to_rva(address_map)
+                    .map(|rva| u32::from(rva) as usize)
+            })
+        };
+
+        SeparatedCode {
+            offset: to_address(offset),
+            len: len as usize,
+            parent_offset: to_address(parent_offset),
+        }
+    }
+}
+
+/// What a [Funclet] does for its parent procedure
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum FuncletKind {
+    /// Body of a `catch` block
+    Catch,
+    /// Body of a `__finally` block
+    Finally,
+    /// Filter expression of an `__except` block
+    Filter,
+    /// Destroys locals while unwinding
+    Unwind,
+}
+
+/// A block of code the compiler split out of a procedure to run during exception
+/// handling, such as `` `Foo'::`1'::catch$0 ``
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Funclet {
+    pub name: String,
+    pub kind: FuncletKind,
+    pub offset: Option<usize>,
+    /// length of this funclet in BYTES
+    pub len: usize,
+}
+
+/// A string from the PDB's `/names` stream
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct StringTableEntry {
+    /// Offset of the string in the stream's buffer, which records refer to it by
+    pub offset: u32,
+    pub value: String,
+}
+
+/// An `S_THUNK32` stub, such as an incremental linking jump or an adjustor thunk
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Thunk {
+    pub name: String,
+    pub offset: Option<usize>,
+    /// Why `offset` is absent, if it is
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub location: Location,
+    /// length of this thunk in BYTES
+    pub len: usize,
+    /// `adjustor`, `vcall`, `pcode`, `load`, or `none`
+    pub kind: String,
+}
+
+impl From<(pdb::ThunkSymbol<'_>, Option<&pdb::AddressMap<'_>>)> for Thunk {
+    fn from(data: (pdb::ThunkSymbol<'_>, Option<&pdb::AddressMap<'_>>)) -> Self {
+        let (sym, address_map) = data;
+
+        let pdb::ThunkSymbol {
+            offset,
+            len,
+            kind,
+            name,
+            ..
+        } = sym;
+
+        let (offset, location) = locate(offset, address_map);
+        let kind = match kind {
+            pdb::ThunkKind::NoType => "none",
+            pdb::ThunkKind::Adjustor(_) => "adjustor",
+            pdb::ThunkKind::VCall(_) => "vcall",
+            pdb::ThunkKind::PCode => "pcode",
+            pdb::ThunkKind::Load => "load",
+            pdb::ThunkKind::Unknown(_) => "unknown",
+        };
+
+        Thunk {
+            name: name.to_string().into_owned(),
+            offset,
+            location,
+            len: len as usize,
+            kind: kind.to_string(),
+        }
+    }
+}
+
+/// The bytes of a symbol or type record as stored in the PDB, kept so records this
+/// crate cannot decode can still be investigated
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RawRecord {
+    /// `tpi` for type records, `globals` for the global symbol stream, or the name of
+    /// the module whose symbol stream holds the record
+    pub stream: String,
+    /// CodeView record kind, e.g. `0x110F` for `S_GPROC32`
+    pub kind: u16,
+    /// Type index of a type record, or the offset of a symbol record in its stream
+    pub index: u32,
+    pub name: Option<String>,
+    /// Why the record could not be decoded, if it couldn't be
+    pub error: Option<String>,
+    /// Record bytes as hex, starting with the record kind
+    pub bytes: String,
+}
+
+impl RawRecord {
+    pub(crate) fn new(
+        stream: &str,
+        kind: u16,
+        index: u32,
+        bytes: &[u8],
+        name: Option<String>,
+        error: Option<String>,
+    ) -> Self {
+        RawRecord {
+            stream: stream.to_string(),
+            kind,
+            index,
+            name,
+            error,
+            bytes: bytes.iter().map(|byte| format!("{:02X}", byte)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SectionContribution {
+    pub offset: Option<usize>,
+    pub size: usize,
+    /// Index into [ParsedPdb::debug_modules] of the module which contributed this range
+    pub module_index: usize,
+}
+
+impl From<(&pdb::DBISectionContribution, Option<&pdb::AddressMap<'_>>)> for SectionContribution {
+    fn from(data: (&pdb::DBISectionContribution, Option<&pdb::AddressMap<'_>>)) -> Self {
+        let (contribution, address_map) = data;
+
+        let offset = address_map.and_then(|address_map| {
+            contribution
+                .offset
+                .to_rva(address_map)
+                .map(|rva| u32::from(rva) as usize)
+        });
+
+        SectionContribution {
+            offset,
+            size: contribution.size as usize,
+            module_index: contribution.module,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Section {
+    pub name: String,
+    /// 1-based section number as used by `section:offset` addresses
+    pub index: usize,
+    pub offset: usize,
+    pub size: usize,
+    pub characteristics: u32,
+}
+
+/// The section contains executable code
+pub const IMAGE_SCN_CNT_CODE: u32 = 0x0000_0020;
+/// The section contains initialized data
+pub const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x0000_0040;
+/// The section contains uninitialized data
+pub const IMAGE_SCN_CNT_UNINITIALIZED_DATA: u32 = 0x0000_0080;
+/// The section can be executed as code
+pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+impl Section {
+    pub fn is_code(&self) -> bool {
+        self.characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0
+    }
+
+    /// Returns whether `address` falls within this section
+    pub fn contains(&self, address: usize) -> bool {
+        address >= self.offset && address - self.offset < self.size
+    }
+}
+
+impl From<(usize, &pdb::ImageSectionHeader)> for Section {
+    fn from(data: (usize, &pdb::ImageSectionHeader)) -> Self {
+        let (index, header) = data;
+
+        let name = String::from_utf8_lossy(&header.name)
+            .trim_end_matches('\0')
+            .to_string();
+
+        // `physical_address` holds the VirtualSize for images. Some linkers leave it as zero
+        let size = if header.physical_address != 0 {
+            header.physical_address
+        } else {
+            header.size_of_raw_data
+        };
+
+        Section {
+            name,
+            index,
+            offset: header.virtual_address as usize,
+            size: size as usize,
+            characteristics: header.characteristics.0,
+        }
+    }
+}
+
+/// An export recorded by the linker in the `* Linker *` module
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct Export {
+    pub name: String,
+    pub ordinal: u16,
+    pub is_constant: bool,
+    pub is_data: bool,
+    pub is_private: bool,
+    pub is_no_name: bool,
+    /// The ordinal was explicitly assigned rather than chosen by the linker
+    pub is_explicit_ordinal: bool,
+    pub is_forwarder: bool,
+}
+
+impl From<pdb::ExportSymbol<'_>> for Export {
+    fn from(sym: pdb::ExportSymbol<'_>) -> Self {
+        let pdb::ExportSymbol {
+            ordinal,
+            flags,
+            name,
+        } = sym;
+
+        Export {
+            name: name.to_string().into_owned(),
+            ordinal,
+            is_constant: flags.constant,
+            is_data: flags.data,
+            is_private: flags.private,
+            is_no_name: flags.no_name,
+            is_explicit_ordinal: flags.ordinal,
+            is_forwarder: flags.forwarder,
+        }
+    }
+}
+
+/// Maps a range of code to the source line it was generated from
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct LineInfo {
+    pub offset: Option<usize>,
+    /// length of the code range in BYTES, if known
+    pub len: Option<usize>,
+    pub file: String,
+    pub line_start: u32,
+    pub line_end: u32,
+    pub column_start: Option<u16>,
+    pub column_end: Option<u16>,
+}
+
+impl
+    TryFrom<(
+        &pdb::LineInfo,
+        &pdb::LineProgram<'_>,
+        &pdb::StringTable<'_>,
+        Option<&pdb::AddressMap<'_>>,
+    )> for LineInfo
+{
+    type Error = crate::error::Error;
+
+    fn try_from(
+        data: (
+            &pdb::LineInfo,
+            &pdb::LineProgram<'_>,
+            &pdb::StringTable<'_>,
+            Option<&pdb::AddressMap<'_>>,
+        ),
+    ) -> Result<Self, Self::Error> {
+        let (line, line_program, string_table, address_map) = data;
+
+        let file = line_program
+            .get_file_info(line.file_index)?
+            .name
+            .to_string_lossy(string_table)?
+            .into_owned();
+
+        let offset = address_map.and_then(|address_map| {
+            line.offset
+                .to_rva(address_map)
+                .map(|rva| u32::from(rva) as usize)
+        });
+
+        Ok(LineInfo {
+            offset,
+            len: line.length.map(|len| len as usize),
+            file,
+            line_start: line.line_start,
+            line_end: line.line_end,
+            column_start: line.column_start,
+            column_end: line.column_end,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct LocalVariable {
+    pub name: String,
+    pub type_index: TypeIndexNumber,
+    /// Whether this is one of the procedure's parameters. Register-relative variables
+    /// don't record this, so the parser infers it from the procedure's type
+    pub is_parameter: bool,
+    /// Register the variable's storage is relative to, if it lives on the stack
+    pub register: Option<String>,
+    /// Offset from `register` to the variable's storage
+    pub register_offset: Option<i32>,
+}
+
+/// Returns the name of a CodeView register number for the registers which
+/// locals are commonly addressed relative to
+pub fn register_name(register: u16) -> String {
+    match register {
+        // CV_REG_*
+        21 => "esp".to_string(),
+        22 => "ebp".to_string(),
+        // CV_AMD64_*
+        334 => "rbp".to_string(),
+        335 => "rsp".to_string(),
+        other => format!("reg{}", other),
+    }
+}
+
+impl From<pdb::RegisterRelativeSymbol<'_>> for LocalVariable {
+    fn from(sym: pdb::RegisterRelativeSymbol<'_>) -> Self {
+        let pdb::RegisterRelativeSymbol {
+            offset,
+            type_index,
+            register,
+            name,
+            ..
+        } = sym;
+
+        LocalVariable {
+            name: name.to_string().into_owned(),
+            type_index: type_index.0,
+            is_parameter: false,
+            register: Some(register_name(register.0)),
+            register_offset: Some(offset),
+        }
+    }
+}
+
+impl From<pdb::LocalSymbol<'_>> for LocalVariable {
+    fn from(sym: pdb::LocalSymbol<'_>) -> Self {
+        let pdb::LocalSymbol {
+            type_index,
+            flags,
+            name,
+        } = sym;
+
+        LocalVariable {
+            name: name.to_string().into_owned(),
+            type_index: type_index.0,
+            is_parameter: flags.isparam,
+            register: None,
+            register_offset: None,
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::type_info::{Modifier, Primitive, PrimitiveKind};
+
+    fn modifier(underlying_type: &TypeRef) -> TypeRef {
+        Rc::new(RefCell::new(Type::Modifier(Modifier {
+            underlying_type: Rc::clone(underlying_type),
+            constant: true,
+            volatile: false,
+            unaligned: false,
+        })))
+    }
+
+    /// A `char` at 0x1000 and two `const char`s referring to it
+    fn pdb_with_shared_type() -> ParsedPdb {
+        let mut pdb = ParsedPdb::new(PathBuf::from("test.pdb"));
+        let char_type = Rc::new(RefCell::new(Type::Primitive(Primitive {
+            kind: PrimitiveKind::Char,
+            indirection: None,
+        })));
+        pdb.types.insert(0x1001, modifier(&char_type));
+        pdb.types.insert(0x1002, modifier(&char_type));
+        pdb.types.insert(0x1000, char_type);
+
+        pdb
+    }
+
+    #[test]
+    fn type_references_are_relinked_by_index() {
+        let json = serde_json::to_value(&pdb_with_shared_type()).unwrap();
+        assert_eq!(json["types"]["4097"]["Modifier"]["underlying_type"], 0x1000);
+
+        let pdb: ParsedPdb = serde_json::from_value(json).unwrap();
+        let underlying_type = |index: TypeIndexNumber| match &*pdb.types[&index].as_ref().borrow() {
+            Type::Modifier(modifier) => Rc::clone(&modifier.underlying_type),
+            _ => panic!("type {:#x} is not a modifier", index),
+        };
+        assert!(Rc::ptr_eq(&underlying_type(0x1001), &pdb.types[&0x1000]));
+        assert!(Rc::ptr_eq(&underlying_type(0x1002), &pdb.types[&0x1000]));
+        assert!(matches!(
+            &*pdb.types[&0x1000].as_ref().borrow(),
+            Type::Primitive(Primitive {
+                kind: PrimitiveKind::Char,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn reference_to_missing_type_is_rejected() {
+        let mut json = serde_json::to_value(&pdb_with_shared_type()).unwrap();
+        json["types"].as_object_mut().unwrap().remove("4096");
+
+        assert!(serde_json::from_value::<ParsedPdb>(json).is_err());
+    }
+
+    #[test]
+    fn newer_format_version_is_rejected() {
+        let mut json = serde_json::to_value(&pdb_with_shared_type()).unwrap();
+        json["format_version"] = (FORMAT_VERSION + 1).into();
+
+        assert!(serde_json::from_value::<ParsedPdb>(json).is_err());
+    }
+}
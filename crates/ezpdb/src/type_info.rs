@@ -1,8 +1,14 @@
 use crate::error::Error;
+#[cfg(feature = "serde")]
+use crate::symbol_types::type_ref;
 use crate::symbol_types::ParsedPdb;
-use crate::symbol_types::TypeRef;
+#[cfg(feature = "schema")]
+use crate::symbol_types::TypeReference;
+use crate::symbol_types::{TypeIndexNumber, TypeRef};
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::convert::{From, TryFrom, TryInto};
 use std::rc::Rc;
 
@@ -15,7 +21,8 @@ pub trait Typed {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum Type {
     Class(Class),
     VirtualBaseClass(VirtualBaseClass),
@@ -42,6 +49,191 @@ pub enum Type {
     VTable(VTable),
 }
 
+impl Type {
+    /// Where this type was defined, if it's a class, union, or enum with a known source
+    pub fn source(&self) -> Option<&TypeSource> {
+        match self {
+            Type::Class(class) => class.source.as_ref(),
+            Type::Union(union) => union.source.as_ref(),
+            Type::Enumeration(e) => e.source.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn source_mut(&mut self) -> Option<&mut TypeSource> {
+        match self {
+            Type::Class(class) => class.source.as_mut(),
+            Type::Union(union) => union.source.as_mut(),
+            Type::Enumeration(e) => e.source.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Records where a class, union, or enum was defined. Other types are unaffected
+    pub(crate) fn set_source(&mut self, source: TypeSource) {
+        match self {
+            Type::Class(class) => class.source = Some(source),
+            Type::Union(union) => union.source = Some(source),
+            Type::Enumeration(e) => e.source = Some(source),
+            _ => {}
+        }
+    }
+
+    /// Returns the name forward references to this class, union, or enum are resolved
+    /// by, its unique name or its name if it has none, and whether it is a forward
+    /// reference itself
+    pub fn definition_key(&self) -> Option<(&str, bool)> {
+        let (name, unique_name, properties) = match self {
+            Type::Class(class) => (&class.name, &class.unique_name, &class.properties),
+            Type::Union(union) => (&union.name, &union.unique_name, &union.properties),
+            Type::Enumeration(e) => (&e.name, &e.unique_name, &e.properties),
+            _ => return None,
+        };
+
+        Some((
+            unique_name.as_deref().unwrap_or(name),
+            properties.forward_reference,
+        ))
+    }
+
+    /// Key of this forward reference's definition in [ParsedPdb::types], if found
+    pub fn definition(&self) -> Option<TypeIndexNumber> {
+        match self {
+            Type::Class(class) => class.definition,
+            Type::Union(union) => union.definition,
+            Type::Enumeration(e) => e.definition,
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_definition(&mut self, definition: TypeIndexNumber) {
+        match self {
+            Type::Class(class) => class.definition = Some(definition),
+            Type::Union(union) => union.definition = Some(definition),
+            Type::Enumeration(e) => e.definition = Some(definition),
+            _ => {}
+        }
+    }
+
+    /// The types this type refers to, such as a class's fields or a pointer's
+    /// underlying type
+    pub fn references_mut(&mut self) -> Vec<&mut TypeRef> {
+        match self {
+            Type::Class(class) => class
+                .derived_from
+                .iter_mut()
+                .chain(class.fields.iter_mut())
+                .collect(),
+            Type::VirtualBaseClass(base) => vec![&mut base.base_class, &mut base.base_pointer],
+            Type::Union(union) => union.fields.iter_mut().collect(),
+            Type::Bitfield(bitfield) => vec![&mut bitfield.underlying_type],
+            Type::Enumeration(e) => vec![&mut e.underlying_type],
+            Type::Pointer(pointer) => pointer.underlying_type.iter_mut().collect(),
+            Type::Array(array) => vec![&mut array.element_type, &mut array.indexing_type],
+            Type::FieldList(fields) => fields.0.iter_mut().collect(),
+            Type::ArgumentList(arguments) => arguments.0.iter_mut().collect(),
+            Type::Modifier(modifier) => vec![&mut modifier.underlying_type],
+            Type::Member(member) => vec![&mut member.underlying_type],
+            Type::Procedure(procedure) => procedure
+                .return_type
+                .iter_mut()
+                .chain(procedure.argument_list.iter_mut())
+                .collect(),
+            Type::MemberFunction(function) => std::iter::once(&mut function.return_type)
+                .chain(std::iter::once(&mut function.class_type))
+                .chain(function.this_pointer_type.iter_mut())
+                .chain(function.argument_list.iter_mut())
+                .collect(),
+            Type::MethodList(methods) => methods
+                .0
+                .iter_mut()
+                .map(|entry| &mut entry.method_type)
+                .collect(),
+            Type::MethodListEntry(entry) => vec![&mut entry.method_type],
+            Type::Nested(nested) => vec![&mut nested.nested_type],
+            Type::OverloadedMethod(overloaded) => vec![&mut overloaded.method_list],
+            Type::Method(method) => vec![&mut method.method_type],
+            Type::StaticMember(member) => vec![&mut member.field_type],
+            Type::BaseClass(base) => vec![&mut base.base_class],
+            Type::VTable(vtable) => vec![&mut vtable.0],
+            Type::EnumVariant(_) | Type::Primitive(_) => vec![],
+        }
+    }
+
+    /// Whether this is a class, union, or enum without a name of its own, such as
+    /// `<unnamed-tag>` or `Outer::<unnamed-type-u>`
+    pub fn is_anonymous(&self) -> bool {
+        let name = match self {
+            Type::Class(class) => &class.name,
+            Type::Union(union) => &union.name,
+            Type::Enumeration(e) => &e.name,
+            _ => return false,
+        };
+
+        let last = name.rsplit("::").next().unwrap_or(name);
+        last.starts_with("<unnamed") || last.starts_with("<anonymous") || last == "__unnamed"
+    }
+
+    /// Key in [ParsedPdb::types] of the class or union this type is nested in
+    pub fn parent(&self) -> Option<TypeIndexNumber> {
+        match self {
+            Type::Class(class) => class.parent,
+            Type::Union(union) => union.parent,
+            Type::Enumeration(e) => e.parent,
+            _ => None,
+        }
+    }
+
+    /// Returns the synthesized name of an anonymous type, or the name of a named class,
+    /// union, or enum
+    pub fn display_name(&self) -> Option<&str> {
+        let (name, synthesized_name) = match self {
+            Type::Class(class) => (&class.name, &class.synthesized_name),
+            Type::Union(union) => (&union.name, &union.synthesized_name),
+            Type::Enumeration(e) => (&e.name, &e.synthesized_name),
+            _ => return None,
+        };
+
+        Some(synthesized_name.as_deref().unwrap_or(name))
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: TypeIndexNumber) {
+        match self {
+            Type::Class(class) => class.parent = Some(parent),
+            Type::Union(union) => union.parent = Some(parent),
+            Type::Enumeration(e) => e.parent = Some(parent),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn set_synthesized_name(&mut self, name: String) {
+        match self {
+            Type::Class(class) => class.synthesized_name = Some(name),
+            Type::Union(union) => union.synthesized_name = Some(name),
+            Type::Enumeration(e) => e.synthesized_name = Some(name),
+            _ => {}
+        }
+    }
+}
+
+/// Returns the size of a forward reference's definition
+fn definition_size(definition: Option<TypeIndexNumber>, pdb: &ParsedPdb) -> Option<usize> {
+    let definition = pdb.types.get(&definition?)?;
+    // Already borrowed if the definition is being completed
+    let definition = definition.as_ref().try_borrow().ok()?;
+
+    Some(definition.type_size(pdb))
+}
+
+/// Source file and line a user-defined type was defined at
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct TypeSource {
+    pub file: String,
+    pub line: u32,
+}
+
 impl Typed for Type {
     fn type_size(&self, pdb: &ParsedPdb) -> usize {
         match self {
@@ -85,7 +277,8 @@ impl Typed for Type {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct TypeProperties {
     pub packed: bool,
     pub constructors: bool,
@@ -126,34 +319,54 @@ impl TryFrom<pdb::TypeProperties> for TypeProperties {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Class {
     pub name: String,
     pub unique_name: Option<String>,
     pub kind: ClassKind,
     pub properties: TypeProperties,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::option"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<TypeReference>"))]
     pub derived_from: Option<TypeRef>,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::vec"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<TypeReference>"))]
     pub fields: Vec<TypeRef>,
     pub size: usize,
+    /// Where this type was defined, from its `LF_UDT_SRC_LINE` record
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub source: Option<TypeSource>,
+    /// For forward references, the key of the full definition in [ParsedPdb::types]
+    /// if the PDB has one
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub definition: Option<TypeIndexNumber>,
+    /// Key in [ParsedPdb::types] of the class or union this type is nested in or is
+    /// the type of an anonymous member of
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub parent: Option<TypeIndexNumber>,
+    /// For anonymous types, a name which stays the same across runs, e.g.
+    /// `Parent::<unnamed_0x1234>` where `0x1234` is the key of the definition
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub synthesized_name: Option<String>,
 }
 
 impl Typed for Class {
     fn type_size(&self, pdb: &ParsedPdb) -> usize {
         if self.properties.forward_reference {
-            // Find the implementation
-            for value in pdb.types.values() {
-                if let Ok(borrow) = value.as_ref().try_borrow() {
-                    if let Type::Class(class) = &*borrow {
-                        if !class.properties.forward_reference
-                            && class.unique_name == self.unique_name
-                        {
-                            return class.type_size(pdb);
-                        }
-                    }
-                }
+            if let Some(size) = definition_size(self.definition, pdb) {
+                return size;
             }
 
-            println!("could not get forward reference for {}", self.name);
+            log::warn!("could not get forward reference for {}", self.name);
         }
 
         self.size
@@ -214,13 +427,20 @@ impl TryFrom<FromClass<'_, '_>> for Class {
             derived_from,
             fields,
             size: size as usize,
+            source: None,
+            definition: None,
+            parent: None,
+            synthesized_name: None,
         })
     }
 }
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct BaseClass {
     pub kind: ClassKind,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub base_class: TypeRef,
     pub offset: usize,
 }
@@ -254,10 +474,15 @@ impl TryFrom<FromBaseClass<'_, '_>> for BaseClass {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct VirtualBaseClass {
     pub direct: bool,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub base_class: TypeRef,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub base_pointer: TypeRef,
     pub base_pointer_offset: usize,
     pub virtual_base_offset: usize,
@@ -299,7 +524,8 @@ impl TryFrom<FromVirtualBaseClass<'_, '_>> for VirtualBaseClass {
 }
 
 #[derive(Debug, Copy, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum ClassKind {
     Class,
     Struct,
@@ -328,33 +554,51 @@ impl std::fmt::Display for ClassKind {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Union {
     pub name: String,
     pub unique_name: Option<String>,
     pub properties: TypeProperties,
     pub size: usize,
     pub count: usize,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::vec"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<TypeReference>"))]
     pub fields: Vec<TypeRef>,
+    /// Where this type was defined, from its `LF_UDT_SRC_LINE` record
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub source: Option<TypeSource>,
+    /// For forward references, the key of the full definition in [ParsedPdb::types]
+    /// if the PDB has one
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub definition: Option<TypeIndexNumber>,
+    /// Key in [ParsedPdb::types] of the class or union this type is nested in or is
+    /// the type of an anonymous member of
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub parent: Option<TypeIndexNumber>,
+    /// For anonymous types, a name which stays the same across runs, e.g.
+    /// `Parent::<unnamed_0x1234>` where `0x1234` is the key of the definition
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub synthesized_name: Option<String>,
 }
 
 impl Typed for Union {
     fn type_size(&self, pdb: &ParsedPdb) -> usize {
         if self.properties.forward_reference {
-            // Find the implementation
-            for (_key, value) in &pdb.types {
-                if let Some(value) = value.as_ref().try_borrow().ok() {
-                    if let Type::Union(union) = &*value {
-                        if !union.properties.forward_reference
-                            && union.unique_name == self.unique_name
-                        {
-                            return union.type_size(pdb);
-                        }
-                    }
-                }
+            if let Some(size) = definition_size(self.definition, pdb) {
+                return size;
             }
 
-            println!("could not get forward reference for {}", self.name);
+            log::warn!("could not get forward reference for {}", self.name);
         }
 
         self.size
@@ -399,6 +643,10 @@ impl TryFrom<FromUnion<'_, '_>> for Union {
             size: *size as usize,
             count: *count as usize,
             fields,
+            source: None,
+            definition: None,
+            parent: None,
+            synthesized_name: None,
         };
 
         Ok(union)
@@ -411,8 +659,11 @@ type FromBitfield<'a, 'b> = (
     &'b mut crate::symbol_types::ParsedPdb,
 );
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Bitfield {
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub underlying_type: TypeRef,
     pub len: usize,
     pub position: usize,
@@ -444,13 +695,40 @@ impl Typed for Bitfield {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Enumeration {
     pub name: String,
     pub unique_name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub underlying_type: TypeRef,
     pub variants: Vec<EnumVariant>,
     pub properties: TypeProperties,
+    /// Where this type was defined, from its `LF_UDT_SRC_LINE` record
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub source: Option<TypeSource>,
+    /// For forward references, the key of the full definition in [ParsedPdb::types]
+    /// if the PDB has one
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub definition: Option<TypeIndexNumber>,
+    /// Key in [ParsedPdb::types] of the class or union this type is nested in or is
+    /// the type of an anonymous member of
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub parent: Option<TypeIndexNumber>,
+    /// For anonymous types, a name which stays the same across runs, e.g.
+    /// `Parent::<unnamed_0x1234>` where `0x1234` is the key of the definition
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub synthesized_name: Option<String>,
 }
 
 type FromEnumeration<'a, 'b> = (
@@ -505,12 +783,17 @@ impl TryFrom<FromEnumeration<'_, '_>> for Enumeration {
             underlying_type,
             variants: fields,
             properties: properties.try_into()?,
+            source: None,
+            definition: None,
+            parent: None,
+            synthesized_name: None,
         })
     }
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct EnumVariant {
     pub name: String,
     pub value: VariantValue,
@@ -537,7 +820,8 @@ impl TryFrom<FromEnumerate<'_, '_>> for EnumVariant {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum VariantValue {
     U8(u8),
     U16(u16),
@@ -572,8 +856,11 @@ impl TryFrom<&FromVariant> for VariantValue {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Pointer {
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::option"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<TypeReference>"))]
     pub underlying_type: Option<TypeRef>,
     pub attributes: PointerAttributes,
 }
@@ -603,7 +890,8 @@ impl TryFrom<FromPointer<'_, '_>> for Pointer {
 }
 
 #[derive(Debug, Copy, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum PointerKind {
     Near16,
     Far16,
@@ -655,7 +943,8 @@ impl Typed for PointerKind {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct PointerAttributes {
     pub kind: PointerKind,
     pub is_volatile: bool,
@@ -686,7 +975,8 @@ impl TryFrom<pdb::PointerAttributes> for PointerAttributes {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Primitive {
     pub kind: PrimitiveKind,
     pub indirection: Option<Indirection>,
@@ -723,7 +1013,8 @@ impl Primitive {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum Indirection {
     Near16,
     Far16,
@@ -769,7 +1060,8 @@ impl Indirection {
 }
 
 #[derive(Debug, Copy, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum PrimitiveKind {
     NoType,
     Void,
@@ -978,9 +1270,14 @@ impl std::fmt::Display for PrimitiveKind {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Array {
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub element_type: TypeRef,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub indexing_type: TypeRef,
     pub stride: Option<u32>,
     pub size: usize,
@@ -1054,8 +1351,13 @@ impl TryFrom<FromArray<'_, '_>> for Array {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
-pub struct FieldList(pub Vec<TypeRef>);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FieldList(
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::vec"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<TypeReference>"))]
+    pub Vec<TypeRef>,
+);
 
 type FromFieldList<'a, 'b> = (
     &'b pdb::FieldList<'b>,
@@ -1098,8 +1400,13 @@ impl TryFrom<FromFieldList<'_, '_>> for FieldList {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
-pub struct ArgumentList(pub Vec<TypeRef>);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ArgumentList(
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::vec"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<TypeReference>"))]
+    pub Vec<TypeRef>,
+);
 
 type FromArgumentList<'a, 'b> = (
     &'b pdb::ArgumentList,
@@ -1124,8 +1431,11 @@ impl TryFrom<FromArgumentList<'_, '_>> for ArgumentList {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Modifier {
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub underlying_type: TypeRef,
     pub constant: bool,
     pub volatile: bool,
@@ -1161,10 +1471,121 @@ impl TryFrom<FromModifier<'_, '_>> for Modifier {
     }
 }
 
+/// One step of a [DerivedType], from a type to the type it's derived from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Derivation {
+    Pointer {
+        is_reference: bool,
+        is_const: bool,
+        is_volatile: bool,
+        size: usize,
+    },
+    Array {
+        /// Number of elements in each dimension, outermost first
+        extents: Vec<usize>,
+    },
+    Modifier {
+        constant: bool,
+        volatile: bool,
+        unaligned: bool,
+    },
+}
+
+/// A type split into the chain of pointers, arrays, and modifiers applied to it and the
+/// type at the end of that chain, e.g. `const Foo *[4]` into an array of 4, a pointer,
+/// and a const modifier applied to `Foo`
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DerivedType {
+    /// Derivations from outermost to innermost
+    pub derivations: Vec<Derivation>,
+    /// The type the derivations are applied to, or `None` if a pointer's underlying
+    /// type couldn't be resolved
+    pub base: Option<TypeRef>,
+}
+
+impl DerivedType {
+    /// Follows the pointers, arrays, and modifiers starting at `ty`. Types which aren't
+    /// derived have no derivations and are their own base
+    pub fn new(ty: &TypeRef) -> DerivedType {
+        let mut derivations = vec![];
+        let mut ty = Rc::clone(ty);
+        loop {
+            let next = match &*ty.as_ref().borrow() {
+                Type::Pointer(pointer) => {
+                    derivations.push(Derivation::Pointer {
+                        is_reference: pointer.attributes.is_reference,
+                        is_const: pointer.attributes.is_const,
+                        is_volatile: pointer.attributes.is_volatile,
+                        size: pointer.attributes.size,
+                    });
+                    match &pointer.underlying_type {
+                        Some(underlying_type) => Rc::clone(underlying_type),
+                        None => {
+                            return DerivedType {
+                                derivations,
+                                base: None,
+                            }
+                        }
+                    }
+                }
+                Type::Array(array) => {
+                    derivations.push(Derivation::Array {
+                        extents: array.dimensions_elements.clone(),
+                    });
+                    Rc::clone(&array.element_type)
+                }
+                Type::Modifier(modifier) => {
+                    derivations.push(Derivation::Modifier {
+                        constant: modifier.constant,
+                        volatile: modifier.volatile,
+                        unaligned: modifier.unaligned,
+                    });
+                    Rc::clone(&modifier.underlying_type)
+                }
+                _ => {
+                    return DerivedType {
+                        derivations,
+                        base: Some(Rc::clone(&ty)),
+                    }
+                }
+            };
+            ty = next;
+        }
+    }
+
+    /// Number of pointers and references which have to be followed to reach the base
+    pub fn indirection_level(&self) -> usize {
+        self.derivations
+            .iter()
+            .filter(|derivation| matches!(derivation, Derivation::Pointer { .. }))
+            .count()
+    }
+
+    /// Number of elements the chain's arrays hold in total, or `None` if it has no arrays
+    /// before its first pointer
+    pub fn element_count(&self) -> Option<usize> {
+        let mut count = None;
+        for derivation in &self.derivations {
+            match derivation {
+                Derivation::Array { extents } => {
+                    count = Some(count.unwrap_or(1) * extents.iter().product::<usize>());
+                }
+                Derivation::Pointer { .. } => break,
+                Derivation::Modifier { .. } => {}
+            }
+        }
+
+        count
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Member {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub underlying_type: TypeRef,
     pub offset: usize,
 }
@@ -1199,9 +1620,14 @@ impl TryFrom<FromMember<'_, '_>> for Member {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Procedure {
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::option"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<TypeReference>"))]
     pub return_type: Option<TypeRef>,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::vec"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<TypeReference>"))]
     pub argument_list: Vec<TypeRef>,
     pub attributes: FunctionAttributes,
 }
@@ -1248,7 +1674,8 @@ impl TryFrom<FromProcedure<'_, '_>> for Procedure {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct FunctionAttributes {
     pub calling_convention: u8,
     pub cxx_return_udt: bool,
@@ -1269,11 +1696,20 @@ impl TryFrom<pdb::FunctionAttributes> for FunctionAttributes {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct MemberFunction {
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub return_type: TypeRef,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub class_type: TypeRef,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::option"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<TypeReference>"))]
     pub this_pointer_type: Option<TypeRef>,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref::vec"))]
+    #[cfg_attr(feature = "schema", schemars(with = "Vec<TypeReference>"))]
     pub argument_list: Vec<TypeRef>,
     pub attributes: FunctionAttributes,
     pub this_adjustment: u32,
@@ -1330,8 +1766,45 @@ impl TryFrom<FromMemberFunction<'_, '_>> for MemberFunction {
     }
 }
 
+/// Access and virtuality of a method, from its `LF_ONEMETHOD` or `LF_METHODLIST` entry
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct MethodAttributes {
+    /// 1 for private, 2 for protected, 3 for public
+    pub access: u8,
+    pub is_static: bool,
+    pub is_virtual: bool,
+    pub is_pure_virtual: bool,
+    /// Whether this method introduces a new vtable slot rather than overriding one
+    /// inherited from a base class
+    pub is_intro_virtual: bool,
+    pub is_compiler_generated: bool,
+}
+
+impl From<pdb::FieldAttributes> for MethodAttributes {
+    fn from(attributes: pdb::FieldAttributes) -> Self {
+        MethodAttributes {
+            access: attributes.access(),
+            is_static: attributes.is_static(),
+            is_virtual: attributes.is_virtual(),
+            is_pure_virtual: attributes.is_pure_virtual(),
+            is_intro_virtual: attributes.is_intro_virtual(),
+            is_compiler_generated: attributes.is_compiler_generated(),
+        }
+    }
+}
+
+impl MethodAttributes {
+    /// Whether this method overrides a virtual method of a base class
+    pub fn is_override(&self) -> bool {
+        (self.is_virtual || self.is_pure_virtual) && !self.is_intro_virtual
+    }
+}
+
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct MethodList(pub Vec<MethodListEntry>);
 
 type FromMethodList<'a, 'b> = (
@@ -1356,10 +1829,21 @@ impl TryFrom<FromMethodList<'_, '_>> for MethodList {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct MethodListEntry {
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub method_type: TypeRef,
     pub vtable_offset: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: MethodAttributes,
+    /// Offsets of the procedures implementing this method
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub implementations: Vec<usize>,
 }
 
 type FromMethodListEntry<'a, 'b> = (
@@ -1384,14 +1868,19 @@ impl TryFrom<FromMethodListEntry<'_, '_>> for MethodListEntry {
         Ok(MethodListEntry {
             method_type,
             vtable_offset: vtable_offset.map(|offset| offset as usize),
+            attributes: attributes.into(),
+            implementations: vec![],
         })
     }
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Nested {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub nested_type: TypeRef,
 }
 
@@ -1422,9 +1911,12 @@ impl TryFrom<FromNested<'_, '_>> for Nested {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct OverloadedMethod {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub method_list: TypeRef,
 }
 
@@ -1455,11 +1947,22 @@ impl TryFrom<FromOverloadedMethod<'_, '_>> for OverloadedMethod {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct Method {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub method_type: TypeRef,
     pub vtable_offset: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: MethodAttributes,
+    /// Offsets of the procedures implementing this method
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub implementations: Vec<usize>,
 }
 
 type FromMethod<'a, 'b> = (
@@ -1486,14 +1989,19 @@ impl TryFrom<FromMethod<'_, '_>> for Method {
             name: name.to_string().into_owned(),
             method_type,
             vtable_offset: vtable_offset.map(|offset| offset as usize),
+            attributes: (*attributes).into(),
+            implementations: vec![],
         })
     }
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub struct StaticMember {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
     pub field_type: TypeRef,
 }
 
@@ -1525,8 +2033,13 @@ impl TryFrom<FromStaticMember<'_, '_>> for StaticMember {
 }
 
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
-pub struct VTable(TypeRef);
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct VTable(
+    #[cfg_attr(feature = "serde", serde(with = "type_ref"))]
+    #[cfg_attr(feature = "schema", schemars(with = "TypeReference"))]
+    TypeRef,
+);
 type FromVirtualFunctionTablePointer<'a, 'b> = (
     &'b pdb::VirtualFunctionTablePointerType,
     &'b pdb::TypeFinder<'a>,
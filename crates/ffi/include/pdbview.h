@@ -0,0 +1,75 @@
+#ifndef PDBVIEW_H
+#define PDBVIEW_H
+
+/* Generated by cbindgen from crates/ffi/src/lib.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A parsed PDB
+typedef struct PdbviewPdb PdbviewPdb;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parses the PDB at `path`, a UTF-8 string. Offsets are rebased to `base_address`
+// unless it is 0, in which case they are RVAs. Returns null on failure; see
+// [pdbview_last_error]
+//
+// # Safety
+//
+// `path` must be null or a NUL-terminated string
+PdbviewPdb *pdbview_open(const char *path, uint64_t base_address);
+
+// Releases a PDB returned by [pdbview_open]. Does nothing if `pdb` is null
+//
+// # Safety
+//
+// `pdb` must be null or a PDB returned by [pdbview_open] which wasn't already freed
+void pdbview_free(PdbviewPdb *pdb);
+
+// Returns the message of the last error on this thread, or null if there was none.
+// The string is owned by the library and valid until the next failing call
+const char *pdbview_last_error(void);
+
+// Releases a string returned by a query. Does nothing if `s` is null
+//
+// # Safety
+//
+// `s` must be null or a string returned by a query which wasn't already freed
+void pdbview_string_free(char *s);
+
+// Returns the whole PDB as JSON. Returns null on failure
+//
+// # Safety
+//
+// `pdb` must be null or a PDB returned by [pdbview_open]
+char *pdbview_to_json(const PdbviewPdb *pdb);
+
+// Returns the procedures, public symbols, and globals named `name` as a JSON object
+// with `procedures`, `public_symbols`, and `global_data` arrays. Returns null on
+// failure
+//
+// # Safety
+//
+// `pdb` must be null or a PDB returned by [pdbview_open], and `name` must be null or a
+// NUL-terminated string
+char *pdbview_query_symbol(const PdbviewPdb *pdb, const char *name);
+
+// Returns the procedure, module, and nearest public symbol of `address` as JSON, like
+// `pdbview lookup`. `address` is in the same address space as the PDB's offsets, so
+// includes the base address passed to [pdbview_open]. Returns null on failure
+//
+// # Safety
+//
+// `pdb` must be null or a PDB returned by [pdbview_open]
+char *pdbview_lookup_address(const PdbviewPdb *pdb, uint64_t address);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* PDBVIEW_H */
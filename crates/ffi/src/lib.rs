@@ -0,0 +1,192 @@
+//! C interface to [ezpdb], for embedding pdbview in debugger plugins and other C and C++
+//! tools. `include/pdbview.h` is generated from this file with cbindgen, see the README.
+//!
+//! Queries return JSON in the same layout as `pdbview --format json`. Strings returned
+//! by the library must be released with [pdbview_string_free] and PDBs with
+//! [pdbview_free]. A [PdbviewPdb] must only be used by one thread at a time
+
+use ezpdb::symbol_types::*;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// A parsed PDB
+pub struct PdbviewPdb {
+    pdb: ParsedPdb,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, recording its error or panic as the last error and returning null instead
+fn guard<T>(f: impl FnOnce() -> Result<*mut T, String>) -> *mut T {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("pdbview panicked".to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe fn to_str<'a>(s: *const c_char, parameter: &str) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err(format!("`{}` is null", parameter));
+    }
+
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| format!("`{}` is not valid UTF-8", parameter))
+}
+
+unsafe fn to_pdb<'a>(pdb: *const PdbviewPdb) -> Result<&'a ParsedPdb, String> {
+    pdb.as_ref()
+        .map(|pdb| &pdb.pdb)
+        .ok_or_else(|| "`pdb` is null".to_string())
+}
+
+fn to_json(value: &impl Serialize) -> Result<*mut c_char, String> {
+    let json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    CString::new(json)
+        .map(CString::into_raw)
+        .map_err(|e| e.to_string())
+}
+
+/// Parses the PDB at `path`, a UTF-8 string. Offsets are rebased to `base_address`
+/// unless it is 0, in which case they are RVAs. Returns null on failure; see
+/// [pdbview_last_error]
+///
+/// # Safety
+///
+/// `path` must be null or a NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn pdbview_open(path: *const c_char, base_address: u64) -> *mut PdbviewPdb {
+    guard(|| {
+        let path = to_str(path, "path")?;
+        let mut pdb = ezpdb::parse_pdb(path).map_err(|e| e.to_string())?;
+        if base_address != 0 {
+            pdb.rebase(base_address as usize);
+        }
+
+        Ok(Box::into_raw(Box::new(PdbviewPdb { pdb })))
+    })
+}
+
+/// Releases a PDB returned by [pdbview_open]. Does nothing if `pdb` is null
+///
+/// # Safety
+///
+/// `pdb` must be null or a PDB returned by [pdbview_open] which wasn't already freed
+#[no_mangle]
+pub unsafe extern "C" fn pdbview_free(pdb: *mut PdbviewPdb) {
+    if !pdb.is_null() {
+        drop(Box::from_raw(pdb));
+    }
+}
+
+/// Returns the message of the last error on this thread, or null if there was none.
+/// The string is owned by the library and valid until the next failing call
+#[no_mangle]
+pub extern "C" fn pdbview_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| {
+        error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Releases a string returned by a query. Does nothing if `s` is null
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by a query which wasn't already freed
+#[no_mangle]
+pub unsafe extern "C" fn pdbview_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Returns the whole PDB as JSON. Returns null on failure
+///
+/// # Safety
+///
+/// `pdb` must be null or a PDB returned by [pdbview_open]
+#[no_mangle]
+pub unsafe extern "C" fn pdbview_to_json(pdb: *const PdbviewPdb) -> *mut c_char {
+    guard(|| to_json(to_pdb(pdb)?))
+}
+
+/// Symbols named by a [pdbview_query_symbol] query
+#[derive(Serialize)]
+struct SymbolMatches<'a> {
+    procedures: Vec<&'a Procedure>,
+    public_symbols: Vec<&'a PublicSymbol>,
+    global_data: Vec<&'a Data>,
+}
+
+/// Returns the procedures, public symbols, and globals named `name` as a JSON object
+/// with `procedures`, `public_symbols`, and `global_data` arrays. Returns null on
+/// failure
+///
+/// # Safety
+///
+/// `pdb` must be null or a PDB returned by [pdbview_open], and `name` must be null or a
+/// NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn pdbview_query_symbol(
+    pdb: *const PdbviewPdb,
+    name: *const c_char,
+) -> *mut c_char {
+    guard(|| {
+        let pdb = to_pdb(pdb)?;
+        let name = to_str(name, "name")?;
+
+        to_json(&SymbolMatches {
+            procedures: pdb
+                .procedures
+                .iter()
+                .filter(|procedure| procedure.name == name)
+                .collect(),
+            public_symbols: pdb
+                .public_symbols
+                .iter()
+                .filter(|symbol| symbol.name == name)
+                .collect(),
+            global_data: pdb
+                .global_data
+                .iter()
+                .filter(|data| data.name == name)
+                .collect(),
+        })
+    })
+}
+
+/// Returns the procedure, module, and nearest public symbol of `address` as JSON, like
+/// `pdbview lookup`. `address` is in the same address space as the PDB's offsets, so
+/// includes the base address passed to [pdbview_open]. Returns null on failure
+///
+/// # Safety
+///
+/// `pdb` must be null or a PDB returned by [pdbview_open]
+#[no_mangle]
+pub unsafe extern "C" fn pdbview_lookup_address(
+    pdb: *const PdbviewPdb,
+    address: u64,
+) -> *mut c_char {
+    guard(|| to_json(&to_pdb(pdb)?.lookup_address(address as usize)))
+}
@@ -0,0 +1,158 @@
+//! Node.js bindings to [ezpdb], built with napi-rs. Queries return plain objects in the
+//! layout of `pdbview --format json`, with field names unchanged.
+//!
+//! Addresses are taken as `BigInt`s so kernel addresses survive the trip from
+//! JavaScript. Offsets in returned objects are numbers, which are exact below 2^53
+
+use ezpdb::symbol_types::*;
+use napi::bindgen_prelude::BigInt;
+use napi::{Error, Result};
+use napi_derive::napi;
+use serde::Serialize;
+use serde_json::Value;
+
+fn to_napi_err(error: impl std::fmt::Display) -> Error {
+    Error::from_reason(error.to_string())
+}
+
+fn to_value(value: &impl Serialize) -> Result<Value> {
+    serde_json::to_value(value).map_err(to_napi_err)
+}
+
+fn to_address(address: BigInt) -> Result<usize> {
+    match address.get_u64() {
+        (false, address, true) => Ok(address as usize),
+        _ => Err(Error::from_reason(
+            "addresses must be positive and fit in 64 bits".to_string(),
+        )),
+    }
+}
+
+/// Parses the PDB at `path`, rebasing every offset to `baseAddress` if provided
+#[napi]
+pub fn parse(path: String, base_address: Option<BigInt>) -> Result<Pdb> {
+    let mut pdb = ezpdb::parse_pdb(&path).map_err(to_napi_err)?;
+    if let Some(base_address) = base_address {
+        pdb.rebase(to_address(base_address)?);
+    }
+
+    Ok(Pdb { pdb })
+}
+
+/// A parsed PDB
+#[napi]
+pub struct Pdb {
+    pdb: ParsedPdb,
+}
+
+/// Symbols named by [Pdb::find_symbol]
+#[derive(Serialize)]
+struct SymbolMatches<'a> {
+    procedures: Vec<&'a Procedure>,
+    public_symbols: Vec<&'a PublicSymbol>,
+    global_data: Vec<&'a Data>,
+}
+
+#[napi]
+impl Pdb {
+    #[napi(getter)]
+    pub fn path(&self) -> String {
+        self.pdb.path.to_string_lossy().into_owned()
+    }
+
+    #[napi(getter)]
+    pub fn guid(&self) -> String {
+        self.pdb.guid.to_string()
+    }
+
+    #[napi(getter)]
+    pub fn age(&self) -> u32 {
+        self.pdb.age
+    }
+
+    #[napi(getter)]
+    pub fn debug_identifier(&self) -> String {
+        self.pdb.debug_identifier()
+    }
+
+    #[napi(getter)]
+    pub fn warnings(&self) -> Vec<String> {
+        self.pdb.warnings.clone()
+    }
+
+    /// Moves every offset so that the image is loaded at `baseAddress`
+    #[napi]
+    pub fn rebase(&mut self, base_address: BigInt) -> Result<()> {
+        self.pdb.rebase(to_address(base_address)?);
+        Ok(())
+    }
+
+    /// Returns the procedures, public symbols, and globals named `name`
+    #[napi]
+    pub fn find_symbol(&self, name: String) -> Result<Value> {
+        to_value(&SymbolMatches {
+            procedures: self
+                .pdb
+                .procedures
+                .iter()
+                .filter(|procedure| procedure.name == name)
+                .collect(),
+            public_symbols: self
+                .pdb
+                .public_symbols
+                .iter()
+                .filter(|symbol| symbol.name == name)
+                .collect(),
+            global_data: self
+                .pdb
+                .global_data
+                .iter()
+                .filter(|data| data.name == name)
+                .collect(),
+        })
+    }
+
+    /// Returns the procedure, module, and nearest public symbol of `address`, like
+    /// `pdbview lookup`
+    #[napi]
+    pub fn lookup(&self, address: BigInt) -> Result<Value> {
+        to_value(&self.pdb.lookup_address(to_address(address)?))
+    }
+
+    #[napi]
+    pub fn procedures(&self) -> Result<Value> {
+        to_value(&self.pdb.procedures)
+    }
+
+    #[napi]
+    pub fn public_symbols(&self) -> Result<Value> {
+        to_value(&self.pdb.public_symbols)
+    }
+
+    #[napi]
+    pub fn global_data(&self) -> Result<Value> {
+        to_value(&self.pdb.global_data)
+    }
+
+    #[napi]
+    pub fn sections(&self) -> Result<Value> {
+        to_value(&self.pdb.sections)
+    }
+
+    #[napi]
+    pub fn exports(&self) -> Result<Value> {
+        to_value(&self.pdb.exports)
+    }
+
+    #[napi]
+    pub fn modules(&self) -> Result<Value> {
+        to_value(&self.pdb.debug_modules)
+    }
+
+    /// Returns the whole PDB as a JSON string, which is faster than building it as an
+    /// object for consumers that forward it elsewhere
+    #[napi]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(&self.pdb).map_err(to_napi_err)
+    }
+}
@@ -0,0 +1,460 @@
+//! Python bindings to [ezpdb], published as the `pdbview` module.
+//!
+//! Symbols are exposed through list views which only convert an entry to a Python
+//! object when it is accessed, so parsing a large PDB doesn't create millions of Python
+//! objects up front. Raw MSF streams support the buffer protocol, so `memoryview` and
+//! `numpy.frombuffer` read them without copying
+
+use ezpdb::symbol_types as types;
+use pyo3::exceptions::{PyBufferError, PyException, PyIndexError};
+use pyo3::prelude::*;
+use pyo3::AsPyPointer;
+use std::os::raw::{c_int, c_void};
+
+pyo3::create_exception!(pdbview, PdbError, PyException);
+
+fn to_py_err(error: impl std::fmt::Display) -> PyErr {
+    PdbError::new_err(error.to_string())
+}
+
+/// Parses the PDB at `path`, rebasing every offset to `base_address` if provided
+#[pyfunction]
+fn parse(path: &str, base_address: Option<usize>) -> PyResult<ParsedPdb> {
+    let mut pdb = ezpdb::parse_pdb(path).map_err(to_py_err)?;
+    if let Some(base_address) = base_address {
+        pdb.rebase(base_address);
+    }
+
+    Ok(ParsedPdb { pdb })
+}
+
+#[pyclass(unsendable, module = "pdbview")]
+pub struct ParsedPdb {
+    pdb: types::ParsedPdb,
+}
+
+#[pymethods]
+impl ParsedPdb {
+    #[getter]
+    fn path(&self) -> String {
+        self.pdb.path.to_string_lossy().into_owned()
+    }
+
+    #[getter]
+    fn guid(&self) -> String {
+        self.pdb.guid.to_string()
+    }
+
+    #[getter]
+    fn age(&self) -> u32 {
+        self.pdb.age
+    }
+
+    #[getter]
+    fn timestamp(&self) -> u32 {
+        self.pdb.timestamp
+    }
+
+    #[getter]
+    fn debug_identifier(&self) -> String {
+        self.pdb.debug_identifier()
+    }
+
+    #[getter]
+    fn machine_type(&self) -> Option<String> {
+        self.pdb
+            .machine_type
+            .as_ref()
+            .map(|machine_type| format!("{:?}", machine_type))
+    }
+
+    #[getter]
+    fn base_address(&self) -> usize {
+        self.pdb.base_address
+    }
+
+    #[getter]
+    fn warnings(&self) -> Vec<String> {
+        self.pdb.warnings.clone()
+    }
+
+    #[getter]
+    fn procedures(slf: PyRef<Self>) -> ProcedureList {
+        ProcedureList { pdb: slf.into() }
+    }
+
+    #[getter]
+    fn public_symbols(slf: PyRef<Self>) -> PublicSymbolList {
+        PublicSymbolList { pdb: slf.into() }
+    }
+
+    #[getter]
+    fn global_data(slf: PyRef<Self>) -> DataList {
+        DataList { pdb: slf.into() }
+    }
+
+    #[getter]
+    fn sections(slf: PyRef<Self>) -> SectionList {
+        SectionList { pdb: slf.into() }
+    }
+
+    #[getter]
+    fn exports(slf: PyRef<Self>) -> ExportList {
+        ExportList { pdb: slf.into() }
+    }
+
+    #[getter]
+    fn modules(slf: PyRef<Self>) -> ModuleList {
+        ModuleList { pdb: slf.into() }
+    }
+
+    /// Moves every offset so that the image is loaded at `base_address`
+    fn rebase(&mut self, base_address: usize) {
+        self.pdb.rebase(base_address);
+    }
+
+    /// Returns the procedure containing `address`, if any
+    fn procedure_at(slf: PyRef<Self>, address: usize) -> Option<Procedure> {
+        let procedure = slf.pdb.lookup_address(address).procedure?;
+        let index = slf
+            .pdb
+            .procedures
+            .iter()
+            .position(|candidate| std::ptr::eq(candidate, procedure))?;
+
+        Some(Procedure {
+            pdb: slf.into(),
+            index,
+        })
+    }
+
+    /// Reads an MSF stream by index, by one of `pdb`, `tpi`, `dbi`, or `ipi`, or by name
+    /// such as `/names`
+    fn read_stream(&self, stream: &str) -> PyResult<Stream> {
+        Ok(Stream {
+            data: ezpdb::streams::read_stream(&self.pdb.path, stream).map_err(to_py_err)?,
+        })
+    }
+
+    /// Returns the whole PDB in the layout of `pdbview --format json`
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.pdb).map_err(to_py_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<ParsedPdb {} {}>", self.path(), self.debug_identifier())
+    }
+}
+
+/// Defines a sequence view over one of the lists of a [types::ParsedPdb] and the class
+/// of its entries, which hold the PDB and their index rather than a copy of the entry
+macro_rules! list_view {
+    ($list:ident, $item:ident, $ty:ty, $field:ident) => {
+        #[pyclass(unsendable, module = "pdbview")]
+        pub struct $list {
+            pdb: Py<ParsedPdb>,
+        }
+
+        #[pymethods]
+        impl $list {
+            fn __len__(&self, py: Python) -> usize {
+                self.pdb.borrow(py).pdb.$field.len()
+            }
+
+            fn __getitem__(&self, py: Python, index: isize) -> PyResult<$item> {
+                let len = self.__len__(py) as isize;
+                let index = if index < 0 { index + len } else { index };
+                if index < 0 || index >= len {
+                    return Err(PyIndexError::new_err("index out of range"));
+                }
+
+                Ok($item {
+                    pdb: self.pdb.clone_ref(py),
+                    index: index as usize,
+                })
+            }
+        }
+
+        #[pyclass(unsendable, module = "pdbview")]
+        pub struct $item {
+            pdb: Py<ParsedPdb>,
+            index: usize,
+        }
+
+        impl $item {
+            fn with<R>(&self, py: Python, f: impl FnOnce(&$ty) -> R) -> R {
+                f(&self.pdb.borrow(py).pdb.$field[self.index])
+            }
+        }
+
+        #[pymethods]
+        impl $item {
+            /// Returns this entry in the layout of `pdbview --format json`
+            fn to_json(&self, py: Python) -> PyResult<String> {
+                self.with(py, |entry| serde_json::to_string(entry).map_err(to_py_err))
+            }
+
+            fn __repr__(&self, py: Python) -> String {
+                self.with(py, |entry| {
+                    format!("<{} {}>", stringify!($item), entry.name)
+                })
+            }
+        }
+    };
+}
+
+list_view!(ProcedureList, Procedure, types::Procedure, procedures);
+list_view!(
+    PublicSymbolList,
+    PublicSymbol,
+    types::PublicSymbol,
+    public_symbols
+);
+list_view!(DataList, Data, types::Data, global_data);
+list_view!(SectionList, Section, types::Section, sections);
+list_view!(ExportList, Export, types::Export, exports);
+list_view!(ModuleList, Module, types::DebugModule, debug_modules);
+
+#[pymethods]
+impl Procedure {
+    #[getter]
+    fn name(&self, py: Python) -> String {
+        self.with(py, |procedure| procedure.name.clone())
+    }
+
+    #[getter]
+    fn signature(&self, py: Python) -> Option<String> {
+        self.with(py, |procedure| procedure.signature.clone())
+    }
+
+    #[getter]
+    fn offset(&self, py: Python) -> Option<usize> {
+        self.with(py, |procedure| procedure.offset)
+    }
+
+    /// Length in BYTES
+    #[getter]
+    fn len(&self, py: Python) -> usize {
+        self.with(py, |procedure| procedure.len)
+    }
+
+    #[getter]
+    fn is_global(&self, py: Python) -> bool {
+        self.with(py, |procedure| procedure.is_global)
+    }
+
+    #[getter]
+    fn stable_id(&self, py: Python) -> Option<String> {
+        self.with(py, |procedure| procedure.stable_id.clone())
+    }
+
+    #[getter]
+    fn inlinees(&self, py: Python) -> Vec<String> {
+        self.with(py, |procedure| procedure.inlinees.clone())
+    }
+}
+
+#[pymethods]
+impl PublicSymbol {
+    #[getter]
+    fn name(&self, py: Python) -> String {
+        self.with(py, |symbol| symbol.name.clone())
+    }
+
+    #[getter]
+    fn offset(&self, py: Python) -> Option<usize> {
+        self.with(py, |symbol| symbol.offset)
+    }
+
+    #[getter]
+    fn is_code(&self, py: Python) -> bool {
+        self.with(py, |symbol| symbol.is_code)
+    }
+
+    #[getter]
+    fn is_function(&self, py: Python) -> bool {
+        self.with(py, |symbol| symbol.is_function)
+    }
+}
+
+#[pymethods]
+impl Data {
+    #[getter]
+    fn name(&self, py: Python) -> String {
+        self.with(py, |data| data.name.clone())
+    }
+
+    #[getter]
+    fn offset(&self, py: Python) -> Option<usize> {
+        self.with(py, |data| data.offset)
+    }
+
+    #[getter]
+    fn is_global(&self, py: Python) -> bool {
+        self.with(py, |data| data.is_global)
+    }
+}
+
+#[pymethods]
+impl Section {
+    #[getter]
+    fn name(&self, py: Python) -> String {
+        self.with(py, |section| section.name.clone())
+    }
+
+    /// 1-based section number
+    #[getter]
+    fn index(&self, py: Python) -> usize {
+        self.with(py, |section| section.index)
+    }
+
+    #[getter]
+    fn offset(&self, py: Python) -> usize {
+        self.with(py, |section| section.offset)
+    }
+
+    /// Size in BYTES
+    #[getter]
+    fn size(&self, py: Python) -> usize {
+        self.with(py, |section| section.size)
+    }
+
+    #[getter]
+    fn characteristics(&self, py: Python) -> u32 {
+        self.with(py, |section| section.characteristics)
+    }
+}
+
+#[pymethods]
+impl Export {
+    #[getter]
+    fn name(&self, py: Python) -> String {
+        self.with(py, |export| export.name.clone())
+    }
+
+    #[getter]
+    fn ordinal(&self, py: Python) -> u16 {
+        self.with(py, |export| export.ordinal)
+    }
+
+    #[getter]
+    fn is_data(&self, py: Python) -> bool {
+        self.with(py, |export| export.is_data)
+    }
+
+    #[getter]
+    fn is_forwarder(&self, py: Python) -> bool {
+        self.with(py, |export| export.is_forwarder)
+    }
+}
+
+#[pymethods]
+impl Module {
+    #[getter]
+    fn name(&self, py: Python) -> String {
+        self.with(py, |module| module.name.clone())
+    }
+
+    #[getter]
+    fn object_file_name(&self, py: Python) -> String {
+        self.with(py, |module| module.object_file_name.clone())
+    }
+
+    #[getter]
+    fn source_files(&self, py: Python) -> Vec<String> {
+        self.with(py, |module| {
+            module
+                .source_files
+                .iter()
+                .flatten()
+                .map(|file| file.name.clone())
+                .collect()
+        })
+    }
+}
+
+/// Contents of an MSF stream. Supports the buffer protocol, so `memoryview(stream)`
+/// reads it without copying
+#[pyclass(module = "pdbview")]
+pub struct Stream {
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl Stream {
+    fn __len__(&self) -> usize {
+        self.data.len()
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> &'py pyo3::types::PyBytes {
+        pyo3::types::PyBytes::new(py, &self.data)
+    }
+
+    fn __getbuffer__(
+        slf: PyRef<Self>,
+        view: *mut pyo3::ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("view is null"));
+        }
+        if flags & pyo3::ffi::PyBUF_WRITABLE == pyo3::ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("streams are read-only"));
+        }
+
+        // The view keeps the stream alive, and the stream never changes, so the buffer
+        // can point directly at its data
+        unsafe {
+            (*view).obj = slf.as_ptr();
+            pyo3::ffi::Py_INCREF((*view).obj);
+            (*view).buf = slf.data.as_ptr() as *mut c_void;
+            (*view).len = slf.data.len() as isize;
+            (*view).readonly = 1;
+            (*view).itemsize = 1;
+            (*view).format = if flags & pyo3::ffi::PyBUF_FORMAT == pyo3::ffi::PyBUF_FORMAT {
+                b"B\0".as_ptr() as *mut _
+            } else {
+                std::ptr::null_mut()
+            };
+            (*view).ndim = 1;
+            (*view).shape = if flags & pyo3::ffi::PyBUF_ND == pyo3::ffi::PyBUF_ND {
+                &mut (*view).len
+            } else {
+                std::ptr::null_mut()
+            };
+            (*view).strides = if flags & pyo3::ffi::PyBUF_STRIDES == pyo3::ffi::PyBUF_STRIDES {
+                &mut (*view).itemsize
+            } else {
+                std::ptr::null_mut()
+            };
+            (*view).suboffsets = std::ptr::null_mut();
+            (*view).internal = std::ptr::null_mut();
+        }
+
+        Ok(())
+    }
+
+    fn __releasebuffer__(&self, _view: *mut pyo3::ffi::Py_buffer) {}
+}
+
+#[pymodule]
+fn pdbview(py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("PdbError", py.get_type::<PdbError>())?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_class::<ParsedPdb>()?;
+    m.add_class::<ProcedureList>()?;
+    m.add_class::<Procedure>()?;
+    m.add_class::<PublicSymbolList>()?;
+    m.add_class::<PublicSymbol>()?;
+    m.add_class::<DataList>()?;
+    m.add_class::<Data>()?;
+    m.add_class::<SectionList>()?;
+    m.add_class::<Section>()?;
+    m.add_class::<ExportList>()?;
+    m.add_class::<Export>()?;
+    m.add_class::<ModuleList>()?;
+    m.add_class::<Module>()?;
+    m.add_class::<Stream>()?;
+
+    Ok(())
+}